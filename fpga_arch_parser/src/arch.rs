@@ -1,5 +1,6 @@
 use crate::{complex_block_graph::ComplexBlockGraph, tile_pin_mapper::TilePinMapper};
 
+#[derive(PartialEq)]
 pub struct ModelPort {
     pub name: String,
     pub is_clock: bool,
@@ -7,6 +8,7 @@ pub struct ModelPort {
     pub combinational_sink_ports: Vec<String>,
 }
 
+#[derive(PartialEq)]
 pub struct Model {
     pub name: String,
     pub never_prune: bool,
@@ -14,17 +16,20 @@ pub struct Model {
     pub output_ports: Vec<ModelPort>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Metadata {
     pub name: String,
     pub value: String,
 }
 
+#[derive(PartialEq)]
 pub enum PinEquivalence {
     None,
     Full,
     Instance,
 }
 
+#[derive(PartialEq)]
 pub enum PortClass {
     None,
     LutIn,
@@ -40,6 +45,7 @@ pub enum PortClass {
     MemoryReadEn(i32),
 }
 
+#[derive(PartialEq)]
 pub struct InputPort {
     pub name: String,
     pub num_pins: usize,
@@ -48,6 +54,7 @@ pub struct InputPort {
     pub port_class: PortClass,
 }
 
+#[derive(PartialEq)]
 pub struct OutputPort {
     pub name: String,
     pub num_pins: usize,
@@ -55,6 +62,7 @@ pub struct OutputPort {
     pub port_class: PortClass,
 }
 
+#[derive(PartialEq)]
 pub struct ClockPort {
     pub name: String,
     pub num_pins: usize,
@@ -62,41 +70,50 @@ pub struct ClockPort {
     pub port_class: PortClass,
 }
 
+#[derive(PartialEq)]
 pub enum Port {
     Input(InputPort),
     Output(OutputPort),
     Clock(ClockPort),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum TileSitePinMapping {
     Direct,
     Custom,
 }
 
+#[derive(PartialEq)]
 pub struct TileSite {
+    /// Name of a root pb_type in the `<complexblocklist>`. This is the only
+    /// place pb_types are referenced by name: every pb_type below a root is
+    /// defined inline where it is used, so a shared block must be written out
+    /// again in each parent.
     pub pb_type: String,
     pub pin_mapping: TileSitePinMapping,
 }
 
+#[derive(Clone, PartialEq)]
 pub enum SubTileIOFC {
     Frac(f32),
     Abs(i32),
 }
 
+#[derive(Clone, PartialEq)]
 pub struct SubTileFCOverride {
     pub fc: SubTileIOFC,
     pub port_name: Option<String>,
     pub segment_name: Option<String>,
 }
 
+#[derive(Clone, PartialEq)]
 pub struct SubTileFC {
     pub in_fc: SubTileIOFC,
     pub out_fc: SubTileIOFC,
     pub fc_overrides: Vec<SubTileFCOverride>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum PinSide {
     Left,
     Right,
@@ -104,6 +121,7 @@ pub enum PinSide {
     Top,
 }
 
+#[derive(PartialEq)]
 pub struct PinLoc {
     pub side: PinSide,
     pub xoffset: usize,
@@ -111,10 +129,12 @@ pub struct PinLoc {
     pub pin_strings: Vec<String>,
 }
 
+#[derive(PartialEq)]
 pub struct CustomPinLocations {
     pub pin_locations: Vec<PinLoc>,
 }
 
+#[derive(PartialEq)]
 pub enum SubTilePinLocations {
     Spread,
     Perimeter,
@@ -122,15 +142,22 @@ pub enum SubTilePinLocations {
     Custom(CustomPinLocations),
 }
 
+#[derive(PartialEq)]
 pub struct SubTile {
     pub name: String,
     pub capacity: i32,
     pub equivalent_sites: Vec<TileSite>,
     pub ports: Vec<Port>,
-    pub fc: SubTileFC,
+    /// The sub tile's `<fc>`, or the device's `<default_fc>` when it has
+    /// none of its own. None only if neither is given, which lenient parsing
+    /// lets through.
+    pub fc: Option<SubTileFC>,
+    /// Whether `fc` is the device's `<default_fc>`.
+    pub fc_from_default: bool,
     pub pin_locations: SubTilePinLocations,
 }
 
+#[derive(PartialEq)]
 pub enum SwitchBlockLocationType {
     Full,
     Straight,
@@ -138,6 +165,7 @@ pub enum SwitchBlockLocationType {
     None,
 }
 
+#[derive(PartialEq)]
 pub struct SwitchBlockLocation {
     pub sb_type: SwitchBlockLocationType,
     pub xoffset: i32,
@@ -145,6 +173,7 @@ pub struct SwitchBlockLocation {
     pub switch_override: Option<String>,
 }
 
+#[derive(PartialEq)]
 pub enum SwitchBlockLocationsPattern {
     ExternalFullInternalStraight,
     All,
@@ -154,11 +183,27 @@ pub enum SwitchBlockLocationsPattern {
     Custom(Vec<SwitchBlockLocation>),
 }
 
+#[derive(PartialEq)]
 pub struct SwitchBlockLocations {
     pub pattern: SwitchBlockLocationsPattern,
     pub internal_switch: Option<String>,
 }
 
+/// Line of a tag in the parsed document, counting from 1, or None for
+/// elements that were not parsed from a file.
+///
+/// Every `SourceLine` compares equal, so an edit higher up in a file, which
+/// moves the elements below it, does not make them unequal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SourceLine(pub Option<usize>);
+
+impl PartialEq for SourceLine {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(PartialEq)]
 pub struct Tile {
     pub name: String,
     // FIXME: Documentation. It is not clear from the documentation if tiles should
@@ -170,27 +215,33 @@ pub struct Tile {
     pub area: Option<f32>,
     pub switchblock_locations: Option<SwitchBlockLocations>,
     pub pin_mapper: TilePinMapper,
+    /// Line of the `<tile>` tag.
+    pub source_line: SourceLine,
 }
 
 // TODO: pb_type and priority is better served as a trait.
+#[derive(PartialEq)]
 pub struct FillGridLocation {
     pub pb_type: String,
     pub priority: i32,
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct PerimeterGridLocation {
     pub pb_type: String,
     pub priority: i32,
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct CornersGridLocation {
     pub pb_type: String,
     pub priority: i32,
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct SingleGridLocation {
     pub pb_type: String,
     pub priority: i32,
@@ -199,6 +250,7 @@ pub struct SingleGridLocation {
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct ColGridLocation {
     pub pb_type: String,
     pub priority: i32,
@@ -209,6 +261,7 @@ pub struct ColGridLocation {
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct RowGridLocation {
     pub pb_type: String,
     pub priority: i32,
@@ -219,6 +272,7 @@ pub struct RowGridLocation {
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct RegionGridLocation {
     pub pb_type: String,
     pub priority: i32,
@@ -233,6 +287,7 @@ pub struct RegionGridLocation {
     pub metadata: Option<Vec<Metadata>>,
 }
 
+#[derive(PartialEq)]
 pub struct InterdieWire {
     pub sg_name: String,
     pub sg_link: String,
@@ -242,12 +297,14 @@ pub struct InterdieWire {
     pub num: String,
 }
 
+#[derive(PartialEq)]
 pub struct InterposerCutGridLocation {
     pub x: Option<String>,
     pub y: Option<String>,
     pub interdie_wires: Vec<InterdieWire>,
 }
 
+#[derive(PartialEq)]
 pub enum GridLocation {
     Fill(FillGridLocation),
     Perimeter(PerimeterGridLocation),
@@ -259,16 +316,36 @@ pub enum GridLocation {
     InterposerCut(InterposerCutGridLocation),
 }
 
+impl GridLocation {
+    /// The `<metadata>` attached to this placement, if any. Interposer cuts
+    /// place no tiles and carry none.
+    pub fn metadata(&self) -> Option<&[Metadata]> {
+        match self {
+            GridLocation::Fill(loc) => loc.metadata.as_deref(),
+            GridLocation::Perimeter(loc) => loc.metadata.as_deref(),
+            GridLocation::Corners(loc) => loc.metadata.as_deref(),
+            GridLocation::Single(loc) => loc.metadata.as_deref(),
+            GridLocation::Col(loc) => loc.metadata.as_deref(),
+            GridLocation::Row(loc) => loc.metadata.as_deref(),
+            GridLocation::Region(loc) => loc.metadata.as_deref(),
+            GridLocation::InterposerCut(_) => None,
+        }
+    }
+}
+
+#[derive(PartialEq)]
 pub struct Layer {
     pub die: usize,
     pub grid_locations: Vec<GridLocation>,
 }
 
+#[derive(PartialEq)]
 pub struct AutoLayout {
     pub aspect_ratio: f32,
     pub layers: Vec<Layer>,
 }
 
+#[derive(PartialEq)]
 pub struct FixedLayout {
     pub name: String,
     pub width: i32,
@@ -276,7 +353,7 @@ pub struct FixedLayout {
     pub layers: Vec<Layer>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct TileableLayoutConfig {
     pub tileable: bool,
     pub through_channel: bool,
@@ -287,21 +364,25 @@ pub struct TileableLayoutConfig {
     pub concat_pass_wire: bool,
 }
 
+#[derive(PartialEq)]
 pub enum Layout {
     AutoLayout(AutoLayout),
     FixedLayout(FixedLayout),
 }
 
+#[derive(Default, PartialEq)]
 pub struct DeviceLayouts {
     pub layout_list: Vec<Layout>,
     pub tileable_config: Option<TileableLayoutConfig>,
 }
 
+#[derive(PartialEq)]
 pub enum CustomSwitchBlockType {
     Unidir,
     Bidir,
 }
 
+#[derive(PartialEq)]
 pub enum CustomSwitchBlockLocation {
     Everywhere,
     Perimeter,
@@ -312,6 +393,7 @@ pub enum CustomSwitchBlockLocation {
     XYSpecified { x: i32, y: i32 },
 }
 
+#[derive(PartialEq)]
 pub enum CustomSwitchFuncType {
     LeftToTop,
     LeftToRight,
@@ -327,21 +409,25 @@ pub enum CustomSwitchFuncType {
     BottomToRight,
 }
 
+#[derive(PartialEq)]
 pub struct CustomSwitchFunc {
     pub func_type: CustomSwitchFuncType,
     pub formula: String,
 }
 
+#[derive(PartialEq)]
 pub struct CustomSwitchBlockConnPoint {
     pub segment_type: String,
     pub switchpoint: Vec<i32>,
 }
 
+#[derive(PartialEq)]
 pub enum CustomSwitchBlockWireConnOrder {
     Shuffled,
     Fixed,
 }
 
+#[derive(PartialEq)]
 pub struct CustomSwitchWireConn {
     pub num_conns: String,
     pub from_points: Vec<CustomSwitchBlockConnPoint>,
@@ -351,6 +437,7 @@ pub struct CustomSwitchWireConn {
     pub switch_override: Option<String>,
 }
 
+#[derive(PartialEq)]
 pub struct CustomSwitchBlock {
     pub name: String,
     pub sb_type: CustomSwitchBlockType,
@@ -359,7 +446,7 @@ pub struct CustomSwitchBlock {
     pub wireconns: Vec<CustomSwitchWireConn>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum SBType {
     Wilton,
     Subset,
@@ -367,6 +454,7 @@ pub enum SBType {
     Custom,
 }
 
+#[derive(PartialEq)]
 pub struct GaussianChanWDist {
     pub peak: f32,
     pub width: f32,
@@ -374,10 +462,12 @@ pub struct GaussianChanWDist {
     pub dc: f32,
 }
 
+#[derive(PartialEq)]
 pub struct UniformChanWDist {
     pub peak: f32,
 }
 
+#[derive(PartialEq)]
 pub struct PulseChanWDist {
     pub peak: f32,
     pub width: f32,
@@ -385,12 +475,14 @@ pub struct PulseChanWDist {
     pub dc: f32,
 }
 
+#[derive(PartialEq)]
 pub struct DeltaChanWDist {
     pub peak: f32,
     pub xpeak: f32,
     pub dc: f32,
 }
 
+#[derive(PartialEq)]
 pub enum ChanWDist {
     Gaussian(GaussianChanWDist),
     Uniform(UniformChanWDist),
@@ -398,40 +490,77 @@ pub enum ChanWDist {
     Delta(DeltaChanWDist),
 }
 
+#[derive(PartialEq)]
 pub struct DeviceSizingInfo {
     pub r_min_w_nmos: f32,
     pub r_min_w_pmos: f32,
 }
 
+#[derive(PartialEq)]
 pub struct DeviceConnectionBlockInfo {
     pub input_switch_name: String,
 }
 
+#[derive(PartialEq)]
 pub struct DeviceAreaInfo {
     pub grid_logic_tile_area: f32,
 }
 
+#[derive(PartialEq)]
 pub struct DeviceSwitchBlockInfo {
     pub sb_type: SBType,
     //      NOTE: SB fs is required if the sb type is non-custom.
     pub sb_fs: Option<i32>,
 }
 
+#[derive(PartialEq)]
 pub struct DeviceChanWidthDistrInfo {
     pub x_distr: ChanWDist,
     pub y_distr: ChanWDist,
 }
 
+#[derive(PartialEq)]
 pub struct DeviceInfo {
     pub sizing: DeviceSizingInfo,
     pub connection_block: DeviceConnectionBlockInfo,
     pub area: DeviceAreaInfo,
     pub switch_block: DeviceSwitchBlockInfo,
     pub chan_width_distr: DeviceChanWidthDistrInfo,
-    // TODO: default_fc
-}
-
-#[derive(Debug)]
+    /// Fc used by sub tiles without an `<fc>` of their own. It has no
+    /// overrides.
+    pub default_fc: Option<SubTileFC>,
+}
+
+impl Default for DeviceInfo {
+    /// A Wilton switch block with uniform channels, using the 40 nm sizing of
+    /// the VTR flagship architectures. The connection block uses VTR's
+    /// delayless switch, which always exists.
+    fn default() -> Self {
+        Self {
+            sizing: DeviceSizingInfo {
+                r_min_w_nmos: 8926.0,
+                r_min_w_pmos: 16067.0,
+            },
+            connection_block: DeviceConnectionBlockInfo {
+                input_switch_name: "__vpr_delayless_switch__".to_string(),
+            },
+            area: DeviceAreaInfo {
+                grid_logic_tile_area: 0.0,
+            },
+            switch_block: DeviceSwitchBlockInfo {
+                sb_type: SBType::Wilton,
+                sb_fs: Some(3),
+            },
+            chan_width_distr: DeviceChanWidthDistrInfo {
+                x_distr: ChanWDist::Uniform(UniformChanWDist { peak: 1.0 }),
+                y_distr: ChanWDist::Uniform(UniformChanWDist { peak: 1.0 }),
+            },
+            default_fc: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub enum SwitchType {
     Mux,
     Tristate,
@@ -440,16 +569,19 @@ pub enum SwitchType {
     Buffer,
 }
 
+#[derive(PartialEq)]
 pub enum SwitchBufSize {
     Auto,
     Val(f32),
 }
 
+#[derive(PartialEq)]
 pub struct SwitchTDel {
     pub num_inputs: i32,
     pub delay: f32,
 }
 
+#[derive(PartialEq)]
 pub struct Switch {
     pub sw_type: SwitchType,
     pub name: String,
@@ -464,7 +596,7 @@ pub struct Switch {
     pub t_del_tags: Vec<SwitchTDel>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum SegmentAxis {
     X,
     Y,
@@ -472,17 +604,36 @@ pub enum SegmentAxis {
     Z,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum SegmentType {
     Bidir,
     Unidir,
 }
 
+/// Number of tiles a segment spans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SegmentLength {
+    Finite(i32),
+    /// `length="longline"`: the segment spans the whole device.
+    LongLine,
+}
+
+impl std::fmt::Display for SegmentLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentLength::Finite(length) => write!(f, "{length}"),
+            SegmentLength::LongLine => write!(f, "longline"),
+        }
+    }
+}
+
+#[derive(PartialEq)]
 pub enum SegmentResourceType {
     Gclk,
     General,
 }
 
+#[derive(PartialEq)]
 pub enum SegmentSwitchPoints {
     Unidir {
         mux_inc: String,
@@ -494,10 +645,11 @@ pub enum SegmentSwitchPoints {
     },
 }
 
+#[derive(PartialEq)]
 pub struct Segment {
     pub axis: SegmentAxis,
     pub name: String,
-    pub length: i32,
+    pub length: SegmentLength,
     pub segment_type: SegmentType,
     pub res_type: SegmentResourceType,
     pub freq: f32,
@@ -508,6 +660,7 @@ pub struct Segment {
     pub switch_points: SegmentSwitchPoints,
 }
 
+#[derive(PartialEq)]
 pub struct GlobalDirect {
     pub name: String,
     pub from_pin: String,
@@ -520,11 +673,13 @@ pub struct GlobalDirect {
     pub to_side: Option<PinSide>,
 }
 
+#[derive(PartialEq)]
 pub enum DelayType {
     Max,
     Min,
 }
 
+#[derive(PartialEq)]
 pub enum DelayInfo {
     Constant {
         min: f32,
@@ -542,12 +697,14 @@ pub enum DelayInfo {
     },
 }
 
+#[derive(PartialEq)]
 pub enum TimingConstraintType {
     Hold,
     Setup,
     ClockToQ,
 }
 
+#[derive(PartialEq)]
 pub struct TimingConstraintInfo {
     pub constraint_type: TimingConstraintType,
     // NOTE: Only ClockToQ can have two different min/max values right now.
@@ -558,18 +715,21 @@ pub struct TimingConstraintInfo {
     pub clock: String,
 }
 
+#[derive(PartialEq)]
 pub struct PackPattern {
     pub name: String,
     pub in_port: String,
     pub out_port: String,
 }
 
+#[derive(PartialEq)]
 pub enum InterconnectType {
     Complete,
     Direct,
     Mux,
 }
 
+#[derive(PartialEq)]
 pub struct Interconnect {
     pub name: String,
     pub input: String,
@@ -578,16 +738,22 @@ pub struct Interconnect {
     pub pack_patterns: Vec<PackPattern>,
     pub delays: Vec<DelayInfo>,
     pub metadata: Option<Vec<Metadata>>,
+    /// Line of the interconnect's tag.
+    pub source_line: SourceLine,
 }
 
+#[derive(PartialEq)]
 pub struct PBMode {
     pub name: String,
+    /// Set by `disable_packing="true"`: the packer never uses this mode, but
+    /// it is still part of the architecture.
+    pub disable_packing: bool,
     pub pb_types: Vec<PBType>,
     pub interconnects: Vec<Interconnect>,
     pub metadata: Option<Vec<Metadata>>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum PBTypeClass {
     None,
     Lut,
@@ -598,20 +764,172 @@ pub enum PBTypeClass {
     InterconnectComplete,
 }
 
+/// The BLIF model a primitive pb_type implements, from its `blif_model`
+/// attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlifModel {
+    /// `.names`: a LUT.
+    Names,
+    /// `.latch`: a flip-flop.
+    Latch,
+    /// `.input`: a primary input.
+    Input,
+    /// `.output`: a primary output.
+    Output,
+    /// `.subckt <model>`: a model defined in `<models>`.
+    Subckt(String),
+    /// A value without a recognized prefix, kept as written.
+    Other(String),
+}
+
+impl BlifModel {
+    /// Parses a `blif_model` attribute. Whitespace around the value and
+    /// between `.subckt` and the model name is ignored.
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value {
+            ".names" => BlifModel::Names,
+            ".latch" => BlifModel::Latch,
+            ".input" => BlifModel::Input,
+            ".output" => BlifModel::Output,
+            _ => match value.strip_prefix(".subckt") {
+                Some(model) if model.starts_with(char::is_whitespace) => {
+                    BlifModel::Subckt(model.trim_start().to_string())
+                }
+                _ => BlifModel::Other(value.to_string()),
+            },
+        }
+    }
+
+    /// The attribute value, e.g. ".names" or ".subckt adder".
+    pub fn raw(&self) -> String {
+        self.to_string()
+    }
+
+    /// Name of the `<models>` entry of a `.subckt` model.
+    pub fn subckt_name(&self) -> Option<&str> {
+        match self {
+            BlifModel::Subckt(model) => Some(model),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BlifModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlifModel::Names => write!(f, ".names"),
+            BlifModel::Latch => write!(f, ".latch"),
+            BlifModel::Input => write!(f, ".input"),
+            BlifModel::Output => write!(f, ".output"),
+            BlifModel::Subckt(model) => write!(f, ".subckt {model}"),
+            BlifModel::Other(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[derive(PartialEq)]
 pub struct PBType {
     pub name: String,
     pub num_pb: usize,
-    pub blif_model: Option<String>,
+    pub blif_model: Option<BlifModel>,
     pub class: PBTypeClass,
     pub ports: Vec<Port>,
     pub modes: Vec<PBMode>,
+    /// Set by `physical_mode_name`: the mode that describes the physical
+    /// implementation of this pb_type. Always names one of `modes`.
+    pub physical_mode_name: Option<String>,
     pub pb_types: Vec<PBType>,
     pub interconnects: Vec<Interconnect>,
     pub delays: Vec<DelayInfo>,
     pub timing_constraints: Vec<TimingConstraintInfo>,
     pub metadata: Option<Vec<Metadata>>,
-}
-
+    /// Line of the `<pb_type>` tag.
+    pub source_line: SourceLine,
+}
+
+impl Default for PBType {
+    /// A single, empty pb_type with no ports, modes or children.
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            num_pb: 1,
+            blif_model: None,
+            class: PBTypeClass::None,
+            ports: Vec::new(),
+            modes: Vec::new(),
+            physical_mode_name: None,
+            pb_types: Vec::new(),
+            interconnects: Vec::new(),
+            delays: Vec::new(),
+            timing_constraints: Vec::new(),
+            metadata: None,
+            source_line: SourceLine::default(),
+        }
+    }
+}
+
+impl PBType {
+    /// Total number of input pins across all input ports.
+    pub fn num_input_pins(&self) -> usize {
+        self.ports
+            .iter()
+            .filter_map(|p| match p {
+                Port::Input(ip) => Some(ip.num_pins),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Total number of output pins across all output ports.
+    pub fn num_output_pins(&self) -> usize {
+        self.ports
+            .iter()
+            .filter_map(|p| match p {
+                Port::Output(op) => Some(op.num_pins),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Total number of clock pins across all clock ports.
+    pub fn num_clock_pins(&self) -> usize {
+        self.ports
+            .iter()
+            .filter_map(|p| match p {
+                Port::Clock(cp) => Some(cp.num_pins),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Index of the mode marked as the physical mode, if any.
+    pub fn physical_mode_index(&self) -> Option<usize> {
+        let name = self.physical_mode_name.as_ref()?;
+        self.modes.iter().position(|mode| mode.name == *name)
+    }
+
+    /// Index of the mode to show when nothing else has been chosen: the
+    /// physical mode if one is marked, and otherwise the first.
+    pub fn default_mode_index(&self) -> usize {
+        self.physical_mode_index().unwrap_or(0)
+    }
+
+    /// Number of interconnects in the given mode. If this pb_type has no modes,
+    /// the mode index is ignored and its own interconnects are counted. Returns
+    /// 0 if the mode index is out of range.
+    pub fn num_interconnects(&self, mode_index: usize) -> usize {
+        if self.modes.is_empty() {
+            self.interconnects.len()
+        } else {
+            self.modes
+                .get(mode_index)
+                .map_or(0, |mode| mode.interconnects.len())
+        }
+    }
+}
+
+#[derive(PartialEq)]
 pub struct NoCRouterInfo {
     pub id: i32,
     pub position_x: f32,
@@ -620,10 +938,12 @@ pub struct NoCRouterInfo {
     pub connections: Vec<i32>,
 }
 
+#[derive(PartialEq)]
 pub struct NoCTopologyInfo {
     pub routers: Vec<NoCRouterInfo>,
 }
 
+#[derive(PartialEq)]
 pub struct NoCInfo {
     pub link_bandwidth: f32,
     pub link_latency: f32,
@@ -632,6 +952,7 @@ pub struct NoCInfo {
     pub topology: NoCTopologyInfo,
 }
 
+#[derive(Default)]
 pub struct FPGAArch {
     pub models: Vec<Model>,
     pub tiles: Vec<Tile>,
@@ -644,4 +965,7 @@ pub struct FPGAArch {
     pub complex_block_list: Vec<PBType>,
     pub complex_block_graphs: Vec<ComplexBlockGraph>,
     pub noc: Option<NoCInfo>,
+    /// Top-level sections that are recognized but not parsed (e.g. "power"),
+    /// in the order they appear in the file.
+    pub skipped_sections: Vec<String>,
 }
@@ -0,0 +1,93 @@
+use xml::common::TextPosition;
+
+use crate::parse_error::FPGAArchParseError;
+use crate::{
+    CustomSwitchBlock, DeviceInfo, FPGAArch, GlobalDirect, Layout, Model, NoCInfo, PBType,
+    ParseOptions, Segment, Switch, Tile, TileableLayoutConfig, finish_architecture,
+};
+
+/// Builds an `FPGAArch` programmatically instead of parsing it from a file.
+///
+/// Anything not added is left empty, and the device settings start from
+/// `DeviceInfo::default()`. `build` derives the complex block graphs and
+/// runs the same checks the parser runs once all tags are read, as a strict
+/// parse with the default `ParseOptions` would. Sub tiles without an Fc get
+/// the device's default Fc.
+#[derive(Default)]
+pub struct FPGAArchBuilder {
+    arch: FPGAArch,
+}
+
+impl FPGAArchBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_model(mut self, model: Model) -> Self {
+        self.arch.models.push(model);
+        self
+    }
+
+    pub fn add_tile(mut self, tile: Tile) -> Self {
+        self.arch.tiles.push(tile);
+        self
+    }
+
+    /// Adds a top-level complex block to the `<complexblocklist>`.
+    pub fn add_pb_type(mut self, pb_type: PBType) -> Self {
+        self.arch.complex_block_list.push(pb_type);
+        self
+    }
+
+    pub fn add_layout(mut self, layout: Layout) -> Self {
+        self.arch.layouts.layout_list.push(layout);
+        self
+    }
+
+    pub fn tileable_config(mut self, tileable_config: TileableLayoutConfig) -> Self {
+        self.arch.layouts.tileable_config = Some(tileable_config);
+        self
+    }
+
+    pub fn device(mut self, device: DeviceInfo) -> Self {
+        self.arch.device = device;
+        self
+    }
+
+    pub fn add_switch(mut self, switch: Switch) -> Self {
+        self.arch.switch_list.push(switch);
+        self
+    }
+
+    pub fn add_segment(mut self, segment: Segment) -> Self {
+        self.arch.segment_list.push(segment);
+        self
+    }
+
+    pub fn add_custom_switch_block(mut self, switch_block: CustomSwitchBlock) -> Self {
+        self.arch.custom_switch_blocks.push(switch_block);
+        self
+    }
+
+    pub fn add_direct(mut self, direct: GlobalDirect) -> Self {
+        self.arch.direct_list.push(direct);
+        self
+    }
+
+    pub fn noc(mut self, noc: NoCInfo) -> Self {
+        self.arch.noc = Some(noc);
+        self
+    }
+
+    pub fn build(mut self) -> Result<FPGAArch, FPGAArchParseError> {
+        // A strict parse only warns about missing routing, which a builder
+        // has no way to report, so those warnings are dropped.
+        finish_architecture(
+            &mut self.arch,
+            &ParseOptions::default(),
+            &mut Vec::new(),
+            TextPosition::new(),
+        )?;
+        Ok(self.arch)
+    }
+}
@@ -0,0 +1,88 @@
+//! Architecture Reader
+//!
+//! The parsers read events through an `ArchReader`, which keeps the stack of
+//! elements opened so far. When the document ends early, the elements still
+//! on the stack are the ones that were left unclosed.
+
+use std::io::Read;
+
+use xml::common::{Position, TextPosition};
+use xml::reader::{self, ErrorKind, EventReader, XmlEvent};
+
+use crate::parse_error::OpenElement;
+
+pub(crate) struct ArchReader<R: Read> {
+    events: EventReader<R>,
+    open_elements: Vec<OpenElement>,
+    ended_early: bool,
+}
+
+impl<R: Read> ArchReader<R> {
+    pub(crate) fn new(source: R) -> Self {
+        ArchReader {
+            events: EventReader::new(source),
+            open_elements: Vec::new(),
+            ended_early: false,
+        }
+    }
+
+    /// Pulls the next event, keeping track of the open elements.
+    pub(crate) fn next(&mut self) -> reader::Result<XmlEvent> {
+        let event = self.events.next();
+        match &event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => self.open_elements.push(OpenElement {
+                tag: name.local_name.clone(),
+                name: attributes
+                    .iter()
+                    .find(|a| a.name.local_name == "name")
+                    .map(|a| a.value.clone()),
+                position: self.events.position(),
+            }),
+            Ok(XmlEvent::EndElement { .. }) => {
+                self.open_elements.pop();
+            }
+            // xml-rs reports a missing end tag at the end of the input as a
+            // syntax error rather than a distinct kind.
+            Err(e) => {
+                self.ended_early |= match e.kind() {
+                    ErrorKind::UnexpectedEof => true,
+                    ErrorKind::Syntax(msg) => msg.starts_with("Unexpected end of stream"),
+                    _ => false,
+                };
+            }
+            Ok(_) => {}
+        }
+        event
+    }
+
+    /// Skips the rest of the element that was just started.
+    pub(crate) fn skip(&mut self) -> reader::Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                XmlEvent::EndDocument => {
+                    self.ended_early = true;
+                    return Err(ErrorKind::UnexpectedEof.into());
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The elements left open, from the outermost in, if the document ended
+    /// before they were closed.
+    pub(crate) fn unclosed_elements(self) -> Option<Vec<OpenElement>> {
+        (self.ended_early && !self.open_elements.is_empty()).then_some(self.open_elements)
+    }
+}
+
+impl<R: Read> Position for ArchReader<R> {
+    fn position(&self) -> TextPosition {
+        self.events.position()
+    }
+}
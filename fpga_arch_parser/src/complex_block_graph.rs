@@ -239,7 +239,7 @@ fn add_pb_type_recursive(
             .blif_model
             .as_ref()
             .map(|blif_model| ComplexBlockPrimitiveInfo {
-                blif_model: blif_model.clone(),
+                blif_model: blif_model.raw(),
                 class: pb_type.class.clone(),
             });
 
@@ -525,3 +525,126 @@ fn build_interconnect_node_and_nets(
 
     Ok((node_id, nets))
 }
+
+impl ComplexBlockGraph {
+    /// Writes the graph in Graphviz DOT format.
+    ///
+    /// Every mode is drawn as a cluster containing its child blocks and
+    /// interconnect nodes. Nets between the same pair of ports are merged into a
+    /// single edge labelled with the number of pins it carries.
+    pub fn to_dot(&self) -> String {
+        let root = &self.complex_block_nodes[self.root_complex_block_node];
+        let mut out = String::new();
+        out.push_str(&format!("digraph \"{}\" {{\n", escape_dot(&root.name)));
+        out.push_str("    rankdir=LR;\n");
+        out.push_str("    node [shape=box];\n");
+        self.write_dot_node(self.root_complex_block_node, &root.name, 1, &mut out);
+
+        // Merge the per-pin nets into one edge per (driver port, sink port) pair.
+        let mut edges: Vec<(ComplexBlockPortId, ComplexBlockPortId, usize)> = Vec::new();
+        let mut edge_index: HashMap<(ComplexBlockPortId, ComplexBlockPortId), usize> =
+            HashMap::new();
+        for mode in &self.complex_block_modes {
+            for net in &mode.interconnect {
+                let (Some(&driver), Some(&sink)) = (net.pins.first(), net.pins.last()) else {
+                    continue;
+                };
+                let key = (
+                    self.complex_block_pins[driver].parent_port,
+                    self.complex_block_pins[sink].parent_port,
+                );
+                match edge_index.get(&key) {
+                    Some(&i) => edges[i].2 += 1,
+                    None => {
+                        edge_index.insert(key, edges.len());
+                        edges.push((key.0, key.1, 1));
+                    }
+                }
+            }
+        }
+        for (from_port, to_port, num_pins) in edges {
+            let from_port = &self.complex_block_ports[from_port];
+            let to_port = &self.complex_block_ports[to_port];
+            let mut label = format!("{} -> {}", from_port.name, to_port.name);
+            if num_pins > 1 {
+                label.push_str(&format!(" (x{num_pins})"));
+            }
+            out.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                from_port.parent_complex_block.0,
+                to_port.parent_complex_block.0,
+                escape_dot(&label)
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    fn write_dot_node(
+        &self,
+        node_id: ComplexBlockNodeId,
+        instance_name: &str,
+        depth: usize,
+        out: &mut String,
+    ) {
+        let indent = "    ".repeat(depth);
+        let node = &self.complex_block_nodes[node_id];
+        let (label, shape) = match &node.primitive_info {
+            Some(info) => match info.class {
+                PBTypeClass::InterconnectDirect
+                | PBTypeClass::InterconnectMux
+                | PBTypeClass::InterconnectComplete => (instance_name.to_string(), "ellipse"),
+                _ => (format!("{instance_name}\n{}", info.blif_model), "box"),
+            },
+            None => (instance_name.to_string(), "box"),
+        };
+        out.push_str(&format!(
+            "{indent}n{} [label=\"{}\", shape={shape}];\n",
+            node_id.0,
+            escape_dot(&label)
+        ));
+
+        for &mode_id in &node.modes {
+            let mode = &self.complex_block_modes[mode_id];
+            out.push_str(&format!("{indent}subgraph cluster_m{} {{\n", mode_id.0));
+            out.push_str(&format!(
+                "{indent}    label=\"{}\";\n",
+                escape_dot(&mode.name)
+            ));
+
+            // Children instantiated num_pb times share a name, so index them.
+            let mut num_seen: HashMap<&str, usize> = HashMap::new();
+            for &child_id in &mode.children_complex_blocks {
+                let child = &self.complex_block_nodes[child_id];
+                let is_interconnect = child.modes.is_empty()
+                    && child.primitive_info.as_ref().is_some_and(|info| {
+                        matches!(
+                            info.class,
+                            PBTypeClass::InterconnectDirect
+                                | PBTypeClass::InterconnectMux
+                                | PBTypeClass::InterconnectComplete
+                        )
+                    });
+                let child_name = if is_interconnect {
+                    child.name.clone()
+                } else {
+                    let index = num_seen.entry(child.name.as_str()).or_default();
+                    let name = format!("{}[{}]", child.name, index);
+                    *index += 1;
+                    name
+                };
+                self.write_dot_node(child_id, &child_name, depth + 1, out);
+            }
+
+            out.push_str(&format!("{indent}}}\n"));
+        }
+    }
+}
+
+// Escapes a string for use inside a double-quoted DOT identifier.
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
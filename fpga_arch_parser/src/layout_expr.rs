@@ -0,0 +1,288 @@
+//! Layout Expressions
+//!
+//! Grid location attributes such as `startx` or `y` hold formulas in terms of
+//! the device size (`W`, `H`) and the tile size (`w`, `h`), e.g. `W/2 - 1`.
+//! They are evaluated once the grid is built, and evaluated with a stand-in
+//! device while the file is parsed to catch typos early.
+
+use std::fmt;
+
+/// Functions VTR's formula evaluator accepts.
+const FUNCTIONS: &[&str] = &["min", "max", "gcd", "lcm"];
+
+/// Variables a layout expression may refer to.
+const VARIABLES: &[&str] = &["W", "H", "w", "h"];
+
+/// Binary operators, with the two-character ones first so they are matched
+/// before their prefixes.
+const OPERATORS: &[&str] = &[
+    "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "<", ">",
+];
+
+/// How tightly a binary operator binds, as in VTR's formula evaluator.
+fn precedence(op: &str) -> u8 {
+    match op {
+        "||" => 1,
+        "&&" => 2,
+        "<=" | ">=" | "==" | "!=" | "<" | ">" => 3,
+        "+" | "-" => 4,
+        _ => 5,
+    }
+}
+
+/// The values the variables of a layout expression stand for.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LayoutExprVars {
+    /// `W` and `H`, or None where the device size is not known yet.
+    pub device_size: Option<(i64, i64)>,
+    /// `w` and `h`.
+    pub tile_size: (i64, i64),
+}
+
+/// Why a layout expression has no value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutExprError {
+    /// The expression is malformed, whatever its variables are.
+    Syntax(String),
+    /// The expression is well formed but has no value for these variables,
+    /// e.g. it divides by zero or needs a device size that is not known.
+    Undefined(String),
+}
+
+impl fmt::Display for LayoutExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutExprError::Syntax(msg) | LayoutExprError::Undefined(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Number(i64),
+    Name(&'a str),
+    Operator(&'a str),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expr.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..len]
+                .parse()
+                .map_err(|_| format!("number '{}' is too large", &rest[..len]))?;
+            tokens.push(Token::Number(value));
+            len
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(&rest[..len]));
+            len
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push(Token::Operator(op));
+            op.len()
+        } else {
+            tokens.push(match c {
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                _ => return Err(format!("unexpected character '{c}'")),
+            });
+            1
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens of an expression, evaluating it as it
+/// goes. A value that is undefined for these variables is taken as 0 so the
+/// rest of the expression is still checked, and reported at the end.
+struct Evaluator<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    vars: &'a LayoutExprVars,
+    undefined: Option<String>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token<'a>> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    fn undefined(&mut self, reason: impl FnOnce() -> String) -> i64 {
+        self.undefined.get_or_insert_with(reason);
+        0
+    }
+
+    /// expr := operand (operator operand)*, with operators of at least
+    /// `min_precedence`.
+    fn expr(&mut self, min_precedence: u8) -> Result<i64, String> {
+        let mut value = self.operand()?;
+        while let Some(&Token::Operator(op)) = self.peek() {
+            if precedence(op) < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            if self.peek().is_none() {
+                return Err(format!("expected a value after '{op}'"));
+            }
+            let rhs = self.expr(precedence(op) + 1)?;
+            value = self.apply(op, value, rhs);
+        }
+        Ok(value)
+    }
+
+    fn apply(&mut self, op: &str, lhs: i64, rhs: i64) -> i64 {
+        let value = match op {
+            "+" => lhs.checked_add(rhs),
+            "-" => lhs.checked_sub(rhs),
+            "*" => lhs.checked_mul(rhs),
+            "/" => lhs.checked_div(rhs),
+            "%" => lhs.checked_rem(rhs),
+            "<" => Some((lhs < rhs).into()),
+            ">" => Some((lhs > rhs).into()),
+            "<=" => Some((lhs <= rhs).into()),
+            ">=" => Some((lhs >= rhs).into()),
+            "==" => Some((lhs == rhs).into()),
+            "!=" => Some((lhs != rhs).into()),
+            "&&" => Some((lhs != 0 && rhs != 0).into()),
+            _ => Some((lhs != 0 || rhs != 0).into()),
+        };
+        value.unwrap_or_else(|| self.undefined(|| format!("{lhs} {op} {rhs} has no value")))
+    }
+
+    /// operand := '-'? (number | variable | function '(' args ')' | '(' expr ')')
+    fn operand(&mut self) -> Result<i64, String> {
+        if self.peek() == Some(&Token::Operator("-")) {
+            self.pos += 1;
+            let value = self.operand()?;
+            return Ok(value
+                .checked_neg()
+                .unwrap_or_else(|| self.undefined(|| format!("-{value} has no value"))));
+        }
+        match self.next() {
+            Some(&Token::Number(value)) => Ok(value),
+            Some(&Token::Name(name)) if VARIABLES.contains(&name) => Ok(self.variable(name)),
+            Some(&Token::Name(name)) if FUNCTIONS.contains(&name) => {
+                if self.next() != Some(&Token::Open) {
+                    return Err(format!("expected '(' after '{name}'"));
+                }
+                let mut value = self.expr(0)?;
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    let arg = self.expr(0)?;
+                    value = self.call(name, value, arg);
+                }
+                self.close()?;
+                Ok(value)
+            }
+            Some(Token::Name(name)) => Err(format!(
+                "unknown name '{name}' (expected one of {})",
+                VARIABLES.join(", ")
+            )),
+            Some(Token::Open) => {
+                let value = self.expr(0)?;
+                self.close()?;
+                Ok(value)
+            }
+            Some(Token::Operator(op)) => Err(format!("unexpected '{op}'")),
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(Token::Comma) => Err("unexpected ','".to_string()),
+            None => Err("expected a value".to_string()),
+        }
+    }
+
+    fn variable(&mut self, name: &str) -> i64 {
+        let (tile_width, tile_height) = self.vars.tile_size;
+        match (name, self.vars.device_size) {
+            ("w", _) => tile_width,
+            ("h", _) => tile_height,
+            ("W", Some((width, _))) => width,
+            ("H", Some((_, height))) => height,
+            _ => self.undefined(|| format!("'{name}' is not known before the device size")),
+        }
+    }
+
+    /// Folds one more argument into a function's value.
+    fn call(&mut self, name: &str, value: i64, arg: i64) -> i64 {
+        match name {
+            "min" => value.min(arg),
+            "max" => value.max(arg),
+            "gcd" => gcd(value, arg),
+            _ => match gcd(value, arg) {
+                0 => 0,
+                divisor => (value / divisor).checked_mul(arg).map_or_else(
+                    || self.undefined(|| format!("lcm({value}, {arg}) has no value")),
+                    i64::abs,
+                ),
+            },
+        }
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Close) => Ok(()),
+            _ => Err("missing ')'".to_string()),
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    i64::try_from(a).unwrap_or(i64::MAX)
+}
+
+/// Evaluates a layout expression for the given variables.
+pub fn eval_layout_expr(expr: &str, vars: &LayoutExprVars) -> Result<i64, LayoutExprError> {
+    let mut evaluator = Evaluator {
+        tokens: tokenize(expr).map_err(LayoutExprError::Syntax)?,
+        pos: 0,
+        vars,
+        undefined: None,
+    };
+    let value = evaluator.expr(0).map_err(LayoutExprError::Syntax)?;
+    match evaluator.peek() {
+        None => {}
+        Some(Token::Close) => return Err(LayoutExprError::Syntax("unmatched ')'".to_string())),
+        Some(_) => {
+            return Err(LayoutExprError::Syntax(
+                "expected an operator between values".to_string(),
+            ));
+        }
+    }
+    match evaluator.undefined {
+        Some(reason) => Err(LayoutExprError::Undefined(reason)),
+        None => Ok(value),
+    }
+}
+
+/// Checks that `expr` is a well-formed layout expression by evaluating it
+/// for a stand-in device, returning what is wrong with it otherwise.
+pub(crate) fn check_layout_expr(expr: &str) -> Result<(), String> {
+    let vars = LayoutExprVars {
+        device_size: Some((100, 100)),
+        tile_size: (1, 1),
+    };
+    match eval_layout_expr(expr, &vars) {
+        Err(LayoutExprError::Syntax(e)) => Err(e),
+        // Dividing by zero, say, may only happen for this stand-in size.
+        Ok(_) | Err(LayoutExprError::Undefined(_)) => Ok(()),
+    }
+}
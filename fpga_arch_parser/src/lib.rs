@@ -1,14 +1,18 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader, Cursor};
+use std::io::{BufRead, Cursor, Read};
 use std::path::Path;
 
 use xml::attribute::OwnedAttribute;
-use xml::common::Position;
+use xml::common::{Position, TextPosition};
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 mod arch;
+mod arch_builder;
+mod arch_reader;
 mod complex_block_graph;
+mod layout_expr;
+mod outline;
 mod parse_complex_block_list;
 mod parse_custom_switch_blocks;
 mod parse_device;
@@ -18,23 +22,37 @@ mod parse_layouts;
 mod parse_metadata;
 mod parse_models;
 mod parse_noc;
+mod parse_options;
 mod parse_port;
 mod parse_segment_list;
+mod parse_streaming;
 mod parse_switch_list;
 mod parse_tiles;
 mod parse_timing;
+mod parse_warning;
+pub mod prelude;
 mod tile_pin_mapper;
+mod validate_arch;
 mod verify_noc;
+mod xinclude;
 
 pub use crate::arch::*;
+pub use crate::arch_builder::FPGAArchBuilder;
+use crate::arch_reader::ArchReader;
 use crate::complex_block_graph::build_complex_block_graph;
 pub use crate::complex_block_graph::{
     ComplexBlockGraph, ComplexBlockMode, ComplexBlockModeId, ComplexBlockNet, ComplexBlockNode,
     ComplexBlockNodeId, ComplexBlockPin, ComplexBlockPinId, ComplexBlockPort, ComplexBlockPortId,
     ComplexBlockPrimitiveInfo,
 };
-pub use crate::parse_error::FPGAArchParseError;
+pub use crate::layout_expr::{LayoutExprError, LayoutExprVars, eval_layout_expr};
+pub use crate::parse_error::{FPGAArchParseError, OpenElement};
+pub use crate::parse_options::ParseOptions;
+pub use crate::parse_streaming::{ArchEvent, parse_streaming};
+pub use crate::parse_warning::ParseWarning;
 pub use crate::tile_pin_mapper::*;
+pub use crate::validate_arch::{RESERVED_SWITCH_NAMES, ValidationWarning, validate_architecture};
+pub use crate::xinclude::IncludeMap;
 
 use crate::parse_complex_block_list::parse_complex_block_list;
 use crate::parse_custom_switch_blocks::parse_switchblocklist;
@@ -45,13 +63,90 @@ use crate::parse_models::parse_models;
 use crate::parse_noc::parse_noc;
 use crate::parse_segment_list::parse_segment_list;
 use crate::parse_switch_list::parse_switch_list;
-use crate::parse_tiles::parse_tiles;
+use crate::parse_tiles::{apply_default_fc, parse_tiles, unresolved_site_references};
 use crate::verify_noc::verify_noc;
 
+/// The routing elements VTR needs at least one of but that the lists leave
+/// out.
+fn missing_routing_tags(
+    switch_list: &[Switch],
+    segment_list: &[Segment],
+) -> impl Iterator<Item = &'static str> {
+    [
+        (switch_list.is_empty(), "<switch> in <switchlist>"),
+        (segment_list.is_empty(), "<segment> in <segmentlist>"),
+    ]
+    .into_iter()
+    .filter_map(|(missing, tag)| missing.then_some(tag))
+}
+
+/// The checks and derived data that need the whole architecture, shared by
+/// the parser and `FPGAArchBuilder`. Fills in the default Fc of sub tiles
+/// without one and builds the complex block graphs. `position` is where NoC
+/// errors are reported.
+pub(crate) fn finish_architecture(
+    arch: &mut FPGAArch,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+    position: TextPosition,
+) -> Result<(), FPGAArchParseError> {
+    let unresolved_sites = unresolved_site_references(&arch.tiles, &arch.complex_block_list);
+    let missing_fc = apply_default_fc(&mut arch.tiles, &arch.device);
+    if options.lenient {
+        // Lenient parsing lets these through, but they are still worth knowing
+        // about.
+        for reference in unresolved_sites {
+            warnings.push(ParseWarning {
+                message: reference,
+                position: None,
+            });
+        }
+        for tag in missing_fc {
+            warnings.push(ParseWarning {
+                message: format!("Missing {tag}; the sub tile has no Fc"),
+                position: None,
+            });
+        }
+    } else {
+        if let Some(reference) = unresolved_sites.into_iter().next() {
+            return Err(FPGAArchParseError::UnresolvedPBTypeReference(reference));
+        }
+        if let Some(tag) = missing_fc.into_iter().next() {
+            return Err(FPGAArchParseError::MissingRequiredTag(tag));
+        }
+    }
+
+    if options.require_routing {
+        if let Some(tag) = missing_routing_tags(&arch.switch_list, &arch.segment_list).next() {
+            return Err(FPGAArchParseError::MissingRequiredTag(tag.to_string()));
+        }
+    } else {
+        for tag in missing_routing_tags(&arch.switch_list, &arch.segment_list) {
+            warnings.push(ParseWarning {
+                message: format!("Missing {tag}; the device cannot be routed without one"),
+                position: None,
+            });
+        }
+    }
+
+    if let Some(noc_info) = &arch.noc {
+        verify_noc(noc_info, &arch.tiles, position)?;
+    }
+
+    arch.complex_block_graphs = arch
+        .complex_block_list
+        .iter()
+        .map(build_complex_block_graph)
+        .collect::<Result<_, _>>()?;
+    Ok(())
+}
+
 fn parse_architecture<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<FPGAArch, FPGAArchParseError> {
     assert!(name.to_string() == "architecture");
     if !attributes.is_empty() {
@@ -71,6 +166,7 @@ fn parse_architecture<R: BufRead>(
     let mut direct_list: Option<Vec<GlobalDirect>> = None;
     let mut complex_block_list: Option<Vec<PBType>> = None;
     let mut noc: Option<NoCInfo> = None;
+    let mut skipped_sections: Vec<String> = Vec::new();
 
     loop {
         match parser.next() {
@@ -91,7 +187,9 @@ fn parse_architecture<R: BufRead>(
                     }
                     "tiles" => {
                         tiles = match tiles {
-                            None => Some(parse_tiles(&name, &attributes, parser)?),
+                            None => {
+                                Some(parse_tiles(&name, &attributes, parser, options, warnings)?)
+                            }
                             Some(_) => {
                                 return Err(FPGAArchParseError::DuplicateTag(
                                     format!("<{name}>"),
@@ -113,7 +211,7 @@ fn parse_architecture<R: BufRead>(
                     }
                     "device" => {
                         device = match device {
-                            None => Some(parse_device(&name, &attributes, parser)?),
+                            None => Some(parse_device(&name, &attributes, parser, warnings)?),
                             Some(_) => {
                                 return Err(FPGAArchParseError::DuplicateTag(
                                     format!("<{name}>"),
@@ -168,7 +266,12 @@ fn parse_architecture<R: BufRead>(
                     }
                     "complexblocklist" => {
                         complex_block_list = match complex_block_list {
-                            None => Some(parse_complex_block_list(&name, &attributes, parser)?),
+                            None => Some(parse_complex_block_list(
+                                &name,
+                                &attributes,
+                                parser,
+                                warnings,
+                            )?),
                             Some(_) => {
                                 return Err(FPGAArchParseError::DuplicateTag(
                                     format!("<{name}>"),
@@ -188,19 +291,15 @@ fn parse_architecture<R: BufRead>(
                             }
                         }
                     }
-                    "power" => {
-                        // TODO: Implement.
-                        // FIXME: Check that this is documented in VTR.
-                        let _ = parser.skip();
-                    }
-                    "clocks" => {
-                        // TODO: Implement.
-                        // FIXME: Check that this is documented in VTR.
-                        let _ = parser.skip();
-                    }
-                    "scatter_gather_list" => {
+                    "power" | "clocks" | "scatter_gather_list" => {
                         // TODO: Implement.
+                        // FIXME: Check that power and clocks are documented in VTR.
+                        warnings.push(ParseWarning::at(
+                            format!("<{name}> is not supported yet and was skipped"),
+                            parser.position(),
+                        ));
                         let _ = parser.skip();
+                        skipped_sections.push(name.to_string());
                     }
                     _ => {
                         return Err(FPGAArchParseError::InvalidTag(
@@ -294,16 +393,7 @@ fn parse_architecture<R: BufRead>(
     let custom_switch_blocks = custom_switch_blocks.unwrap_or_default();
     let direct_list = direct_list.unwrap_or_default();
 
-    if let Some(noc_info) = &noc {
-        verify_noc(noc_info, &tiles, parser.position())?;
-    }
-
-    let mut complex_block_graphs = Vec::with_capacity(complex_block_list.len());
-    for root_pb_type in &complex_block_list {
-        complex_block_graphs.push(build_complex_block_graph(root_pb_type)?);
-    }
-
-    Ok(FPGAArch {
+    let mut arch = FPGAArch {
         models,
         tiles,
         layouts,
@@ -313,12 +403,19 @@ fn parse_architecture<R: BufRead>(
         custom_switch_blocks,
         direct_list,
         complex_block_list,
-        complex_block_graphs,
+        complex_block_graphs: Vec::new(),
         noc,
-    })
+        skipped_sections,
+    };
+    finish_architecture(&mut arch, options, warnings, parser.position())?;
+    Ok(arch)
 }
 
-fn parse_file<R: BufRead>(mut parser: EventReader<R>) -> Result<FPGAArch, FPGAArchParseError> {
+fn parse_file<R: BufRead>(
+    parser: &mut ArchReader<R>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<FPGAArch, FPGAArchParseError> {
     // Parse the top-level tags.
     // At the top-level, we only expect the architecture tag.
     let mut arch: Option<FPGAArch> = None;
@@ -335,7 +432,13 @@ fn parse_file<R: BufRead>(mut parser: EventReader<R>) -> Result<FPGAArch, FPGAAr
                                 parser.position(),
                             ));
                         }
-                        arch = Some(parse_architecture(&name, &attributes, &mut parser)?);
+                        arch = Some(parse_architecture(
+                            &name,
+                            &attributes,
+                            parser,
+                            options,
+                            warnings,
+                        )?);
                     }
                     _ => {
                         return Err(FPGAArchParseError::InvalidTag(
@@ -375,28 +478,117 @@ fn parse_file<R: BufRead>(mut parser: EventReader<R>) -> Result<FPGAArch, FPGAAr
     }
 }
 
+/// Parses an architecture description file into an `FPGAArch`.
+///
+/// This is the default entry point. For files too large to hold in memory,
+/// see `parse_streaming`.
 pub fn parse(arch_file: &Path) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_with_options(arch_file, &ParseOptions::default())
+}
+
+pub fn parse_with_options(
+    arch_file: &Path,
+    options: &ParseOptions,
+) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_with_warnings(arch_file, options).map(|(arch, _)| arch)
+}
+
+/// Like `parse_with_options`, but also returns the problems the parser worked
+/// around instead of failing on, such as skipped sections.
+pub fn parse_with_warnings(
+    arch_file: &Path,
+    options: &ParseOptions,
+) -> Result<(FPGAArch, Vec<ParseWarning>), FPGAArchParseError> {
+    let data = read_arch_source(arch_file)?;
+
+    // Begin parsing the file.
+    parse_from_bytes_with_warnings(&data, options)
+}
+
+/// Reads the document `parse` would parse from `arch_file`: the file itself,
+/// with any XIncludes merged in. The `source_line`s of the parsed elements
+/// are lines of this document.
+pub fn read_arch_source(arch_file: &Path) -> Result<Vec<u8>, FPGAArchParseError> {
+    read_arch_source_with_map(arch_file).map(|(data, _)| data)
+}
+
+/// Like `read_arch_source`, but also returns where each line of the document
+/// was read from, for reporting positions in files split up with XInclude.
+pub fn read_arch_source_with_map(
+    arch_file: &Path,
+) -> Result<(Vec<u8>, IncludeMap), FPGAArchParseError> {
     // Try to open the file.
     let file = File::open(arch_file);
-    let file = match file {
+    let mut file = match file {
         Ok(f) => f,
         Err(error) => return Err(FPGAArchParseError::ArchFileOpenError(format!("{error:?}"))),
     };
+    let mut data = Vec::new();
+    if let Err(error) = file.read_to_end(&mut data) {
+        return Err(FPGAArchParseError::ArchFileOpenError(format!("{error:?}")));
+    }
 
-    // Create an XML event reader.
-    // Buffering is used for performance.
-    let file = BufReader::new(file);
-    let parser = EventReader::new(file);
-
-    // Begin parsing the file.
-    parse_file(parser)
+    // Files split up with XInclude are merged into one document first.
+    if xinclude::may_contain_includes(&data) {
+        return xinclude::resolve_includes(arch_file);
+    }
+    Ok((data, IncludeMap::identity(arch_file)))
 }
 
 pub fn parse_from_bytes(data: &[u8]) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_from_bytes_with_options(data, &ParseOptions::default())
+}
+
+pub fn parse_from_bytes_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_from_bytes_with_warnings(data, options).map(|(arch, _)| arch)
+}
+
+/// Like `parse_from_bytes_with_options`, but also returns the problems the
+/// parser worked around instead of failing on.
+pub fn parse_from_bytes_with_warnings(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<(FPGAArch, Vec<ParseWarning>), FPGAArchParseError> {
     // Create a cursor from the byte slice for in-memory reading.
-    let cursor = Cursor::new(data);
-    let parser = EventReader::new(cursor);
+    parse_from_reader_with_warnings(Cursor::new(data), options)
+}
+
+/// Parses an architecture description read from any buffered source, such as
+/// a network response.
+pub fn parse_from_reader<R: BufRead>(reader: R) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_from_reader_with_options(reader, &ParseOptions::default())
+}
+
+pub fn parse_from_reader_with_options<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> Result<FPGAArch, FPGAArchParseError> {
+    parse_from_reader_with_warnings(reader, options).map(|(arch, _)| arch)
+}
+
+/// Like `parse_from_reader_with_options`, but also returns the problems the
+/// parser worked around instead of failing on.
+pub fn parse_from_reader_with_warnings<R: BufRead>(
+    reader: R,
+    options: &ParseOptions,
+) -> Result<(FPGAArch, Vec<ParseWarning>), FPGAArchParseError> {
+    let mut parser = ArchReader::new(reader);
 
     // Begin parsing the file.
-    parse_file(parser)
+    let mut warnings = Vec::new();
+    match parse_file(&mut parser, options, &mut warnings) {
+        Ok(arch) => Ok((arch, warnings)),
+        // A truncated document is reported against the elements it leaves
+        // open.
+        Err(
+            e @ (FPGAArchParseError::XMLParseError(..)
+            | FPGAArchParseError::UnexpectedEndOfDocument(..)),
+        ) => Err(parser
+            .unclosed_elements()
+            .map_or(e, FPGAArchParseError::UnclosedElements)),
+        Err(e) => Err(e),
+    }
 }
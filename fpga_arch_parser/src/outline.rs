@@ -0,0 +1,328 @@
+//! Text Outline
+//!
+//! Writes an architecture as an indented tree, in the style of `tree`, for
+//! people to read, grep and paste into bug reports. It is not meant to be
+//! parsed back.
+
+use crate::arch::*;
+
+/// A line of the outline along with the lines nested under it.
+struct OutlineNode {
+    text: String,
+    children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            children: Vec::new(),
+        }
+    }
+
+    fn with_children(text: impl Into<String>, children: Vec<OutlineNode>) -> Self {
+        Self {
+            text: text.into(),
+            children,
+        }
+    }
+
+    /// A heading with the number of entries under it, e.g. "tiles (3)".
+    fn section(heading: &str, children: Vec<OutlineNode>) -> Self {
+        Self::with_children(format!("{heading} ({})", children.len()), children)
+    }
+
+    fn write_children(&self, prefix: &str, out: &mut String) {
+        for (i, child) in self.children.iter().enumerate() {
+            let is_last = i + 1 == self.children.len();
+            let (branch, continuation) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            out.push_str(&format!("{prefix}{branch}{}\n", child.text));
+            child.write_children(&format!("{prefix}{continuation}"), out);
+        }
+    }
+}
+
+impl FPGAArch {
+    /// Writes the architecture as an indented text tree: its models, tiles,
+    /// layouts, device, switches, segments, directs and the pb_type hierarchy
+    /// of every complex block.
+    pub fn to_outline(&self) -> String {
+        let root = OutlineNode::with_children(
+            "architecture",
+            vec![
+                OutlineNode::section("models", self.models.iter().map(model_node).collect()),
+                OutlineNode::section("tiles", self.tiles.iter().map(tile_node).collect()),
+                OutlineNode::section(
+                    "layouts",
+                    self.layouts.layout_list.iter().map(layout_node).collect(),
+                ),
+                device_node(&self.device),
+                OutlineNode::section(
+                    "switches",
+                    self.switch_list.iter().map(switch_node).collect(),
+                ),
+                OutlineNode::section(
+                    "segments",
+                    self.segment_list.iter().map(segment_node).collect(),
+                ),
+                OutlineNode::section(
+                    "directs",
+                    self.direct_list.iter().map(direct_node).collect(),
+                ),
+                OutlineNode::section(
+                    "complex blocks",
+                    self.complex_block_list.iter().map(pb_type_node).collect(),
+                ),
+            ],
+        );
+
+        let mut out = format!("{}\n", root.text);
+        root.write_children("", &mut out);
+        out
+    }
+}
+
+fn model_node(model: &Model) -> OutlineNode {
+    let ports = model
+        .input_ports
+        .iter()
+        .map(|port| OutlineNode::new(format!("input {}", port.name)))
+        .chain(
+            model
+                .output_ports
+                .iter()
+                .map(|port| OutlineNode::new(format!("output {}", port.name))),
+        )
+        .collect();
+    OutlineNode::with_children(&model.name, ports)
+}
+
+fn port_node(port: &Port) -> OutlineNode {
+    let (direction, name, num_pins) = match port {
+        Port::Input(p) => ("input", &p.name, p.num_pins),
+        Port::Output(p) => ("output", &p.name, p.num_pins),
+        Port::Clock(p) => ("clock", &p.name, p.num_pins),
+    };
+    OutlineNode::new(format!("{direction} {name}[{num_pins}]"))
+}
+
+fn tile_node(tile: &Tile) -> OutlineNode {
+    let mut children: Vec<OutlineNode> = tile.ports.iter().map(port_node).collect();
+    for sub_tile in &tile.sub_tiles {
+        let sites: Vec<&str> = sub_tile
+            .equivalent_sites
+            .iter()
+            .map(|site| site.pb_type.as_str())
+            .collect();
+        let mut sub_tile_children = vec![OutlineNode::new(format!("sites: {}", sites.join(", ")))];
+        sub_tile_children.extend(sub_tile.ports.iter().map(port_node));
+        children.push(OutlineNode::with_children(
+            format!(
+                "sub_tile {} (capacity {})",
+                sub_tile.name, sub_tile.capacity
+            ),
+            sub_tile_children,
+        ));
+    }
+    OutlineNode::with_children(
+        format!("{} ({}x{})", tile.name, tile.width, tile.height),
+        children,
+    )
+}
+
+fn layout_node(layout: &Layout) -> OutlineNode {
+    let (text, layers) = match layout {
+        Layout::AutoLayout(auto_layout) => (
+            format!("auto_layout (aspect_ratio {})", auto_layout.aspect_ratio),
+            &auto_layout.layers,
+        ),
+        Layout::FixedLayout(fixed_layout) => (
+            format!(
+                "fixed_layout {} ({}x{})",
+                fixed_layout.name, fixed_layout.width, fixed_layout.height
+            ),
+            &fixed_layout.layers,
+        ),
+    };
+    let layer_node = |layer: &Layer| -> Vec<OutlineNode> {
+        layer
+            .grid_locations
+            .iter()
+            .map(grid_location_node)
+            .collect()
+    };
+    // Single-layer layouts list their locations directly.
+    let children = match layers.as_slice() {
+        [layer] => layer_node(layer),
+        _ => layers
+            .iter()
+            .map(|layer| {
+                OutlineNode::with_children(format!("layer die={}", layer.die), layer_node(layer))
+            })
+            .collect(),
+    };
+    OutlineNode::with_children(text, children)
+}
+
+fn grid_location_node(location: &GridLocation) -> OutlineNode {
+    let text = match location {
+        GridLocation::Fill(fill) => format!("fill {} (priority {})", fill.pb_type, fill.priority),
+        GridLocation::Perimeter(perimeter) => format!(
+            "perimeter {} (priority {})",
+            perimeter.pb_type, perimeter.priority
+        ),
+        GridLocation::Corners(corners) => format!(
+            "corners {} (priority {})",
+            corners.pb_type, corners.priority
+        ),
+        GridLocation::Single(single) => format!(
+            "single {} x={} y={} (priority {})",
+            single.pb_type, single.x_expr, single.y_expr, single.priority
+        ),
+        GridLocation::Col(col) => {
+            let mut text = format!("col {} startx={}", col.pb_type, col.start_x_expr);
+            if let Some(repeat_x) = &col.repeat_x_expr {
+                text.push_str(&format!(" repeatx={repeat_x}"));
+            }
+            text.push_str(&format!(
+                " starty={} incry={} (priority {})",
+                col.start_y_expr, col.incr_y_expr, col.priority
+            ));
+            text
+        }
+        GridLocation::Row(row) => {
+            let mut text = format!(
+                "row {} startx={} incrx={}",
+                row.pb_type, row.start_x_expr, row.incr_x_expr
+            );
+            text.push_str(&format!(" starty={}", row.start_y_expr));
+            if let Some(repeat_y) = &row.repeat_y_expr {
+                text.push_str(&format!(" repeaty={repeat_y}"));
+            }
+            text.push_str(&format!(" (priority {})", row.priority));
+            text
+        }
+        GridLocation::Region(region) => format!(
+            "region {} x={}..{} y={}..{} (priority {})",
+            region.pb_type,
+            region.start_x_expr,
+            region.end_x_expr,
+            region.start_y_expr,
+            region.end_y_expr,
+            region.priority
+        ),
+        GridLocation::InterposerCut(cut) => match (&cut.x, &cut.y) {
+            (Some(x), _) => format!("interposer_cut x={x}"),
+            (None, Some(y)) => format!("interposer_cut y={y}"),
+            (None, None) => "interposer_cut".to_string(),
+        },
+    };
+    OutlineNode::new(text)
+}
+
+fn device_node(device: &DeviceInfo) -> OutlineNode {
+    let sb_type = match device.switch_block.sb_type {
+        SBType::Wilton => "wilton",
+        SBType::Subset => "subset",
+        SBType::Universal => "universal",
+        SBType::Custom => "custom",
+    };
+    let mut switch_block = format!("switch_block {sb_type}");
+    if let Some(fs) = device.switch_block.sb_fs {
+        switch_block.push_str(&format!(" fs={fs}"));
+    }
+    OutlineNode::with_children(
+        "device",
+        vec![
+            OutlineNode::new(switch_block),
+            OutlineNode::new(format!(
+                "connection_block input_switch={}",
+                device.connection_block.input_switch_name
+            )),
+        ],
+    )
+}
+
+fn switch_node(switch: &Switch) -> OutlineNode {
+    let sw_type = match switch.sw_type {
+        SwitchType::Mux => "mux",
+        SwitchType::Tristate => "tristate",
+        SwitchType::PassGate => "pass_gate",
+        SwitchType::Short => "short",
+        SwitchType::Buffer => "buffer",
+    };
+    let mut text = format!(
+        "{} ({sw_type}) R={} Cin={} Cout={}",
+        switch.name, switch.resistance, switch.c_in, switch.c_out
+    );
+    if let Some(t_del) = switch.t_del {
+        text.push_str(&format!(" Tdel={t_del}"));
+    }
+    OutlineNode::new(text)
+}
+
+fn segment_node(segment: &Segment) -> OutlineNode {
+    let segment_type = match segment.segment_type {
+        SegmentType::Bidir => "bidir",
+        SegmentType::Unidir => "unidir",
+    };
+    OutlineNode::new(format!(
+        "{} (length {}, {segment_type}, freq {})",
+        segment.name, segment.length, segment.freq
+    ))
+}
+
+fn direct_node(direct: &GlobalDirect) -> OutlineNode {
+    OutlineNode::new(format!(
+        "{}: {} -> {} (offset {}, {}, {})",
+        direct.name,
+        direct.from_pin,
+        direct.to_pin,
+        direct.x_offset,
+        direct.y_offset,
+        direct.z_offset
+    ))
+}
+
+fn interconnect_node(interconnect: &Interconnect) -> OutlineNode {
+    let interconnect_type = match interconnect.interconnect_type {
+        InterconnectType::Complete => "complete",
+        InterconnectType::Direct => "direct",
+        InterconnectType::Mux => "mux",
+    };
+    OutlineNode::new(format!(
+        "{interconnect_type} {}: {} -> {}",
+        interconnect.name, interconnect.input, interconnect.output
+    ))
+}
+
+fn pb_type_node(pb_type: &PBType) -> OutlineNode {
+    let mut text = format!("pb_type {}", pb_type.name);
+    if pb_type.num_pb > 1 {
+        text.push_str(&format!(" x{}", pb_type.num_pb));
+    }
+    if let Some(blif_model) = &pb_type.blif_model {
+        text.push_str(&format!(" ({blif_model})"));
+    }
+
+    let mut children: Vec<OutlineNode> = pb_type.ports.iter().map(port_node).collect();
+    children.extend(pb_type.pb_types.iter().map(pb_type_node));
+    children.extend(pb_type.interconnects.iter().map(interconnect_node));
+    for mode in &pb_type.modes {
+        let mode_children = mode
+            .pb_types
+            .iter()
+            .map(pb_type_node)
+            .chain(mode.interconnects.iter().map(interconnect_node))
+            .collect();
+        children.push(OutlineNode::with_children(
+            format!("mode {}", mode.name),
+            mode_children,
+        ));
+    }
+    OutlineNode::with_children(text, children)
+}
@@ -3,11 +3,13 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
+use crate::parse_layouts::parse_bool_attribute;
 use crate::parse_metadata::parse_metadata;
 use crate::parse_port::parse_port;
 use crate::parse_timing::parse_clock_to_q;
@@ -15,11 +17,12 @@ use crate::parse_timing::parse_delay_constant;
 use crate::parse_timing::parse_delay_matrix;
 use crate::parse_timing::parse_t_hold;
 use crate::parse_timing::parse_t_setup;
+use crate::parse_warning::ParseWarning;
 
 fn parse_pack_pattern<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<PackPattern, FPGAArchParseError> {
     assert!(name.to_string() == "pack_pattern");
 
@@ -141,8 +144,9 @@ fn parse_pack_pattern<R: BufRead>(
 fn parse_interconnect<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Interconnect, FPGAArchParseError> {
+    let source_line = source_line(parser);
     let mut inter_name: Option<String> = None;
     let mut input: Option<String> = None;
     let mut output: Option<String> = None;
@@ -299,13 +303,17 @@ fn parse_interconnect<R: BufRead>(
         pack_patterns,
         delays,
         metadata,
+        source_line,
     })
 }
 
+/// Parses an <interconnect> block. `parent` describes the mode or pb_type the
+/// block belongs to, and is used to report interconnects with duplicate names.
 fn parse_interconnects<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    parent: &str,
 ) -> Result<Vec<Interconnect>, FPGAArchParseError> {
     assert!(name.to_string() == "interconnect");
     if !attributes.is_empty() {
@@ -323,7 +331,16 @@ fn parse_interconnects<R: BufRead>(
             }) => {
                 match name.to_string().as_str() {
                     "direct" | "mux" | "complete" => {
-                        interconnects.push(parse_interconnect(&name, &attributes, parser)?);
+                        let position = parser.position();
+                        let interconnect = parse_interconnect(&name, &attributes, parser)?;
+                        // VTR requires interconnect names to be unique within a mode.
+                        if interconnects.iter().any(|i| i.name == interconnect.name) {
+                            return Err(FPGAArchParseError::DuplicateName(
+                                format!("interconnect '{}' in {}", interconnect.name, parent),
+                                position,
+                            ));
+                        }
+                        interconnects.push(interconnect);
                     }
                     _ => {
                         return Err(FPGAArchParseError::InvalidTag(
@@ -363,11 +380,13 @@ fn parse_interconnects<R: BufRead>(
 fn parse_pb_mode<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<PBMode, FPGAArchParseError> {
     assert!(name.to_string() == "mode");
 
     let mut mode_name: Option<String> = None;
+    let mut disable_packing: Option<bool> = None;
     for a in attributes {
         match a.name.to_string().as_ref() {
             "name" => {
@@ -381,6 +400,17 @@ fn parse_pb_mode<R: BufRead>(
                     }
                 }
             }
+            "disable_packing" => {
+                disable_packing = match disable_packing {
+                    None => Some(parse_bool_attribute(&a.value, parser)?),
+                    Some(_) => {
+                        return Err(FPGAArchParseError::DuplicateAttribute(
+                            a.to_string(),
+                            parser.position(),
+                        ));
+                    }
+                }
+            }
             _ => {
                 return Err(FPGAArchParseError::UnknownAttribute(
                     a.to_string(),
@@ -410,11 +440,16 @@ fn parse_pb_mode<R: BufRead>(
             }) => {
                 match name.to_string().as_str() {
                     "pb_type" => {
-                        pb_types.push(parse_pb_type(&name, &attributes, parser)?);
+                        pb_types.push(parse_pb_type(&name, &attributes, parser, warnings)?);
                     }
                     "interconnect" => {
                         interconnects = match interconnects {
-                            None => Some(parse_interconnects(&name, &attributes, parser)?),
+                            None => Some(parse_interconnects(
+                                &name,
+                                &attributes,
+                                parser,
+                                &format!("mode '{mode_name}'"),
+                            )?),
                             Some(_) => {
                                 return Err(FPGAArchParseError::DuplicateTag(
                                     name.to_string(),
@@ -472,6 +507,7 @@ fn parse_pb_mode<R: BufRead>(
 
     Ok(PBMode {
         name: mode_name,
+        disable_packing: disable_packing.unwrap_or(false),
         pb_types,
         interconnects,
         metadata,
@@ -481,14 +517,17 @@ fn parse_pb_mode<R: BufRead>(
 fn parse_pb_type<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<PBType, FPGAArchParseError> {
     assert!(name.to_string() == "pb_type");
 
+    let source_line = source_line(parser);
     let mut pb_type_name: Option<String> = None;
     let mut num_pb: Option<usize> = None;
-    let mut blif_model: Option<String> = None;
+    let mut blif_model: Option<BlifModel> = None;
     let mut class: Option<PBTypeClass> = None;
+    let mut physical_mode_name: Option<String> = None;
 
     for a in attributes {
         match a.name.to_string().as_ref() {
@@ -524,7 +563,7 @@ fn parse_pb_type<R: BufRead>(
             }
             "blif_model" => {
                 blif_model = match blif_model {
-                    None => Some(a.value.clone()),
+                    None => Some(BlifModel::parse(&a.value)),
                     Some(_) => {
                         return Err(FPGAArchParseError::DuplicateAttribute(
                             a.to_string(),
@@ -554,6 +593,17 @@ fn parse_pb_type<R: BufRead>(
                     }
                 }
             }
+            "physical_mode_name" => {
+                physical_mode_name = match physical_mode_name {
+                    None => Some(a.value.clone()),
+                    Some(_) => {
+                        return Err(FPGAArchParseError::DuplicateAttribute(
+                            a.to_string(),
+                            parser.position(),
+                        ));
+                    }
+                }
+            }
             _ => {
                 return Err(FPGAArchParseError::UnknownAttribute(
                     a.to_string(),
@@ -595,14 +645,19 @@ fn parse_pb_type<R: BufRead>(
                         pb_ports.push(parse_port(&name, &attributes, parser)?);
                     }
                     "pb_type" => {
-                        pb_types.push(parse_pb_type(&name, &attributes, parser)?);
+                        pb_types.push(parse_pb_type(&name, &attributes, parser, warnings)?);
                     }
                     "mode" => {
-                        pb_modes.push(parse_pb_mode(&name, &attributes, parser)?);
+                        pb_modes.push(parse_pb_mode(&name, &attributes, parser, warnings)?);
                     }
                     "interconnect" => {
                         interconnects = match interconnects {
-                            None => Some(parse_interconnects(&name, &attributes, parser)?),
+                            None => Some(parse_interconnects(
+                                &name,
+                                &attributes,
+                                parser,
+                                &format!("pb_type '{pb_type_name}'"),
+                            )?),
                             Some(_) => {
                                 return Err(FPGAArchParseError::DuplicateTag(
                                     name.to_string(),
@@ -649,7 +704,13 @@ fn parse_pb_type<R: BufRead>(
                         //        files.
                         //        Will skip for now without error so we can support
                         //        their arch files.
-                        // TODO: Print a warning.
+                        warnings.push(ParseWarning::at(
+                            format!(
+                                "<{name}> in pb_type '{}' belongs in a <sub_tile> and was ignored",
+                                pb_type_name
+                            ),
+                            parser.position(),
+                        ));
                         let _ = parser.skip();
                     }
                     _ => {
@@ -688,6 +749,17 @@ fn parse_pb_type<R: BufRead>(
     //       Assuming that it is not.
     let interconnects = interconnects.unwrap_or_default();
 
+    if let Some(mode_name) = &physical_mode_name
+        && !pb_modes.iter().any(|mode| mode.name == *mode_name)
+    {
+        return Err(FPGAArchParseError::AttributeParseError(
+            format!(
+                "physical_mode_name=\"{mode_name}\": pb_type '{pb_type_name}' has no mode named '{mode_name}'"
+            ),
+            parser.position(),
+        ));
+    }
+
     Ok(PBType {
         name: pb_type_name,
         num_pb,
@@ -695,18 +767,21 @@ fn parse_pb_type<R: BufRead>(
         class,
         ports: pb_ports,
         modes: pb_modes,
+        physical_mode_name,
         pb_types,
         interconnects,
         delays,
         timing_constraints,
         metadata,
+        source_line,
     })
 }
 
 pub fn parse_complex_block_list<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<Vec<PBType>, FPGAArchParseError> {
     assert!(name.to_string() == "complexblocklist");
     if !attributes.is_empty() {
@@ -724,7 +799,12 @@ pub fn parse_complex_block_list<R: BufRead>(
             }) => {
                 match name.to_string().as_str() {
                     "pb_type" => {
-                        complex_block_list.push(parse_pb_type(&name, &attributes, parser)?);
+                        complex_block_list.push(parse_pb_type(
+                            &name,
+                            &attributes,
+                            parser,
+                            warnings,
+                        )?);
                     }
                     _ => {
                         return Err(FPGAArchParseError::InvalidTag(
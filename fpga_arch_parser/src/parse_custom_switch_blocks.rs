@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_switchblock_location<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<CustomSwitchBlockLocation, FPGAArchParseError> {
     assert!(name.to_string() == "switchblock_location");
 
@@ -179,7 +180,7 @@ fn parse_switchblock_location<R: BufRead>(
 fn parse_func<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<CustomSwitchFunc, FPGAArchParseError> {
     assert!(name.to_string() == "func");
 
@@ -295,7 +296,7 @@ fn parse_func<R: BufRead>(
 fn parse_switchfuncs<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<CustomSwitchFunc>, FPGAArchParseError> {
     assert!(name.to_string() == "switchfuncs");
     if !attributes.is_empty() {
@@ -353,7 +354,7 @@ fn parse_switchfuncs<R: BufRead>(
 
 fn parse_switchpoint_list<R: BufRead>(
     switchpoint_list: &str,
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<i32>, FPGAArchParseError> {
     let mut switchpoints: Vec<i32> = Vec::new();
 
@@ -377,7 +378,7 @@ fn parse_switchpoint_list<R: BufRead>(
 fn parse_conn_point<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<CustomSwitchBlockConnPoint, FPGAArchParseError> {
     assert!(name.to_string() == "from" || name.to_string() == "to");
 
@@ -476,7 +477,7 @@ fn parse_conn_point<R: BufRead>(
 fn parse_wireconn<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<CustomSwitchWireConn, FPGAArchParseError> {
     assert!(name.to_string() == "wireconn");
 
@@ -756,7 +757,7 @@ fn parse_wireconn<R: BufRead>(
 fn parse_switchblock<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<CustomSwitchBlock, FPGAArchParseError> {
     assert!(name.to_string() == "switchblock");
 
@@ -918,7 +919,7 @@ fn parse_switchblock<R: BufRead>(
 pub fn parse_switchblocklist<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<CustomSwitchBlock>, FPGAArchParseError> {
     assert!(name.to_string() == "switchblocklist");
     if !attributes.is_empty() {
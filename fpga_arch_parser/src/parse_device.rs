@@ -3,15 +3,18 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
+use crate::parse_tiles::parse_fc_attributes;
+use crate::parse_warning::ParseWarning;
 
 fn parse_device_sizing<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceSizingInfo, FPGAArchParseError> {
     assert!(name.to_string() == "sizing");
 
@@ -126,7 +129,7 @@ fn parse_device_sizing<R: BufRead>(
 fn parse_device_connection_block<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceConnectionBlockInfo, FPGAArchParseError> {
     assert!(name.to_string() == "connection_block");
 
@@ -203,7 +206,7 @@ fn parse_device_connection_block<R: BufRead>(
 fn parse_device_area<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceAreaInfo, FPGAArchParseError> {
     assert!(name.to_string() == "area");
 
@@ -288,7 +291,7 @@ fn parse_device_area<R: BufRead>(
 fn parse_device_switch_block<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceSwitchBlockInfo, FPGAArchParseError> {
     assert!(name.to_string() == "switch_block");
 
@@ -394,7 +397,7 @@ fn parse_device_switch_block<R: BufRead>(
 fn parse_chan_w_dist<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<ChanWDist, FPGAArchParseError> {
     assert!(name.to_string() == "x" || name.to_string() == "y");
 
@@ -656,7 +659,7 @@ fn parse_chan_w_dist<R: BufRead>(
 fn parse_device_chan_w_distr<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceChanWidthDistrInfo, FPGAArchParseError> {
     assert!(name.to_string() == "chan_width_distr");
     if !attributes.is_empty() {
@@ -740,10 +743,60 @@ fn parse_device_chan_w_distr<R: BufRead>(
     Ok(DeviceChanWidthDistrInfo { x_distr, y_distr })
 }
 
+fn parse_device_default_fc<R: BufRead>(
+    name: &OwnedName,
+    attributes: &[OwnedAttribute],
+    parser: &mut ArchReader<R>,
+) -> Result<SubTileFC, FPGAArchParseError> {
+    assert!(name.to_string() == "default_fc");
+
+    let (in_fc, out_fc) = parse_fc_attributes(attributes, parser)?;
+
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement { name, .. }) => {
+                return Err(FPGAArchParseError::InvalidTag(
+                    name.to_string(),
+                    parser.position(),
+                ));
+            }
+            Ok(XmlEvent::EndElement { name: end_name }) => {
+                if end_name.to_string() == name.to_string() {
+                    break;
+                } else {
+                    return Err(FPGAArchParseError::UnexpectedEndTag(
+                        name.to_string(),
+                        parser.position(),
+                    ));
+                }
+            }
+            Ok(XmlEvent::EndDocument) => {
+                return Err(FPGAArchParseError::UnexpectedEndOfDocument(
+                    name.to_string(),
+                ));
+            }
+            Err(e) => {
+                return Err(FPGAArchParseError::XMLParseError(
+                    format!("{e:?}"),
+                    parser.position(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(SubTileFC {
+        in_fc,
+        out_fc,
+        fc_overrides: Vec::new(),
+    })
+}
+
 pub fn parse_device<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<DeviceInfo, FPGAArchParseError> {
     assert!(name.to_string() == "device");
     if !attributes.is_empty() {
@@ -758,6 +811,7 @@ pub fn parse_device<R: BufRead>(
     let mut area: Option<DeviceAreaInfo> = None;
     let mut switch_block: Option<DeviceSwitchBlockInfo> = None;
     let mut chan_width_distr: Option<DeviceChanWidthDistrInfo> = None;
+    let mut default_fc: Option<SubTileFC> = None;
 
     loop {
         match parser.next() {
@@ -822,9 +876,24 @@ pub fn parse_device<R: BufRead>(
                             }
                         }
                     }
+                    "default_fc" => {
+                        default_fc = match default_fc {
+                            None => Some(parse_device_default_fc(&name, &attributes, parser)?),
+                            Some(_) => {
+                                return Err(FPGAArchParseError::DuplicateTag(
+                                    name.to_string(),
+                                    parser.position(),
+                                ));
+                            }
+                        }
+                    }
                     "opin_chanz_connectivity" => {
                         // TODO: This is only used in special cases of 3D which we likely will not
                         //       need to visualize. Skipping for now.
+                        warnings.push(ParseWarning::at(
+                            format!("<{name}> is not supported yet and was skipped"),
+                            parser.position(),
+                        ));
                         let _ = parser.skip();
                     }
                     _ => {
@@ -902,5 +971,6 @@ pub fn parse_device<R: BufRead>(
         connection_block,
         switch_block,
         chan_width_distr,
+        default_fc,
     })
 }
@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_direct<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<GlobalDirect, FPGAArchParseError> {
     assert!(tag_name.to_string() == "direct");
 
@@ -282,7 +283,7 @@ fn parse_direct<R: BufRead>(
 pub fn parse_direct_list<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<GlobalDirect>, FPGAArchParseError> {
     assert!(name.to_string() == "directlist");
     if !attributes.is_empty() {
@@ -1,4 +1,7 @@
-use xml::common::TextPosition;
+use xml::common::{Position, TextPosition};
+
+use crate::arch::SourceLine;
+use crate::arch_reader::ArchReader;
 
 #[derive(Debug)]
 pub enum FPGAArchParseError {
@@ -10,8 +13,59 @@ pub enum FPGAArchParseError {
     UnknownAttribute(String, TextPosition),
     DuplicateTag(String, TextPosition),
     DuplicateAttribute(String, TextPosition),
+    DuplicateName(String, TextPosition),
     UnexpectedEndTag(String, TextPosition),
     AttributeParseError(String, TextPosition),
     UnexpectedEndOfDocument(String),
     PinParsingError(String),
+    /// An `<xi:include>` could not be resolved.
+    IncludeError(String),
+    /// The document ended while these elements were still open, listed from
+    /// the outermost in. `parse_streaming` reports the XML error instead.
+    UnclosedElements(Vec<OpenElement>),
+    /// A tile's equivalent site names a pb_type that is not a root of the
+    /// `<complexblocklist>`.
+    UnresolvedPBTypeReference(String),
+}
+
+impl FPGAArchParseError {
+    /// Where in the file the error is, if it points at one. For a truncated
+    /// document this is the innermost element left open.
+    pub fn position(&self) -> Option<TextPosition> {
+        match self {
+            FPGAArchParseError::MissingRequiredAttribute(_, pos)
+            | FPGAArchParseError::InvalidTag(_, pos)
+            | FPGAArchParseError::XMLParseError(_, pos)
+            | FPGAArchParseError::UnknownAttribute(_, pos)
+            | FPGAArchParseError::DuplicateTag(_, pos)
+            | FPGAArchParseError::DuplicateAttribute(_, pos)
+            | FPGAArchParseError::DuplicateName(_, pos)
+            | FPGAArchParseError::UnexpectedEndTag(_, pos)
+            | FPGAArchParseError::AttributeParseError(_, pos) => Some(*pos),
+            FPGAArchParseError::UnclosedElements(open_elements) => {
+                open_elements.last().map(|element| element.position)
+            }
+            FPGAArchParseError::ArchFileOpenError(_)
+            | FPGAArchParseError::MissingRequiredTag(_)
+            | FPGAArchParseError::UnexpectedEndOfDocument(_)
+            | FPGAArchParseError::PinParsingError(_)
+            | FPGAArchParseError::IncludeError(_)
+            | FPGAArchParseError::UnresolvedPBTypeReference(_) => None,
+        }
+    }
+}
+
+/// An element that was never closed.
+#[derive(Debug, Clone)]
+pub struct OpenElement {
+    pub tag: String,
+    /// The element's `name` attribute, if it has one.
+    pub name: Option<String>,
+    /// Where the element's start tag is.
+    pub position: TextPosition,
+}
+
+/// Line, counting from 1, of the element the parser just started.
+pub(crate) fn source_line<R: std::io::Read>(parser: &ArchReader<R>) -> SourceLine {
+    SourceLine(usize::try_from(parser.position().row + 1).ok())
 }
@@ -3,16 +3,19 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
+use crate::layout_expr::check_layout_expr;
 use crate::parse_error::*;
 
 use crate::parse_metadata::parse_metadata;
 
-fn parse_bool_attribute<R: BufRead>(
+/// Parses a boolean attribute, accepting the spellings VTR accepts.
+pub(crate) fn parse_bool_attribute<R: BufRead>(
     value: &str,
-    parser: &EventReader<R>,
+    parser: &ArchReader<R>,
 ) -> Result<bool, FPGAArchParseError> {
     match value.to_lowercase().as_str() {
         "true" | "on" | "1" | "yes" => Ok(true),
@@ -24,10 +27,30 @@ fn parse_bool_attribute<R: BufRead>(
     }
 }
 
+/// Rejects the first malformed expression among the `(attribute, value)`
+/// pairs of a grid location, naming the tag, attribute and expression.
+fn check_location_exprs<R: BufRead>(
+    name: &OwnedName,
+    exprs: &[(&str, &Option<String>)],
+    parser: &ArchReader<R>,
+) -> Result<(), FPGAArchParseError> {
+    for (attribute, expr) in exprs {
+        if let Some(expr) = expr
+            && let Err(e) = check_layout_expr(expr)
+        {
+            return Err(FPGAArchParseError::AttributeParseError(
+                format!("<{name}> {attribute}=\"{expr}\": {e}"),
+                parser.position(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn parse_grid_location<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<GridLocation, FPGAArchParseError> {
     // Interposer cuts are parsed special.
     if name.to_string() == "interposer_cut" {
@@ -217,6 +240,23 @@ fn parse_grid_location<R: BufRead>(
         }
     };
 
+    check_location_exprs(
+        name,
+        &[
+            ("x", &x_expr),
+            ("y", &y_expr),
+            ("startx", &start_x_expr),
+            ("endx", &end_x_expr),
+            ("repeatx", &repeat_x_expr),
+            ("incrx", &incr_x_expr),
+            ("starty", &start_y_expr),
+            ("endy", &end_y_expr),
+            ("repeaty", &repeat_y_expr),
+            ("incry", &incr_y_expr),
+        ],
+        parser,
+    )?;
+
     let start_x_expr = start_x_expr.unwrap_or(String::from("0"));
     let end_x_expr = end_x_expr.unwrap_or(String::from("W - 1"));
     let incr_x_expr = incr_x_expr.unwrap_or(String::from("w"));
@@ -359,7 +399,7 @@ fn parse_grid_location<R: BufRead>(
 fn parse_interposer_cut<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<GridLocation, FPGAArchParseError> {
     assert!(name.to_string() == "interposer_cut");
 
@@ -416,6 +456,7 @@ fn parse_interposer_cut<R: BufRead>(
         }
         _ => {}
     }
+    check_location_exprs(name, &[("x", &x), ("y", &y)], parser)?;
 
     let mut interdie_wires: Vec<InterdieWire> = Vec::new();
     loop {
@@ -470,7 +511,7 @@ fn parse_interposer_cut<R: BufRead>(
 fn parse_interdie_wire<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<InterdieWire, FPGAArchParseError> {
     assert!(tag_name.to_string() == "interdie_wire");
 
@@ -679,7 +720,7 @@ fn parse_interdie_wire<R: BufRead>(
 
 fn parse_grid_location_list<R: BufRead>(
     layout_type_name: &OwnedName,
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
     prev_grid_loc: Option<GridLocation>,
 ) -> Result<Vec<GridLocation>, FPGAArchParseError> {
     let mut grid_locations: Vec<GridLocation> = Vec::new();
@@ -724,7 +765,7 @@ fn parse_grid_location_list<R: BufRead>(
 fn parse_layer<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Layer, FPGAArchParseError> {
     assert!(name.to_string() == "layer");
 
@@ -770,7 +811,7 @@ fn parse_layer<R: BufRead>(
 
 fn parse_layers<R: BufRead>(
     layout_type_name: &OwnedName,
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<Layer>, FPGAArchParseError> {
     let mut layers: Vec<Layer> = Vec::new();
 
@@ -849,7 +890,7 @@ fn parse_layers<R: BufRead>(
 fn parse_auto_layout<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<AutoLayout, FPGAArchParseError> {
     assert!(name.to_string() == "auto_layout");
 
@@ -898,7 +939,7 @@ fn parse_auto_layout<R: BufRead>(
 fn parse_fixed_layout<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<FixedLayout, FPGAArchParseError> {
     assert!(name.to_string() == "fixed_layout");
 
@@ -1007,7 +1048,7 @@ fn parse_fixed_layout<R: BufRead>(
 pub fn parse_layouts<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DeviceLayouts, FPGAArchParseError> {
     assert!(name.to_string() == "layout");
 
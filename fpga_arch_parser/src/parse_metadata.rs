@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_meta<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Metadata, FPGAArchParseError> {
     assert!(tag_name.to_string() == "meta");
 
@@ -101,7 +102,7 @@ fn parse_meta<R: BufRead>(
 pub fn parse_metadata<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<Metadata>, FPGAArchParseError> {
     assert!(tag_name.to_string() == "metadata");
     if !attributes.is_empty() {
@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_model_port<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<ModelPort, FPGAArchParseError> {
     assert!(tag_name.to_string() == "port");
 
@@ -137,7 +138,7 @@ fn parse_model_port<R: BufRead>(
 fn parse_model_port_list<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<ModelPort>, FPGAArchParseError> {
     assert!(tag_name.to_string() == "input_ports" || tag_name.to_string() == "output_ports");
     if !attributes.is_empty() {
@@ -196,7 +197,7 @@ fn parse_model_port_list<R: BufRead>(
 fn parse_model<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Model, FPGAArchParseError> {
     assert!(tag_name.to_string() == "model");
 
@@ -412,7 +413,7 @@ fn get_built_in_models() -> Vec<Model> {
 pub fn parse_models<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<Model>, FPGAArchParseError> {
     assert!(name.to_string() == "models");
     if !attributes.is_empty() {
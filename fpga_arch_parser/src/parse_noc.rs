@@ -3,14 +3,15 @@ use std::io::BufRead;
 
 use xml::common::Position;
 use xml::reader::XmlEvent;
-use xml::{EventReader, attribute::OwnedAttribute, name::OwnedName};
+use xml::{attribute::OwnedAttribute, name::OwnedName};
 
+use crate::arch_reader::ArchReader;
 use crate::{FPGAArchParseError, NoCInfo, NoCRouterInfo, NoCTopologyInfo};
 
 fn parse_router<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<NoCRouterInfo, FPGAArchParseError> {
     assert!(tag_name.to_string() == "router");
 
@@ -195,7 +196,7 @@ fn parse_router<R: BufRead>(
 fn parse_mesh<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<NoCTopologyInfo, FPGAArchParseError> {
     assert!(tag_name.to_string() == "mesh");
 
@@ -508,7 +509,7 @@ fn parse_mesh<R: BufRead>(
 fn parse_topology<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<NoCTopologyInfo, FPGAArchParseError> {
     assert!(tag_name.to_string() == "topology");
     if !attributes.is_empty() {
@@ -592,7 +593,7 @@ fn parse_topology<R: BufRead>(
 pub fn parse_noc<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<NoCInfo, FPGAArchParseError> {
     assert!(name.to_string() == "noc");
 
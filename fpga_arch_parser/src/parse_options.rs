@@ -0,0 +1,23 @@
+/// Options controlling how strictly an architecture file is parsed.
+///
+/// The default is strict, which matches what VTR itself accepts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    /// Accept some incomplete descriptions (for example, a sub-tile without
+    /// any equivalent sites) so that architectures can be visualized while
+    /// they are still being written.
+    pub lenient: bool,
+    /// Reject an architecture whose `<switchlist>` or `<segmentlist>` is
+    /// empty, as VTR does when it routes the device. Otherwise an empty list
+    /// is only a warning, so unroutable architectures can still be viewed.
+    pub require_routing: bool,
+}
+
+impl ParseOptions {
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::default()
+        }
+    }
+}
@@ -3,10 +3,12 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::{Position, TextPosition};
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
+use crate::parse_layouts::parse_bool_attribute;
 
 fn parse_port_class(value: &str, position: TextPosition) -> Result<PortClass, FPGAArchParseError> {
     // Try to parse as simple port class first
@@ -67,7 +69,7 @@ fn parse_port_class(value: &str, position: TextPosition) -> Result<PortClass, FP
 pub fn parse_port<R: BufRead>(
     tag_name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Port, FPGAArchParseError> {
     let mut port_name: Option<String> = None;
     let mut num_pins: Option<usize> = None;
@@ -131,15 +133,7 @@ pub fn parse_port<R: BufRead>(
             "is_non_clock_global" => {
                 is_non_clock_global = match is_non_clock_global {
                     None => match tag_name.to_string().as_str() {
-                        "input" => match a.value.parse() {
-                            Ok(v) => Some(v),
-                            Err(e) => {
-                                return Err(FPGAArchParseError::AttributeParseError(
-                                    format!("{a}: {e}"),
-                                    parser.position(),
-                                ));
-                            }
-                        },
+                        "input" => Some(parse_bool_attribute(&a.value, parser)?),
                         _ => {
                             return Err(FPGAArchParseError::AttributeParseError(
                                 "is_non_clock_global attribute only valid in input tag."
@@ -188,8 +182,17 @@ pub fn parse_port<R: BufRead>(
             ));
         }
     };
+    // A port without pins would not be drawn, and every bus referring to it
+    // would silently resolve to nothing.
     let num_pins = match num_pins {
+        Some(0) => {
+            return Err(FPGAArchParseError::AttributeParseError(
+                format!("Port '{port_name}' num_pins must be at least 1: num_pins=0"),
+                parser.position(),
+            ));
+        }
         Some(n) => n,
+        // The position is the port's start tag, which names the port.
         None => {
             return Err(FPGAArchParseError::MissingRequiredAttribute(
                 "num_pins".to_string(),
@@ -3,14 +3,15 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_pattern_int_list<R: BufRead>(
     text: &str,
-    parser: &EventReader<R>,
+    parser: &ArchReader<R>,
 ) -> Result<Vec<bool>, FPGAArchParseError> {
     let mut list: Vec<bool> = Vec::new();
 
@@ -42,7 +43,7 @@ fn parse_pattern_int_list<R: BufRead>(
 fn parse_segment_pattern<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<bool>, FPGAArchParseError> {
     assert!(name.to_string() == "sb" || name.to_string() == "cb");
 
@@ -144,7 +145,7 @@ fn parse_segment_pattern<R: BufRead>(
 fn parse_segment_switch_point_descriptor<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<String, FPGAArchParseError> {
     let mut desc_name: Option<String> = None;
     for a in attributes {
@@ -217,13 +218,13 @@ fn parse_segment_switch_point_descriptor<R: BufRead>(
 fn parse_segment<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Segment, FPGAArchParseError> {
     assert!(name.to_string() == "segment");
 
     let mut axis: Option<SegmentAxis> = None;
     let mut name: Option<String> = None;
-    let mut length: Option<i32> = None;
+    let mut length: Option<SegmentLength> = None;
     let mut segment_type: Option<SegmentType> = None;
     let mut res_type: Option<SegmentResourceType> = None;
     let mut freq: Option<f32> = None;
@@ -266,8 +267,9 @@ fn parse_segment<R: BufRead>(
             }
             "length" => {
                 length = match length {
+                    None if a.value == "longline" => Some(SegmentLength::LongLine),
                     None => match a.value.parse() {
-                        Ok(v) => Some(v),
+                        Ok(v) => Some(SegmentLength::Finite(v)),
                         Err(e) => {
                             return Err(FPGAArchParseError::AttributeParseError(
                                 format!("{a}: {e}"),
@@ -585,9 +587,13 @@ fn parse_segment<R: BufRead>(
             _ => {}
         }
     }
+    // A long line spans the device, so its patterns cannot be checked
+    // against its length here.
     let sb_pattern = match sb_pattern {
         Some(p) => {
-            if p.len() as i32 != length + 1 {
+            if let SegmentLength::Finite(length) = length
+                && p.len() as i32 != length + 1
+            {
                 return Err(FPGAArchParseError::InvalidTag(
                     "For a length L wire there must be L+1 entries separated by spaces for <sb>"
                         .to_string(),
@@ -605,7 +611,9 @@ fn parse_segment<R: BufRead>(
     };
     let cb_pattern = match cb_pattern {
         Some(p) => {
-            if p.len() as i32 != length {
+            if let SegmentLength::Finite(length) = length
+                && p.len() as i32 != length
+            {
                 return Err(FPGAArchParseError::InvalidTag(
                     "For a length L wire there must be L entries separated by spaces for <cb>"
                         .to_string(),
@@ -686,7 +694,7 @@ fn parse_segment<R: BufRead>(
 pub fn parse_segment_list<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<Segment>, FPGAArchParseError> {
     assert!(name.to_string() == "segmentlist");
     if !attributes.is_empty() {
@@ -0,0 +1,183 @@
+use std::io::{BufReader, Read};
+
+use xml::attribute::OwnedAttribute;
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::parse_error::FPGAArchParseError;
+
+/// A high-level event emitted by [`parse_streaming`].
+///
+/// Start and end events are always balanced, so callers can track nesting
+/// (for example, the modes a pb_type is instantiated under) on their own.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArchEvent {
+    /// A `<tile>` inside `<tiles>` was entered.
+    TileStart {
+        name: String,
+    },
+    TileEnd {
+        name: String,
+    },
+    /// A `<pb_type>` inside `<complexblocklist>` was entered. Top-level
+    /// complex blocks have a depth of 0.
+    PbTypeStart {
+        name: String,
+        depth: usize,
+        num_pb: usize,
+        blif_model: Option<String>,
+    },
+    PbTypeEnd {
+        name: String,
+        depth: usize,
+    },
+    /// A `<mode>` of the enclosing pb_type was entered.
+    ModeStart {
+        name: String,
+    },
+    ModeEnd {
+        name: String,
+    },
+}
+
+/// Walks an architecture description once, calling `callback` for each tile,
+/// pb_type and mode, without building an `FPGAArch`.
+///
+/// Only the tags needed to produce the events are inspected, so this is much
+/// cheaper than a full parse on very large files; it does not validate the
+/// rest of the description. Use `parse` unless memory is a concern.
+pub fn parse_streaming<R: Read>(
+    reader: R,
+    callback: &mut impl FnMut(ArchEvent),
+) -> Result<(), FPGAArchParseError> {
+    let mut parser = EventReader::new(BufReader::new(reader));
+
+    // The names of the currently open elements, along with the end event to
+    // emit when each one closes.
+    let mut open_elements: Vec<(String, Option<ArchEvent>)> = Vec::new();
+    let mut pb_type_depth = 0;
+    let mut found_architecture = false;
+
+    loop {
+        match parser.next() {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let name = name.to_string();
+                let parent = open_elements.last().map(|(n, _)| n.as_str());
+                let in_complex_block_list = open_elements
+                    .get(1)
+                    .is_some_and(|(n, _)| n == "complexblocklist");
+
+                let end_event = match (parent, name.as_str()) {
+                    (None, "architecture") => {
+                        if found_architecture {
+                            return Err(FPGAArchParseError::DuplicateTag(
+                                "<architecture>".to_string(),
+                                parser.position(),
+                            ));
+                        }
+                        found_architecture = true;
+                        None
+                    }
+                    (None, _) => {
+                        return Err(FPGAArchParseError::InvalidTag(
+                            format!("Invalid top-level tag: {name}, expected only <architecture>"),
+                            parser.position(),
+                        ));
+                    }
+                    (Some("tiles"), "tile") if open_elements.len() == 2 => {
+                        let tile_name = get_name_attribute(&attributes, &parser)?;
+                        callback(ArchEvent::TileStart {
+                            name: tile_name.clone(),
+                        });
+                        Some(ArchEvent::TileEnd { name: tile_name })
+                    }
+                    (Some("complexblocklist" | "pb_type" | "mode"), "pb_type")
+                        if in_complex_block_list =>
+                    {
+                        let pb_type_name = get_name_attribute(&attributes, &parser)?;
+                        let num_pb = match attributes.iter().find(|a| a.name.local_name == "num_pb")
+                        {
+                            Some(a) => a.value.parse().map_err(|e| {
+                                FPGAArchParseError::AttributeParseError(
+                                    format!("{a}: {e}"),
+                                    parser.position(),
+                                )
+                            })?,
+                            None => 1,
+                        };
+                        let blif_model = attributes
+                            .iter()
+                            .find(|a| a.name.local_name == "blif_model")
+                            .map(|a| a.value.clone());
+                        callback(ArchEvent::PbTypeStart {
+                            name: pb_type_name.clone(),
+                            depth: pb_type_depth,
+                            num_pb,
+                            blif_model,
+                        });
+                        let end_event = ArchEvent::PbTypeEnd {
+                            name: pb_type_name,
+                            depth: pb_type_depth,
+                        };
+                        pb_type_depth += 1;
+                        Some(end_event)
+                    }
+                    (Some("pb_type"), "mode") if in_complex_block_list => {
+                        let mode_name = get_name_attribute(&attributes, &parser)?;
+                        callback(ArchEvent::ModeStart {
+                            name: mode_name.clone(),
+                        });
+                        Some(ArchEvent::ModeEnd { name: mode_name })
+                    }
+                    _ => None,
+                };
+                open_elements.push((name, end_event));
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                let Some((_, end_event)) = open_elements.pop() else {
+                    return Err(FPGAArchParseError::UnexpectedEndTag(
+                        name.to_string(),
+                        parser.position(),
+                    ));
+                };
+                if let Some(end_event) = end_event {
+                    if matches!(end_event, ArchEvent::PbTypeEnd { .. }) {
+                        pb_type_depth -= 1;
+                    }
+                    callback(end_event);
+                }
+            }
+            Ok(XmlEvent::EndDocument) => break,
+            Err(e) => {
+                return Err(FPGAArchParseError::XMLParseError(
+                    format!("{e:?}"),
+                    parser.position(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if !found_architecture {
+        return Err(FPGAArchParseError::MissingRequiredTag(
+            "<architecture>".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn get_name_attribute<R: Read>(
+    attributes: &[OwnedAttribute],
+    parser: &EventReader<R>,
+) -> Result<String, FPGAArchParseError> {
+    match attributes.iter().find(|a| a.name.local_name == "name") {
+        Some(a) => Ok(a.value.clone()),
+        None => Err(FPGAArchParseError::MissingRequiredAttribute(
+            "name".to_string(),
+            parser.position(),
+        )),
+    }
+}
@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 fn parse_switch_t_del<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<SwitchTDel, FPGAArchParseError> {
     assert!(name.to_string() == "Tdel");
 
@@ -123,7 +124,7 @@ fn parse_switch_t_del<R: BufRead>(
 fn parse_switch<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Switch, FPGAArchParseError> {
     assert!(name.to_string() == "switch");
 
@@ -457,7 +458,7 @@ fn parse_switch<R: BufRead>(
 pub fn parse_switch_list<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<Switch>, FPGAArchParseError> {
     assert!(name.to_string() == "switchlist");
     if !attributes.is_empty() {
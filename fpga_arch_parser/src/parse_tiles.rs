@@ -3,10 +3,13 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
+use crate::parse_options::ParseOptions;
+use crate::parse_warning::ParseWarning;
 
 use crate::parse_port::parse_port;
 use crate::tile_pin_mapper::build_tile_pin_mapper;
@@ -14,7 +17,7 @@ use crate::tile_pin_mapper::build_tile_pin_mapper;
 fn parse_sb_loc<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<SwitchBlockLocation, FPGAArchParseError> {
     assert!(name.to_string() == "sb_loc");
 
@@ -153,7 +156,7 @@ fn parse_sb_loc<R: BufRead>(
 fn parse_switchblock_locations<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<SwitchBlockLocations, FPGAArchParseError> {
     assert!(name.to_string() == "switchblock_locations");
 
@@ -303,7 +306,7 @@ fn parse_switchblock_locations<R: BufRead>(
 fn parse_tile_site<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<TileSite, FPGAArchParseError> {
     assert!(name.to_string() == "site");
 
@@ -403,7 +406,7 @@ fn parse_tile_site<R: BufRead>(
 fn parse_equivalent_sites<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<Vec<TileSite>, FPGAArchParseError> {
     assert!(name.to_string() == "equivalent_sites");
     if !attributes.is_empty() {
@@ -463,7 +466,7 @@ fn parse_equivalent_sites<R: BufRead>(
 fn create_sub_tile_io_fc<R: BufRead>(
     ty: &str,
     val: &str,
-    parser: &EventReader<R>,
+    parser: &ArchReader<R>,
 ) -> Result<SubTileIOFC, FPGAArchParseError> {
     match ty {
         "frac" => Ok(SubTileIOFC::Frac(match val.parse() {
@@ -494,7 +497,7 @@ fn create_sub_tile_io_fc<R: BufRead>(
 fn parse_sub_tile_fc_override<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<SubTileFCOverride, FPGAArchParseError> {
     assert!(name.to_string() == "fc_override");
 
@@ -622,13 +625,12 @@ fn parse_sub_tile_fc_override<R: BufRead>(
     })
 }
 
-fn parse_sub_tile_fc<R: BufRead>(
-    name: &OwnedName,
+/// Reads the in_type, in_val, out_type and out_val attributes shared by a sub
+/// tile's `<fc>` and the device's `<default_fc>`.
+pub(crate) fn parse_fc_attributes<R: BufRead>(
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
-) -> Result<SubTileFC, FPGAArchParseError> {
-    assert!(name.to_string() == "fc");
-
+    parser: &ArchReader<R>,
+) -> Result<(SubTileIOFC, SubTileIOFC), FPGAArchParseError> {
     let mut in_type: Option<String> = None;
     let mut in_val: Option<String> = None;
     let mut out_type: Option<String> = None;
@@ -728,6 +730,18 @@ fn parse_sub_tile_fc<R: BufRead>(
     let in_fc = create_sub_tile_io_fc(&in_type, &in_val, parser)?;
     let out_fc = create_sub_tile_io_fc(&out_type, &out_val, parser)?;
 
+    Ok((in_fc, out_fc))
+}
+
+fn parse_sub_tile_fc<R: BufRead>(
+    name: &OwnedName,
+    attributes: &[OwnedAttribute],
+    parser: &mut ArchReader<R>,
+) -> Result<SubTileFC, FPGAArchParseError> {
+    assert!(name.to_string() == "fc");
+
+    let (in_fc, out_fc) = parse_fc_attributes(attributes, parser)?;
+
     let mut fc_overrides: Vec<SubTileFCOverride> = Vec::new();
     loop {
         match parser.next() {
@@ -780,7 +794,7 @@ fn parse_sub_tile_fc<R: BufRead>(
 fn parse_pin_loc<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<PinLoc, FPGAArchParseError> {
     assert!(name.to_string() == "loc");
 
@@ -870,20 +884,13 @@ fn parse_pin_loc<R: BufRead>(
     let xoffset = xoffset.unwrap_or_default();
     let yoffset = yoffset.unwrap_or_default();
 
-    // Parse the pin strings.
-    let mut pin_strings: Option<Vec<String>> = None;
+    // Parse the pin strings. The text may arrive split across several
+    // events, so gather all of it before splitting it into pins.
+    let mut pin_text = String::new();
     loop {
         match parser.next() {
             Ok(XmlEvent::Characters(text)) => {
-                pin_strings = match pin_strings {
-                    None => Some(text.split_whitespace().map(|s| s.to_string()).collect()),
-                    Some(_) => {
-                        return Err(FPGAArchParseError::InvalidTag(
-                            "Duplicate characters within loc tag.".to_string(),
-                            parser.position(),
-                        ));
-                    }
-                }
+                pin_text.push_str(&text);
             }
             Ok(XmlEvent::EndElement { name }) => match name.to_string().as_str() {
                 "loc" => break,
@@ -919,7 +926,7 @@ fn parse_pin_loc<R: BufRead>(
     //        pin strings. Need to update the documentation to make this
     //        clear what to do in this case.
     // For now, just make the pin strings empty.
-    let pin_strings = pin_strings.unwrap_or_default();
+    let pin_strings = pin_text.split_whitespace().map(|s| s.to_string()).collect();
 
     Ok(PinLoc {
         side,
@@ -932,7 +939,7 @@ fn parse_pin_loc<R: BufRead>(
 fn parse_sub_tile_pin_locations<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<SubTilePinLocations, FPGAArchParseError> {
     assert!(name.to_string() == "pinlocations");
 
@@ -1033,10 +1040,14 @@ fn parse_sub_tile_pin_locations<R: BufRead>(
     }
 }
 
+/// Parses a `<sub_tile>`. One without an `<fc>` of its own is given the
+/// device's `<default_fc>` once the whole architecture has been read.
 fn parse_sub_tile<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<SubTile, FPGAArchParseError> {
     assert!(name.to_string() == "sub_tile");
 
@@ -1092,7 +1103,14 @@ fn parse_sub_tile<R: BufRead>(
             ));
         }
     };
+    // If the capacity is not provided, it is assumed to be 1.
     let sub_tile_capacity = sub_tile_capacity.unwrap_or(1);
+    if sub_tile_capacity < 1 {
+        return Err(FPGAArchParseError::AttributeParseError(
+            format!("Sub-tile capacity must be at least 1: capacity={sub_tile_capacity}"),
+            parser.position(),
+        ));
+    }
 
     let mut equivalent_sites: Option<Vec<TileSite>> = None;
     let mut ports: Vec<Port> = Vec::new();
@@ -1174,16 +1192,20 @@ fn parse_sub_tile<R: BufRead>(
 
     let equivalent_sites = match equivalent_sites {
         Some(t) => t,
+        // Abstract tiles that are still being written may not have any sites yet.
+        None if options.lenient => {
+            warnings.push(ParseWarning::at(
+                format!("sub_tile '{sub_tile_name}' has no <equivalent_sites>"),
+                parser.position(),
+            ));
+            Vec::new()
+        }
         None => {
             return Err(FPGAArchParseError::MissingRequiredTag(
                 "<equivalent_sites>".to_string(),
             ));
         }
     };
-    let sub_tile_fc = match sub_tile_fc {
-        Some(t) => t,
-        None => return Err(FPGAArchParseError::MissingRequiredTag("<fc>".to_string())),
-    };
     let pin_locations = match pin_locations {
         Some(t) => t,
         None => {
@@ -1199,6 +1221,7 @@ fn parse_sub_tile<R: BufRead>(
         equivalent_sites,
         ports,
         fc: sub_tile_fc,
+        fc_from_default: false,
         pin_locations,
     })
 }
@@ -1206,10 +1229,13 @@ fn parse_sub_tile<R: BufRead>(
 fn parse_tile<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<Tile, FPGAArchParseError> {
     assert!(name.to_string() == "tile");
 
+    let source_line = source_line(parser);
     let mut tile_name: Option<String> = None;
     let mut width: Option<i32> = None;
     let mut height: Option<i32> = None;
@@ -1306,15 +1332,22 @@ fn parse_tile<R: BufRead>(
     // If the width or height is not provided, they are assumed to be 1.
     let width = width.unwrap_or(1);
     let height = height.unwrap_or(1);
-    if width <= 0 || height <= 0 {
+    if width < 1 || height < 1 {
         return Err(FPGAArchParseError::AttributeParseError(
             format!(
-                "Tile dimensions must be positive: width={}, height={}",
-                width, height
+                "Tile '{tile_name}' dimensions must be at least 1: width={width}, height={height}"
             ),
             parser.position(),
         ));
     }
+    if let Some(area) = area
+        && area < 0.0
+    {
+        return Err(FPGAArchParseError::AttributeParseError(
+            format!("Tile '{tile_name}' area must not be negative: area={area}"),
+            parser.position(),
+        ));
+    }
 
     let mut ports: Vec<Port> = Vec::new();
     let mut sub_tiles: Vec<SubTile> = Vec::new();
@@ -1326,7 +1359,13 @@ fn parse_tile<R: BufRead>(
             }) => {
                 match name.to_string().as_str() {
                     "sub_tile" => {
-                        sub_tiles.push(parse_sub_tile(&name, &attributes, parser)?);
+                        sub_tiles.push(parse_sub_tile(
+                            &name,
+                            &attributes,
+                            parser,
+                            options,
+                            warnings,
+                        )?);
                     }
                     "input" | "output" | "clock" => {
                         ports.push(parse_port(&name, &attributes, parser)?);
@@ -1385,13 +1424,16 @@ fn parse_tile<R: BufRead>(
         area,
         switchblock_locations,
         pin_mapper,
+        source_line,
     })
 }
 
 pub fn parse_tiles<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<Vec<Tile>, FPGAArchParseError> {
     assert!(name.to_string() == "tiles");
     if !attributes.is_empty() {
@@ -1410,7 +1452,7 @@ pub fn parse_tiles<R: BufRead>(
             }) => {
                 match name.to_string().as_str() {
                     "tile" => {
-                        tiles.push(parse_tile(&name, &attributes, parser)?);
+                        tiles.push(parse_tile(&name, &attributes, parser, options, warnings)?);
                     }
                     _ => {
                         return Err(FPGAArchParseError::InvalidTag(
@@ -1446,3 +1488,54 @@ pub fn parse_tiles<R: BufRead>(
 
     Ok(tiles)
 }
+
+/// Lists every equivalent site that does not name a root pb_type of the
+/// complex block list. There is no other way to refer to a pb_type by name, so
+/// a site that does not resolve leaves its tile with nothing to draw.
+pub(crate) fn unresolved_site_references(
+    tiles: &[Tile],
+    complex_block_list: &[PBType],
+) -> Vec<String> {
+    let mut unresolved = Vec::new();
+    for tile in tiles {
+        for sub_tile in &tile.sub_tiles {
+            for site in &sub_tile.equivalent_sites {
+                if !complex_block_list
+                    .iter()
+                    .any(|pb_type| pb_type.name == site.pb_type)
+                {
+                    unresolved.push(format!(
+                        "tile '{}' sub_tile '{}' references unknown pb_type '{}'",
+                        tile.name, sub_tile.name, site.pb_type
+                    ));
+                }
+            }
+        }
+    }
+    unresolved
+}
+
+/// Gives the sub tiles without an `<fc>` of their own the device's
+/// `<default_fc>`. Without one, they keep no Fc and the missing tag of each is
+/// returned.
+pub(crate) fn apply_default_fc(tiles: &mut [Tile], device: &DeviceInfo) -> Vec<String> {
+    let mut missing = Vec::new();
+    for tile in tiles {
+        for sub_tile in &mut tile.sub_tiles {
+            if sub_tile.fc.is_some() {
+                continue;
+            }
+            match &device.default_fc {
+                Some(default_fc) => {
+                    sub_tile.fc = Some(default_fc.clone());
+                    sub_tile.fc_from_default = true;
+                }
+                None => missing.push(format!(
+                    "<fc> in tile '{}' sub_tile '{}' (or <default_fc> in <device>)",
+                    tile.name, sub_tile.name
+                )),
+            }
+        }
+    }
+    missing
+}
@@ -3,15 +3,16 @@ use std::io::BufRead;
 use xml::attribute::OwnedAttribute;
 use xml::common::Position;
 use xml::name::OwnedName;
-use xml::reader::{EventReader, XmlEvent};
+use xml::reader::XmlEvent;
 
 use crate::arch::*;
+use crate::arch_reader::ArchReader;
 use crate::parse_error::*;
 
 pub fn parse_delay_constant<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DelayInfo, FPGAArchParseError> {
     assert!(name.to_string() == "delay_constant");
 
@@ -165,7 +166,7 @@ pub fn parse_delay_constant<R: BufRead>(
 
 fn parse_matrix_text<R: BufRead>(
     text: &str,
-    parser: &EventReader<R>,
+    parser: &ArchReader<R>,
 ) -> Result<Vec<Vec<f32>>, FPGAArchParseError> {
     let mut matrix: Vec<Vec<f32>> = Vec::new();
 
@@ -192,7 +193,7 @@ fn parse_matrix_text<R: BufRead>(
 pub fn parse_delay_matrix<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<DelayInfo, FPGAArchParseError> {
     assert!(name.to_string() == "delay_matrix");
 
@@ -345,7 +346,7 @@ fn parse_setup_or_hold<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
     constraint_type: TimingConstraintType,
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<TimingConstraintInfo, FPGAArchParseError> {
     assert!(name.to_string() == "T_setup" || name.to_string() == "T_hold");
 
@@ -476,7 +477,7 @@ fn parse_setup_or_hold<R: BufRead>(
 pub fn parse_t_setup<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<TimingConstraintInfo, FPGAArchParseError> {
     assert!(name.to_string() == "T_setup");
 
@@ -486,7 +487,7 @@ pub fn parse_t_setup<R: BufRead>(
 pub fn parse_t_hold<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<TimingConstraintInfo, FPGAArchParseError> {
     assert!(name.to_string() == "T_hold");
 
@@ -496,7 +497,7 @@ pub fn parse_t_hold<R: BufRead>(
 pub fn parse_clock_to_q<R: BufRead>(
     name: &OwnedName,
     attributes: &[OwnedAttribute],
-    parser: &mut EventReader<R>,
+    parser: &mut ArchReader<R>,
 ) -> Result<TimingConstraintInfo, FPGAArchParseError> {
     assert!(name.to_string() == "T_clock_to_Q");
 
@@ -0,0 +1,39 @@
+use std::fmt;
+
+use xml::common::TextPosition;
+
+/// A problem the parser worked around instead of failing on, such as a
+/// section it skipped or a tag it ignored.
+///
+/// Returned by the `*_with_warnings` entry points, alongside the parsed
+/// architecture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseWarning {
+    pub message: String,
+    /// Where in the file the problem is, if it points at one.
+    pub position: Option<TextPosition>,
+}
+
+impl ParseWarning {
+    pub(crate) fn at(message: String, position: TextPosition) -> Self {
+        Self {
+            message,
+            position: Some(position),
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(pos) => write!(
+                f,
+                "{} (line {}, column {})",
+                self.message,
+                pos.row + 1,
+                pos.column + 1
+            ),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Commonly Used Types
+//!
+//! The architecture types most consumers need, gathered so they can be
+//! imported at once:
+//!
+//! ```
+//! use fpga_arch_parser::prelude::*;
+//! ```
+//!
+//! Everything here is also available from the crate root.
+
+pub use crate::arch::{
+    AutoLayout, BlifModel, ClockPort, DelayInfo, DelayType, DeviceInfo, DeviceLayouts, FPGAArch,
+    FixedLayout, GlobalDirect, GridLocation, InputPort, Interconnect, InterconnectType, Layout,
+    Metadata, Model, ModelPort, OutputPort, PBMode, PBType, PBTypeClass, PinEquivalence, PinSide,
+    Port, PortClass, SBType, Segment, SegmentLength, SegmentType, SourceLine, SubTile, Switch,
+    SwitchType, Tile, TileSite, TileableLayoutConfig,
+};
+pub use crate::arch_builder::FPGAArchBuilder;
+pub use crate::complex_block_graph::ComplexBlockGraph;
+pub use crate::parse_error::FPGAArchParseError;
+pub use crate::parse_options::ParseOptions;
+pub use crate::parse_warning::ParseWarning;
+pub use crate::tile_pin_mapper::TilePinMapper;
+pub use crate::validate_arch::ValidationWarning;
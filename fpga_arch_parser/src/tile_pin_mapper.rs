@@ -8,7 +8,7 @@ use crate::{
 type TilePinIndexMap = HashMap<String, Vec<HashMap<String, Vec<usize>>>>;
 
 // TODO: This should be consolidated with PinLoc.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct PhysicalPinLoc {
     pub side: PinSide,
     pub xoffset: usize,
@@ -25,6 +25,7 @@ impl Default for PhysicalPinLoc {
     }
 }
 
+#[derive(PartialEq)]
 pub struct TilePinMapper {
     pub num_pins_in_tile: usize,
     // [sub_tile_name][sub_tile_cap_index][port_bus_name][port_index] -> pin_index
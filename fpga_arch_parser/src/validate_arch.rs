@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{
+    BlifModel, ComplexBlockPortId, FPGAArch, PBType, PBTypeClass, SBType, SegmentSwitchPoints,
+    SwitchBlockLocationsPattern,
+};
+
+/// Switches that VTR adds to every architecture on its own, so they may be
+/// referenced without appearing in the `<switchlist>`.
+pub const RESERVED_SWITCH_NAMES: &[&str] = &["__vpr_delayless_switch__"];
+
+/// A non-fatal problem found in an otherwise parseable architecture.
+///
+/// These are issues that VTR may reject or silently mishandle, but which do
+/// not stop the architecture from being visualized.
+#[derive(Clone, Debug)]
+pub struct ValidationWarning {
+    pub message: String,
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Runs the non-fatal consistency checks over a parsed architecture and
+/// returns every warning found.
+pub fn validate_architecture(arch: &FPGAArch) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+
+    validate_switch_references(arch, &mut warnings);
+    validate_custom_switch_blocks(arch, &mut warnings);
+    validate_complete_interconnects(arch, &mut warnings);
+    for pb_type in &arch.complex_block_list {
+        validate_pb_type(pb_type, &mut warnings);
+    }
+
+    warnings
+}
+
+fn validate_pb_type(pb_type: &PBType, warnings: &mut Vec<ValidationWarning>) {
+    validate_pb_type_class(pb_type, warnings);
+
+    for child in &pb_type.pb_types {
+        validate_pb_type(child, warnings);
+    }
+    for mode in &pb_type.modes {
+        for child in &mode.pb_types {
+            validate_pb_type(child, warnings);
+        }
+    }
+}
+
+/// Checks that a pb_type's class agrees with its blif_model.
+fn validate_pb_type_class(pb_type: &PBType, warnings: &mut Vec<ValidationWarning>) {
+    let (class_name, expected_model) = match pb_type.class {
+        PBTypeClass::Lut => ("lut", ".names"),
+        PBTypeClass::FlipFlop => ("flipflop", ".latch"),
+        PBTypeClass::Memory => ("memory", ".subckt"),
+        _ => return,
+    };
+
+    let matches = matches!(
+        (&pb_type.class, &pb_type.blif_model),
+        (PBTypeClass::Lut, Some(BlifModel::Names))
+            | (PBTypeClass::FlipFlop, Some(BlifModel::Latch))
+            | (PBTypeClass::Memory, Some(BlifModel::Subckt(_)))
+    );
+    if !matches {
+        warnings.push(ValidationWarning {
+            message: format!(
+                "pb_type '{}' has class '{}' but blif_model '{}' (expected '{}')",
+                pb_type.name,
+                class_name,
+                pb_type
+                    .blif_model
+                    .as_ref()
+                    .map_or("<none>".to_string(), BlifModel::raw),
+                expected_model,
+            ),
+        });
+    }
+}
+
+/// Checks that every `complete` interconnect has pins on both sides. A
+/// crossbar without sources or sinks is legal, but is usually a mistake in its
+/// port references.
+fn validate_complete_interconnects(arch: &FPGAArch, warnings: &mut Vec<ValidationWarning>) {
+    // Every instance of a pb_type has its own interconnect nodes, so the same
+    // interconnect, by its parent pb_type and name, is only reported once.
+    let mut reported: HashSet<(&str, &str)> = HashSet::new();
+    for graph in &arch.complex_block_graphs {
+        for node in &graph.complex_block_nodes {
+            let is_complete = node
+                .primitive_info
+                .as_ref()
+                .is_some_and(|info| info.class == PBTypeClass::InterconnectComplete);
+            let Some(parent_mode) = node.parent_mode else {
+                continue;
+            };
+            if !is_complete {
+                continue;
+            }
+            let count_pins = |port_ids: &[ComplexBlockPortId]| -> usize {
+                port_ids
+                    .iter()
+                    .map(|&port_id| graph.complex_block_ports[port_id].pins.len())
+                    .sum()
+            };
+            let num_sources = count_pins(&node.input_ports);
+            let num_sinks = count_pins(&node.output_ports);
+            if num_sources > 0 && num_sinks > 0 {
+                continue;
+            }
+
+            let mode = &graph.complex_block_modes[parent_mode];
+            let parent = &graph.complex_block_nodes[mode.parent_complex_block];
+            if !reported.insert((&parent.name, &node.name)) {
+                continue;
+            }
+            warnings.push(ValidationWarning {
+                message: format!(
+                    "complete interconnect '{}' in pb_type '{}' connects nothing ({num_sources} source pin(s), {num_sinks} sink pin(s))",
+                    node.name, parent.name
+                ),
+            });
+        }
+    }
+}
+
+/// Checks that a device asking for custom switch blocks has at least one
+/// `<switchblock>` to build them from.
+fn validate_custom_switch_blocks(arch: &FPGAArch, warnings: &mut Vec<ValidationWarning>) {
+    if matches!(arch.device.switch_block.sb_type, SBType::Custom)
+        && arch.custom_switch_blocks.is_empty()
+    {
+        warnings.push(ValidationWarning {
+            message:
+                "<switch_block> has type 'custom' but <switchblocklist> defines no switchblocks"
+                    .to_string(),
+        });
+    }
+}
+
+/// Checks that every switch referenced by name is defined in the switch list
+/// or is one of VTR's reserved switches.
+fn validate_switch_references(arch: &FPGAArch, warnings: &mut Vec<ValidationWarning>) {
+    let defined: HashSet<&str> = arch
+        .switch_list
+        .iter()
+        .map(|switch| switch.name.as_str())
+        .chain(RESERVED_SWITCH_NAMES.iter().copied())
+        .collect();
+
+    let mut check = |switch_name: &str, referenced_by: String| {
+        if !defined.contains(switch_name) {
+            warnings.push(ValidationWarning {
+                message: format!("{referenced_by} references unknown switch '{switch_name}'"),
+            });
+        }
+    };
+
+    check(
+        &arch.device.connection_block.input_switch_name,
+        "<connection_block>".to_string(),
+    );
+    for segment in &arch.segment_list {
+        let switch_names = match &segment.switch_points {
+            SegmentSwitchPoints::Unidir { mux_inc, mux_dec } => [mux_inc, mux_dec],
+            SegmentSwitchPoints::Bidir {
+                wire_switch,
+                opin_switch,
+            } => [wire_switch, opin_switch],
+        };
+        for switch_name in switch_names {
+            check(switch_name, format!("segment '{}'", segment.name));
+        }
+    }
+    for direct in &arch.direct_list {
+        if let Some(switch_name) = &direct.switch_name {
+            check(switch_name, format!("direct '{}'", direct.name));
+        }
+    }
+    for tile in &arch.tiles {
+        let Some(locations) = &tile.switchblock_locations else {
+            continue;
+        };
+        if let Some(switch_name) = &locations.internal_switch {
+            check(switch_name, format!("tile '{}'", tile.name));
+        }
+        if let SwitchBlockLocationsPattern::Custom(custom_locations) = &locations.pattern {
+            for location in custom_locations {
+                if let Some(switch_name) = &location.switch_override {
+                    check(switch_name, format!("tile '{}'", tile.name));
+                }
+            }
+        }
+    }
+    for switch_block in &arch.custom_switch_blocks {
+        for wireconn in &switch_block.wireconns {
+            if let Some(switch_name) = &wireconn.switch_override {
+                check(switch_name, format!("switchblock '{}'", switch_block.name));
+            }
+        }
+    }
+}
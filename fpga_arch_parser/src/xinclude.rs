@@ -0,0 +1,191 @@
+//! XInclude Resolution
+//!
+//! Architecture files may be split across several files with
+//! `<xi:include href="..."/>`. The includes are resolved up front by inlining
+//! the referenced files into a single document, which is then parsed as usual.
+//! Positions in parse errors refer to this merged document; an `IncludeMap`
+//! takes them back to the file they came from.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use xml::EmitterConfig;
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::EventWriter;
+
+use crate::parse_error::FPGAArchParseError;
+
+const XINCLUDE_NAMESPACE: &str = "http://www.w3.org/2001/XInclude";
+
+/// Whether `data` may hold XInclude references. Files that cannot are parsed
+/// directly, without the extra pass.
+pub(crate) fn may_contain_includes(data: &[u8]) -> bool {
+    data.windows(XINCLUDE_NAMESPACE.len())
+        .any(|window| window == XINCLUDE_NAMESPACE.as_bytes())
+}
+
+/// Maps the lines of a document with its includes merged in back to the
+/// files they were read from. Rows count from 0, as in `TextPosition`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeMap {
+    root: PathBuf,
+    /// (first merged row, file, row in that file) of each run of lines copied
+    /// from one file, in merged row order.
+    spans: Vec<(u64, PathBuf, u64)>,
+}
+
+impl IncludeMap {
+    /// The map of a document without includes, whose lines are all its own.
+    pub(crate) fn identity(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// The file and row that `row` of the merged document was read from.
+    pub fn source_of(&self, row: u64) -> (&Path, u64) {
+        let index = self.spans.partition_point(|(start, ..)| *start <= row);
+        match index.checked_sub(1).map(|i| &self.spans[i]) {
+            Some((start, file, source_row)) => (file, source_row + (row - start)),
+            None => (&self.root, row),
+        }
+    }
+
+    fn record(&mut self, merged_row: u64, file: &Path, source_row: u64) {
+        if let Some((start, last_file, last_row)) = self.spans.last()
+            && last_file == file
+            && merged_row >= *start
+            && last_row + (merged_row - start) == source_row
+        {
+            return;
+        }
+        self.spans
+            .push((merged_row, file.to_path_buf(), source_row));
+    }
+}
+
+/// Reads the file at `path` with every include replaced by the contents of
+/// the file it references. Includes are resolved relative to the file that
+/// contains them and may be nested; a file that includes itself, directly or
+/// not, is an error.
+pub(crate) fn resolve_includes(path: &Path) -> Result<(Vec<u8>, IncludeMap), FPGAArchParseError> {
+    let mut merger = Merger {
+        writer: EmitterConfig::new()
+            .write_document_declaration(true)
+            .create_writer(Vec::new()),
+        include_stack: Vec::new(),
+        map: IncludeMap::identity(path),
+        counted_bytes: 0,
+        merged_row: 0,
+    };
+    merger.inline_file(path, true)?;
+    Ok((merger.writer.into_inner(), merger.map))
+}
+
+struct Merger {
+    writer: EventWriter<Vec<u8>>,
+    include_stack: Vec<PathBuf>,
+    map: IncludeMap,
+    /// How much of the output has been scanned for line breaks so far, and
+    /// the row it ends on.
+    counted_bytes: usize,
+    merged_row: u64,
+}
+
+impl Merger {
+    /// The row the next write to the output starts on.
+    fn current_row(&mut self) -> u64 {
+        let output = self.writer.inner_ref();
+        self.merged_row += output[self.counted_bytes..]
+            .iter()
+            .filter(|&&byte| byte == b'\n')
+            .count() as u64;
+        self.counted_bytes = output.len();
+        self.merged_row
+    }
+
+    fn inline_file(&mut self, path: &Path, is_root: bool) -> Result<(), FPGAArchParseError> {
+        let canonical_path = path
+            .canonicalize()
+            .map_err(|e| FPGAArchParseError::IncludeError(format!("{}: {e}", path.display())))?;
+        if self.include_stack.contains(&canonical_path) {
+            return Err(FPGAArchParseError::IncludeError(format!(
+                "Include cycle: {} includes itself",
+                path.display()
+            )));
+        }
+        let file = File::open(&canonical_path)
+            .map_err(|e| FPGAArchParseError::IncludeError(format!("{}: {e}", path.display())))?;
+        self.include_stack.push(canonical_path);
+
+        let base_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut parser = EventReader::new(BufReader::new(file));
+        loop {
+            let event = match parser.next() {
+                Ok(event) => event,
+                Err(e) => {
+                    return Err(FPGAArchParseError::XMLParseError(
+                        format!("{}: {e:?}", path.display()),
+                        parser.position(),
+                    ));
+                }
+            };
+            match &event {
+                XmlEvent::EndDocument => break,
+                // Only the main file contributes the document declaration.
+                XmlEvent::StartDocument { .. } | XmlEvent::Doctype { .. } if !is_root => continue,
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } if name.local_name == "include"
+                    && name.namespace.as_deref() == Some(XINCLUDE_NAMESPACE) =>
+                {
+                    let mut href = None;
+                    for a in attributes {
+                        match a.name.local_name.as_str() {
+                            "href" => href = Some(a.value.clone()),
+                            "parse" if a.value != "xml" => {
+                                return Err(FPGAArchParseError::AttributeParseError(
+                                    format!("{a}: only XML includes are supported"),
+                                    parser.position(),
+                                ));
+                            }
+                            _ => {}
+                        }
+                    }
+                    let Some(href) = href else {
+                        return Err(FPGAArchParseError::MissingRequiredAttribute(
+                            "href".to_string(),
+                            parser.position(),
+                        ));
+                    };
+                    self.inline_file(&base_dir.join(href), false)?;
+                    // Drop the include element itself, along with any fallback.
+                    parser.skip().map_err(|e| {
+                        FPGAArchParseError::XMLParseError(
+                            format!("{}: {e:?}", path.display()),
+                            parser.position(),
+                        )
+                    })?;
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some(writer_event) = event.as_writer_event() {
+                let merged_row = self.current_row();
+                self.map.record(merged_row, path, parser.position().row);
+                self.writer.write(writer_event).map_err(|e| {
+                    FPGAArchParseError::XMLParseError(
+                        format!("{}: {e}", path.display()),
+                        parser.position(),
+                    )
+                })?;
+            }
+        }
+
+        self.include_stack.pop();
+        Ok(())
+    }
+}
@@ -1,12 +1,28 @@
 use std::path::{PathBuf, absolute};
 
 use fpga_arch_parser::{
-    ChanWDist, CustomSwitchBlockLocation, CustomSwitchBlockType, FPGAArchParseError, GridLocation,
-    Layout, PBTypeClass, Port, SBType, SegmentType, SubTileIOFC, SubTilePinLocations,
-    SwitchBlockLocationType, SwitchBlockLocationsPattern, SwitchBufSize, SwitchType,
-    TileSitePinMapping,
+    ArchEvent, AutoLayout, BlifModel, ChanWDist, CustomSwitchBlockLocation, CustomSwitchBlockType,
+    FPGAArchBuilder, FPGAArchParseError, FillGridLocation, GridLocation, InputPort, Interconnect,
+    InterconnectType, Layer, Layout, LayoutExprError, LayoutExprVars, OutputPort, PBType,
+    PBTypeClass, ParseOptions, PinEquivalence, Port, PortClass, SBType, SegmentLength, SegmentType,
+    SourceLine, SubTile, SubTileFC, SubTileIOFC, SubTilePinLocations, SwitchBlockLocationType,
+    SwitchBlockLocationsPattern, SwitchBufSize, SwitchType, Tile, TileSite, TileSitePinMapping,
+    eval_layout_expr,
 };
 
+/// The k4_N4_90nm test architecture with each `(from, to)` replacement
+/// applied in turn. Every `from` must appear in the file, so a fixture that
+/// drifts from the architecture fails loudly instead of testing nothing.
+fn k4_n4_with(replacements: &[(&str, &str)]) -> String {
+    let mut xml = std::fs::read_to_string(PathBuf::from("tests/k4_N4_90nm.xml"))
+        .expect("Failed to read arch file");
+    for (from, to) in replacements {
+        assert!(xml.contains(from), "k4_N4_90nm.xml has no {from:?}");
+        xml = xml.replace(from, to);
+    }
+    xml
+}
+
 #[test]
 #[allow(clippy::excessive_precision)]
 fn test_k4_n4_90nm_parse() -> Result<(), FPGAArchParseError> {
@@ -51,12 +67,12 @@ fn test_k4_n4_90nm_parse() -> Result<(), FPGAArchParseError> {
     ));
     // TODO: Add stronger tests for ports.
     assert!(matches!(
-        res.tiles[0].sub_tiles[0].fc.in_fc,
-        SubTileIOFC::Frac { .. }
-    ));
-    assert!(matches!(
-        res.tiles[0].sub_tiles[0].fc.out_fc,
-        SubTileIOFC::Frac { .. }
+        res.tiles[0].sub_tiles[0].fc,
+        Some(SubTileFC {
+            in_fc: SubTileIOFC::Frac { .. },
+            out_fc: SubTileIOFC::Frac { .. },
+            ..
+        })
     ));
     assert!(matches!(
         res.tiles[0].sub_tiles[0].pin_locations,
@@ -143,7 +159,7 @@ fn test_k4_n4_90nm_parse() -> Result<(), FPGAArchParseError> {
     // Check segment list
     assert_eq!(res.segment_list.len(), 1);
     assert_eq!(res.segment_list[0].freq, 1.0);
-    assert_eq!(res.segment_list[0].length, 1);
+    assert_eq!(res.segment_list[0].length, SegmentLength::Finite(1));
     assert!(matches!(
         res.segment_list[0].segment_type,
         SegmentType::Unidir
@@ -174,7 +190,7 @@ fn test_k4_n4_90nm_parse() -> Result<(), FPGAArchParseError> {
     assert_eq!(clb0_mode0.name, "inpad");
     assert_eq!(clb0_mode0.pb_types.len(), 1);
     assert_eq!(clb0_mode0.pb_types[0].name, "inpad");
-    assert!(clb0_mode0.pb_types[0].blif_model.is_some());
+    assert_eq!(clb0_mode0.pb_types[0].blif_model, Some(BlifModel::Input));
     assert_eq!(clb0_mode0.interconnects.len(), 1);
     assert_eq!(clb0.modes[1].name, "outpad");
     assert_eq!(clb0.modes[1].pb_types.len(), 1);
@@ -202,7 +218,8 @@ fn test_k6_n10_40nm_parse() -> Result<(), FPGAArchParseError> {
     let input_xml_relative = PathBuf::from("tests/k6_N10_40nm.xml");
     let input_xml = absolute(&input_xml_relative).expect("Failed to get absolute path");
 
-    let _res = fpga_arch_parser::parse(&input_xml)?;
+    let res = fpga_arch_parser::parse(&input_xml)?;
+    assert_eq!(res.skipped_sections, vec!["power", "clocks"]);
 
     Ok(())
 }
@@ -318,14 +335,14 @@ fn test_stratix_iv_parse() -> Result<(), FPGAArchParseError> {
     let seg1 = &res.segment_list[0];
     assert_eq!(seg1.name, "L4");
     assert_eq!(seg1.freq, 260.0);
-    assert_eq!(seg1.length, 4);
+    assert_eq!(seg1.length, SegmentLength::Finite(4));
     assert!(matches!(seg1.segment_type, SegmentType::Unidir));
     assert_eq!(seg1.r_metal, 201.7);
     assert_eq!(seg1.c_metal, 18.0e-15);
     let seg2 = &res.segment_list[1];
     assert_eq!(seg2.name, "L16");
     assert_eq!(seg2.freq, 40.0);
-    assert_eq!(seg2.length, 16);
+    assert_eq!(seg2.length, SegmentLength::Finite(16));
     assert!(matches!(seg2.segment_type, SegmentType::Unidir));
     assert_eq!(seg2.r_metal, 50.42);
     assert_eq!(seg2.c_metal, 20.7e-15);
@@ -1086,3 +1103,1279 @@ fn embedded_star_noc_topology() -> Result<(), FPGAArchParseError> {
 
     Ok(())
 }
+
+#[test]
+fn test_k4_n4_90nm_pb_type_counts() -> Result<(), FPGAArchParseError> {
+    let input_xml_relative = PathBuf::from("tests/k4_N4_90nm.xml");
+    let input_xml = absolute(&input_xml_relative).expect("Failed to get absolute path");
+
+    let res = fpga_arch_parser::parse(&input_xml)?;
+
+    // The clb has inputs, outputs, and a clock.
+    let clb = &res.complex_block_list[1];
+    assert_eq!(clb.name, "clb");
+    assert_eq!(clb.num_input_pins(), 10);
+    assert_eq!(clb.num_output_pins(), 4);
+    assert_eq!(clb.num_clock_pins(), 1);
+    // No modes, so the mode index is ignored.
+    assert_eq!(clb.num_interconnects(0), 3);
+    assert_eq!(clb.num_interconnects(5), 3);
+
+    // The fle has a single mode.
+    let fle = &clb.pb_types[0];
+    assert_eq!(fle.name, "fle");
+    assert_eq!(fle.num_input_pins(), 4);
+    assert_eq!(fle.num_output_pins(), 1);
+    assert_eq!(fle.num_clock_pins(), 1);
+    assert_eq!(fle.num_interconnects(0), 3);
+    assert_eq!(fle.num_interconnects(1), 0);
+
+    // The io has one mode per direction.
+    let io = &res.complex_block_list[0];
+    assert_eq!(io.name, "io");
+    assert_eq!(io.num_input_pins(), 1);
+    assert_eq!(io.num_output_pins(), 1);
+    assert_eq!(io.num_clock_pins(), 1);
+    assert_eq!(io.num_interconnects(0), 1);
+    assert_eq!(io.num_interconnects(1), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_pb_type_class_blif_model() -> Result<(), FPGAArchParseError> {
+    let input_xml_relative = PathBuf::from("tests/k6_frac_N10_frac_chain_mem32K_40nm.xml");
+    let input_xml = absolute(&input_xml_relative).expect("Failed to get absolute path");
+
+    // A well-formed architecture should not produce any warnings.
+    let res = fpga_arch_parser::parse(&input_xml)?;
+    assert!(fpga_arch_parser::validate_architecture(&res).is_empty());
+
+    // Swap the lut and flipflop models so both classes disagree with them.
+    let input_xml = k4_n4_with(&[
+        (
+            r#"name="lut4" blif_model=".names""#,
+            r#"name="lut4" blif_model=".latch""#,
+        ),
+        (
+            r#"name="ff" blif_model=".latch""#,
+            r#"name="ff" blif_model=".names""#,
+        ),
+    ]);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let warnings = fpga_arch_parser::validate_architecture(&res);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        warnings[0].message,
+        "pb_type 'lut4' has class 'lut' but blif_model '.latch' (expected '.names')"
+    );
+    assert_eq!(
+        warnings[1].message,
+        "pb_type 'ff' has class 'flipflop' but blif_model '.names' (expected '.latch')"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blif_model_parse() {
+    assert_eq!(BlifModel::parse(".names"), BlifModel::Names);
+    assert_eq!(BlifModel::parse(".latch"), BlifModel::Latch);
+    assert_eq!(BlifModel::parse(".input"), BlifModel::Input);
+    assert_eq!(BlifModel::parse(".output"), BlifModel::Output);
+
+    // Extra whitespace around the model name is dropped.
+    let adder = BlifModel::parse(" .subckt   adder ");
+    assert_eq!(adder, BlifModel::Subckt("adder".to_string()));
+    assert_eq!(adder.subckt_name(), Some("adder"));
+    assert_eq!(adder.raw(), ".subckt adder");
+
+    // A bare model name, or a prefix that only starts like ".subckt", is kept
+    // as written.
+    assert_eq!(
+        BlifModel::parse("adder"),
+        BlifModel::Other("adder".to_string())
+    );
+    assert_eq!(
+        BlifModel::parse(".subcktadder"),
+        BlifModel::Other(".subcktadder".to_string())
+    );
+    assert_eq!(BlifModel::parse("adder").raw(), "adder");
+    assert_eq!(BlifModel::Names.subckt_name(), None);
+}
+
+#[test]
+fn test_lenient_sub_tile_without_equivalent_sites() -> Result<(), FPGAArchParseError> {
+    // Remove the equivalent sites from the clb tile.
+    let input_xml = k4_n4_with(&[(
+        r#"<equivalent_sites>
+          <site pb_type="clb" pin_mapping="direct"/>
+        </equivalent_sites>"#,
+        "",
+    )]);
+
+    // Strict parsing requires the equivalent sites.
+    assert!(matches!(
+        fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()),
+        Err(FPGAArchParseError::MissingRequiredTag(_))
+    ));
+
+    // Lenient parsing leaves the sub-tile without any sites.
+    let res = fpga_arch_parser::parse_from_bytes_with_options(
+        input_xml.as_bytes(),
+        &ParseOptions::lenient(),
+    )?;
+    assert_eq!(res.tiles[1].name, "clb");
+    assert!(res.tiles[1].sub_tiles[0].equivalent_sites.is_empty());
+    assert_eq!(res.tiles[0].sub_tiles[0].equivalent_sites.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_warnings() -> Result<(), FPGAArchParseError> {
+    // The power and clocks sections are skipped with a warning each.
+    let input_xml = std::fs::read_to_string(PathBuf::from("tests/k6_frac_N10_40nm.xml"))
+        .expect("Failed to read arch file");
+    let (_, warnings) = fpga_arch_parser::parse_from_bytes_with_warnings(
+        input_xml.as_bytes(),
+        &ParseOptions::default(),
+    )?;
+    let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "<power> is not supported yet and was skipped",
+            "<clocks> is not supported yet and was skipped",
+        ]
+    );
+    let position = warnings[0].position.expect("warning has a position");
+    assert_eq!(position.row + 1, 375);
+    assert!(warnings[0].to_string().ends_with("(line 375, column 3)"));
+
+    // A lenient parse reports what it filled in.
+    let input_xml = k4_n4_with(&[(
+        r#"<equivalent_sites>
+          <site pb_type="clb" pin_mapping="direct"/>
+        </equivalent_sites>"#,
+        "",
+    )]);
+    let (_, warnings) = fpga_arch_parser::parse_from_bytes_with_warnings(
+        input_xml.as_bytes(),
+        &ParseOptions::lenient(),
+    )?;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "sub_tile 'clb' has no <equivalent_sites>"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_empty_routing_lists() -> Result<(), FPGAArchParseError> {
+    let input_xml = k4_n4_with(&[]);
+    let require_routing = ParseOptions {
+        require_routing: true,
+        ..ParseOptions::default()
+    };
+    let require_routing_lenient = ParseOptions {
+        require_routing: true,
+        ..ParseOptions::lenient()
+    };
+
+    // Lists with entries parse under any options, without warnings.
+    for options in [
+        ParseOptions::default(),
+        ParseOptions::lenient(),
+        require_routing,
+        require_routing_lenient,
+    ] {
+        let (res, warnings) =
+            fpga_arch_parser::parse_from_bytes_with_warnings(input_xml.as_bytes(), &options)?;
+        assert_eq!(res.switch_list.len(), 2);
+        assert_eq!(res.segment_list.len(), 1);
+        assert!(warnings.is_empty());
+    }
+
+    let switches_start = input_xml.find("<switchlist>").unwrap();
+    let switches_end = input_xml.find("</switchlist>").unwrap() + "</switchlist>".len();
+    let segments_start = input_xml.find("<segmentlist>").unwrap();
+    let segments_end = input_xml.find("</segmentlist>").unwrap() + "</segmentlist>".len();
+    let no_segments = format!(
+        "{}<segmentlist/>{}",
+        &input_xml[..segments_start],
+        &input_xml[segments_end..]
+    );
+    let no_routing = format!(
+        "{}<switchlist></switchlist>{}<segmentlist/>{}",
+        &input_xml[..switches_start],
+        &input_xml[switches_end..segments_start],
+        &input_xml[segments_end..]
+    );
+
+    // By default, strict or lenient, the empty lists are kept with a warning
+    // about each of them.
+    for options in [ParseOptions::default(), ParseOptions::lenient()] {
+        let (res, warnings) =
+            fpga_arch_parser::parse_from_bytes_with_warnings(no_routing.as_bytes(), &options)?;
+        assert!(res.switch_list.is_empty());
+        assert!(res.segment_list.is_empty());
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Missing <switch> in <switchlist>; the device cannot be routed without one",
+                "Missing <segment> in <segmentlist>; the device cannot be routed without one",
+            ]
+        );
+    }
+
+    // Requiring routing rejects an empty list, naming the first one found.
+    for options in [require_routing, require_routing_lenient] {
+        match fpga_arch_parser::parse_from_bytes_with_options(no_segments.as_bytes(), &options) {
+            Err(FPGAArchParseError::MissingRequiredTag(tag)) => {
+                assert_eq!(tag, "<segment> in <segmentlist>")
+            }
+            other => panic!("expected a missing segment, got {:?}", other.err()),
+        }
+        match fpga_arch_parser::parse_from_bytes_with_options(no_routing.as_bytes(), &options) {
+            Err(FPGAArchParseError::MissingRequiredTag(tag)) => {
+                assert_eq!(tag, "<switch> in <switchlist>")
+            }
+            other => panic!("expected a missing switch, got {:?}", other.err()),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_complex_block_graph_to_dot() -> Result<(), FPGAArchParseError> {
+    let input_xml_relative = PathBuf::from("tests/k4_N4_90nm.xml");
+    let input_xml = absolute(&input_xml_relative).expect("Failed to get absolute path");
+
+    let res = fpga_arch_parser::parse(&input_xml)?;
+
+    let dot = res.complex_block_graphs[1].to_dot();
+    assert!(dot.starts_with("digraph \"clb\" {\n"));
+    assert!(dot.ends_with("}\n"));
+    // Each fle instance is indexed, and primitives show their blif_model.
+    assert!(dot.contains("[label=\"fle[0]\", shape=box];"));
+    assert!(dot.contains("[label=\"fle[3]\", shape=box];"));
+    assert!(dot.contains("[label=\"lut4[0]\\n.names\", shape=box];"));
+    // The per-pin nets of the crossbar are merged into one edge per port pair.
+    assert!(dot.contains("n0 -> n45 [label=\"I -> input_0 (x10)\"];"));
+
+    Ok(())
+}
+
+#[test]
+fn test_arch_to_outline() -> Result<(), FPGAArchParseError> {
+    let input_xml_relative = PathBuf::from("tests/k4_N4_90nm.xml");
+    let input_xml = absolute(&input_xml_relative).expect("Failed to get absolute path");
+
+    let res = fpga_arch_parser::parse(&input_xml)?;
+
+    let outline = res.to_outline();
+    assert!(outline.starts_with("architecture\n├── models ("));
+    assert!(outline.contains("├── tiles (2)\n│   ├── io (1x1)\n"));
+    assert!(outline.contains("│   └── auto_layout (aspect_ratio 1)\n"));
+    assert!(outline.contains("│       ├── perimeter io (priority 100)\n"));
+    // The pb_type hierarchy nests modes, children and interconnects.
+    assert!(outline.contains("└── complex blocks (2)\n"));
+    assert!(outline.contains("    └── pb_type clb\n"));
+    assert!(outline.contains("pb_type fle x4"));
+    assert!(outline.contains("└── mode n1_lut4\n"));
+    assert!(outline.contains("pb_type lut4 (.names)"));
+    assert!(outline.contains("direct direct1: ble4.in -> lut4[0:0].in"));
+
+    Ok(())
+}
+
+#[test]
+fn test_clock_port_not_named_clk() -> Result<(), FPGAArchParseError> {
+    // Rename the flip-flop clock so its name gives no hint that it is a clock.
+    let input_xml = k4_n4_with(&[
+        (
+            r#"<clock name="clk" num_pins="1" port_class="clock"/>
+              <T_setup value="2.160000e-10" port="ff.D" clock="clk"/>
+              <T_clock_to_Q max="1.426000e-10" port="ff.Q" clock="clk"/>"#,
+            r#"<clock name="trigger" num_pins="1" port_class="clock"/>
+              <T_setup value="2.160000e-10" port="ff.D" clock="trigger"/>
+              <T_clock_to_Q max="1.426000e-10" port="ff.Q" clock="trigger"/>"#,
+        ),
+        (
+            r#"input="ble4.clk" output="ff.clk""#,
+            r#"input="ble4.clk" output="ff.trigger""#,
+        ),
+    ]);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let fle = &res.complex_block_list[1].pb_types[0];
+    let ble4 = &fle.modes[0].pb_types[0];
+    let ff = &ble4.pb_types[1];
+    assert_eq!(ff.name, "ff");
+    assert_eq!(ff.num_clock_pins(), 1);
+    match &ff.ports[2] {
+        Port::Clock(clock_port) => assert_eq!(clock_port.name, "trigger"),
+        _ => panic!("Expected the ff trigger port to be a clock port"),
+    }
+
+    // The renamed clock is still connected in the complex block graph.
+    let g = &res.complex_block_graphs[1];
+    assert!(
+        g.complex_block_ports
+            .iter()
+            .any(|port| port.name == "trigger")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_reserved_switch_names() -> Result<(), FPGAArchParseError> {
+    let arch_xml = k4_n4_with(&[]);
+
+    // VTR's built-in delayless switch does not need to be in the switch list.
+    let input_xml = arch_xml.replace(
+        r#"<mux name="0"/>"#,
+        r#"<mux name="__vpr_delayless_switch__"/>"#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    assert!(fpga_arch_parser::validate_architecture(&res).is_empty());
+
+    // Any other undefined switch is reported.
+    let input_xml = arch_xml.replace(r#"<mux name="0"/>"#, r#"<mux name="missing_sw"/>"#);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    let warnings = fpga_arch_parser::validate_architecture(&res);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(
+        warnings[0].message,
+        "segment 'UnnamedSegment' references unknown switch 'missing_sw'"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_tile_site_pb_types() -> Result<(), FPGAArchParseError> {
+    // Point the clb tile at a pb_type that is not in the complex block list.
+    let input_xml = k4_n4_with(&[(
+        r#"<site pb_type="clb" pin_mapping="direct"/>"#,
+        r#"<site pb_type="old_clb" pin_mapping="direct"/>"#,
+    )]);
+
+    // Strict parsing rejects the unresolved reference.
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::UnresolvedPBTypeReference(msg)) => assert_eq!(
+            msg,
+            "tile 'clb' sub_tile 'clb' references unknown pb_type 'old_clb'"
+        ),
+        other => panic!("expected an unresolved reference, got {:?}", other.err()),
+    }
+
+    // Lenient parsing keeps it and reports it once, as a parse warning.
+    let (res, warnings) = fpga_arch_parser::parse_from_bytes_with_warnings(
+        input_xml.as_bytes(),
+        &ParseOptions::lenient(),
+    )?;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "tile 'clb' sub_tile 'clb' references unknown pb_type 'old_clb'"
+    );
+    assert!(fpga_arch_parser::validate_architecture(&res).is_empty());
+
+    // Every unresolved site and every missing Fc is a parse warning.
+    let input_xml = k4_n4_with(&[
+        (
+            r#"<site pb_type="io" pin_mapping="direct"/>"#,
+            r#"<site pb_type="old_io" pin_mapping="direct"/>"#,
+        ),
+        (
+            r#"<site pb_type="clb" pin_mapping="direct"/>"#,
+            r#"<site pb_type="old_clb" pin_mapping="direct"/>"#,
+        ),
+        (
+            r#"<fc in_type="frac" in_val="1.0" out_type="frac" out_val="0.25"/>"#,
+            "",
+        ),
+        (
+            r#"<fc in_type="frac" in_val="0.15" out_type="frac" out_val="0.25"/>"#,
+            "",
+        ),
+    ]);
+    let (_, warnings) = fpga_arch_parser::parse_from_bytes_with_warnings(
+        input_xml.as_bytes(),
+        &ParseOptions::lenient(),
+    )?;
+    let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "tile 'io' sub_tile 'io' references unknown pb_type 'old_io'",
+            "tile 'clb' sub_tile 'clb' references unknown pb_type 'old_clb'",
+            "Missing <fc> in tile 'io' sub_tile 'io' (or <default_fc> in <device>); the sub tile has no Fc",
+            "Missing <fc> in tile 'clb' sub_tile 'clb' (or <default_fc> in <device>); the sub tile has no Fc",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_custom_switch_block_list() -> Result<(), FPGAArchParseError> {
+    // Ask for custom switch blocks without defining any.
+    let input_xml = k4_n4_with(&[(
+        r#"<switch_block type="wilton" fs="3"/>"#,
+        r#"<switch_block type="custom"/>"#,
+    )]);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let warnings = fpga_arch_parser::validate_architecture(&res);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "<switch_block> has type 'custom' but <switchblocklist> defines no switchblocks"
+    );
+
+    // An architecture that defines its switchblocks is fine.
+    let input_xml = PathBuf::from("tests/k6FracN10LB_mem20K_complexDSP_customSB_22nm.xml");
+    let input_xml = absolute(&input_xml).expect("Failed to get absolute path");
+    let res = fpga_arch_parser::parse(&input_xml)?;
+    assert!(matches!(res.device.switch_block.sb_type, SBType::Custom));
+    assert!(
+        fpga_arch_parser::validate_architecture(&res)
+            .iter()
+            .all(|w| !w.message.contains("switchblocklist"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_complete_interconnect_widths() -> Result<(), FPGAArchParseError> {
+    // Give the clb crossbar no sinks.
+    let input_xml = k4_n4_with(&[(
+        r#"<complete name="crossbar" input="clb.I fle[3:0].out" output="fle[3:0].in">"#,
+        r#"<complete name="crossbar" input="clb.I fle[3:0].out" output="">"#,
+    )]);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let warnings = fpga_arch_parser::validate_architecture(&res);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].message,
+        "complete interconnect 'crossbar' in pb_type 'clb' connects nothing (14 source pin(s), 0 sink pin(s))"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_mode_disable_packing() -> Result<(), FPGAArchParseError> {
+    let input_xml = k4_n4_with(&[(
+        r#"<mode name="outpad">"#,
+        r#"<mode name="outpad" disable_packing="true">"#,
+    )]);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let io = &res.complex_block_list[0];
+    assert_eq!(io.modes[0].name, "inpad");
+    assert!(!io.modes[0].disable_packing);
+    assert_eq!(io.modes[1].name, "outpad");
+    assert!(io.modes[1].disable_packing);
+
+    // The attribute is a boolean.
+    let input_xml = input_xml.replace(
+        r#"disable_packing="true""#,
+        r#"disable_packing="sometimes""#,
+    );
+    assert!(matches!(
+        fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()),
+        Err(FPGAArchParseError::AttributeParseError(..))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_physical_mode_name() -> Result<(), FPGAArchParseError> {
+    let input_xml = k4_n4_with(&[]);
+
+    // Without a physical mode, the first mode is the default.
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    let io = &res.complex_block_list[0];
+    assert_eq!(io.physical_mode_name, None);
+    assert_eq!(io.physical_mode_index(), None);
+    assert_eq!(io.default_mode_index(), 0);
+
+    let input_xml = input_xml.replace(
+        r#"<pb_type name="io">"#,
+        r#"<pb_type name="io" physical_mode_name="outpad">"#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    let io = &res.complex_block_list[0];
+    assert_eq!(io.physical_mode_name.as_deref(), Some("outpad"));
+    assert_eq!(io.physical_mode_index(), Some(1));
+    assert_eq!(io.default_mode_index(), 1);
+
+    // The physical mode must be one of the modes of the pb_type.
+    let input_xml = input_xml.replace(
+        r#"physical_mode_name="outpad""#,
+        r#"physical_mode_name="bidir""#,
+    );
+    assert!(matches!(
+        fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()),
+        Err(FPGAArchParseError::AttributeParseError(..))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_source_lines() -> Result<(), FPGAArchParseError> {
+    let arch_file = PathBuf::from("tests/k4_N4_90nm.xml");
+    let res = fpga_arch_parser::parse(&arch_file)?;
+    let source = String::from_utf8(fpga_arch_parser::read_arch_source(&arch_file)?)
+        .expect("arch file is UTF-8");
+    let line = |n: SourceLine| source.lines().nth(n.0.expect("element has a line") - 1);
+
+    // Each element points at the line its tag opens on.
+    let clb_tile = &res.tiles[1];
+    assert_eq!(
+        line(clb_tile.source_line).map(str::trim),
+        Some(r#"<tile name="clb">"#)
+    );
+    let clb = &res.complex_block_list[1];
+    assert_eq!(
+        line(clb.source_line).map(str::trim),
+        Some(r#"<pb_type name="clb">"#)
+    );
+    let fle = &clb.pb_types[0];
+    assert_eq!(
+        line(fle.source_line).map(str::trim),
+        Some(r#"<pb_type name="fle" num_pb="4">"#)
+    );
+    let crossbar = &clb.interconnects[0];
+    assert!(line(crossbar.source_line).unwrap().contains(&crossbar.name));
+
+    // Moving the elements down a few lines does not make them unequal.
+    let moved = fpga_arch_parser::parse_from_bytes(
+        k4_n4_with(&[("<tiles>", "<!-- Tiles -->\n\n  <tiles>")]).as_bytes(),
+    )?;
+    assert_ne!(moved.tiles[1].source_line.0, clb_tile.source_line.0);
+    assert!(moved.tiles == res.tiles);
+    assert!(moved.complex_block_list == res.complex_block_list);
+
+    Ok(())
+}
+
+#[test]
+fn test_device_default_fc() -> Result<(), FPGAArchParseError> {
+    let input_xml = k4_n4_with(&[(
+        r#"<fc in_type="frac" in_val="0.15" out_type="frac" out_val="0.25"/>"#,
+        "",
+    )]);
+
+    // Without its own <fc>, the clb needs a device default. Lenient parsing
+    // leaves it without an Fc rather than making one up.
+    assert!(matches!(
+        fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()),
+        Err(FPGAArchParseError::MissingRequiredTag(..))
+    ));
+    let res = fpga_arch_parser::parse_from_bytes_with_options(
+        input_xml.as_bytes(),
+        &ParseOptions::lenient(),
+    )?;
+    assert!(res.tiles[1].sub_tiles[0].fc.is_none());
+    assert!(!res.tiles[1].sub_tiles[0].fc_from_default);
+
+    let input_xml = input_xml.replace(
+        "<device>",
+        r#"<device>
+    <default_fc in_type="frac" in_val="0.2" out_type="abs" out_val="4"/>"#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    let default_fc = res
+        .device
+        .default_fc
+        .as_ref()
+        .expect("default_fc is parsed");
+    assert!(default_fc.in_fc == SubTileIOFC::Frac(0.2));
+    assert!(default_fc.out_fc == SubTileIOFC::Abs(4));
+
+    // The io keeps its own Fc and the clb falls back to the default.
+    let io = &res.tiles[0].sub_tiles[0];
+    let clb = &res.tiles[1].sub_tiles[0];
+    assert!(io.fc.as_ref() != Some(default_fc));
+    assert!(!io.fc_from_default);
+    assert!(clb.fc.as_ref() == Some(default_fc));
+    assert!(clb.fc_from_default);
+
+    // An <fc> that happens to equal the default is still the sub tile's own.
+    let input_xml = input_xml.replace(
+        r#"<default_fc in_type="frac" in_val="0.2" out_type="abs" out_val="4"/>"#,
+        r#"<default_fc in_type="frac" in_val="1.0" out_type="frac" out_val="0.25"/>"#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    let io = &res.tiles[0].sub_tiles[0];
+    assert!(io.fc == res.device.default_fc);
+    assert!(!io.fc_from_default);
+    assert!(res.tiles[1].sub_tiles[0].fc_from_default);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_streaming() -> Result<(), FPGAArchParseError> {
+    let input_xml = PathBuf::from("tests/k6_frac_N10_frac_chain_mem32K_40nm.xml");
+    let input_xml = absolute(&input_xml).expect("Failed to get absolute path");
+    let arch = fpga_arch_parser::parse(&input_xml)?;
+
+    let file = std::fs::File::open(&input_xml).expect("Failed to open architecture file");
+    let mut tile_names = Vec::new();
+    let mut root_pb_type_names = Vec::new();
+    let mut num_pb_types = 0;
+    let mut open_events = 0;
+    fpga_arch_parser::parse_streaming(file, &mut |event| match event {
+        ArchEvent::TileStart { name } => {
+            tile_names.push(name);
+            open_events += 1;
+        }
+        ArchEvent::PbTypeStart { name, depth, .. } => {
+            if depth == 0 {
+                root_pb_type_names.push(name);
+            }
+            num_pb_types += 1;
+            open_events += 1;
+        }
+        ArchEvent::ModeStart { .. } => open_events += 1,
+        ArchEvent::TileEnd { .. } | ArchEvent::PbTypeEnd { .. } | ArchEvent::ModeEnd { .. } => {
+            open_events -= 1
+        }
+    })?;
+
+    let expected_tile_names: Vec<_> = arch.tiles.iter().map(|t| t.name.clone()).collect();
+    assert_eq!(tile_names, expected_tile_names);
+    let expected_root_names: Vec<_> = arch
+        .complex_block_list
+        .iter()
+        .map(|pb_type| pb_type.name.clone())
+        .collect();
+    assert_eq!(root_pb_type_names, expected_root_names);
+    assert!(num_pb_types > root_pb_type_names.len());
+    assert_eq!(open_events, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_arch_builder_one_tile() -> Result<(), FPGAArchParseError> {
+    let input_port = |name: &str, num_pins| InputPort {
+        name: name.to_string(),
+        num_pins,
+        equivalent: PinEquivalence::None,
+        is_non_clock_global: false,
+        port_class: PortClass::None,
+    };
+    let output_port = |name: &str, num_pins| OutputPort {
+        name: name.to_string(),
+        num_pins,
+        equivalent: PinEquivalence::None,
+        port_class: PortClass::None,
+    };
+
+    let sub_tiles = vec![SubTile {
+        name: "clb".to_string(),
+        capacity: 1,
+        equivalent_sites: vec![TileSite {
+            pb_type: "clb".to_string(),
+            pin_mapping: TileSitePinMapping::Direct,
+        }],
+        ports: vec![
+            Port::Input(input_port("I", 4)),
+            Port::Output(output_port("O", 1)),
+        ],
+        fc: Some(SubTileFC {
+            in_fc: SubTileIOFC::Frac(0.15),
+            out_fc: SubTileIOFC::Frac(0.1),
+            fc_overrides: Vec::new(),
+        }),
+        fc_from_default: false,
+        pin_locations: SubTilePinLocations::Spread,
+    }];
+    let pin_mapper = fpga_arch_parser::build_tile_pin_mapper(&sub_tiles, 1, 1)?;
+    let tile = Tile {
+        name: "clb".to_string(),
+        ports: Vec::new(),
+        sub_tiles,
+        width: 1,
+        height: 1,
+        area: None,
+        switchblock_locations: None,
+        pin_mapper,
+        source_line: SourceLine(None),
+    };
+
+    let direct = |name: &str, input: &str, output: &str| Interconnect {
+        name: name.to_string(),
+        input: input.to_string(),
+        output: output.to_string(),
+        interconnect_type: InterconnectType::Direct,
+        pack_patterns: Vec::new(),
+        delays: Vec::new(),
+        metadata: None,
+        source_line: SourceLine(None),
+    };
+    let pb_type = PBType {
+        name: "clb".to_string(),
+        ports: vec![
+            Port::Input(input_port("I", 4)),
+            Port::Output(output_port("O", 1)),
+        ],
+        pb_types: vec![PBType {
+            name: "lut4".to_string(),
+            blif_model: Some(BlifModel::Names),
+            class: PBTypeClass::Lut,
+            ports: vec![
+                Port::Input(input_port("in", 4)),
+                Port::Output(output_port("out", 1)),
+            ],
+            ..Default::default()
+        }],
+        interconnects: vec![
+            direct("lut_in", "clb.I", "lut4.in"),
+            direct("lut_out", "lut4.out", "clb.O"),
+        ],
+        ..Default::default()
+    };
+
+    let layout = Layout::AutoLayout(AutoLayout {
+        aspect_ratio: 1.0,
+        layers: vec![Layer {
+            die: 0,
+            grid_locations: vec![GridLocation::Fill(FillGridLocation {
+                pb_type: "clb".to_string(),
+                priority: 10,
+                metadata: None,
+            })],
+        }],
+    });
+
+    let arch = FPGAArchBuilder::new()
+        .add_tile(tile)
+        .add_pb_type(pb_type)
+        .add_layout(layout)
+        .build()?;
+
+    assert_eq!(arch.tiles.len(), 1);
+    assert_eq!(arch.tiles[0].pin_mapper.num_pins_in_tile, 5);
+    assert_eq!(arch.layouts.layout_list.len(), 1);
+    assert_eq!(arch.complex_block_graphs.len(), 1);
+    assert!(fpga_arch_parser::validate_architecture(&arch).is_empty());
+
+    let dot = arch.complex_block_graphs[0].to_dot();
+    assert!(dot.contains("n0 -> n2 [label=\"I -> input (x4)\"];"));
+    assert!(dot.contains("n3 -> n0 [label=\"output -> O\"];"));
+
+    Ok(())
+}
+
+#[test]
+fn test_arch_builder_checks_like_the_parser() -> Result<(), FPGAArchParseError> {
+    let tile = |site: &str| -> Result<Tile, FPGAArchParseError> {
+        let sub_tiles = vec![SubTile {
+            name: "clb".to_string(),
+            capacity: 1,
+            equivalent_sites: vec![TileSite {
+                pb_type: site.to_string(),
+                pin_mapping: TileSitePinMapping::Direct,
+            }],
+            ports: Vec::new(),
+            fc: None,
+            fc_from_default: false,
+            pin_locations: SubTilePinLocations::Spread,
+        }];
+        Ok(Tile {
+            name: "clb".to_string(),
+            ports: Vec::new(),
+            pin_mapper: fpga_arch_parser::build_tile_pin_mapper(&sub_tiles, 1, 1)?,
+            sub_tiles,
+            width: 1,
+            height: 1,
+            area: None,
+            switchblock_locations: None,
+            source_line: SourceLine(None),
+        })
+    };
+    let pb_type = || PBType {
+        name: "clb".to_string(),
+        ..Default::default()
+    };
+
+    let res = FPGAArchBuilder::new()
+        .add_tile(tile("old_clb")?)
+        .add_pb_type(pb_type())
+        .build();
+    assert!(matches!(
+        res,
+        Err(FPGAArchParseError::UnresolvedPBTypeReference(..))
+    ));
+
+    let res = FPGAArchBuilder::new()
+        .add_tile(tile("clb")?)
+        .add_pb_type(pb_type())
+        .build();
+    assert!(matches!(
+        res,
+        Err(FPGAArchParseError::MissingRequiredTag(..))
+    ));
+
+    let default_fc = SubTileFC {
+        in_fc: SubTileIOFC::Frac(0.5),
+        out_fc: SubTileIOFC::Abs(2),
+        fc_overrides: Vec::new(),
+    };
+    let device = fpga_arch_parser::DeviceInfo {
+        default_fc: Some(default_fc.clone()),
+        ..Default::default()
+    };
+    let arch = FPGAArchBuilder::new()
+        .add_tile(tile("clb")?)
+        .add_pb_type(pb_type())
+        .device(device)
+        .build()?;
+    let sub_tile = &arch.tiles[0].sub_tiles[0];
+    assert!(sub_tile.fc == Some(default_fc));
+    assert!(sub_tile.fc_from_default);
+
+    Ok(())
+}
+
+#[test]
+fn test_pin_loc_split_characters() -> Result<(), FPGAArchParseError> {
+    // The processing instruction splits the pin list into two separate text events.
+    let input_xml = k4_n4_with(&[]).replacen(
+        r#"<loc side="left">io.outpad io.inpad io.clock</loc>"#,
+        r#"<loc side="left">io.outpad io.inpad <?split?>io.clock</loc>"#,
+        1,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+
+    match &res.tiles[0].sub_tiles[0].pin_locations {
+        SubTilePinLocations::Custom(custom) => {
+            assert_eq!(
+                custom.pin_locations[0].pin_strings,
+                vec!["io.outpad", "io.inpad", "io.clock"]
+            );
+        }
+        _ => panic!("Expected custom pin locations"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_interconnect_name() {
+    // Give a second mux in ble4 the same name as the existing mux1.
+    let input_xml = k4_n4_with(&[(
+        r#"<direct name="direct3" input="ble4.clk" output="ff.clk"/>"#,
+        r#"<mux name="mux1" input="ble4.clk" output="ff.clk"/>"#,
+    )]);
+
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::DuplicateName(name, _)) => {
+            assert_eq!(name, "interconnect 'mux1' in pb_type 'ble4'");
+        }
+        Err(e) => panic!("Expected a duplicate name error, got {e:?}"),
+        Ok(_) => panic!("Expected a duplicate name error"),
+    }
+}
+
+#[test]
+fn test_unknown_pin_equivalence() {
+    // Use an equivalence that VTR does not define on the clb input port.
+    let input_xml = k4_n4_with(&[(
+        r#"<input name="I" num_pins="10" equivalent="full"/>"#,
+        r#"<input name="I" num_pins="10" equivalent="partial"/>"#,
+    )]);
+
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::AttributeParseError(msg, _)) => {
+            assert_eq!(msg, "Unknown pin equivalence: partial");
+        }
+        Err(e) => panic!("Expected an attribute parse error, got {e:?}"),
+        Ok(_) => panic!("Expected an attribute parse error"),
+    }
+}
+
+#[test]
+fn test_port_num_pins_is_required() {
+    let input_xml = k4_n4_with(&[(
+        r#"<input name="I" num_pins="10" equivalent="full"/>"#,
+        r#"<input name="I" equivalent="full"/>"#,
+    )]);
+
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::MissingRequiredAttribute(attr, pos)) => {
+            assert_eq!(attr, "num_pins");
+            let line = input_xml.lines().nth(pos.row as usize).unwrap();
+            assert!(line.contains(r#"<input name="I" equivalent="full"/>"#));
+        }
+        Err(e) => panic!("Expected a missing attribute error, got {e:?}"),
+        Ok(_) => panic!("Expected a missing attribute error"),
+    }
+}
+
+#[test]
+fn test_port_num_pins_must_be_positive() {
+    let input_xml = k4_n4_with(&[(
+        r#"<input name="I" num_pins="10" equivalent="full"/>"#,
+        r#"<input name="I" num_pins="0" equivalent="full"/>"#,
+    )]);
+
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::AttributeParseError(msg, _)) => {
+            assert_eq!(msg, "Port 'I' num_pins must be at least 1: num_pins=0");
+        }
+        Err(e) => panic!("Expected an attribute parse error, got {e:?}"),
+        Ok(_) => panic!("Expected an attribute parse error"),
+    }
+}
+
+#[test]
+fn test_sub_tile_capacity_must_be_positive() {
+    let input_xml = k4_n4_with(&[(
+        r#"<sub_tile name="io" capacity="3">"#,
+        r#"<sub_tile name="io" capacity="0">"#,
+    )]);
+
+    match fpga_arch_parser::parse_from_bytes(input_xml.as_bytes()) {
+        Err(FPGAArchParseError::AttributeParseError(msg, _)) => {
+            assert_eq!(msg, "Sub-tile capacity must be at least 1: capacity=0");
+        }
+        Err(e) => panic!("Expected an attribute parse error, got {e:?}"),
+        Ok(_) => panic!("Expected an attribute parse error"),
+    }
+}
+
+#[test]
+fn test_parse_from_reader() -> Result<(), FPGAArchParseError> {
+    let file = std::fs::File::open(PathBuf::from("tests/k4_N4_90nm.xml"))
+        .expect("Failed to open architecture file");
+    let res = fpga_arch_parser::parse_from_reader(std::io::BufReader::new(file))?;
+
+    assert_eq!(res.tiles.len(), 2);
+    assert_eq!(res.complex_block_list.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_segment_longline_length() -> Result<(), FPGAArchParseError> {
+    let arch_xml = k4_n4_with(&[]);
+
+    let res = fpga_arch_parser::parse_from_bytes(arch_xml.as_bytes())?;
+    assert_eq!(res.segment_list[0].length, SegmentLength::Finite(1));
+
+    let input_xml = arch_xml.replace(r#"length="1""#, r#"length="longline""#);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    assert_eq!(res.segment_list[0].length, SegmentLength::LongLine);
+
+    // Anything else that is not a number is still rejected.
+    let input_xml = arch_xml.replace(r#"length="1""#, r#"length="long""#);
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes());
+    assert!(matches!(
+        res,
+        Err(FPGAArchParseError::AttributeParseError(_, _))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_tile_dimensions_and_area() -> Result<(), FPGAArchParseError> {
+    let arch_xml = k4_n4_with(&[]);
+    let with_clb_attrs = |attrs: &str| {
+        arch_xml.replace(
+            r#"<tile name="clb">"#,
+            &format!(r#"<tile name="clb" {attrs}>"#),
+        )
+    };
+
+    // The smallest valid footprint and a zero area are accepted.
+    let res = fpga_arch_parser::parse_from_bytes(
+        with_clb_attrs(r#"width="1" height="1" area="0""#).as_bytes(),
+    )?;
+    let clb = res.tiles.iter().find(|tile| tile.name == "clb").unwrap();
+    assert_eq!((clb.width, clb.height), (1, 1));
+    assert_eq!(clb.area, Some(0.0));
+
+    for attrs in [
+        r#"width="0""#,
+        r#"height="0""#,
+        r#"width="-2""#,
+        r#"height="-1""#,
+        r#"area="-1.5""#,
+    ] {
+        let res = fpga_arch_parser::parse_from_bytes(with_clb_attrs(attrs).as_bytes());
+        match res {
+            Err(FPGAArchParseError::AttributeParseError(msg, _)) => {
+                assert!(msg.contains("'clb'"), "{attrs}: {msg}");
+            }
+            Err(e) => panic!("{attrs}: unexpected error {e:?}"),
+            Ok(_) => panic!("{attrs}: expected an error"),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_grid_location_expressions() -> Result<(), FPGAArchParseError> {
+    let arch_xml = k4_n4_with(&[]);
+    let with_location = |location: &str| {
+        arch_xml.replace(
+            r#"<fill type="clb" priority="10"/>"#,
+            &format!(r#"<fill type="clb" priority="10"/>{location}"#),
+        )
+    };
+
+    // Expressions are evaluated the way the grid will evaluate them.
+    let vars = LayoutExprVars {
+        device_size: Some((10, 8)),
+        tile_size: (2, 1),
+    };
+    for (expr, value) in [
+        ("W/2 - 1", 4),
+        ("(w + 2) * 4", 16),
+        ("max(W - 2, 1)", 8),
+        ("-h + H", 7),
+        ("min(W, H) % 3 + gcd(W, 4) * lcm(w, 3)", 14),
+        ("W > H && H >= 8 || 0", 1),
+        ("1 + 2 * 3 == 7", 1),
+    ] {
+        assert_eq!(eval_layout_expr(expr, &vars), Ok(value), "{expr}");
+    }
+    assert!(matches!(
+        eval_layout_expr("W / (H - 8)", &vars),
+        Err(LayoutExprError::Undefined(_))
+    ));
+    let unknown_device = LayoutExprVars {
+        device_size: None,
+        ..vars
+    };
+    assert!(matches!(
+        eval_layout_expr("W - 1", &unknown_device),
+        Err(LayoutExprError::Undefined(_))
+    ));
+    assert_eq!(eval_layout_expr("w * 3", &unknown_device), Ok(6));
+
+    for location in [
+        r#"<col type="clb" startx="W/2 - 1" repeatx="(w + 2) * 4" priority="20"/>"#,
+        r#"<single type="clb" x="max(W - 2, 1)" y="-h + H" priority="20"/>"#,
+    ] {
+        fpga_arch_parser::parse_from_bytes(with_location(location).as_bytes())?;
+    }
+
+    for (location, expected) in [
+        (
+            r#"<col type="clb" startx="W - " priority="20"/>"#,
+            r#"<col> startx="W - ": expected a value after '-'"#,
+        ),
+        (
+            r#"<row type="clb" starty="(H - 1" priority="20"/>"#,
+            r#"<row> starty="(H - 1": missing ')'"#,
+        ),
+        (
+            r#"<single type="clb" x="2" y="X / 2" priority="20"/>"#,
+            r#"<single> y="X / 2": unknown name 'X'"#,
+        ),
+        (
+            r#"<region type="clb" endx="W 2" priority="20"/>"#,
+            r#"<region> endx="W 2": expected an operator between values"#,
+        ),
+    ] {
+        match fpga_arch_parser::parse_from_bytes(with_location(location).as_bytes()) {
+            Err(FPGAArchParseError::AttributeParseError(msg, _)) => {
+                assert!(msg.starts_with(expected), "{location}: {msg}");
+            }
+            Err(e) => panic!("{location}: unexpected error {e:?}"),
+            Ok(_) => panic!("{location}: expected an error"),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_non_clock_global_ports() -> Result<(), FPGAArchParseError> {
+    let arch_xml = std::fs::read_to_string(PathBuf::from(
+        "tests/k6FracN10LB_mem20K_complexDSP_customSB_22nm.xml",
+    ))
+    .expect("Failed to read arch file");
+    let is_global_reset = |arch: &fpga_arch_parser::FPGAArch| {
+        let dsp_top = arch
+            .complex_block_list
+            .iter()
+            .find(|pb| pb.name == "dsp_top")
+            .expect("dsp_top exists");
+        dsp_top.ports.iter().any(
+            |port| matches!(port, Port::Input(p) if p.name == "reset" && p.is_non_clock_global),
+        )
+    };
+
+    let res = fpga_arch_parser::parse_from_bytes(arch_xml.as_bytes())?;
+    assert!(is_global_reset(&res));
+
+    // VTR also accepts numeric booleans.
+    let input_xml = arch_xml.replace(
+        r#"is_non_clock_global="true""#,
+        r#"is_non_clock_global="1""#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes())?;
+    assert!(is_global_reset(&res));
+
+    let input_xml = arch_xml.replace(
+        r#"is_non_clock_global="true""#,
+        r#"is_non_clock_global="maybe""#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes());
+    assert!(matches!(
+        res,
+        Err(FPGAArchParseError::AttributeParseError(_, _))
+    ));
+
+    // Only inputs can be non-clock globals.
+    let input_xml = arch_xml.replace(
+        r#"<input name="reset" num_pins="1" is_non_clock_global="true"/>"#,
+        r#"<output name="reset" num_pins="1" is_non_clock_global="true"/>"#,
+    );
+    let res = fpga_arch_parser::parse_from_bytes(input_xml.as_bytes());
+    assert!(matches!(
+        res,
+        Err(FPGAArchParseError::AttributeParseError(_, _))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_truncated_document() {
+    // Cut the file off just after the ble4 pb_type is opened.
+    let input_xml = k4_n4_with(&[]);
+    let ble4 = r#"<pb_type name="ble4" num_pb="1">"#;
+    let end = input_xml.find(ble4).expect("k4_N4_90nm.xml has a ble4") + ble4.len();
+
+    let res = fpga_arch_parser::parse_from_bytes(&input_xml.as_bytes()[..end]);
+    // The error points at the innermost element left open.
+    let position = res.as_ref().err().and_then(FPGAArchParseError::position);
+    assert_eq!(position.map(|pos| pos.row + 1), Some(136));
+    let Err(FPGAArchParseError::UnclosedElements(open_elements)) = res else {
+        panic!("Expected an unclosed elements error");
+    };
+    let open: Vec<(&str, Option<&str>, u64)> = open_elements
+        .iter()
+        .map(|e| (e.tag.as_str(), e.name.as_deref(), e.position.row + 1))
+        .collect();
+    assert_eq!(
+        open,
+        vec![
+            ("architecture", None, 6),
+            ("complexblocklist", None, 82),
+            ("pb_type", Some("clb"), 124),
+            ("pb_type", Some("fle"), 129),
+            ("mode", Some("n1_lut4"), 134),
+            ("pb_type", Some("ble4"), 136),
+        ]
+    );
+
+    // Readers, which cannot be read again, report the same open elements.
+    let res = fpga_arch_parser::parse_from_reader(&input_xml.as_bytes()[..end]);
+    let Err(FPGAArchParseError::UnclosedElements(reader_open_elements)) = res else {
+        panic!("Expected an unclosed elements error");
+    };
+    let reader_open: Vec<(&str, Option<&str>, u64)> = reader_open_elements
+        .iter()
+        .map(|e| (e.tag.as_str(), e.name.as_deref(), e.position.row + 1))
+        .collect();
+    assert_eq!(reader_open, open);
+}
+
+#[test]
+fn test_xinclude() -> Result<(), FPGAArchParseError> {
+    let arch_xml = k4_n4_with(&[]);
+    let segments_start = arch_xml.find("<segmentlist>").unwrap();
+    let segments_end = arch_xml.find("</segmentlist>").unwrap() + "</segmentlist>".len();
+    let segments_xml = &arch_xml[segments_start..segments_end];
+
+    // Move the segment list into its own file in a subdirectory.
+    let dir =
+        std::env::temp_dir().join(format!("fpga_arch_parser_xinclude_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("parts")).expect("Failed to create test directory");
+    std::fs::write(
+        dir.join("parts/segments.xml"),
+        format!("<?xml version=\"1.0\"?>\n{segments_xml}\n"),
+    )
+    .expect("Failed to write include");
+    let main_xml = arch_xml
+        .replace(
+            "<architecture>",
+            r#"<architecture xmlns:xi="http://www.w3.org/2001/XInclude">"#,
+        )
+        .replace(segments_xml, r#"<xi:include href="parts/segments.xml"/>"#);
+    let main_path = dir.join("arch.xml");
+    std::fs::write(&main_path, &main_xml).expect("Failed to write arch file");
+
+    let res = fpga_arch_parser::parse(&main_path)?;
+    assert_eq!(res.segment_list.len(), 1);
+    assert_eq!(res.segment_list[0].length, SegmentLength::Finite(1));
+
+    // Lines of the merged document map back to the file they came from.
+    let (data, map) = fpga_arch_parser::read_arch_source_with_map(&main_path)?;
+    let merged = String::from_utf8(data).expect("Merged document is not UTF-8");
+    let merged_row = |tag: &str| merged.lines().position(|l| l.contains(tag)).unwrap() as u64;
+    let segments_path = dir.join("parts/segments.xml");
+    assert_eq!(
+        map.source_of(merged_row("<segmentlist>")),
+        (segments_path.as_path(), 1)
+    );
+    assert_eq!(
+        map.source_of(merged_row("<segment ")),
+        (segments_path.as_path(), 2)
+    );
+    let main_row = main_xml
+        .lines()
+        .position(|l| l.contains("<complexblocklist>"))
+        .unwrap() as u64;
+    assert_eq!(
+        map.source_of(merged_row("<complexblocklist>")),
+        (main_path.as_path(), main_row)
+    );
+    assert_eq!(
+        map.source_of(merged_row("<switchlist>")),
+        (main_path.as_path(), 69)
+    );
+
+    // An included file that includes the main file again is a cycle.
+    std::fs::write(
+        dir.join("parts/segments.xml"),
+        r#"<segmentlist xmlns:xi="http://www.w3.org/2001/XInclude"><xi:include href="../arch.xml"/></segmentlist>"#,
+    )
+    .expect("Failed to write include");
+    let res = fpga_arch_parser::parse(&main_path);
+    assert!(matches!(res, Err(FPGAArchParseError::IncludeError(_))));
+
+    // So is a missing file.
+    std::fs::remove_file(dir.join("parts/segments.xml")).expect("Failed to remove include");
+    let res = fpga_arch_parser::parse(&main_path);
+    assert!(matches!(res, Err(FPGAArchParseError::IncludeError(_))));
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove test directory");
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Architecture Diff
+//!
+//! When a file is reloaded after being edited, the new architecture is
+//! compared against the one it replaces so the views can briefly highlight
+//! what changed.
+
+use std::collections::HashSet;
+
+use fpga_arch_parser::prelude::*;
+
+/// How long, in seconds, changes stay highlighted after a reload.
+pub const HIGHLIGHT_SECONDS: f64 = 4.0;
+
+/// The tiles and pb_types that differ between two loads of a file.
+#[derive(Debug, Default, PartialEq)]
+pub struct ArchDiff {
+    pub added_tiles: Vec<String>,
+    pub removed_tiles: Vec<String>,
+    pub changed_tiles: Vec<String>,
+    /// pb_types that are new or whose own definition changed, as type paths
+    /// such as "clb.fle.ble4".
+    pub changed_pb_types: Vec<String>,
+}
+
+impl ArchDiff {
+    pub fn between(old: &FPGAArch, new: &FPGAArch) -> Self {
+        let mut diff = Self::default();
+
+        for tile in &new.tiles {
+            match old.tiles.iter().find(|t| t.name == tile.name) {
+                None => diff.added_tiles.push(tile.name.clone()),
+                Some(old_tile) if old_tile != tile => diff.changed_tiles.push(tile.name.clone()),
+                Some(_) => {}
+            }
+        }
+        for tile in &old.tiles {
+            if !new.tiles.iter().any(|t| t.name == tile.name) {
+                diff.removed_tiles.push(tile.name.clone());
+            }
+        }
+
+        for pb_type in &new.complex_block_list {
+            let old_pb_type = old
+                .complex_block_list
+                .iter()
+                .find(|pb| pb.name == pb_type.name);
+            diff_pb_type(
+                old_pb_type,
+                pb_type,
+                &pb_type.name,
+                &mut diff.changed_pb_types,
+            );
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Tile types whose instances are highlighted in the grid.
+    pub fn highlighted_tiles(&self) -> HashSet<String> {
+        self.added_tiles
+            .iter()
+            .chain(&self.changed_tiles)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Names to highlight in a view, and how strongly.
+#[derive(Debug, Clone)]
+pub struct ChangeHighlight {
+    pub names: HashSet<String>,
+    /// Fades from 1 to 0 over `HIGHLIGHT_SECONDS`.
+    pub strength: f32,
+}
+
+/// How strongly something is highlighted `elapsed` seconds after it was
+/// shown, fading from 1 to 0 over `seconds` (`HIGHLIGHT_SECONDS` for changes).
+pub fn highlight_strength(elapsed: f64, seconds: f64) -> f32 {
+    (1.0 - elapsed / seconds).clamp(0.0, 1.0) as f32
+}
+
+/// The type path of a block instance: "clb.fle[2].ble4" -> "clb.fle.ble4".
+pub fn type_path(instance_path: &str) -> String {
+    instance_path
+        .split('.')
+        .map(|name| name.split_once('[').map_or(name, |(name, _)| name))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Records `new` at `path` if it is new or its own definition changed, then
+/// looks for changes among its children.
+fn diff_pb_type(old: Option<&PBType>, new: &PBType, path: &str, changed: &mut Vec<String>) {
+    let Some(old) = old else {
+        changed.push(path.to_string());
+        return;
+    };
+    if old == new {
+        return;
+    }
+
+    let old_children = child_pb_types(old);
+    let new_children = child_pb_types(new);
+    let children_removed = old_children.iter().any(|(mode, child)| {
+        !new_children
+            .iter()
+            .any(|(new_mode, new_child)| new_mode == mode && new_child.name == child.name)
+    });
+    if children_removed || !shallow_eq(old, new) {
+        changed.push(path.to_string());
+    }
+
+    for (mode, child) in new_children {
+        let old_child = old_children
+            .iter()
+            .find(|(old_mode, old_child)| *old_mode == mode && old_child.name == child.name)
+            .map(|(_, old_child)| *old_child);
+        diff_pb_type(old_child, child, &format!("{path}.{}", child.name), changed);
+    }
+}
+
+/// Every child pb_type, along with the name of the mode it is in.
+fn child_pb_types(pb_type: &PBType) -> Vec<(Option<&str>, &PBType)> {
+    pb_type
+        .pb_types
+        .iter()
+        .map(|child| (None, child))
+        .chain(pb_type.modes.iter().flat_map(|mode| {
+            mode.pb_types
+                .iter()
+                .map(move |child| (Some(mode.name.as_str()), child))
+        }))
+        .collect()
+}
+
+/// Compares two pb_types without looking into their children.
+fn shallow_eq(a: &PBType, b: &PBType) -> bool {
+    a.name == b.name
+        && a.num_pb == b.num_pb
+        && a.blif_model == b.blif_model
+        && a.class == b.class
+        && a.ports == b.ports
+        && a.interconnects == b.interconnects
+        && a.delays == b.delays
+        && a.timing_constraints == b.timing_constraints
+        && a.metadata == b.metadata
+        && a.physical_mode_name == b.physical_mode_name
+        && a.modes.len() == b.modes.len()
+        && a.modes.iter().zip(&b.modes).all(|(a, b)| {
+            a.name == b.name
+                && a.disable_packing == b.disable_packing
+                && a.interconnects == b.interconnects
+                && a.metadata == b.metadata
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{k4_n4, k4_n4_with};
+
+    #[test]
+    fn finds_changed_tiles_and_pb_types() {
+        let old = k4_n4();
+        assert!(ArchDiff::between(&old, &k4_n4()).is_empty());
+
+        // Widen the ff's D input.
+        let new = k4_n4_with(&[(
+            r#"<input name="D" num_pins="1" port_class="D"/>"#,
+            r#"<input name="D" num_pins="2" port_class="D"/>"#,
+        )]);
+        let diff = ArchDiff::between(&old, &new);
+        assert_eq!(diff.changed_pb_types, vec!["clb.fle.ble4.ff"]);
+        assert!(diff.highlighted_tiles().is_empty());
+
+        // Rename the clb tile.
+        let new = k4_n4_with(&[(r#"<tile name="clb">"#, r#"<tile name="logic">"#)]);
+        let diff = ArchDiff::between(&old, &new);
+        assert_eq!(diff.added_tiles, vec!["logic"]);
+        assert_eq!(diff.removed_tiles, vec!["clb"]);
+        assert!(diff.changed_pb_types.is_empty());
+
+        // Lines added above the blocks move them without changing them.
+        let new = k4_n4_with(&[("<tiles>", "<!-- Tiles -->\n\n  <tiles>")]);
+        assert!(ArchDiff::between(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn maps_instance_paths_to_type_paths() {
+        assert_eq!(type_path("clb.fle[2].ble4"), "clb.fle.ble4");
+        assert_eq!(type_path("clb"), "clb");
+    }
+}
@@ -0,0 +1,74 @@
+//! Architecture Index
+//!
+//! Maps tile and complex block names to where they are in the architecture,
+//! so the views can look them up every frame without scanning the lists.
+
+use std::collections::HashMap;
+
+use fpga_arch_parser::prelude::*;
+
+/// Name lookups into the architecture it was built from. It holds indices
+/// into that architecture's lists, so it is rebuilt whenever a file loads.
+#[derive(Debug, Default)]
+pub struct ArchIndex {
+    tiles: HashMap<String, usize>,
+    complex_blocks: HashMap<String, usize>,
+}
+
+impl ArchIndex {
+    pub fn new(arch: &FPGAArch) -> Self {
+        // A repeated name resolves to its first definition, as a linear
+        // search would.
+        let mut tiles = HashMap::new();
+        for (i, tile) in arch.tiles.iter().enumerate() {
+            tiles.entry(tile.name.clone()).or_insert(i);
+        }
+        let mut complex_blocks = HashMap::new();
+        for (i, pb_type) in arch.complex_block_list.iter().enumerate() {
+            complex_blocks.entry(pb_type.name.clone()).or_insert(i);
+        }
+        Self {
+            tiles,
+            complex_blocks,
+        }
+    }
+
+    pub fn tile<'a>(&self, arch: &'a FPGAArch, name: &str) -> Option<&'a Tile> {
+        arch.tiles.get(*self.tiles.get(name)?)
+    }
+
+    /// Position of the named complex block in `complex_block_list`, which is
+    /// also its position in `complex_block_graphs`.
+    pub fn complex_block_index(&self, name: &str) -> Option<usize> {
+        self.complex_blocks.get(name).copied()
+    }
+
+    pub fn complex_block<'a>(&self, arch: &'a FPGAArch, name: &str) -> Option<&'a PBType> {
+        arch.complex_block_list.get(self.complex_block_index(name)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4;
+
+    #[test]
+    fn finds_tiles_and_complex_blocks_by_name() {
+        let arch = k4_n4();
+        let index = ArchIndex::new(&arch);
+
+        for tile in &arch.tiles {
+            assert!(std::ptr::eq(index.tile(&arch, &tile.name).unwrap(), tile));
+        }
+        for (i, pb_type) in arch.complex_block_list.iter().enumerate() {
+            assert_eq!(index.complex_block_index(&pb_type.name), Some(i));
+            assert!(std::ptr::eq(
+                index.complex_block(&arch, &pb_type.name).unwrap(),
+                pb_type
+            ));
+        }
+        assert!(index.tile(&arch, "missing").is_none());
+        assert!(index.complex_block(&arch, "missing").is_none());
+    }
+}
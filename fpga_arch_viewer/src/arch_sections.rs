@@ -0,0 +1,201 @@
+//! Architecture Sections
+//!
+//! Lists the optional sections an architecture file contains and how much of
+//! each the viewer shows, for the banner above the views.
+
+use eframe::egui;
+use fpga_arch_parser::prelude::*;
+
+/// How much of a section the viewer shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coverage {
+    Shown,
+    Partial,
+    NotShown,
+}
+
+impl Coverage {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Shown => "shown",
+            Self::Partial => "partly shown",
+            Self::NotShown => "not shown",
+        }
+    }
+}
+
+/// An optional section of the architecture file that is present.
+#[derive(Debug, PartialEq)]
+pub struct SectionInfo {
+    pub name: String,
+    /// What the section holds, e.g. "3 directs".
+    pub detail: String,
+    pub coverage: Coverage,
+}
+
+/// The optional sections present in `arch`, with what each holds and how much
+/// of it is shown.
+pub fn optional_sections(arch: &FPGAArch) -> Vec<SectionInfo> {
+    let mut sections = Vec::new();
+    let mut add = |name: &str, detail: String, coverage| {
+        sections.push(SectionInfo {
+            name: name.to_string(),
+            detail,
+            coverage,
+        });
+    };
+
+    if !arch.direct_list.is_empty() {
+        // Listed in the summary, but not drawn on the grid.
+        add(
+            "directlist",
+            format!("{} directs", arch.direct_list.len()),
+            Coverage::Partial,
+        );
+    }
+    if !arch.custom_switch_blocks.is_empty() {
+        add(
+            "switchblocklist",
+            format!("{} switchblocks", arch.custom_switch_blocks.len()),
+            Coverage::Partial,
+        );
+    }
+    if let Some(noc) = &arch.noc {
+        add(
+            "noc",
+            format!("{} routers", noc.topology.routers.len()),
+            Coverage::Shown,
+        );
+    }
+    if arch.layouts.tileable_config.is_some() {
+        // The options are listed, but no routing channels are drawn.
+        add("tileable", "routing options".to_string(), Coverage::Partial);
+    }
+    let metadata = count_metadata(arch);
+    if metadata > 0 {
+        add("metadata", format!("{metadata} entries"), Coverage::Shown);
+    }
+    for name in &arch.skipped_sections {
+        add(name, "present".to_string(), Coverage::NotShown);
+    }
+
+    sections
+}
+
+/// Number of `<meta>` entries on grid locations, pb_types, modes and
+/// interconnects.
+fn count_metadata(arch: &FPGAArch) -> usize {
+    let grid_locations: usize = arch
+        .layouts
+        .layout_list
+        .iter()
+        .flat_map(|layout| match layout {
+            Layout::AutoLayout(auto_layout) => &auto_layout.layers,
+            Layout::FixedLayout(fixed_layout) => &fixed_layout.layers,
+        })
+        .flat_map(|layer| &layer.grid_locations)
+        .map(|location| location.metadata().map_or(0, <[Metadata]>::len))
+        .sum();
+    let pb_types: usize = arch
+        .complex_block_list
+        .iter()
+        .map(count_pb_type_metadata)
+        .sum();
+    grid_locations + pb_types
+}
+
+fn count_pb_type_metadata(pb_type: &PBType) -> usize {
+    let mut count = metadata_len(&pb_type.metadata)
+        + count_interconnect_metadata(&pb_type.interconnects)
+        + pb_type
+            .pb_types
+            .iter()
+            .map(count_pb_type_metadata)
+            .sum::<usize>();
+    for mode in &pb_type.modes {
+        count += metadata_len(&mode.metadata)
+            + count_interconnect_metadata(&mode.interconnects)
+            + mode
+                .pb_types
+                .iter()
+                .map(count_pb_type_metadata)
+                .sum::<usize>();
+    }
+    count
+}
+
+fn count_interconnect_metadata(interconnects: &[Interconnect]) -> usize {
+    interconnects
+        .iter()
+        .map(|inter| metadata_len(&inter.metadata))
+        .sum()
+}
+
+fn metadata_len(metadata: &Option<Vec<Metadata>>) -> usize {
+    metadata.as_ref().map_or(0, Vec::len)
+}
+
+/// Shows the optional sections in a collapsible strip. Sets `dismissed` when
+/// the strip is closed.
+pub fn render_sections_banner(ui: &mut egui::Ui, sections: &[SectionInfo], dismissed: &mut bool) {
+    ui.horizontal(|ui| {
+        egui::CollapsingHeader::new(format!(
+            "ℹ Optional sections in this file ({})",
+            sections.len()
+        ))
+        .id_salt("arch_sections_banner")
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (i, section) in sections.iter().enumerate() {
+                    if i > 0 {
+                        ui.label(";");
+                    }
+                    let text = format!(
+                        "{}: {}, {}",
+                        section.name,
+                        section.detail,
+                        section.coverage.label()
+                    );
+                    if section.coverage == Coverage::NotShown {
+                        ui.weak(text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+        });
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+            if ui.small_button("✕").on_hover_text("Hide").clicked() {
+                *dismissed = true;
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_optional_sections_and_skipped_ones() {
+        let arch_file = std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../fpga_arch_parser/tests/k6_frac_N10_frac_chain_mem32K_40nm_with_a_2x2_mesh_noc_topology.xml"
+        ));
+        let arch =
+            fpga_arch_parser::parse(arch_file).expect("Failed to parse the NoC architecture");
+
+        let sections = optional_sections(&arch);
+        let noc = sections
+            .iter()
+            .find(|section| section.name == "noc")
+            .expect("the NoC is listed");
+        assert_eq!(noc.coverage, Coverage::Shown);
+        assert!(
+            sections
+                .iter()
+                .any(|section| section.name == "power" && section.coverage == Coverage::NotShown)
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use crate::color_scheme;
+use crate::color_scheme::{self, Palette};
 use eframe::egui::{self, Color32};
+use fpga_arch_parser::prelude::*;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockShape {
@@ -78,13 +79,104 @@ pub fn get_dark_color_palette() -> Vec<Color32> {
     ]
 }
 
-pub fn get_tile_color(_tile_name: &str, tile_index: usize, dark_mode: bool) -> Color32 {
-    let palette = if dark_mode {
+pub fn get_tile_color(
+    _tile_name: &str,
+    tile_index: usize,
+    palette: Palette,
+    dark_mode: bool,
+) -> Color32 {
+    if palette == Palette::ColorBlindSafe {
+        return color_scheme::okabe_ito_tile_color(tile_index, dark_mode);
+    }
+    let colors = if dark_mode {
         get_dark_color_palette()
     } else {
         get_default_color_palette()
     };
-    palette[tile_index % palette.len()]
+    colors[tile_index % colors.len()]
+}
+
+/// Kind of primitive a pb_type is, or holds, for coloring block headers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeaderClass {
+    Lut,
+    FlipFlop,
+    Memory,
+    Blif,
+    Generic,
+}
+
+impl HeaderClass {
+    /// The class of a pb_type itself, or Generic if it has none.
+    fn of_primitive(pb_type: &PBType) -> Self {
+        match pb_type.class {
+            PBTypeClass::Lut => HeaderClass::Lut,
+            PBTypeClass::FlipFlop => HeaderClass::FlipFlop,
+            PBTypeClass::Memory => HeaderClass::Memory,
+            _ if pb_type.blif_model.is_some() => HeaderClass::Blif,
+            _ => HeaderClass::Generic,
+        }
+    }
+
+    /// The class a block's header is colored by. A block without a class of
+    /// its own takes the class of its primitives when they all share one, so
+    /// e.g. a collapsed memory hierarchy still reads as memory.
+    pub fn of(pb_type: &PBType) -> Self {
+        let own = Self::of_primitive(pb_type);
+        if own != HeaderClass::Generic {
+            return own;
+        }
+        let mut shared = None;
+        if leaves_share_class(pb_type, &mut shared) {
+            shared.unwrap_or(HeaderClass::Generic)
+        } else {
+            HeaderClass::Generic
+        }
+    }
+}
+
+/// Whether every leaf below `pb_type`, in any mode, has the same class as
+/// `shared` (which is set from the first leaf found).
+fn leaves_share_class(pb_type: &PBType, shared: &mut Option<HeaderClass>) -> bool {
+    let children = pb_type
+        .pb_types
+        .iter()
+        .chain(pb_type.modes.iter().flat_map(|mode| &mode.pb_types));
+    let mut has_children = false;
+    for child in children {
+        has_children = true;
+        if !leaves_share_class(child, shared) {
+            return false;
+        }
+    }
+    if has_children {
+        return true;
+    }
+    let class = HeaderClass::of_primitive(pb_type);
+    *shared.get_or_insert(class) == class
+}
+
+/// Header background for a block, using the body color of its class.
+pub fn header_color(class: HeaderClass, palette: Palette, dark_mode: bool) -> Color32 {
+    match class {
+        HeaderClass::Lut => color_scheme::lut_colors(palette, dark_mode).bg,
+        HeaderClass::FlipFlop => color_scheme::flip_flop_colors(palette, dark_mode).bg,
+        HeaderClass::Memory => color_scheme::memory_colors(palette, dark_mode).bg,
+        HeaderClass::Blif => color_scheme::blif_colors(palette, dark_mode).bg,
+        HeaderClass::Generic => color_scheme::theme_header_bg(dark_mode),
+    }
+}
+
+/// Black or white, whichever reads better on `background`.
+pub fn contrasting_text_color(background: Color32) -> Color32 {
+    let luminance = 0.2126 * background.r() as f32
+        + 0.7152 * background.g() as f32
+        + 0.0722 * background.b() as f32;
+    if luminance > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
 }
 
 // Default block styles for the inter-tile grid view
@@ -198,3 +290,89 @@ pub fn draw_block(
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pb_type(name: &str, class: PBTypeClass, children: Vec<PBType>) -> PBType {
+        PBType {
+            name: name.to_string(),
+            class,
+            pb_types: children,
+            ..PBType::default()
+        }
+    }
+
+    #[test]
+    fn color_blind_palette_is_deterministic_and_distinct() {
+        for dark_mode in [false, true] {
+            let colors: Vec<Color32> = (0..7)
+                .map(|i| get_tile_color("tile", i, Palette::ColorBlindSafe, dark_mode))
+                .collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[..i].contains(color));
+                // Text on tiles is drawn in the theme's text color.
+                assert_eq!(
+                    contrasting_text_color(*color),
+                    if dark_mode {
+                        Color32::WHITE
+                    } else {
+                        Color32::BLACK
+                    }
+                );
+            }
+            // The palette repeats once every color has been used.
+            assert_eq!(
+                get_tile_color("other", 7, Palette::ColorBlindSafe, dark_mode),
+                colors[0]
+            );
+        }
+    }
+
+    #[test]
+    fn header_class_follows_primitives() {
+        let lut = pb_type("lut", PBTypeClass::Lut, vec![]);
+        let ff = pb_type("ff", PBTypeClass::FlipFlop, vec![]);
+        let mem = || pb_type("mem", PBTypeClass::Memory, vec![]);
+
+        assert_eq!(HeaderClass::of(&lut), HeaderClass::Lut);
+        // A wrapper around primitives of one class takes that class.
+        let memory = pb_type("memory", PBTypeClass::None, vec![mem(), mem()]);
+        assert_eq!(HeaderClass::of(&memory), HeaderClass::Memory);
+        // Mixed logic has no single class.
+        let ble = pb_type("ble", PBTypeClass::None, vec![lut, ff]);
+        assert_eq!(HeaderClass::of(&ble), HeaderClass::Generic);
+        // An empty block is generic.
+        let empty = pb_type("empty", PBTypeClass::None, vec![]);
+        assert_eq!(HeaderClass::of(&empty), HeaderClass::Generic);
+    }
+
+    #[test]
+    fn text_contrasts_with_header() {
+        for (palette, dark_mode) in Palette::ALL
+            .into_iter()
+            .flat_map(|palette| [(palette, false), (palette, true)])
+        {
+            let expected = if dark_mode {
+                Color32::WHITE
+            } else {
+                Color32::BLACK
+            };
+            for class in [
+                HeaderClass::Lut,
+                HeaderClass::FlipFlop,
+                HeaderClass::Memory,
+                HeaderClass::Blif,
+                HeaderClass::Generic,
+            ] {
+                let bg = header_color(class, palette, dark_mode);
+                assert_eq!(
+                    contrasting_text_color(bg),
+                    expected,
+                    "{palette:?} {class:?}"
+                );
+            }
+        }
+    }
+}
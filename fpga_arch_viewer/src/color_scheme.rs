@@ -4,6 +4,80 @@
 
 use eframe::egui;
 
+// ----------------------------------------------------------------------------
+// Palettes
+// ----------------------------------------------------------------------------
+
+/// Colors used for tile types, block classes, segment lengths, and pack
+/// patterns.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// The Okabe-Ito colors, which stay distinguishable with the common
+    /// kinds of color blindness.
+    ColorBlindSafe,
+}
+
+impl Palette {
+    pub const ALL: [Self; 2] = [Self::Standard, Self::ColorBlindSafe];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::ColorBlindSafe => "Color-blind safe",
+        }
+    }
+}
+
+const OKABE_ITO_ORANGE: egui::Color32 = egui::Color32::from_rgb(0xE6, 0x9F, 0x00);
+const OKABE_ITO_SKY_BLUE: egui::Color32 = egui::Color32::from_rgb(0x56, 0xB4, 0xE9);
+const OKABE_ITO_BLUISH_GREEN: egui::Color32 = egui::Color32::from_rgb(0x00, 0x9E, 0x73);
+const OKABE_ITO_YELLOW: egui::Color32 = egui::Color32::from_rgb(0xF0, 0xE4, 0x42);
+const OKABE_ITO_BLUE: egui::Color32 = egui::Color32::from_rgb(0x00, 0x72, 0xB2);
+const OKABE_ITO_VERMILLION: egui::Color32 = egui::Color32::from_rgb(0xD5, 0x5E, 0x00);
+const OKABE_ITO_REDDISH_PURPLE: egui::Color32 = egui::Color32::from_rgb(0xCC, 0x79, 0xA7);
+
+/// The Okabe-Ito colors for filled areas, leaving out its black.
+const OKABE_ITO_FILLS: [egui::Color32; 7] = [
+    OKABE_ITO_ORANGE,
+    OKABE_ITO_SKY_BLUE,
+    OKABE_ITO_BLUISH_GREEN,
+    OKABE_ITO_YELLOW,
+    OKABE_ITO_BLUE,
+    OKABE_ITO_VERMILLION,
+    OKABE_ITO_REDDISH_PURPLE,
+];
+
+/// An Okabe-Ito color as a line, lightened in dark mode so that it shows up
+/// on the dark background.
+fn okabe_ito_line(color: egui::Color32, dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        color.lerp_to_gamma(egui::Color32::WHITE, 0.25)
+    } else {
+        color
+    }
+}
+
+/// An Okabe-Ito color as a filled area: a pale tint in light mode and a deep
+/// shade in dark mode, so that text drawn over it stays readable.
+fn okabe_ito_fill(color: egui::Color32, dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        color.lerp_to_gamma(egui::Color32::BLACK, 0.6)
+    } else {
+        color.lerp_to_gamma(egui::Color32::WHITE, 0.7)
+    }
+}
+
+/// Color-blind safe fill for the tile type at `tile_index` among the sorted
+/// tile names.
+pub fn okabe_ito_tile_color(tile_index: usize, dark_mode: bool) -> egui::Color32 {
+    okabe_ito_fill(
+        OKABE_ITO_FILLS[tile_index % OKABE_ITO_FILLS.len()],
+        dark_mode,
+    )
+}
+
 // ----------------------------------------------------------------------------
 // Intra Tile Colors
 // ----------------------------------------------------------------------------
@@ -53,6 +127,33 @@ pub fn theme_interconnect_bg(dark_mode: bool) -> egui::Color32 {
     }
 }
 
+/// Line, ticks, and label of the measuring ruler
+pub fn theme_ruler_color(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgb(255, 200, 80)
+    } else {
+        egui::Color32::from_rgb(200, 60, 0)
+    }
+}
+
+/// Background behind the measuring ruler's label
+pub fn theme_ruler_label_bg(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_black_alpha(200)
+    } else {
+        egui::Color32::from_white_alpha(220)
+    }
+}
+
+/// Dots of the background grid behind the block diagram
+pub fn theme_canvas_grid_color(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgba_unmultiplied(255, 255, 255, 40)
+    } else {
+        egui::Color32::from_rgba_unmultiplied(0, 0, 0, 50)
+    }
+}
+
 // =======================
 // Block-Specific Colors
 // =======================
@@ -63,7 +164,15 @@ pub struct LutColors {
     pub text: egui::Color32,
 }
 
-pub fn lut_colors(dark_mode: bool) -> LutColors {
+pub fn lut_colors(palette: Palette, dark_mode: bool) -> LutColors {
+    if palette == Palette::ColorBlindSafe {
+        let line = okabe_ito_line(OKABE_ITO_ORANGE, dark_mode);
+        return LutColors {
+            bg: okabe_ito_fill(OKABE_ITO_ORANGE, dark_mode),
+            border: line,
+            text: line,
+        };
+    }
     LutColors {
         bg: if dark_mode {
             egui::Color32::from_rgb(100, 100, 50)
@@ -90,7 +199,15 @@ pub struct FlipFlopColors {
     pub text: egui::Color32,
 }
 
-pub fn flip_flop_colors(dark_mode: bool) -> FlipFlopColors {
+pub fn flip_flop_colors(palette: Palette, dark_mode: bool) -> FlipFlopColors {
+    if palette == Palette::ColorBlindSafe {
+        let line = okabe_ito_line(OKABE_ITO_BLUE, dark_mode);
+        return FlipFlopColors {
+            bg: okabe_ito_fill(OKABE_ITO_BLUE, dark_mode),
+            border: line,
+            text: line,
+        };
+    }
     FlipFlopColors {
         bg: if dark_mode {
             egui::Color32::from_rgb(50, 60, 100)
@@ -118,7 +235,16 @@ pub struct MemoryColors {
     pub grid: egui::Color32,
 }
 
-pub fn memory_colors(dark_mode: bool) -> MemoryColors {
+pub fn memory_colors(palette: Palette, dark_mode: bool) -> MemoryColors {
+    if palette == Palette::ColorBlindSafe {
+        let line = okabe_ito_line(OKABE_ITO_BLUISH_GREEN, dark_mode);
+        return MemoryColors {
+            bg: okabe_ito_fill(OKABE_ITO_BLUISH_GREEN, dark_mode),
+            border: line,
+            text: line,
+            grid: line,
+        };
+    }
     let border = if dark_mode {
         egui::Color32::from_rgb(0, 150, 0)
     } else {
@@ -143,7 +269,15 @@ pub struct BlifColors {
     pub text: egui::Color32,
 }
 
-pub fn blif_colors(dark_mode: bool) -> BlifColors {
+pub fn blif_colors(palette: Palette, dark_mode: bool) -> BlifColors {
+    if palette == Palette::ColorBlindSafe {
+        let line = okabe_ito_line(OKABE_ITO_VERMILLION, dark_mode);
+        return BlifColors {
+            bg: okabe_ito_fill(OKABE_ITO_VERMILLION, dark_mode),
+            border: line,
+            text: line,
+        };
+    }
     BlifColors {
         bg: if dark_mode {
             egui::Color32::from_rgb(100, 50, 50)
@@ -170,12 +304,25 @@ pub fn blif_colors(dark_mode: bool) -> BlifColors {
 /// Highlight color for hovered/selected elements
 pub const HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::RED;
 
+/// Outline color for highlighted tiles in the grid view. Kept distinct from
+/// the red interposer cut lines.
+pub const GRID_HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 0);
+
+/// Color that briefly marks what changed when a file is reloaded.
+pub const CHANGE_HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(40, 200, 120);
+
+/// Outline of the block that has the keyboard focus in the intra-tile view.
+pub const FOCUS_OUTLINE_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 140, 255);
+
 /// Input pin color
 pub const PIN_COLOR: egui::Color32 = egui::Color32::BLACK;
 
 /// Clock pin color
 pub const CLOCK_PIN_COLOR: egui::Color32 = egui::Color32::RED;
 
+/// Color of input pins marked `is_non_clock_global` (e.g. resets and enables)
+pub const GLOBAL_PIN_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 120, 0);
+
 // ----------------------------------------------------------------------------
 // Grid Tile Colors (Inter-Tile View)
 // ----------------------------------------------------------------------------
@@ -216,6 +363,124 @@ pub fn grid_cb_color(dark_mode: bool) -> egui::Color32 {
     }
 }
 
+/// Lines between the cells covered by a tile larger than one cell
+pub fn grid_cell_border_color(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgba_unmultiplied(200, 200, 200, 50)
+    } else {
+        egui::Color32::from_rgba_unmultiplied(40, 40, 40, 50)
+    }
+}
+
+/// Hatching on cells that were explicitly placed as EMPTY
+pub fn grid_empty_hatch_color(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgba_unmultiplied(160, 160, 160, 60)
+    } else {
+        egui::Color32::from_rgba_unmultiplied(100, 100, 100, 60)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Routing Segment Colors
+// ----------------------------------------------------------------------------
+
+/// Color for a routing segment, chosen by the rank of its length among the
+/// distinct segment lengths so that segments of equal length share a color.
+pub fn segment_length_color(
+    length_rank: usize,
+    palette: Palette,
+    dark_mode: bool,
+) -> egui::Color32 {
+    if palette == Palette::ColorBlindSafe {
+        // Yellow is left out, as thin lines of it are hard to see on white.
+        const LINES: [egui::Color32; 6] = [
+            OKABE_ITO_BLUE,
+            OKABE_ITO_ORANGE,
+            OKABE_ITO_BLUISH_GREEN,
+            OKABE_ITO_VERMILLION,
+            OKABE_ITO_REDDISH_PURPLE,
+            OKABE_ITO_SKY_BLUE,
+        ];
+        return okabe_ito_line(LINES[length_rank % LINES.len()], dark_mode);
+    }
+    const LIGHT: [egui::Color32; 6] = [
+        egui::Color32::from_rgb(0x1F, 0x77, 0xB4), // Blue
+        egui::Color32::from_rgb(0xFF, 0x7F, 0x0E), // Orange
+        egui::Color32::from_rgb(0x2C, 0xA0, 0x2C), // Green
+        egui::Color32::from_rgb(0xD6, 0x27, 0x28), // Red
+        egui::Color32::from_rgb(0x94, 0x67, 0xBD), // Purple
+        egui::Color32::from_rgb(0x8C, 0x56, 0x4B), // Brown
+    ];
+    const DARK: [egui::Color32; 6] = [
+        egui::Color32::from_rgb(0x6B, 0xAE, 0xD6), // Blue
+        egui::Color32::from_rgb(0xFD, 0xAE, 0x6B), // Orange
+        egui::Color32::from_rgb(0x74, 0xC4, 0x76), // Green
+        egui::Color32::from_rgb(0xFB, 0x6A, 0x4A), // Red
+        egui::Color32::from_rgb(0xBC, 0xBD, 0xDC), // Purple
+        egui::Color32::from_rgb(0xD9, 0xB3, 0x8C), // Brown
+    ];
+    let palette = if dark_mode { &DARK } else { &LIGHT };
+    palette[length_rank % palette.len()]
+}
+
+// ----------------------------------------------------------------------------
+// Pack Pattern Colors
+// ----------------------------------------------------------------------------
+
+/// Color for a pack pattern, chosen by the rank of its name among the pattern
+/// names in the complex block. Avoids red, which is used for highlighting.
+pub fn pack_pattern_color(pattern_rank: usize, palette: Palette, dark_mode: bool) -> egui::Color32 {
+    if palette == Palette::ColorBlindSafe {
+        // Vermillion is left out as well, as it is close to the highlight.
+        const LINES: [egui::Color32; 5] = [
+            OKABE_ITO_BLUISH_GREEN,
+            OKABE_ITO_REDDISH_PURPLE,
+            OKABE_ITO_BLUE,
+            OKABE_ITO_ORANGE,
+            OKABE_ITO_SKY_BLUE,
+        ];
+        return okabe_ito_line(LINES[pattern_rank % LINES.len()], dark_mode);
+    }
+    const LIGHT: [egui::Color32; 5] = [
+        egui::Color32::from_rgb(0x00, 0x89, 0x7B), // Teal
+        egui::Color32::from_rgb(0xC2, 0x18, 0x5B), // Magenta
+        egui::Color32::from_rgb(0x5E, 0x35, 0xB1), // Indigo
+        egui::Color32::from_rgb(0xEF, 0x6C, 0x00), // Orange
+        egui::Color32::from_rgb(0x55, 0x8B, 0x2F), // Olive
+    ];
+    const DARK: [egui::Color32; 5] = [
+        egui::Color32::from_rgb(0x4D, 0xD0, 0xE1), // Teal
+        egui::Color32::from_rgb(0xF0, 0x62, 0x92), // Magenta
+        egui::Color32::from_rgb(0x95, 0x75, 0xCD), // Indigo
+        egui::Color32::from_rgb(0xFF, 0xB7, 0x4D), // Orange
+        egui::Color32::from_rgb(0xAE, 0xD5, 0x81), // Olive
+    ];
+    let palette = if dark_mode { &DARK } else { &LIGHT };
+    palette[pattern_rank % palette.len()]
+}
+
+// ----------------------------------------------------------------------------
+// Delay Heatmap Colors
+// ----------------------------------------------------------------------------
+
+/// Heatmap cell color for a delay normalized to `t` in [0, 1], running from
+/// the fastest (cool) to the slowest (hot) delay in the matrix.
+pub fn heatmap_color(t: f32, dark_mode: bool) -> egui::Color32 {
+    let (cool, hot) = if dark_mode {
+        (
+            egui::Color32::from_rgb(0x1F, 0x4E, 0x79),
+            egui::Color32::from_rgb(0xE8, 0x5D, 0x3F),
+        )
+    } else {
+        (
+            egui::Color32::from_rgb(0xDE, 0xEB, 0xF7),
+            egui::Color32::from_rgb(0xCB, 0x18, 0x1D),
+        )
+    };
+    cool.lerp_to_gamma(hot, t.clamp(0.0, 1.0))
+}
+
 // ----------------------------------------------------------------------------
 // CRR Switch Block View Colors
 // ----------------------------------------------------------------------------
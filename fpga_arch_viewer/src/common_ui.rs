@@ -1,7 +1,11 @@
-use crate::viewer::ViewMode;
+use fpga_arch_parser::prelude::*;
+
+/// Metadata values longer than this many characters are cut short until
+/// clicked.
+const METADATA_VALUE_MAX_CHARS: usize = 40;
 
 /// Renders a welcome message when no architecture is loaded
-pub fn render_welcome_message(ui: &mut egui::Ui, view_mode: &ViewMode) {
+pub fn render_welcome_message(ui: &mut egui::Ui) {
     let available_rect = ui.available_rect_before_wrap();
     ui.scope_builder(
         egui::UiBuilder::new().max_rect(egui::Rect::from_center_size(
@@ -17,9 +21,7 @@ pub fn render_welcome_message(ui: &mut egui::Ui, view_mode: &ViewMode) {
                 ui.label("You can:");
                 ui.label("  • Use File > Open to load a VTR architecture file");
                 ui.label("  • Try File > Open Sample to explore sample architectures");
-                ui.label("  • Drag and drop an architecture file directly into the app");
-                ui.add_space(20.0);
-                ui.label(format!("Current mode: {:?}", view_mode));
+                ui.label("  • Drag and drop architecture files directly into the app");
             });
         },
     );
@@ -55,3 +57,104 @@ pub fn render_centered_message(
     );
     button_clicked
 }
+
+/// Shortens `name` to at most `max_chars` characters, ending it with "..."
+/// when it is cut. Counts characters rather than bytes so names with
+/// multi-byte characters are not split inside a character.
+pub fn truncate_label(name: &str, max_chars: usize) -> String {
+    if name.chars().count() > max_chars {
+        let kept: String = name.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{kept}...")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The shortened form of a metadata value, or None if it fits on one line.
+/// Values spanning several lines, such as lists of FASM features, show
+/// their first line.
+pub fn metadata_value_preview(value: &str) -> Option<String> {
+    let value = value.trim();
+    let first_line = value.lines().next().unwrap_or_default().trim_end();
+    if first_line.len() == value.len() && value.chars().count() <= METADATA_VALUE_MAX_CHARS {
+        return None;
+    }
+    let preview = truncate_label(first_line, METADATA_VALUE_MAX_CHARS);
+    if preview.ends_with("...") {
+        Some(preview)
+    } else {
+        Some(format!("{preview}..."))
+    }
+}
+
+/// Lists `<metadata>` key/value pairs under a "Metadata" heading. Long
+/// values are shortened; clicking one shows it in full, and clicking it
+/// again shortens it.
+pub fn render_metadata(ui: &mut egui::Ui, metadata: &[Metadata]) {
+    render_metadata_grid(ui, metadata, true);
+}
+
+/// Like `render_metadata`, for tooltips, which cannot be clicked: every
+/// value is shown in full.
+pub fn render_metadata_tooltip(ui: &mut egui::Ui, metadata: &[Metadata]) {
+    render_metadata_grid(ui, metadata, false);
+}
+
+fn render_metadata_grid(ui: &mut egui::Ui, metadata: &[Metadata], expandable: bool) {
+    ui.separator();
+    ui.strong("Metadata");
+    egui::Grid::new(ui.id().with("metadata"))
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, meta) in metadata.iter().enumerate() {
+                ui.monospace(&meta.name);
+                match metadata_value_preview(&meta.value).filter(|_| expandable) {
+                    None => {
+                        ui.monospace(meta.value.trim());
+                    }
+                    Some(preview) => {
+                        let id = ui.id().with(("metadata_expanded", index));
+                        let expanded = ui.data(|data| data.get_temp(id)).unwrap_or(false);
+                        let (text, hint) = if expanded {
+                            (meta.value.trim().to_string(), "Click to shorten")
+                        } else {
+                            (preview, "Click to show the full value")
+                        };
+                        let response = ui
+                            .add(
+                                egui::Label::new(egui::RichText::new(text).monospace())
+                                    .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text(hint);
+                        if response.clicked() {
+                            ui.data_mut(|data| data.insert_temp(id, !expanded));
+                        }
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_long_metadata_values() {
+        assert_eq!(metadata_value_preview("CLB_X3Y4"), None);
+        assert_eq!(metadata_value_preview("  padded  "), None);
+
+        let long = "a".repeat(METADATA_VALUE_MAX_CHARS + 1);
+        let preview = metadata_value_preview(&long).expect("long value is shortened");
+        assert_eq!(preview.chars().count(), METADATA_VALUE_MAX_CHARS);
+        assert!(preview.ends_with("..."));
+
+        // Values over several lines show their first line.
+        assert_eq!(
+            metadata_value_preview("\n  LUT.INIT\n  FF.ZINI\n").as_deref(),
+            Some("LUT.INIT...")
+        );
+    }
+}
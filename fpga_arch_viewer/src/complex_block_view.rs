@@ -1,16 +1,77 @@
-use fpga_arch_parser::FPGAArch;
+use fpga_arch_parser::prelude::*;
 
 use crate::{
-    common_ui,
-    intra_tile::{self, IntraTileState},
+    arch_index::ArchIndex,
+    color_scheme::{self, Palette},
+    common_ui, intra_hierarchy_tree,
+    intra_tile::{self, IntraTileSnapshot, IntraTileState, UnresolvedInterconnectRef, WireStyle},
     viewer::ViewMode,
 };
 
+/// How much of a complex block is expanded when it is first opened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DefaultExpansion {
+    Collapsed,
+    OneLevel,
+    ExpandAll,
+}
+
+impl DefaultExpansion {
+    pub const ALL: [Self; 3] = [Self::Collapsed, Self::OneLevel, Self::ExpandAll];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Collapsed => "Collapsed",
+            Self::OneLevel => "One level",
+            Self::ExpandAll => "Expand all",
+        }
+    }
+}
+
+/// Settings of the complex block view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComplexBlockSettings {
+    /// How much of a complex block is expanded when it is opened.
+    pub default_expansion: DefaultExpansion,
+    /// Wire widths and highlight color.
+    pub wire_style: WireStyle,
+    /// Draw a dotted grid behind the block diagram.
+    pub show_canvas_grid: bool,
+    /// Blocks this many levels below the root, or deeper, are drawn collapsed.
+    pub max_render_depth: usize,
+    /// Grid step, in pixels, that coordinates of exported blocks are rounded
+    /// to. None exports the exact layout coordinates.
+    pub export_snap_step: Option<f32>,
+}
+
+impl Default for ComplexBlockSettings {
+    fn default() -> Self {
+        Self {
+            default_expansion: DefaultExpansion::Collapsed,
+            wire_style: WireStyle::default(),
+            show_canvas_grid: false,
+            max_render_depth: intra_tile::DEFAULT_MAX_RENDER_DEPTH,
+            export_snap_step: None,
+        }
+    }
+}
+
+/// A named view of a complex block that can be restored with one click.
+pub struct Bookmark {
+    pub name: String,
+    pub complex_block_name: String,
+    pub view: IntraTileSnapshot,
+}
+
 pub struct ComplexBlockViewState {
     pub selected_complex_block_name: Option<String>,
     pub intra_tile_state: IntraTileState,
     pub all_blocks_expanded: bool,
     pub draw_intra_interconnects: bool,
+    /// Bookmarks made this session, in the order they were added.
+    pub bookmarks: Vec<Bookmark>,
+    /// Name typed for the next bookmark.
+    pub new_bookmark_name: String,
 }
 
 pub struct ComplexBlockView {
@@ -25,6 +86,8 @@ impl Default for ComplexBlockView {
                 intra_tile_state: IntraTileState::default(),
                 all_blocks_expanded: false,
                 draw_intra_interconnects: true,
+                bookmarks: Vec::new(),
+                new_bookmark_name: String::new(),
             },
         }
     }
@@ -34,23 +97,58 @@ impl ComplexBlockView {
     pub fn render(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         next_view_mode: &mut ViewMode,
+        settings: ComplexBlockSettings,
+        palette: Palette,
         dark_mode: bool,
         ctx: &egui::Context,
     ) {
-        self.render_side_panel(arch, ctx);
+        let intra_tile_state = &mut self.complex_block_view_state.intra_tile_state;
+        intra_tile_state.wire_style = settings.wire_style;
+        intra_tile_state.show_canvas_grid = settings.show_canvas_grid;
+        intra_tile_state.max_render_depth = settings.max_render_depth;
+        self.render_side_panel(
+            arch,
+            arch_index,
+            settings.default_expansion,
+            palette,
+            dark_mode,
+            ctx,
+        );
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.render_complex_block_view(arch, next_view_mode, dark_mode, ui);
+            self.render_complex_block_view(
+                arch,
+                arch_index,
+                next_view_mode,
+                palette,
+                dark_mode,
+                ui,
+            );
         });
     }
 
-    pub fn on_view_open(&mut self, arch: &Option<FPGAArch>) {
-        if let Some(arch) = &arch {
-            self.apply_expand_all_state(arch);
+    pub fn on_view_open(
+        &mut self,
+        arch: Option<&FPGAArch>,
+        arch_index: &ArchIndex,
+        default_expansion: DefaultExpansion,
+    ) {
+        if let Some(arch) = arch {
+            self.apply_expand_all_state(arch, arch_index, default_expansion);
         }
     }
 
+    /// Restores the initial selection, expansion, modes, and zoom.
+    pub fn reset(&mut self) {
+        let state = &mut self.complex_block_view_state;
+        state.selected_complex_block_name = None;
+        state.intra_tile_state.reset();
+        state.all_blocks_expanded = false;
+        state.draw_intra_interconnects = true;
+    }
+
     pub fn on_view_close(&mut self) {
         self.complex_block_view_state.selected_complex_block_name = None;
     }
@@ -58,28 +156,30 @@ impl ComplexBlockView {
     fn render_complex_block_view(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         next_view_mode: &mut ViewMode,
+        palette: Palette,
         dark_mode: bool,
         ui: &mut egui::Ui,
     ) {
         if let Some(pb_type_name) = &self.complex_block_view_state.selected_complex_block_name {
-            if let Some(root_pb) = arch
-                .complex_block_list
-                .iter()
-                .find(|b| b.name == *pb_type_name)
-            {
+            if let Some(root_pb) = arch_index.complex_block(arch, pb_type_name) {
                 intra_tile::render_intra_tile_view(
                     ui,
                     root_pb,
                     &mut self.complex_block_view_state.intra_tile_state,
                     self.complex_block_view_state.all_blocks_expanded,
                     self.complex_block_view_state.draw_intra_interconnects,
+                    palette,
                     dark_mode,
                 );
             } else if common_ui::render_centered_message(
                 ui,
                 "Complex block not found",
-                &format!("Could not find complex block: {}", pb_type_name),
+                &format!(
+                    "Could not find complex block: {}\n\nEquivalent sites must name a top-level pb_type in <complexblocklist>.",
+                    pb_type_name
+                ),
                 Some("Go to Grid View"),
             ) {
                 *next_view_mode = ViewMode::Grid;
@@ -94,44 +194,397 @@ impl ComplexBlockView {
         }
     }
 
-    fn render_side_panel(&mut self, arch: &FPGAArch, ctx: &egui::Context) {
+    fn render_side_panel(
+        &mut self,
+        arch: &FPGAArch,
+        arch_index: &ArchIndex,
+        default_expansion: DefaultExpansion,
+        palette: Palette,
+        dark_mode: bool,
+        ctx: &egui::Context,
+    ) {
         egui::SidePanel::right("complex_block_controls")
             .default_width(250.0)
             .show(ctx, |ui| {
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        let should_expand_all = render_intra_tile_controls_panel(
+                        let previous_selection = self
+                            .complex_block_view_state
+                            .selected_complex_block_name
+                            .clone();
+                        let mut should_expand_all = render_intra_tile_controls_panel(
                             ui,
                             arch,
                             &mut self.complex_block_view_state.all_blocks_expanded,
                             &mut self.complex_block_view_state.draw_intra_interconnects,
+                            &mut self
+                                .complex_block_view_state
+                                .intra_tile_state
+                                .show_port_widths,
+                            &mut self
+                                .complex_block_view_state
+                                .intra_tile_state
+                                .show_pin_indices,
+                            &mut self
+                                .complex_block_view_state
+                                .intra_tile_state
+                                .show_connection_heat,
+                            &mut self.complex_block_view_state.intra_tile_state.bundle_buses,
+                            &mut self.complex_block_view_state.selected_complex_block_name,
+                        );
+                        should_expand_all |= render_equivalent_sites_section(
+                            ui,
+                            arch,
+                            arch_index,
                             &mut self.complex_block_view_state.selected_complex_block_name,
                         );
                         if should_expand_all {
-                            self.apply_expand_all_state(arch);
+                            // Only a newly selected block starts from the
+                            // default expansion; toggling "Expand All" off
+                            // collapses everything.
+                            let block_changed =
+                                self.complex_block_view_state.selected_complex_block_name
+                                    != previous_selection;
+                            self.apply_expand_all_state(
+                                arch,
+                                arch_index,
+                                if block_changed {
+                                    default_expansion
+                                } else {
+                                    DefaultExpansion::Collapsed
+                                },
+                            );
                         }
+
+                        render_bookmarks_section(ui, &mut self.complex_block_view_state);
+
+                        if let Some(root_pb) = self
+                            .complex_block_view_state
+                            .selected_complex_block_name
+                            .as_ref()
+                            .and_then(|name| arch_index.complex_block(arch, name))
+                        {
+                            render_instance_tree_section(
+                                ui,
+                                root_pb,
+                                &mut self.complex_block_view_state.intra_tile_state,
+                            );
+                        }
+
+                        if self.complex_block_view_state.draw_intra_interconnects {
+                            render_pack_pattern_legend(
+                                ui,
+                                &self
+                                    .complex_block_view_state
+                                    .intra_tile_state
+                                    .pack_pattern_names,
+                                palette,
+                                dark_mode,
+                            );
+                        }
+
+                        render_unresolved_refs(
+                            ui,
+                            &self
+                                .complex_block_view_state
+                                .intra_tile_state
+                                .unresolved_refs,
+                        );
                     });
             });
     }
 
-    fn apply_expand_all_state(&mut self, arch: &FPGAArch) {
-        if self.complex_block_view_state.all_blocks_expanded {
-            if let Some(pb_type_name) = &self.complex_block_view_state.selected_complex_block_name
-                && let Some(root_pb) = arch
-                    .complex_block_list
-                    .iter()
-                    .find(|b| b.name == *pb_type_name)
+    /// Expands the selected block fully if "Expand All" is on, and otherwise
+    /// to the given default.
+    fn apply_expand_all_state(
+        &mut self,
+        arch: &FPGAArch,
+        arch_index: &ArchIndex,
+        default_expansion: DefaultExpansion,
+    ) {
+        let state = &mut self.complex_block_view_state;
+        let expansion = if state.all_blocks_expanded {
+            DefaultExpansion::ExpandAll
+        } else {
+            default_expansion
+        };
+
+        intra_tile::collapse_all_blocks(&mut state.intra_tile_state);
+        let Some(root_pb) = state
+            .selected_complex_block_name
+            .as_ref()
+            .and_then(|name| arch_index.complex_block(arch, name))
+        else {
+            return;
+        };
+        match expansion {
+            DefaultExpansion::Collapsed => {}
+            DefaultExpansion::OneLevel => {
+                intra_tile::expand_blocks_to_depth(&mut state.intra_tile_state, root_pb, 1);
+            }
+            DefaultExpansion::ExpandAll => {
+                intra_tile::expand_all_blocks(&mut state.intra_tile_state, root_pb, &root_pb.name);
+            }
+        }
+    }
+}
+
+/// Lists the bookmarks, restoring one when it is clicked, and offers to
+/// bookmark the current view.
+fn render_bookmarks_section(ui: &mut egui::Ui, state: &mut ComplexBlockViewState) {
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.label("Bookmarks:");
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.new_bookmark_name)
+                .hint_text("Bookmark name")
+                .desired_width(140.0),
+        );
+        let name = state.new_bookmark_name.trim();
+        let can_add = !name.is_empty() && state.selected_complex_block_name.is_some();
+        if ui
+            .add_enabled(can_add, egui::Button::new("Add"))
+            .on_hover_text("Save the current block, modes, expansion, zoom and scroll")
+            .clicked()
+            && let Some(complex_block_name) = state.selected_complex_block_name.clone()
+        {
+            let bookmark = Bookmark {
+                name: name.to_string(),
+                complex_block_name,
+                view: state.intra_tile_state.snapshot(),
+            };
+            // Saving under an existing name updates that bookmark.
+            match state.bookmarks.iter_mut().find(|b| b.name == bookmark.name) {
+                Some(existing) => *existing = bookmark,
+                None => state.bookmarks.push(bookmark),
+            }
+            state.new_bookmark_name.clear();
+        }
+    });
+
+    let mut restore = None;
+    let mut remove = None;
+    for (idx, bookmark) in state.bookmarks.iter().enumerate() {
+        ui.horizontal(|ui| {
+            if ui
+                .button(&bookmark.name)
+                .on_hover_text(format!("Show {}", bookmark.complex_block_name))
+                .clicked()
             {
-                intra_tile::expand_all_blocks(
-                    &mut self.complex_block_view_state.intra_tile_state,
-                    root_pb,
-                    &root_pb.name,
-                );
+                restore = Some(idx);
             }
-        } else {
-            intra_tile::collapse_all_blocks(&mut self.complex_block_view_state.intra_tile_state);
+            if ui
+                .small_button("🗑")
+                .on_hover_text("Delete bookmark")
+                .clicked()
+            {
+                remove = Some(idx);
+            }
+        });
+    }
+
+    if let Some(idx) = restore {
+        let bookmark = &state.bookmarks[idx];
+        state.selected_complex_block_name = Some(bookmark.complex_block_name.clone());
+        // The bookmark's expansion wins over "Expand All".
+        state.all_blocks_expanded = false;
+        state.intra_tile_state.restore(&bookmark.view);
+    }
+    if let Some(idx) = remove {
+        state.bookmarks.remove(idx);
+    }
+}
+
+/// The sub tiles that list `pb_type_name` among their equivalent sites, with
+/// the tile each belongs to.
+fn sub_tiles_with_site<'a>(arch: &'a FPGAArch, pb_type_name: &str) -> Vec<(&'a Tile, &'a SubTile)> {
+    arch.tiles
+        .iter()
+        .flat_map(|tile| tile.sub_tiles.iter().map(move |sub_tile| (tile, sub_tile)))
+        .filter(|(_, sub_tile)| {
+            sub_tile
+                .equivalent_sites
+                .iter()
+                .any(|site| site.pb_type == pb_type_name)
+        })
+        .collect()
+}
+
+/// A short description of the pb_type a site places, for its hover text.
+fn site_info(arch: &FPGAArch, arch_index: &ArchIndex, site: &TileSite) -> String {
+    let pin_mapping = match site.pin_mapping {
+        fpga_arch_parser::TileSitePinMapping::Direct => "direct",
+        fpga_arch_parser::TileSitePinMapping::Custom => "custom",
+    };
+    match arch_index.complex_block(arch, &site.pb_type) {
+        Some(pb_type) => format!(
+            "{}: {} ports, {} modes, {} child pb_types\nPin mapping: {pin_mapping}",
+            pb_type.name,
+            pb_type.ports.len(),
+            pb_type.modes.len(),
+            pb_type.pb_types.len()
+                + pb_type
+                    .modes
+                    .iter()
+                    .map(|mode| mode.pb_types.len())
+                    .sum::<usize>()
+        ),
+        None => format!("{}: not found in <complexblocklist>", site.pb_type),
+    }
+}
+
+/// Lists the sub tiles that can hold the current complex block along with
+/// their other equivalent sites. Clicking a site shows its pb_type instead.
+/// Returns true if the selection changed.
+fn render_equivalent_sites_section(
+    ui: &mut egui::Ui,
+    arch: &FPGAArch,
+    arch_index: &ArchIndex,
+    selected_complex_block_name: &mut Option<String>,
+) -> bool {
+    let Some(current) = selected_complex_block_name.clone() else {
+        return false;
+    };
+    let sub_tiles = sub_tiles_with_site(arch, &current);
+    if sub_tiles.is_empty() {
+        return false;
+    }
+
+    ui.add_space(10.0);
+    ui.label("Equivalent Sites:");
+    let mut picked = None;
+    for (tile, sub_tile) in sub_tiles {
+        ui.horizontal_wrapped(|ui| {
+            ui.weak(format!("{}.{}:", tile.name, sub_tile.name));
+            for site in &sub_tile.equivalent_sites {
+                if ui
+                    .selectable_label(site.pb_type == current, &site.pb_type)
+                    .on_hover_text(site_info(arch, arch_index, site))
+                    .clicked()
+                    && site.pb_type != current
+                {
+                    picked = Some(site.pb_type.clone());
+                }
+            }
+        });
+    }
+
+    match picked {
+        Some(name) => {
+            *selected_complex_block_name = Some(name);
+            true
         }
+        None => false,
+    }
+}
+
+/// Shows the block instances of the current complex block. Clicking one
+/// expands the blocks above it and brings it into view on the canvas.
+fn render_instance_tree_section(
+    ui: &mut egui::Ui,
+    root_pb: &fpga_arch_parser::PBType,
+    state: &mut IntraTileState,
+) {
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.label("Hierarchy:");
+    ui.checkbox(&mut state.zoom_to_selection, "Zoom to selection");
+    ui.add_space(5.0);
+    if let Some(instance_path) = intra_hierarchy_tree::render_instance_tree(ui, root_pb, state) {
+        intra_tile::focus_block(state, &instance_path);
+    }
+}
+
+/// Lists the pack patterns of the current complex block in the colors their
+/// wires are drawn with.
+fn render_pack_pattern_legend(
+    ui: &mut egui::Ui,
+    pack_pattern_names: &[String],
+    palette: Palette,
+    dark_mode: bool,
+) {
+    if pack_pattern_names.is_empty() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.label("Pack Patterns:");
+    ui.add_space(5.0);
+    for (rank, name) in pack_pattern_names.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(24.0, 12.0), egui::Sense::hover());
+            ui.painter().extend(egui::Shape::dashed_line(
+                &[rect.left_center(), rect.right_center()],
+                egui::Stroke::new(
+                    3.0,
+                    color_scheme::pack_pattern_color(rank, palette, dark_mode),
+                ),
+                6.0,
+                3.0,
+            ));
+            ui.label(name);
+        });
+    }
+}
+
+/// Shows the scale of the connection heat overlay.
+fn render_connection_heat_legend(ui: &mut egui::Ui) {
+    let dark_mode = ui.visuals().dark_mode;
+    ui.horizontal(|ui| {
+        ui.label("Fewest");
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(100.0, 12.0), egui::Sense::hover());
+        const STEPS: usize = 20;
+        let step_width = rect.width() / STEPS as f32;
+        for step in 0..STEPS {
+            let t = step as f32 / (STEPS - 1) as f32;
+            let step_rect = egui::Rect::from_min_size(
+                rect.min + egui::vec2(step as f32 * step_width, 0.0),
+                egui::vec2(step_width, rect.height()),
+            );
+            ui.painter().rect_filled(
+                step_rect,
+                egui::CornerRadius::ZERO,
+                color_scheme::heatmap_color(t, dark_mode),
+            );
+        }
+        ui.label("Most");
+    });
+    ui.label(
+        egui::RichText::new("Interconnect pins per block, relative to its siblings")
+            .weak()
+            .small(),
+    );
+}
+
+/// Lists the interconnect references that did not match any port.
+fn render_unresolved_refs(ui: &mut egui::Ui, unresolved_refs: &[UnresolvedInterconnectRef]) {
+    if unresolved_refs.is_empty() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.colored_label(
+        ui.visuals().error_fg_color,
+        format!("⚠ Unresolved References ({})", unresolved_refs.len()),
+    );
+    ui.add_space(5.0);
+    for unresolved in unresolved_refs {
+        ui.label(format!(
+            "{} / {}: {}",
+            unresolved.instance_path, unresolved.interconnect_name, unresolved.port_ref
+        ));
     }
 }
 
@@ -141,6 +594,10 @@ fn render_intra_tile_controls_panel(
     arch: &FPGAArch,
     all_blocks_expanded: &mut bool,
     draw_intra_interconnects: &mut bool,
+    show_port_widths: &mut bool,
+    show_pin_indices: &mut bool,
+    show_connection_heat: &mut bool,
+    bundle_buses: &mut bool,
     selected_complex_block_name: &mut Option<String>,
 ) -> bool {
     let mut expand_all = false;
@@ -152,6 +609,10 @@ fn render_intra_tile_controls_panel(
     let mut expand_all_toggle_val = *all_blocks_expanded;
     if ui
         .checkbox(&mut expand_all_toggle_val, "Expand All")
+        .on_hover_text(
+            "Alt+click a block header to expand it and collapse its siblings. \
+             Right-click a header to collapse everything below it.",
+        )
         .changed()
     {
         *all_blocks_expanded = expand_all_toggle_val;
@@ -161,6 +622,29 @@ fn render_intra_tile_controls_panel(
     // Interconnect toggle
     ui.checkbox(draw_intra_interconnects, "Draw Interconnects");
 
+    // Bus bundling for wide direct connections
+    ui.checkbox(bundle_buses, "Bundle Buses")
+        .on_hover_text(format!(
+            "Draw direct connections between buses as a single line labelled with its width. \
+         Hover a bundle, or zoom to {:.0}% or more, to see its wires.",
+            intra_tile::BUNDLE_EXPAND_ZOOM * 100.0
+        ));
+
+    // Pin count summary on collapsed blocks
+    ui.checkbox(show_port_widths, "Show Port Widths")
+        .on_hover_text("Show input (I), output (O) and clock (C) pin counts on collapsed blocks");
+
+    // Per-pin index labels on bus ports
+    ui.checkbox(show_pin_indices, "Show Pin Indices")
+        .on_hover_text("Label bus pins with their indices, or with the bus range when zoomed out");
+
+    // Interconnect complexity overlay
+    ui.checkbox(show_connection_heat, "Connection Heat")
+        .on_hover_text("Tint child blocks by how many interconnect pins connect to them");
+    if *show_connection_heat {
+        render_connection_heat_legend(ui);
+    }
+
     ui.add_space(10.0);
     ui.separator();
     ui.add_space(10.0);
@@ -202,3 +686,29 @@ fn render_intra_tile_controls_panel(
 
     expand_all
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4_with;
+
+    #[test]
+    fn finds_the_sub_tiles_that_hold_a_site() {
+        let arch = k4_n4_with(&[(
+            r#"<site pb_type="clb" pin_mapping="direct"/>"#,
+            r#"<site pb_type="clb" pin_mapping="direct"/>
+          <site pb_type="io" pin_mapping="custom"/>"#,
+        )]);
+
+        let holders: Vec<(&str, usize)> = sub_tiles_with_site(&arch, "io")
+            .into_iter()
+            .map(|(tile, sub_tile)| (tile.name.as_str(), sub_tile.equivalent_sites.len()))
+            .collect();
+        assert_eq!(holders, vec![("io", 1), ("clb", 2)]);
+        assert!(sub_tiles_with_site(&arch, "missing").is_empty());
+
+        let clb_sites = &arch.tiles[1].sub_tiles[0].equivalent_sites;
+        let arch_index = ArchIndex::new(&arch);
+        assert!(site_info(&arch, &arch_index, &clb_sites[1]).ends_with("Pin mapping: custom"));
+    }
+}
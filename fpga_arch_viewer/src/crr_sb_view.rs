@@ -4,7 +4,7 @@ use crr_sb_parser::{
     CRRSwitchBlockDeserialized, CRRSwitchDir, CRRSwitchSinkNodeInfo, CRRSwitchSourceNodeInfo,
     CRRSwitchSourcePin,
 };
-use fpga_arch_parser::{FPGAArch, Layout, TilePinMapper};
+use fpga_arch_parser::prelude::*;
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::crr_view::parse_sb_maps_yaml::parse_sb_maps_yaml_from_string;
@@ -0,0 +1,227 @@
+//! Delay Matrix Heatmap
+//!
+//! Part of the Intra Tile Visualization, this module shows the pin-to-pin
+//! delays of a `<delay_matrix>` as a colored grid in a popup window.
+
+use eframe::egui;
+use fpga_arch_parser::prelude::*;
+
+use super::color_scheme;
+
+const CELL_SIZE: f32 = 28.0;
+
+/// One `<delay_matrix>`, copied out of the architecture so the popup can
+/// outlive the frame it was opened in.
+pub struct DelayMatrix {
+    pub delay_type: &'static str,
+    pub in_port: String,
+    pub out_port: String,
+    /// Delays indexed as [in_pin][out_pin]. Rows may differ in length.
+    pub matrix: Vec<Vec<f32>>,
+}
+
+/// The delay matrices of one interconnect or pb_type, shown in a window.
+pub struct DelayMatrixPopup {
+    pub title: String,
+    pub matrices: Vec<DelayMatrix>,
+}
+
+impl DelayMatrixPopup {
+    /// Collects the matrices among `delays`, or None if there are none.
+    pub fn from_delays(title: String, delays: &[DelayInfo]) -> Option<Self> {
+        let matrices: Vec<DelayMatrix> = delays
+            .iter()
+            .filter_map(|delay| match delay {
+                DelayInfo::Matrix {
+                    delay_type,
+                    matrix,
+                    in_port,
+                    out_port,
+                } => Some(DelayMatrix {
+                    delay_type: match delay_type {
+                        DelayType::Max => "max",
+                        DelayType::Min => "min",
+                    },
+                    in_port: in_port.clone(),
+                    out_port: out_port.clone(),
+                    matrix: matrix.clone(),
+                }),
+                DelayInfo::Constant { .. } => None,
+            })
+            .collect();
+        if matrices.is_empty() {
+            None
+        } else {
+            Some(Self { title, matrices })
+        }
+    }
+}
+
+/// Whether any of `delays` is a `<delay_matrix>`.
+pub fn has_delay_matrix(delays: &[DelayInfo]) -> bool {
+    delays
+        .iter()
+        .any(|delay| matches!(delay, DelayInfo::Matrix { .. }))
+}
+
+/// Shows the open popup, if any. Closing the window clears `popup`.
+pub fn render_delay_matrix_window(
+    ctx: &egui::Context,
+    popup: &mut Option<DelayMatrixPopup>,
+    dark_mode: bool,
+) {
+    let Some(current) = popup.as_ref() else {
+        return;
+    };
+    let mut open = true;
+    egui::Window::new(format!("Delay Matrix: {}", current.title))
+        .id(egui::Id::new("delay_matrix_window"))
+        .open(&mut open)
+        .resizable(true)
+        .show(ctx, |ui| {
+            egui::ScrollArea::both().show(ui, |ui| {
+                for (idx, matrix) in current.matrices.iter().enumerate() {
+                    if idx > 0 {
+                        ui.separator();
+                    }
+                    render_heatmap(ui, matrix, dark_mode);
+                }
+            });
+        });
+    if !open {
+        *popup = None;
+    }
+}
+
+/// Draws one matrix with a row per input pin and a column per output pin.
+fn render_heatmap(ui: &mut egui::Ui, matrix: &DelayMatrix, dark_mode: bool) {
+    ui.strong(format!(
+        "{}: {} \u{2192} {}",
+        matrix.delay_type, matrix.in_port, matrix.out_port
+    ));
+
+    let num_rows = matrix.matrix.len();
+    let num_cols = matrix.matrix.iter().map(Vec::len).max().unwrap_or(0);
+    if num_rows == 0 || num_cols == 0 {
+        ui.label("Empty matrix.");
+        return;
+    }
+
+    let (min, max) = matrix
+        .matrix
+        .iter()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    ui.label(format!("Range: {min:e} s to {max:e} s"));
+
+    let text_color = color_scheme::theme_text_color(dark_mode);
+    let font = egui::FontId::monospace(11.0);
+    let row_labels: Vec<String> = (0..num_rows)
+        .map(|row| format!("{}[{}]", matrix.in_port, row))
+        .collect();
+    let col_labels: Vec<String> = (0..num_cols)
+        .map(|col| format!("{}[{}]", matrix.out_port, col))
+        .collect();
+
+    let row_label_width = row_labels
+        .iter()
+        .map(|label| {
+            ui.painter()
+                .layout_no_wrap(label.clone(), font.clone(), text_color)
+                .size()
+                .x
+        })
+        .fold(0.0, f32::max)
+        + 6.0;
+    let col_label_height = col_labels
+        .iter()
+        .map(|label| {
+            ui.painter()
+                .layout_no_wrap(label.clone(), font.clone(), text_color)
+                .size()
+                .x
+        })
+        .fold(0.0, f32::max)
+        + 6.0;
+
+    let size = egui::vec2(
+        row_label_width + CELL_SIZE * num_cols as f32,
+        col_label_height + CELL_SIZE * num_rows as f32,
+    );
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let origin = rect.min + egui::vec2(row_label_width, col_label_height);
+
+    // Column labels are written bottom-to-top above their column.
+    for (col, label) in col_labels.into_iter().enumerate() {
+        let galley = painter.layout_no_wrap(label, font.clone(), text_color);
+        let x = origin.x + CELL_SIZE * (col as f32 + 0.5) - galley.size().y / 2.0;
+        let pos = egui::pos2(x, origin.y - 3.0);
+        painter.add(
+            egui::epaint::TextShape::new(pos, galley, text_color)
+                .with_angle(-std::f32::consts::FRAC_PI_2),
+        );
+    }
+    for (row, label) in row_labels.iter().enumerate() {
+        let y = origin.y + CELL_SIZE * (row as f32 + 0.5);
+        painter.text(
+            egui::pos2(origin.x - 3.0, y),
+            egui::Align2::RIGHT_CENTER,
+            label,
+            font.clone(),
+            text_color,
+        );
+    }
+
+    let pointer = ui.input(|i| i.pointer.hover_pos());
+    let mut hovered = None;
+    for (row, values) in matrix.matrix.iter().enumerate() {
+        for col in 0..num_cols {
+            let cell = egui::Rect::from_min_size(
+                origin + egui::vec2(CELL_SIZE * col as f32, CELL_SIZE * row as f32),
+                egui::vec2(CELL_SIZE, CELL_SIZE),
+            );
+            let fill = match values.get(col) {
+                Some(&value) => {
+                    let t = if max > min {
+                        (value - min) / (max - min)
+                    } else {
+                        0.0
+                    };
+                    if pointer.is_some_and(|p| cell.contains(p)) {
+                        hovered = Some((row, col, value));
+                    }
+                    color_scheme::heatmap_color(t, dark_mode)
+                }
+                // Short rows leave their missing cells blank.
+                None => color_scheme::theme_block_bg(dark_mode),
+            };
+            painter.rect(
+                cell,
+                egui::CornerRadius::ZERO,
+                fill,
+                egui::Stroke::new(1.0, color_scheme::theme_border_color(dark_mode)),
+                egui::epaint::StrokeKind::Inside,
+            );
+        }
+    }
+
+    if let Some((row, col, value)) = hovered {
+        egui::Tooltip::always_open(
+            ui.ctx().clone(),
+            ui.layer_id(),
+            egui::Id::new("delay_matrix_cell_tooltip"),
+            egui::PopupAnchor::Pointer,
+        )
+        .gap(12.0)
+        .show(|ui| {
+            ui.label(format!(
+                "{}[{}] \u{2192} {}[{}]",
+                matrix.in_port, row, matrix.out_port, col
+            ));
+            ui.label(format!("{value:e} s"));
+        });
+    }
+}
@@ -1,4 +1,4 @@
-use fpga_arch_parser::FPGAArchParseError;
+use fpga_arch_parser::prelude::*;
 use std::io::{BufRead, BufReader};
 
 fn get_file_line(file_path: &std::path::Path, line_num: u64) -> Option<String> {
@@ -16,6 +16,38 @@ fn format_context_line(line: &str, column: u64) -> String {
     result
 }
 
+/// The file and row that `row` of the document parsed from `file_path` was
+/// read from. Files split up with XInclude are parsed as one merged document.
+fn source_line(file_path: &std::path::Path, row: u64) -> (std::path::PathBuf, u64) {
+    match fpga_arch_parser::read_arch_source_with_map(file_path) {
+        Ok((_, map)) => {
+            let (source_path, source_row) = map.source_of(row);
+            (source_path.to_path_buf(), source_row)
+        }
+        Err(_) => (file_path.to_path_buf(), row),
+    }
+}
+
+/// The source line an error points at, with a caret under the column, to
+/// follow the error message. An included file is named, as the message's
+/// line number counts lines of the merged document.
+fn source_context(file_path: &std::path::Path, row: u64, column: u64) -> String {
+    let (source_path, source_row) = source_line(file_path, row);
+    let Some(line) = get_file_line(&source_path, source_row + 1) else {
+        return String::new();
+    };
+    let mut context = String::from("\n\n");
+    if source_path != file_path {
+        context.push_str(&format!(
+            "In {}, line {}:\n",
+            source_path.display(),
+            source_row + 1
+        ));
+    }
+    context.push_str(&format_context_line(&line, column + 1));
+    context
+}
+
 pub(crate) fn format_parse_error(
     error: &FPGAArchParseError,
     file_path: Option<&std::path::Path>,
@@ -34,11 +66,8 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -49,11 +78,8 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -64,11 +90,8 @@ pub(crate) fn format_parse_error(
                 pos.column + 1,
                 msg_text
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -79,11 +102,8 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -94,11 +114,8 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -109,11 +126,20 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
+            }
+            msg
+        }
+        FPGAArchParseError::DuplicateName(name, pos) => {
+            let mut msg = format!(
+                "Duplicate name for {} at line {}, column {}",
+                name,
+                pos.row + 1,
+                pos.column + 1
+            );
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -124,11 +150,8 @@ pub(crate) fn format_parse_error(
                 pos.row + 1,
                 pos.column + 1
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
@@ -139,19 +162,205 @@ pub(crate) fn format_parse_error(
                 pos.column + 1,
                 msg_text
             );
-            if let Some(path) = file_path
-                && let Some(line) = get_file_line(path, pos.row + 1)
-            {
-                msg.push_str("\n\n");
-                msg.push_str(&format_context_line(&line, pos.column + 1));
+            if let Some(path) = file_path {
+                msg.push_str(&source_context(path, pos.row, pos.column));
             }
             msg
         }
         FPGAArchParseError::UnexpectedEndOfDocument(msg) => {
             format!("Unexpected end of document:\n{}", msg)
         }
+        FPGAArchParseError::UnclosedElements(open_elements) => {
+            let describe = |element: &fpga_arch_parser::OpenElement| {
+                let mut text = format!("<{}>", element.tag);
+                if let Some(name) = &element.name {
+                    text.push_str(&format!(" '{}'", name));
+                }
+                format!("{} (opened at line {})", text, element.position.row + 1)
+            };
+            let Some((innermost, outer)) = open_elements.split_last() else {
+                return "Reached the end of the file".to_string();
+            };
+            let mut msg = format!(
+                "Reached the end of the file while inside {}",
+                describe(innermost)
+            );
+            if !outer.is_empty() {
+                msg.push_str("\n\nStill open:");
+                for element in outer.iter().rev() {
+                    msg.push_str(&format!("\n  {}", describe(element)));
+                }
+            }
+            msg
+        }
         FPGAArchParseError::PinParsingError(msg) => {
             format!("Pin parsing error:\n{}", msg)
         }
+        FPGAArchParseError::IncludeError(msg) => {
+            format!("Failed to resolve an included file:\n{}", msg)
+        }
+        FPGAArchParseError::UnresolvedPBTypeReference(msg) => {
+            format!(
+                "Unresolved pb_type reference:\n{}\n\nEquivalent sites must name a top-level pb_type in <complexblocklist>.",
+                msg
+            )
+        }
+    }
+}
+
+/// A place in an architecture file that a parse error points at. Lines and
+/// columns count from 1. Errors in a file split up with XInclude point into
+/// the included file they came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorLocation {
+    pub file_path: std::path::PathBuf,
+    pub line: u64,
+    pub column: u64,
+}
+
+impl ErrorLocation {
+    pub fn of(error: &FPGAArchParseError, file_path: &std::path::Path) -> Option<Self> {
+        let pos = error.position()?;
+        let (source_path, source_row) = source_line(file_path, pos.row);
+        Some(Self {
+            file_path: source_path,
+            line: source_row + 1,
+            column: pos.column + 1,
+        })
+    }
+}
+
+/// Builds the command that opens `location` in `editor`, a command line such
+/// as "code --wait" taken from `VISUAL`. Editors known to accept
+/// a line number are sent to the error's line; others just open the file.
+#[cfg(not(target_arch = "wasm32"))]
+fn editor_command(editor: &str, location: &ErrorLocation) -> Option<(String, Vec<String>)> {
+    let mut words = editor.split_whitespace().map(str::to_string);
+    let program = words.next()?;
+    let mut args: Vec<String> = words.collect();
+    let file = location.file_path.display().to_string();
+    let name = std::path::Path::new(&program)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    match name {
+        "code" | "code-insiders" | "codium" => {
+            args.push("--goto".to_string());
+            args.push(format!("{}:{}:{}", file, location.line, location.column));
+        }
+        "subl" | "zed" | "hx" | "helix" => {
+            args.push(format!("{}:{}:{}", file, location.line, location.column));
+        }
+        "vi" | "vim" | "nvim" | "gvim" | "nano" | "emacs" | "emacsclient" | "kak" | "micro"
+        | "gedit" | "kate" => {
+            args.push(format!("+{}", location.line));
+            args.push(file);
+        }
+        _ => args.push(file),
+    }
+    Some((program, args))
+}
+
+/// Opens the file at `location` in the user's editor, from `VISUAL`, or else
+/// in the system's default application for the file, which cannot be sent to
+/// a line. `EDITOR` is left alone: it usually names a terminal editor, and
+/// the viewer has no terminal to run one in.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn open_in_editor(location: &ErrorLocation) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty());
+    let (program, args) = match editor.and_then(|editor| editor_command(&editor, location)) {
+        Some(command) => command,
+        None => {
+            let file = location.file_path.display().to_string();
+            if cfg!(target_os = "windows") {
+                (
+                    "cmd".to_string(),
+                    vec!["/C".into(), "start".into(), String::new(), file],
+                )
+            } else if cfg!(target_os = "macos") {
+                ("open".to_string(), vec![file])
+            } else {
+                ("xdg-open".to_string(), vec![file])
+            }
+        }
+    };
+    let mut child = std::process::Command::new(&program)
+        .args(&args)
+        .spawn()
+        .map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+    // Editors run for as long as the file is open, so they are waited on in
+    // the background rather than left behind as zombies.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_editor_commands_for_the_error_line() {
+        let location = ErrorLocation {
+            file_path: "arch.xml".into(),
+            line: 12,
+            column: 5,
+        };
+        let command = |editor: &str| editor_command(editor, &location);
+
+        assert_eq!(
+            command("vim"),
+            Some((
+                "vim".to_string(),
+                vec!["+12".to_string(), "arch.xml".to_string()]
+            ))
+        );
+        assert_eq!(
+            command("/usr/bin/code --wait"),
+            Some((
+                "/usr/bin/code".to_string(),
+                vec![
+                    "--wait".to_string(),
+                    "--goto".to_string(),
+                    "arch.xml:12:5".to_string()
+                ]
+            ))
+        );
+        // Unknown editors just get the file.
+        assert_eq!(
+            command("notepad.exe"),
+            Some(("notepad.exe".to_string(), vec!["arch.xml".to_string()]))
+        );
+        assert_eq!(command("  "), None);
+    }
+
+    #[test]
+    fn locates_errors_in_included_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "fpga_arch_viewer_error_location_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory");
+        let main_path = dir.join("arch.xml");
+        std::fs::write(
+            &main_path,
+            "<architecture xmlns:xi=\"http://www.w3.org/2001/XInclude\">\n  <xi:include href=\"models.xml\"/>\n</architecture>\n",
+        )
+        .expect("Failed to write arch file");
+        std::fs::write(
+            dir.join("models.xml"),
+            "<?xml version=\"1.0\"?>\n<models>\n  <bogus/>\n</models>\n",
+        )
+        .expect("Failed to write include");
+
+        let error = crate::viewer::ParsedArch::from_file(&main_path, &ParseOptions::default())
+            .err()
+            .expect("The bogus tag should not parse");
+        let location = ErrorLocation::of(&error, &main_path).expect("The error has a position");
+        assert_eq!(location.file_path, dir.join("models.xml"));
+        assert_eq!(location.line, 3);
+
+        std::fs::remove_dir_all(&dir).expect("Failed to remove test directory");
     }
 }
@@ -1,6 +1,7 @@
-use fpga_arch_parser::{AutoLayout, FPGAArch, GridLocation};
+use fpga_arch_parser::prelude::*;
 use log::warn;
 use std::collections::HashMap;
+use std::fmt;
 
 // A single cell in the FPGA grid
 #[derive(Debug, Clone, PartialEq)]
@@ -29,6 +30,81 @@ pub struct DeviceGridLayer {
 
     pub horizontal_interposer_cut_lines: Vec<usize>,
     pub vertical_interposer_cut_lines: Vec<usize>,
+
+    // The grid location that placed each tile with metadata, as (index in
+    // the layout's layers, index in that layer's grid locations), keyed by
+    // the tile's anchor (row, col).
+    anchor_locations: HashMap<(usize, usize), (usize, usize)>,
+}
+
+impl DeviceGridLayer {
+    /// Returns true if the cell was left empty by an explicit EMPTY placement,
+    /// rather than because nothing was ever placed there.
+    pub fn is_explicitly_empty(&self, row: usize, col: usize) -> bool {
+        matches!(self.cells[row][col], GridCell::Empty)
+            && self.grid_priorities[row][col] != i32::MIN
+    }
+
+    /// The grid location that placed the tile covering this cell, if it had
+    /// metadata.
+    fn placed_by(&self, row: usize, col: usize) -> Option<(usize, usize)> {
+        let anchor = match self.cells.get(row)?.get(col)? {
+            GridCell::Empty => return None,
+            GridCell::BlockAnchor { .. } => (row, col),
+            GridCell::BlockOccupied {
+                anchor_row,
+                anchor_col,
+                ..
+            } => (*anchor_row, *anchor_col),
+        };
+        self.anchor_locations.get(&anchor).copied()
+    }
+
+    /// The tile type placed at this cell, "EMPTY" for an explicit EMPTY
+    /// placement, or None if nothing was placed there.
+    fn placed_type(&self, row: usize, col: usize) -> Option<&str> {
+        match &self.cells[row][col] {
+            GridCell::BlockAnchor { pb_type, .. } | GridCell::BlockOccupied { pb_type, .. } => {
+                Some(pb_type)
+            }
+            GridCell::Empty if self.is_explicitly_empty(row, col) => Some("EMPTY"),
+            GridCell::Empty => None,
+        }
+    }
+}
+
+/// Two placements of different tile types that cover the same cell with the
+/// same priority. The later one in the file is kept, as in VTR, but the
+/// layout is ambiguous and usually a mistake.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementConflict {
+    pub layout_name: String,
+    pub die: usize,
+    pub x: usize,
+    pub y: usize,
+    pub priority: i32,
+    /// The tile type that was replaced.
+    pub replaced: String,
+    /// The tile type that replaced it.
+    pub placed: String,
+}
+
+impl fmt::Display for PlacementConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Layout '{}': '{}' and '{}' are both placed at ({}, {})",
+            self.layout_name, self.replaced, self.placed, self.x, self.y
+        )?;
+        if self.die > 0 {
+            write!(f, " on die {}", self.die)?;
+        }
+        write!(
+            f,
+            " with priority {}; the later '{}' was kept",
+            self.priority, self.placed
+        )
+    }
 }
 
 // FPGA device grid
@@ -41,6 +117,14 @@ pub struct DeviceGrid {
     tile_sizes: HashMap<String, (usize, usize)>,
 
     pub grid_layers: Vec<DeviceGridLayer>,
+
+    // Name of the fixed layout the grid was built from, for reporting
+    // conflicts. Auto layouts are not checked.
+    layout_name: Option<String>,
+    // Index of that layout in the architecture's layout list.
+    layout_index: usize,
+    /// Cells that placements of equal priority fought over while building.
+    pub placement_conflicts: Vec<PlacementConflict>,
 }
 
 impl DeviceGrid {
@@ -65,6 +149,28 @@ impl DeviceGrid {
         Self::from_auto_layout_impl(auto_layout, width, height, tile_sizes)
     }
 
+    /// The metadata of the grid location that placed the tile covering this
+    /// cell on `die_id`, if it had any. `arch` is the architecture the grid
+    /// was built from.
+    pub fn metadata<'a>(
+        &self,
+        arch: &'a FPGAArch,
+        die_id: usize,
+        row: usize,
+        col: usize,
+    ) -> Option<&'a [Metadata]> {
+        let (layer_index, location_index) = self.grid_layers.get(die_id)?.placed_by(row, col)?;
+        let layers = match arch.layouts.layout_list.get(self.layout_index)? {
+            fpga_arch_parser::Layout::AutoLayout(layout) => &layout.layers,
+            fpga_arch_parser::Layout::FixedLayout(layout) => &layout.layers,
+        };
+        layers
+            .get(layer_index)?
+            .grid_locations
+            .get(location_index)?
+            .metadata()
+    }
+
     pub fn from_fixed_layout(arch: &FPGAArch, layout_index: usize) -> Self {
         let fixed_layout = match arch.layouts.layout_list.get(layout_index) {
             Some(fpga_arch_parser::Layout::FixedLayout(fl)) => fl,
@@ -85,6 +191,9 @@ impl DeviceGrid {
             num_layers,
             tile_sizes,
             grid_layers: Vec::new(),
+            layout_name: Some(fixed_layout.name.clone()),
+            layout_index,
+            placement_conflicts: Vec::new(),
         };
 
         for _ in 0..num_layers {
@@ -93,18 +202,77 @@ impl DeviceGrid {
                 grid_priorities: vec![vec![i32::MIN; width]; height],
                 horizontal_interposer_cut_lines: Vec::new(),
                 vertical_interposer_cut_lines: Vec::new(),
+                anchor_locations: HashMap::new(),
             });
         }
 
-        for layer in &fixed_layout.layers {
-            for grid_location in &layer.grid_locations {
-                grid.apply_grid_location(grid_location, layer.die);
+        for (layer_index, layer) in fixed_layout.layers.iter().enumerate() {
+            for (location_index, grid_location) in layer.grid_locations.iter().enumerate() {
+                grid.apply_grid_location(grid_location, layer.die, (layer_index, location_index));
             }
         }
 
         grid
     }
 
+    /// Estimates a size for an auto layout, no smaller than 10x10, that fits
+    /// every `single`, `col`, `row` and `region` placement with room for the
+    /// perimeter, shows each repeating placement at least twice, and keeps
+    /// the layout's aspect ratio. Coordinates that depend on the device size
+    /// (`W` or `H`) fit any size and are not considered.
+    pub fn recommended_auto_layout_size(
+        arch: &FPGAArch,
+        auto_layout: &AutoLayout,
+    ) -> (usize, usize) {
+        let tile_sizes = Self::build_tile_size_map(arch);
+        let mut min_width = 10;
+        let mut min_height = 10;
+        for layer in &auto_layout.layers {
+            for grid_location in &layer.grid_locations {
+                let (pb_type, x_exprs, y_exprs) = match grid_location {
+                    GridLocation::Single(single) => (
+                        &single.pb_type,
+                        vec![Some(&single.x_expr)],
+                        vec![Some(&single.y_expr)],
+                    ),
+                    GridLocation::Col(col_loc) => (
+                        &col_loc.pb_type,
+                        vec![Some(&col_loc.start_x_expr), col_loc.repeat_x_expr.as_ref()],
+                        vec![Some(&col_loc.start_y_expr)],
+                    ),
+                    GridLocation::Row(row_loc) => (
+                        &row_loc.pb_type,
+                        vec![Some(&row_loc.start_x_expr)],
+                        vec![Some(&row_loc.start_y_expr), row_loc.repeat_y_expr.as_ref()],
+                    ),
+                    GridLocation::Region(region) => (
+                        &region.pb_type,
+                        vec![Some(&region.end_x_expr), region.repeat_x_expr.as_ref()],
+                        vec![Some(&region.end_y_expr), region.repeat_y_expr.as_ref()],
+                    ),
+                    _ => continue,
+                };
+                let (tile_width, tile_height) = tile_sizes.get(pb_type).copied().unwrap_or((1, 1));
+                // The furthest cell reached is the sum of the position and
+                // its repeat, plus the tile itself and the perimeter.
+                let extent = |exprs: Vec<Option<&String>>| -> usize {
+                    exprs
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|expr| eval_absolute_expr(expr, tile_width, tile_height))
+                        .sum()
+                };
+                min_width = min_width.max(extent(x_exprs) + tile_width + 1);
+                min_height = min_height.max(extent(y_exprs) + tile_height + 1);
+            }
+        }
+
+        let aspect_ratio = auto_layout.aspect_ratio;
+        let width = min_width.max((min_height as f32 * aspect_ratio).ceil() as usize);
+        let height = ((width as f32 / aspect_ratio).round() as usize).max(1);
+        (width, height)
+    }
+
     fn from_auto_layout_impl(
         auto_layout: &AutoLayout,
         width: usize,
@@ -122,6 +290,10 @@ impl DeviceGrid {
             num_layers,
             tile_sizes,
             grid_layers: Vec::new(),
+            layout_name: None,
+            // Auto layouts are always built from the first layout.
+            layout_index: 0,
+            placement_conflicts: Vec::new(),
         };
 
         for _ in 0..num_layers {
@@ -130,12 +302,13 @@ impl DeviceGrid {
                 grid_priorities: vec![vec![i32::MIN; width]; height],
                 horizontal_interposer_cut_lines: Vec::new(),
                 vertical_interposer_cut_lines: Vec::new(),
+                anchor_locations: HashMap::new(),
             });
         }
 
-        for layer in &auto_layout.layers {
-            for grid_location in &layer.grid_locations {
-                grid.apply_grid_location(grid_location, layer.die);
+        for (layer_index, layer) in auto_layout.layers.iter().enumerate() {
+            for (location_index, grid_location) in layer.grid_locations.iter().enumerate() {
+                grid.apply_grid_location(grid_location, layer.die, (layer_index, location_index));
             }
         }
 
@@ -155,6 +328,7 @@ impl DeviceGrid {
         pb_type: &str,
         priority: i32,
         die_id: usize,
+        placed_by: Option<(usize, usize)>,
     ) -> bool {
         let (tile_width, tile_height) = self.get_tile_size(pb_type);
 
@@ -184,6 +358,9 @@ impl DeviceGrid {
         if priority < max_priority {
             return false;
         }
+        if priority == max_priority && self.layout_name.is_some() {
+            self.record_conflict(row, col, tile_width, tile_height, pb_type, priority, die_id);
+        }
 
         // Find all tiles that will be intersected and clear them entirely
         let mut tiles_to_clear = Vec::new();
@@ -218,6 +395,9 @@ impl DeviceGrid {
             {
                 let old_width = *width;
                 let old_height = *height;
+                self.grid_layers[die_id]
+                    .anchor_locations
+                    .remove(&(anchor_row, anchor_col));
                 // Clear the entire old tile
                 for dy in 0..old_height {
                     for dx in 0..old_width {
@@ -246,6 +426,11 @@ impl DeviceGrid {
                 width: tile_width,
                 height: tile_height,
             };
+            if let Some(placed_by) = placed_by {
+                self.grid_layers[die_id]
+                    .anchor_locations
+                    .insert((row, col), placed_by);
+            }
         }
         self.grid_layers[die_id].grid_priorities[row][col] = priority;
 
@@ -269,7 +454,49 @@ impl DeviceGrid {
         true
     }
 
-    fn apply_grid_location(&mut self, location: &GridLocation, die_id: usize) {
+    /// Records the first cell under a tile about to be placed that holds a
+    /// different tile type of the same priority.
+    fn record_conflict(
+        &mut self,
+        row: usize,
+        col: usize,
+        tile_width: usize,
+        tile_height: usize,
+        pb_type: &str,
+        priority: i32,
+        die_id: usize,
+    ) {
+        let layer = &self.grid_layers[die_id];
+        let replaced = (row..row + tile_height)
+            .flat_map(|r| (col..col + tile_width).map(move |c| (r, c)))
+            .filter(|&(r, c)| layer.grid_priorities[r][c] == priority)
+            .find_map(|(r, c)| Some((r, c, layer.placed_type(r, c).filter(|t| *t != pb_type)?)));
+        if let Some((conflict_row, conflict_col, replaced)) = replaced {
+            self.placement_conflicts.push(PlacementConflict {
+                layout_name: self.layout_name.clone().unwrap_or_default(),
+                die: die_id,
+                x: conflict_col,
+                y: conflict_row,
+                priority,
+                replaced: replaced.to_string(),
+                placed: pb_type.to_string(),
+            });
+        }
+    }
+
+    /// Places the tiles of `location`, the grid location at `index` as
+    /// (layer, location) in the layout.
+    fn apply_grid_location(
+        &mut self,
+        location: &GridLocation,
+        die_id: usize,
+        index: (usize, usize),
+    ) {
+        // Only placements with metadata are remembered, to look it up later.
+        let placed_by = location
+            .metadata()
+            .is_some_and(|metadata| !metadata.is_empty())
+            .then_some(index);
         match location {
             GridLocation::Fill(fill) => {
                 let (tile_width, tile_height) = self.get_tile_size(&fill.pb_type);
@@ -277,7 +504,14 @@ impl DeviceGrid {
                 while row < self.height {
                     let mut col = 0;
                     while col < self.width {
-                        if self.place_tile(row, col, &fill.pb_type, fill.priority, die_id) {
+                        if self.place_tile(
+                            row,
+                            col,
+                            &fill.pb_type,
+                            fill.priority,
+                            die_id,
+                            placed_by,
+                        ) {
                             col += tile_width;
                         } else {
                             col += 1;
@@ -291,7 +525,14 @@ impl DeviceGrid {
                 // Top edge
                 let mut col = 0;
                 while col < self.width {
-                    if self.place_tile(0, col, &perimeter.pb_type, perimeter.priority, die_id) {
+                    if self.place_tile(
+                        0,
+                        col,
+                        &perimeter.pb_type,
+                        perimeter.priority,
+                        die_id,
+                        placed_by,
+                    ) {
                         col += tile_width;
                     } else {
                         col += 1;
@@ -308,6 +549,7 @@ impl DeviceGrid {
                             &perimeter.pb_type,
                             perimeter.priority,
                             die_id,
+                            placed_by,
                         ) {
                             col += tile_width;
                         } else {
@@ -319,7 +561,14 @@ impl DeviceGrid {
                 // Left edge
                 let mut row = 0;
                 while row < self.height {
-                    if self.place_tile(row, 0, &perimeter.pb_type, perimeter.priority, die_id) {
+                    if self.place_tile(
+                        row,
+                        0,
+                        &perimeter.pb_type,
+                        perimeter.priority,
+                        die_id,
+                        placed_by,
+                    ) {
                         row += tile_height;
                     } else {
                         row += 1;
@@ -336,6 +585,7 @@ impl DeviceGrid {
                             &perimeter.pb_type,
                             perimeter.priority,
                             die_id,
+                            placed_by,
                         ) {
                             row += tile_height;
                         } else {
@@ -354,7 +604,14 @@ impl DeviceGrid {
 
                 for (row, col) in corners_positions {
                     if row < self.height && col < self.width {
-                        self.place_tile(row, col, &corners.pb_type, corners.priority, die_id);
+                        self.place_tile(
+                            row,
+                            col,
+                            &corners.pb_type,
+                            corners.priority,
+                            die_id,
+                            placed_by,
+                        );
                     }
                 }
             }
@@ -366,7 +623,7 @@ impl DeviceGrid {
                 ) && y < self.height
                     && x < self.width
                 {
-                    self.place_tile(y, x, &single.pb_type, single.priority, die_id);
+                    self.place_tile(y, x, &single.pb_type, single.priority, die_id, placed_by);
                 }
             }
             GridLocation::Col(col_loc) => {
@@ -392,7 +649,14 @@ impl DeviceGrid {
                     } else {
                         for x in (start_x..self.width).step_by(repeat_x) {
                             for y in (start_y..self.height).step_by(incr_y) {
-                                self.place_tile(y, x, &col_loc.pb_type, col_loc.priority, die_id);
+                                self.place_tile(
+                                    y,
+                                    x,
+                                    &col_loc.pb_type,
+                                    col_loc.priority,
+                                    die_id,
+                                    placed_by,
+                                );
                             }
                         }
                     }
@@ -421,7 +685,14 @@ impl DeviceGrid {
                     } else {
                         for y in (start_y..self.height).step_by(repeat_y) {
                             for x in (start_x..self.width).step_by(incr_x) {
-                                self.place_tile(y, x, &row_loc.pb_type, row_loc.priority, die_id);
+                                self.place_tile(
+                                    y,
+                                    x,
+                                    &row_loc.pb_type,
+                                    row_loc.priority,
+                                    die_id,
+                                    placed_by,
+                                );
                             }
                         }
                     }
@@ -450,7 +721,14 @@ impl DeviceGrid {
                     } else {
                         for y in (start_y..=end_y.min(self.height - 1)).step_by(incr_y) {
                             for x in (start_x..=end_x.min(self.width - 1)).step_by(incr_x) {
-                                self.place_tile(y, x, &region.pb_type, region.priority, die_id);
+                                self.place_tile(
+                                    y,
+                                    x,
+                                    &region.pb_type,
+                                    region.priority,
+                                    die_id,
+                                    placed_by,
+                                );
                             }
                         }
                     }
@@ -484,15 +762,12 @@ impl DeviceGrid {
     }
 
     fn eval_expr(&self, expr: &str, tile_width: usize, tile_height: usize) -> Option<usize> {
-        let expr = expr.trim();
-        // Replace lowercase w/h with tile dimensions
-        let expr = expr.replace('w', &tile_width.to_string());
-        let expr = expr.replace('h', &tile_height.to_string());
-        // Replace uppercase W/H with grid dimensions
-        let expr = expr.replace('W', &self.width.to_string());
-        let expr = expr.replace('H', &self.height.to_string());
-        let expr = expr.replace(' ', "");
-        eval_expr_recursive(&expr)
+        eval_layout_expr_at(
+            expr,
+            Some((self.width, self.height)),
+            tile_width,
+            tile_height,
+        )
     }
 
     pub fn get(&self, row: usize, col: usize, die_id: usize) -> Option<&GridCell> {
@@ -504,72 +779,24 @@ impl DeviceGrid {
     }
 }
 
-fn eval_expr_recursive(expr: &str) -> Option<usize> {
-    let expr = expr.trim();
-
-    if let Ok(val) = expr.parse::<i32>() {
-        return if val >= 0 { Some(val as usize) } else { None };
-    }
-
-    let chars: Vec<char> = expr.chars().collect();
-    let len = chars.len();
-
-    // Addition and subtraction (lowest precedence)
-    let mut depth = 0;
-    for i in (0..len).rev() {
-        let ch = chars[i];
-        match ch {
-            ')' => depth += 1,
-            '(' => depth -= 1,
-            '+' | '-' if depth == 0 && i > 0 => {
-                let left: String = chars[..i].iter().collect();
-                let right: String = chars[i + 1..].iter().collect();
-
-                if let (Some(l), Some(r)) =
-                    (eval_expr_recursive(&left), eval_expr_recursive(&right))
-                {
-                    return if ch == '+' {
-                        Some(l + r)
-                    } else if l >= r {
-                        Some(l - r)
-                    } else {
-                        None
-                    };
-                }
-            }
-            _ => {}
-        }
-    }
-
-    // Multiplication and division (higher precedence)
-    depth = 0;
-    for i in (0..len).rev() {
-        let ch = chars[i];
-        match ch {
-            ')' => depth += 1,
-            '(' => depth -= 1,
-            '*' | '/' if depth == 0 && i > 0 => {
-                let left: String = chars[..i].iter().collect();
-                let right: String = chars[i + 1..].iter().collect();
-
-                if let (Some(l), Some(r)) =
-                    (eval_expr_recursive(&left), eval_expr_recursive(&right))
-                {
-                    return if ch == '*' {
-                        Some(l * r)
-                    } else {
-                        l.checked_div(r)
-                    };
-                }
-            }
-            _ => {}
-        }
-    }
-
-    // Handle parentheses
-    if expr.starts_with('(') && expr.ends_with(')') {
-        return eval_expr_recursive(&expr[1..expr.len() - 1]);
-    }
+/// Evaluates a layout expression that does not depend on the device size.
+fn eval_absolute_expr(expr: &str, tile_width: usize, tile_height: usize) -> Option<usize> {
+    eval_layout_expr_at(expr, None, tile_width, tile_height)
+}
 
-    None
+/// Evaluates a layout expression as the parser does, or None if it has no
+/// value or the value is not a grid coordinate.
+fn eval_layout_expr_at(
+    expr: &str,
+    device_size: Option<(usize, usize)>,
+    tile_width: usize,
+    tile_height: usize,
+) -> Option<usize> {
+    let to_i64 = |n: usize| i64::try_from(n).unwrap_or(i64::MAX);
+    let vars = fpga_arch_parser::LayoutExprVars {
+        device_size: device_size.map(|(width, height)| (to_i64(width), to_i64(height))),
+        tile_size: (to_i64(tile_width), to_i64(tile_height)),
+    };
+    let value = fpga_arch_parser::eval_layout_expr(expr, &vars).ok()?;
+    usize::try_from(value).ok()
 }
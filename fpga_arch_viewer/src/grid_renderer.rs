@@ -1,27 +1,58 @@
+use crate::arch_diff;
+use crate::arch_index::ArchIndex;
 use crate::block_style::darken_color;
 use crate::color_scheme;
+use crate::common_ui;
 use crate::grid::{DeviceGrid, GridCell};
-use crate::grid_view::GridState;
+use crate::grid_view::{GridState, JUMP_HIGHLIGHT_SECONDS, tile_area, tile_capacity};
+use crate::perf_overlay::DrawStats;
 use eframe::egui;
-use fpga_arch_parser::FPGAArch;
+use fpga_arch_parser::prelude::*;
 use std::collections::HashMap;
 
+/// A click on a tile in the grid, or an action picked from its context menu.
+pub enum TileClick {
+    /// Left-click: open the tile's internal structure.
+    Open(String),
+    /// Highlight every instance of the tile type.
+    Highlight(String),
+    /// Open the complex block of the tile in the intra-tile view.
+    Inspect(String),
+    /// Show the details of the tile placed at (col, row).
+    ShowInfo {
+        tile_name: String,
+        col: usize,
+        row: usize,
+    },
+}
+
 #[derive(Default)]
 pub struct GridRenderer {
     // Prerendered shapes that make up the grid.
     grid_shapes: Vec<Vec<egui::Shape>>,
     // Prerendered shapes that make up the text on the grid.
     text_shapes: Vec<Vec<egui::Shape>>,
+    // The tile (name, col, row) that the context menu was opened on.
+    context_tile: Option<(String, usize, usize)>,
+    // Number of tiles prerendered on each layer.
+    tiles_per_layer: Vec<usize>,
+    // Type of the tile under the pointer in the last frame.
+    pub hovered_tile: Option<String>,
+    // Tiles and NoC links drawn in the last frame.
+    pub draw_stats: DrawStats,
 }
 
 impl GridRenderer {
     const MIN_CELL_SIZE_FOR_TEXT: f32 = 50.0;
+    // Opacity of tiles dimmed by the tile filter.
+    const DIMMED_ALPHA: f32 = 0.15;
 
     pub fn prerender_grid(
         &mut self,
         grid: &DeviceGrid,
         tile_colors: &HashMap<String, egui::Color32>,
-        zoom_factor: f32,
+        tile_capacities: &HashMap<String, usize>,
+        state: &GridState,
         dark_mode: bool,
         ui: &egui::Ui,
     ) {
@@ -29,17 +60,19 @@ impl GridRenderer {
         self.grid_shapes.resize(grid.num_layers, Vec::new());
         self.text_shapes.clear();
         self.text_shapes.resize(grid.num_layers, Vec::new());
-        let cell_size = get_cell_size(grid, zoom_factor, ui);
+        self.tiles_per_layer.clear();
+        self.tiles_per_layer.resize(grid.num_layers, 0);
+        let cell_size = get_cell_size(grid, state.zoom_factor, state.cell_size, ui);
+        let y_origin = state.y_origin;
 
         // Draw grid
         for die_id in 0..grid.num_layers {
             for row in 0..grid.height {
                 for col in 0..grid.width {
                     if let Some(cell) = grid.get(row, col, die_id) {
-                        // Flip y-coordinate so (0,0) is at bottom-left
                         let cell_pos = egui::Pos2::new(
                             col as f32 * cell_size,
-                            (grid.height - 1 - row) as f32 * cell_size,
+                            y_origin.screen_row(grid.height, row, 1) as f32 * cell_size,
                         );
 
                         match cell {
@@ -49,28 +82,41 @@ impl GridRenderer {
                                     cell_pos,
                                     egui::vec2(cell_size, cell_size),
                                 );
-                                self.grid_shapes[die_id].push(egui::Shape::rect_stroke(
-                                    rect,
-                                    egui::CornerRadius::ZERO,
-                                    egui::Stroke::new(
-                                        0.5,
-                                        color_scheme::theme_border_color(dark_mode),
-                                    ),
-                                    egui::epaint::StrokeKind::Inside,
-                                ));
+                                if state.show_cell_borders {
+                                    self.grid_shapes[die_id].push(egui::Shape::rect_stroke(
+                                        rect,
+                                        egui::CornerRadius::ZERO,
+                                        egui::Stroke::new(
+                                            0.5,
+                                            color_scheme::theme_border_color(dark_mode),
+                                        ),
+                                        egui::epaint::StrokeKind::Inside,
+                                    ));
+                                }
+
+                                // Hatch cells that were intentionally left EMPTY so they
+                                // can be told apart from cells where nothing was placed.
+                                if state.mark_empty_cells
+                                    && grid.grid_layers[die_id].is_explicitly_empty(row, col)
+                                {
+                                    self.push_empty_cell_marker(die_id, rect, dark_mode, ui);
+                                }
                             }
                             GridCell::BlockAnchor {
                                 pb_type,
                                 width,
                                 height,
                             } => {
+                                self.tiles_per_layer[die_id] += 1;
+
                                 // Draw merged rectangle for multi-cell tile
                                 let tile_width = *width as f32 * cell_size;
                                 let tile_height = *height as f32 * cell_size;
 
                                 let visual_top = egui::Pos2::new(
                                     col as f32 * cell_size,
-                                    (grid.height - row - height) as f32 * cell_size,
+                                    y_origin.screen_row(grid.height, row, *height) as f32
+                                        * cell_size,
                                 );
                                 let rect = egui::Rect::from_min_size(
                                     visual_top,
@@ -82,7 +128,17 @@ impl GridRenderer {
                                     .copied()
                                     .unwrap_or(color_scheme::grid_lb_color(dark_mode));
 
-                                let outline_color = darken_color(color, 0.5);
+                                let mut outline_color = darken_color(color, 0.5);
+                                // Types left out of the tile filter fade into
+                                // the background.
+                                let dimmed = state.is_tile_dimmed(pb_type);
+                                let color = if dimmed {
+                                    outline_color =
+                                        outline_color.gamma_multiply(Self::DIMMED_ALPHA);
+                                    color.gamma_multiply(Self::DIMMED_ALPHA)
+                                } else {
+                                    color
+                                };
 
                                 // Draw filled rectangle
                                 self.grid_shapes[die_id].push(egui::Shape::rect_filled(
@@ -91,17 +147,28 @@ impl GridRenderer {
                                     color,
                                 ));
 
-                                // Draw outline
+                                // Draw the lines between the cells of a multi-cell tile,
+                                // then outline its footprint more heavily.
                                 // TODO: This can probably be combined with the filled rectangle.
-                                self.grid_shapes[die_id].push(egui::Shape::rect_stroke(
-                                    rect,
-                                    egui::CornerRadius::ZERO,
-                                    egui::Stroke::new(1.0, outline_color),
-                                    egui::epaint::StrokeKind::Inside,
-                                ));
+                                if state.show_cell_borders {
+                                    self.push_cell_lines(
+                                        die_id,
+                                        rect,
+                                        cell_size,
+                                        color_scheme::grid_cell_border_color(dark_mode),
+                                    );
+                                    let outline_width =
+                                        if *width > 1 || *height > 1 { 2.0 } else { 1.0 };
+                                    self.grid_shapes[die_id].push(egui::Shape::rect_stroke(
+                                        rect,
+                                        egui::CornerRadius::ZERO,
+                                        egui::Stroke::new(outline_width, outline_color),
+                                        egui::epaint::StrokeKind::Inside,
+                                    ));
+                                }
 
                                 // Only draw the text if the tile is large enough.
-                                if cell_size > Self::MIN_CELL_SIZE_FOR_TEXT {
+                                if cell_size > Self::MIN_CELL_SIZE_FOR_TEXT && !dimmed {
                                     // Draw tile name in center (uppercase)
                                     let tile_name_upper = pb_type.to_uppercase();
                                     let font_size = (cell_size * 0.2).min(tile_height * 0.15);
@@ -115,6 +182,15 @@ impl GridRenderer {
                                             color_scheme::theme_text_color(dark_mode),
                                         ));
                                     });
+
+                                    // Tiles holding several blocks get a "×N" badge.
+                                    let capacity =
+                                        tile_capacities.get(pb_type).copied().unwrap_or(1);
+                                    if capacity > 1 {
+                                        self.push_capacity_badge(
+                                            die_id, rect, capacity, cell_size, dark_mode, ui,
+                                        );
+                                    }
                                 }
                             }
                             GridCell::BlockOccupied { .. } => {
@@ -137,7 +213,8 @@ impl GridRenderer {
                 });
             }
             for horizontal_cut in &grid.grid_layers[die_id].horizontal_interposer_cut_lines {
-                let cut_y = (grid.height - *horizontal_cut) as f32 * cell_size;
+                let cut_y =
+                    y_origin.screen_y(grid.height as f32, *horizontal_cut as f32) * cell_size;
                 self.grid_shapes[die_id].push(egui::Shape::LineSegment {
                     points: [
                         egui::Pos2::new(0.0, cut_y),
@@ -149,18 +226,132 @@ impl GridRenderer {
         }
     }
 
+    /// Draws a thin line along every cell edge inside `rect`.
+    fn push_cell_lines(
+        &mut self,
+        die_id: usize,
+        rect: egui::Rect,
+        cell_size: f32,
+        color: egui::Color32,
+    ) {
+        let stroke = egui::Stroke::new(0.5, color);
+        let columns = (rect.width() / cell_size).round() as usize;
+        let rows = (rect.height() / cell_size).round() as usize;
+        for i in 1..columns {
+            let x = rect.left() + i as f32 * cell_size;
+            self.grid_shapes[die_id].push(egui::Shape::line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                stroke,
+            ));
+        }
+        for i in 1..rows {
+            let y = rect.top() + i as f32 * cell_size;
+            self.grid_shapes[die_id].push(egui::Shape::line_segment(
+                [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+                stroke,
+            ));
+        }
+    }
+
+    fn push_empty_cell_marker(
+        &mut self,
+        die_id: usize,
+        rect: egui::Rect,
+        dark_mode: bool,
+        ui: &egui::Ui,
+    ) {
+        const NUM_HATCH_LINES: usize = 4;
+        let hatch_stroke = egui::Stroke::new(1.0, color_scheme::grid_empty_hatch_color(dark_mode));
+        // Diagonal lines spaced evenly across the cell, clipped to its edges.
+        let spacing = rect.width() / NUM_HATCH_LINES as f32;
+        for i in 1..(2 * NUM_HATCH_LINES) {
+            let d = i as f32 * spacing;
+            let start = if d <= rect.width() {
+                egui::pos2(rect.left() + d, rect.bottom())
+            } else {
+                egui::pos2(rect.right(), rect.bottom() - (d - rect.width()))
+            };
+            let end = if d <= rect.height() {
+                egui::pos2(rect.left(), rect.bottom() - d)
+            } else {
+                egui::pos2(rect.left() + (d - rect.height()), rect.top())
+            };
+            self.grid_shapes[die_id].push(egui::Shape::line_segment([start, end], hatch_stroke));
+        }
+
+        if rect.width() > Self::MIN_CELL_SIZE_FOR_TEXT {
+            ui.fonts(|fonts| {
+                self.text_shapes[die_id].push(egui::Shape::text(
+                    fonts,
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "EMPTY",
+                    egui::FontId::proportional(rect.width() * 0.15),
+                    color_scheme::theme_border_color(dark_mode),
+                ));
+            });
+        }
+    }
+
+    fn push_capacity_badge(
+        &mut self,
+        die_id: usize,
+        rect: egui::Rect,
+        capacity: usize,
+        cell_size: f32,
+        dark_mode: bool,
+        ui: &egui::Ui,
+    ) {
+        let text_color = color_scheme::theme_text_color(dark_mode);
+        let galley = ui.fonts(|fonts| {
+            fonts.layout_no_wrap(
+                format!("\u{00D7}{capacity}"),
+                egui::FontId::proportional(cell_size * 0.15),
+                text_color,
+            )
+        });
+        let margin = cell_size * 0.05;
+        let badge_rect = egui::Rect::from_min_size(
+            egui::pos2(
+                rect.right() - margin - galley.size().x - 2.0 * margin,
+                rect.top() + margin,
+            ),
+            galley.size() + egui::vec2(2.0 * margin, 0.0),
+        );
+        self.text_shapes[die_id].push(egui::Shape::rect_filled(
+            badge_rect,
+            egui::CornerRadius::same(3),
+            color_scheme::theme_block_bg(dark_mode),
+        ));
+        self.text_shapes[die_id].push(egui::Shape::galley(
+            badge_rect.min + egui::vec2(margin, 0.0),
+            galley,
+            text_color,
+        ));
+    }
+
     pub fn render_grid(
         &mut self,
         ui: &mut egui::Ui,
         grid: &DeviceGrid,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         state: &GridState,
+        highlighted_tile: Option<&str>,
         dark_mode: bool,
-    ) -> Option<String> {
-        // Cell size is based on the available space
-        let cell_size = get_cell_size(grid, state.zoom_factor, ui);
+    ) -> Option<TileClick> {
+        // Cell size is based on the available space or the chosen cell size
+        let cell_size = get_cell_size(grid, state.zoom_factor, state.cell_size, ui);
 
-        let mut clicked_tile: Option<String> = None;
+        let mut clicked_tile: Option<TileClick> = None;
+        self.draw_stats = DrawStats {
+            blocks: self
+                .tiles_per_layer
+                .get(state.selected_die_id)
+                .copied()
+                .unwrap_or(0),
+            wires: 0,
+        };
 
         egui::ScrollArea::both()
             .auto_shrink([false, false])
@@ -206,6 +397,61 @@ impl GridRenderer {
                     painter.extend(text_shapes);
                 }
 
+                // Outline every instance of the highlighted tile type.
+                if let Some(highlighted_tile) = highlighted_tile {
+                    painter.extend(tile_outline_shapes(
+                        grid,
+                        state,
+                        offset,
+                        cell_size,
+                        |pb_type| pb_type == highlighted_tile,
+                        color_scheme::GRID_HIGHLIGHT_COLOR,
+                    ));
+                }
+
+                // Briefly outline the tile types that changed in a reload.
+                if let Some(changes) = &state.recent_changes {
+                    painter.extend(tile_outline_shapes(
+                        grid,
+                        state,
+                        offset,
+                        cell_size,
+                        |pb_type| changes.names.contains(pb_type),
+                        color_scheme::CHANGE_HIGHLIGHT_COLOR.gamma_multiply(changes.strength),
+                    ));
+                }
+
+                // Outline the cell that was jumped to, fading out.
+                if let Some((col, row, started)) = state.jump_highlight {
+                    let cell_rect = egui::Rect::from_min_size(
+                        offset
+                            + egui::vec2(
+                                col as f32 * cell_size,
+                                state.y_origin.screen_row(grid.height, row, 1) as f32 * cell_size,
+                            ),
+                        egui::vec2(cell_size, cell_size),
+                    );
+                    if state.jump_to_cell == Some((col, row)) {
+                        ui.scroll_to_rect(cell_rect, Some(egui::Align::Center));
+                    }
+                    let strength = arch_diff::highlight_strength(
+                        ui.input(|i| i.time) - started,
+                        JUMP_HIGHLIGHT_SECONDS,
+                    );
+                    painter.rect_stroke(
+                        cell_rect,
+                        egui::CornerRadius::ZERO,
+                        egui::Stroke::new(
+                            3.0,
+                            color_scheme::theme_text_color(dark_mode).gamma_multiply(strength),
+                        ),
+                        egui::epaint::StrokeKind::Inside,
+                    );
+                    if strength > 0.0 {
+                        ui.ctx().request_repaint();
+                    }
+                }
+
                 // Draw the NoC if requested.
                 if state.show_noc
                     && let Some(noc_info) = &arch.noc
@@ -218,7 +464,10 @@ impl GridRenderer {
                             continue;
                         }
                         let x_pos = router.position_x * cell_size;
-                        let y_pos = (grid.height as f32 - router.position_y) * cell_size;
+                        let y_pos = state
+                            .y_origin
+                            .screen_y(grid.height as f32, router.position_y)
+                            * cell_size;
                         router_positions.insert(router.id, offset + egui::vec2(x_pos, y_pos));
                     }
 
@@ -233,6 +482,7 @@ impl GridRenderer {
                             let Some(&to_pos) = router_positions.get(target_id) else {
                                 continue;
                             };
+                            self.draw_stats.wires += 1;
                             noc_shapes.push(egui::Shape::line_segment(
                                 [from_pos, to_pos],
                                 egui::Stroke::new(2.0, color_scheme::theme_text_color(dark_mode)),
@@ -253,12 +503,11 @@ impl GridRenderer {
                 }
 
                 // Check for which tile is currently being hovered over.
+                let mut hovered_tile = None;
                 if let Some(hover_pos) = response.hover_pos() {
                     let mut col = ((hover_pos.x - offset.x) / cell_size).floor() as usize;
-                    let mut row = grid
-                        .height
-                        .saturating_sub(1)
-                        .saturating_sub(((hover_pos.y - offset.y) / cell_size).floor() as usize);
+                    let screen_row = ((hover_pos.y - offset.y) / cell_size).floor() as usize;
+                    let mut row = state.y_origin.grid_row(grid.height, screen_row);
                     if let Some(GridCell::BlockOccupied {
                         pb_type: _,
                         anchor_row,
@@ -275,21 +524,79 @@ impl GridRenderer {
                         height,
                     }) = grid.get(row, col, state.selected_die_id)
                     {
-                        // If a tile has been clicked, mark it as the clicked tile.
-                        if response.clicked() {
-                            clicked_tile = Some(pb_type.clone());
+                        hovered_tile = Some((pb_type, col, row, *width, *height));
+                    }
+                }
+
+                // The side panel is drawn before the grid, so ask for another
+                // frame to show the new hovered tile there.
+                let hovered_name = hovered_tile.map(|(pb_type, ..)| pb_type.clone());
+                if hovered_name != self.hovered_tile {
+                    self.hovered_tile = hovered_name;
+                    ui.ctx().request_repaint();
+                }
+
+                // If a tile has been clicked, mark it as the clicked tile.
+                if response.clicked()
+                    && let Some((pb_type, ..)) = hovered_tile
+                {
+                    clicked_tile = Some(TileClick::Open(pb_type.clone()));
+                }
+                // Remember which tile was right-clicked, since the pointer may
+                // move off of it while the context menu is open.
+                if response.secondary_clicked() {
+                    self.context_tile =
+                        hovered_tile.map(|(pb_type, col, row, ..)| (pb_type.clone(), col, row));
+                }
+
+                if let Some((tile_name, col, row)) = &self.context_tile {
+                    response.context_menu(|ui| {
+                        ui.label(format!("{} [{}, {}]", tile_name.to_uppercase(), col, row));
+                        ui.separator();
+                        if ui.button("Inspect tile (intra view)").clicked() {
+                            clicked_tile = Some(TileClick::Inspect(tile_name.clone()));
+                        }
+                        if ui.button("Highlight all of this type").clicked() {
+                            clicked_tile = Some(TileClick::Highlight(tile_name.clone()));
+                        }
+                        if ui.button("Copy tile name").clicked() {
+                            ui.ctx().copy_text(tile_name.clone());
                         }
+                        if ui.button("Show tile info").clicked() {
+                            clicked_tile = Some(TileClick::ShowInfo {
+                                tile_name: tile_name.clone(),
+                                col: *col,
+                                row: *row,
+                            });
+                        }
+                    });
+                }
 
-                        // On hover, show ui at the pointer.
-                        response.on_hover_ui_at_pointer(|ui| {
-                            ui.label(format!("{} [{}, {}]", pb_type, col, row));
-                            ui.label(format!("Size: {}x{}", width, height));
-                            if let Some(tile) = arch.tiles.iter().find(|t| t.name == *pb_type) {
-                                ui.label(format!("Contains {} sub-tiles", tile.sub_tiles.len()));
+                // On hover, show ui at the pointer.
+                if let Some((pb_type, col, row, width, height)) = hovered_tile
+                    && !response.context_menu_opened()
+                {
+                    response.on_hover_ui_at_pointer(|ui| {
+                        ui.label(format!("{} [{}, {}]", pb_type, col, row));
+                        ui.label(format!("Size: {}x{}", width, height));
+                        if let Some(tile) = arch_index.tile(arch, pb_type) {
+                            ui.label(format!("Contains {} sub-tiles", tile.sub_tiles.len()));
+                            ui.label(format!("Total capacity: {}", tile_capacity(tile)));
+                            let (area, is_default) = tile_area(arch, tile);
+                            if is_default {
+                                ui.label(format!("Area: {:.2} (grid default)", area));
+                            } else {
+                                ui.label(format!("Area: {:.2}", area));
                             }
-                            ui.label("Click to view internal structure");
-                        });
-                    }
+                        }
+                        if let Some(metadata) = grid.metadata(arch, state.selected_die_id, row, col)
+                        {
+                            common_ui::render_metadata_tooltip(ui, metadata);
+                            ui.separator();
+                        }
+                        ui.label("Click to view internal structure");
+                        ui.label("Right-click for more actions");
+                    });
                 }
             });
 
@@ -297,8 +604,62 @@ impl GridRenderer {
     }
 }
 
-pub fn get_cell_size(grid: &DeviceGrid, zoom_factor: f32, ui: &egui::Ui) -> f32 {
+/// Returns the on-screen size of one grid cell in pixels. Without a fixed cell
+/// size, the grid is scaled to fit the available space.
+pub fn get_cell_size(
+    grid: &DeviceGrid,
+    zoom_factor: f32,
+    fixed_cell_size: Option<f32>,
+    ui: &egui::Ui,
+) -> f32 {
+    if let Some(cell_size) = fixed_cell_size {
+        return cell_size * zoom_factor;
+    }
     let available_size = ui.available_size();
     let max_dim = grid.width.max(grid.height).max(1) as f32;
     (available_size.x.min(available_size.y) / max_dim) * zoom_factor
 }
+
+/// Outlines, in `color`, every tile on the selected layer whose type passes
+/// `is_outlined`.
+fn tile_outline_shapes(
+    grid: &DeviceGrid,
+    state: &GridState,
+    offset: egui::Pos2,
+    cell_size: f32,
+    is_outlined: impl Fn(&str) -> bool,
+    color: egui::Color32,
+) -> Vec<egui::Shape> {
+    let layer = &grid.grid_layers[state.selected_die_id];
+    let mut shapes = Vec::new();
+    for (row, cells) in layer.cells.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let GridCell::BlockAnchor {
+                pb_type,
+                width,
+                height,
+            } = cell
+            else {
+                continue;
+            };
+            if !is_outlined(pb_type) {
+                continue;
+            }
+            let rect = egui::Rect::from_min_size(
+                offset
+                    + egui::vec2(
+                        col as f32 * cell_size,
+                        state.y_origin.screen_row(grid.height, row, *height) as f32 * cell_size,
+                    ),
+                egui::vec2(*width as f32 * cell_size, *height as f32 * cell_size),
+            );
+            shapes.push(egui::Shape::rect_stroke(
+                rect,
+                egui::CornerRadius::ZERO,
+                egui::Stroke::new(3.0, color),
+                egui::epaint::StrokeKind::Inside,
+            ));
+        }
+    }
+    shapes
+}
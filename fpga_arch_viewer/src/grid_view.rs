@@ -1,21 +1,116 @@
-use fpga_arch_parser::FPGAArch;
-use std::{cmp::max, collections::HashMap};
+use fpga_arch_parser::prelude::*;
+use std::{
+    cmp::max,
+    collections::{BTreeSet, HashMap},
+};
 
 use crate::{
+    arch_diff::ChangeHighlight,
+    arch_index::ArchIndex,
+    color_scheme::{self, Palette},
+    common_ui,
     grid::{DeviceGrid, GridCell},
-    grid_renderer::GridRenderer,
+    grid_renderer::{GridRenderer, TileClick},
+    tile_rendering::tile_renderer::build_render_tile,
     viewer::ViewMode,
 };
 
+const DEFAULT_CELL_SIZE: f32 = 40.0;
+const MIN_CELL_SIZE: f32 = 4.0;
+const MAX_CELL_SIZE: f32 = 200.0;
+// Minimum time, in seconds, between grid rebuilds while a dimension slider
+// is dragged.
+const DRAG_REBUILD_INTERVAL: f64 = 0.2;
+// Length, in pixels, of the longer side of the tile footprint preview.
+const TILE_PREVIEW_SIZE: f32 = 120.0;
+// How long, in seconds, a cell jumped to stays outlined.
+pub const JUMP_HIGHLIGHT_SECONDS: f64 = 2.0;
+
+/// Settings of the grid view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridSettings {
+    /// Pixel size of a cell; None fits the grid to the view.
+    pub cell_size: Option<f32>,
+    /// Hatch cells that the layout explicitly fills with EMPTY.
+    pub mark_empty_cells: bool,
+    /// Outline cells and tile footprints.
+    pub show_cell_borders: bool,
+    /// Which edge of the view row 0 is drawn at.
+    pub y_origin: GridYOrigin,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: None,
+            mark_empty_cells: false,
+            show_cell_borders: true,
+            y_origin: GridYOrigin::default(),
+        }
+    }
+}
+
+/// Which edge of the view row 0 of the grid is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridYOrigin {
+    /// Row 0 at the bottom, as in VTR.
+    #[default]
+    Bottom,
+    /// Row 0 at the top, as screen coordinates grow downward.
+    Top,
+}
+
+impl GridYOrigin {
+    pub const ALL: [GridYOrigin; 2] = [GridYOrigin::Bottom, GridYOrigin::Top];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GridYOrigin::Bottom => "Bottom (VTR)",
+            GridYOrigin::Top => "Top (screen)",
+        }
+    }
+
+    /// Screen row, counted down from the top of the view, of the top edge of
+    /// a tile that covers `height` rows starting at `row`.
+    pub fn screen_row(self, grid_height: usize, row: usize, height: usize) -> usize {
+        match self {
+            GridYOrigin::Bottom => grid_height.saturating_sub(row + height),
+            GridYOrigin::Top => row,
+        }
+    }
+
+    /// Grid row shown at the given screen row.
+    pub fn grid_row(self, grid_height: usize, screen_row: usize) -> usize {
+        match self {
+            GridYOrigin::Bottom => grid_height.saturating_sub(1).saturating_sub(screen_row),
+            GridYOrigin::Top => screen_row,
+        }
+    }
+
+    /// Distance from the top of the view, in cells, of the grid coordinate
+    /// `y`. Used for positions between rows, such as cut lines and routers.
+    pub fn screen_y(self, grid_height: f32, y: f32) -> f32 {
+        match self {
+            GridYOrigin::Bottom => grid_height - y,
+            GridYOrigin::Top => y,
+        }
+    }
+}
+
 /// State for grid view
 #[derive(Debug, Clone)]
 pub struct GridState {
     pub grid_width: usize,
     pub grid_height: usize,
     pub aspect_ratio: f32,
+    // Keep an auto layout's width and height at its aspect ratio while they
+    // are edited. When off, each is set on its own and the ratio follows.
+    pub lock_aspect_ratio: bool,
     pub selected_layout_index: usize,
     pub selected_die_id: usize,
     pub zoom_factor: f32,
+    // Fixed pixel size of a grid cell; None fits the grid to the view.
+    pub cell_size: Option<f32>,
 
     pub max_zoom: f32,
 
@@ -24,6 +119,31 @@ pub struct GridState {
     pub last_available_size: egui::Vec2,
 
     pub show_noc: bool,
+    // Hatch cells that were explicitly placed as EMPTY.
+    pub mark_empty_cells: bool,
+    // Outline every cell and tile. Large grids draw faster without them.
+    pub show_cell_borders: bool,
+    // Which edge of the view row 0 is drawn at.
+    pub y_origin: GridYOrigin,
+    // Tile type whose instances are outlined across the grid.
+    pub highlighted_tile: Option<String>,
+    // Placed tile (name, col, row) whose details are shown in the tile info window.
+    pub info_tile: Option<(String, usize, usize)>,
+    // Whether a width or height slider was being dragged this frame.
+    pub dimension_slider_dragged: bool,
+    // Tile types that changed in the last reload, outlined until it fades.
+    pub recent_changes: Option<ChangeHighlight>,
+    pub rebuild_throttle: GridRebuildThrottle,
+    // Text typed into the "Go to cell" box, and why it was rejected.
+    pub jump_text: String,
+    pub jump_error: Option<String>,
+    // Cell (col, row) to scroll to on the next frame.
+    pub jump_to_cell: Option<(usize, usize)>,
+    // Cell (col, row) that was jumped to, and when, outlined until it fades.
+    pub jump_highlight: Option<(usize, usize, f64)>,
+    // Tile types picked in the tile counts table. When any are picked, the
+    // other types are dimmed. Kept across reloads for the session.
+    pub visible_tiles: BTreeSet<String>,
 }
 
 impl Default for GridState {
@@ -32,14 +152,29 @@ impl Default for GridState {
             grid_width: 10,
             grid_height: 10,
             aspect_ratio: 1.0,
+            lock_aspect_ratio: true,
             selected_layout_index: 0,
             selected_die_id: 0,
             zoom_factor: 1.0,
+            cell_size: None,
             max_zoom: 10.0,
             grid_changed: false,
             zoom_changed: false,
             last_available_size: egui::Vec2::ZERO,
             show_noc: false,
+            mark_empty_cells: false,
+            show_cell_borders: true,
+            y_origin: GridYOrigin::default(),
+            highlighted_tile: None,
+            info_tile: None,
+            dimension_slider_dragged: false,
+            recent_changes: None,
+            rebuild_throttle: GridRebuildThrottle::default(),
+            jump_text: String::new(),
+            jump_error: None,
+            jump_to_cell: None,
+            jump_highlight: None,
+            visible_tiles: BTreeSet::new(),
         }
     }
 }
@@ -65,6 +200,12 @@ impl GridState {
         self.update_zoom();
     }
 
+    /// Whether tiles of type `tile_name` are dimmed because other types were
+    /// picked to be shown.
+    pub fn is_tile_dimmed(&self, tile_name: &str) -> bool {
+        !self.visible_tiles.is_empty() && !self.visible_tiles.contains(tile_name)
+    }
+
     /// Reset zoom to 1.0
     pub fn reset_zoom(&mut self) {
         self.zoom_factor = 1.0;
@@ -78,6 +219,45 @@ impl GridState {
     }
 }
 
+/// Limits how often the grid is rebuilt while a dimension slider is dragged.
+/// Building a large auto layout is slow, so rebuilding on every frame of a
+/// drag makes the slider lag.
+#[derive(Debug, Clone, Default)]
+pub struct GridRebuildThrottle {
+    pending: bool,
+    last_rebuild: Option<f64>,
+}
+
+impl GridRebuildThrottle {
+    /// Marks the grid as out of date.
+    pub fn request(&mut self) {
+        self.pending = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Returns true if the grid should be rebuilt now. While `dragging`, a
+    /// pending rebuild waits until DRAG_REBUILD_INTERVAL has passed since the
+    /// last one; otherwise it happens right away.
+    pub fn poll(&mut self, now: f64, dragging: bool) -> bool {
+        if !self.pending {
+            return false;
+        }
+        if dragging
+            && self
+                .last_rebuild
+                .is_some_and(|last| now - last < DRAG_REBUILD_INTERVAL)
+        {
+            return false;
+        }
+        self.pending = false;
+        self.last_rebuild = Some(now);
+        true
+    }
+}
+
 #[derive(Default)]
 pub struct GridView {
     // Device grid and grid view state
@@ -90,10 +270,14 @@ pub struct GridView {
     // Renderer object in charge of the grid.
     pub grid_renderer: GridRenderer,
 
-    // Sorted tile names for rebuilding colors when dark mode changes.
+    // Number of blocks each tile type holds, summed over its sub-tiles.
+    pub tile_capacities: HashMap<String, usize>,
+
+    // Sorted tile names for rebuilding colors when the theme or palette changes.
     sorted_tile_names: Vec<String>,
-    // Last dark mode value used to build tile_colors; None forces a rebuild.
-    last_dark_mode: Option<bool>,
+    // Last palette and dark mode value used to build tile_colors; None forces
+    // a rebuild.
+    last_colors: Option<(Palette, bool)>,
 }
 
 impl GridView {
@@ -106,26 +290,38 @@ impl GridView {
         self.sorted_tile_names = tile_names.into_iter().collect();
         self.sorted_tile_names.sort();
 
+        self.tile_capacities = arch
+            .tiles
+            .iter()
+            .map(|tile| (tile.name.clone(), tile_capacity(tile)))
+            .collect();
+
         // Force a color rebuild on the next update_tile_colors call.
-        self.last_dark_mode = None;
+        self.last_colors = None;
         self.tile_colors.clear();
 
         // Reset layout selection and rebuild grid
         self.grid_state.selected_layout_index = 0;
         self.grid_state.selected_die_id = 0;
+        self.grid_state.highlighted_tile = None;
+        self.grid_state.info_tile = None;
+        self.grid_state.jump_error = None;
+        self.grid_state.jump_to_cell = None;
+        self.grid_state.jump_highlight = None;
         self.rebuild_grid(arch);
     }
 
-    /// Rebuilds tile_colors for the given dark mode. Called every frame from
-    /// the top-level update so colors stay current regardless of active view.
-    pub fn update_tile_colors(&mut self, dark_mode: bool) {
-        if self.last_dark_mode == Some(dark_mode) {
+    /// Rebuilds tile_colors for the given palette and dark mode. Called every
+    /// frame from the top-level update so colors stay current regardless of
+    /// active view.
+    pub fn update_tile_colors(&mut self, palette: Palette, dark_mode: bool) {
+        if self.last_colors == Some((palette, dark_mode)) {
             return;
         }
-        self.last_dark_mode = Some(dark_mode);
+        self.last_colors = Some((palette, dark_mode));
         self.tile_colors.clear();
         for (i, tile_name) in self.sorted_tile_names.iter().enumerate() {
-            let color = crate::block_style::get_tile_color(tile_name, i, dark_mode);
+            let color = crate::block_style::get_tile_color(tile_name, i, palette, dark_mode);
             self.tile_colors.insert(tile_name.clone(), color);
         }
         self.grid_state.grid_changed = true;
@@ -134,17 +330,69 @@ impl GridView {
     pub fn render(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         selected_tile_name: &mut Option<String>,
+        selected_complex_block_name: &mut Option<String>,
         next_view_mode: &mut ViewMode,
+        settings: &mut GridSettings,
         dark_mode: bool,
         ctx: &egui::Context,
     ) {
-        self.render_side_panel(arch, ctx);
+        // The grid settings are viewer settings, so they may have changed
+        // elsewhere.
+        if self.grid_state.cell_size != settings.cell_size {
+            self.grid_state.cell_size = settings.cell_size;
+            self.grid_state.zoom_changed = true;
+        }
+        if self.grid_state.mark_empty_cells != settings.mark_empty_cells {
+            self.grid_state.mark_empty_cells = settings.mark_empty_cells;
+            self.grid_state.grid_changed = true;
+        }
+        if self.grid_state.show_cell_borders != settings.show_cell_borders {
+            self.grid_state.show_cell_borders = settings.show_cell_borders;
+            self.grid_state.grid_changed = true;
+        }
+        if self.grid_state.y_origin != settings.y_origin {
+            self.grid_state.y_origin = settings.y_origin;
+            self.grid_state.grid_changed = true;
+        }
+
+        self.render_side_panel(arch, arch_index, ctx);
+        *settings = GridSettings {
+            cell_size: self.grid_state.cell_size,
+            mark_empty_cells: self.grid_state.mark_empty_cells,
+            show_cell_borders: self.grid_state.show_cell_borders,
+            y_origin: self.grid_state.y_origin,
+        };
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.render_grid_view(arch, selected_tile_name, next_view_mode, dark_mode, ui);
+            self.render_grid_view(
+                arch,
+                arch_index,
+                selected_tile_name,
+                selected_complex_block_name,
+                next_view_mode,
+                dark_mode,
+                ui,
+            );
         });
 
+        let info_tile_metadata = self
+            .grid_state
+            .info_tile
+            .as_ref()
+            .zip(self.device_grid.as_ref())
+            .and_then(|((_, col, row), grid)| {
+                grid.metadata(arch, self.grid_state.selected_die_id, *row, *col)
+            });
+        render_tile_info_window(
+            arch,
+            arch_index,
+            &mut self.grid_state.info_tile,
+            info_tile_metadata,
+            ctx,
+        );
+
         self.grid_state.zoom_changed = false;
         self.grid_state.grid_changed = false;
     }
@@ -157,8 +405,10 @@ impl GridView {
         {
             let grid = match &layout {
                 fpga_arch_parser::Layout::AutoLayout(auto_layout) => {
-                    self.grid_state.aspect_ratio = auto_layout.aspect_ratio;
-                    update_grid_height_from_width(&mut self.grid_state);
+                    if self.grid_state.lock_aspect_ratio {
+                        self.grid_state.aspect_ratio = auto_layout.aspect_ratio;
+                        update_grid_height_from_width(&mut self.grid_state);
+                    }
                     DeviceGrid::from_auto_layout_with_dimensions(
                         arch,
                         self.grid_state.grid_width,
@@ -188,7 +438,9 @@ impl GridView {
     fn render_grid_view(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         selected_tile_name: &mut Option<String>,
+        selected_complex_block_name: &mut Option<String>,
         next_view_mode: &mut ViewMode,
         dark_mode: bool,
         ui: &mut egui::Ui,
@@ -222,25 +474,77 @@ impl GridView {
                 self.grid_renderer.prerender_grid(
                     grid,
                     &self.tile_colors,
-                    self.grid_state.zoom_factor,
+                    &self.tile_capacities,
+                    &self.grid_state,
                     dark_mode,
                     ui,
                 );
                 self.grid_state.last_available_size = current_available_size;
             }
-            if let Some(clicked_tile) =
-                self.grid_renderer
-                    .render_grid(ui, grid, arch, &self.grid_state, dark_mode)
+            let tile_click = self.grid_renderer.render_grid(
+                ui,
+                grid,
+                arch,
+                arch_index,
+                &self.grid_state,
+                self.grid_state.highlighted_tile.as_deref(),
+                dark_mode,
+            );
+            // The renderer scrolled to the cell; only the outline remains.
+            self.grid_state.jump_to_cell = None;
+            if let Some((.., started)) = self.grid_state.jump_highlight
+                && ui.input(|i| i.time) - started > JUMP_HIGHLIGHT_SECONDS
             {
-                *selected_tile_name = Some(clicked_tile);
-                *next_view_mode = ViewMode::Tile;
+                self.grid_state.jump_highlight = None;
+            }
+            match tile_click {
+                Some(TileClick::Open(tile_name)) => {
+                    *selected_tile_name = Some(tile_name);
+                    *next_view_mode = ViewMode::Tile;
+                }
+                Some(TileClick::Highlight(tile_name)) => {
+                    // Highlighting the highlighted type again clears it.
+                    if self.grid_state.highlighted_tile.as_ref() == Some(&tile_name) {
+                        self.grid_state.highlighted_tile = None;
+                    } else {
+                        self.grid_state.highlighted_tile = Some(tile_name);
+                    }
+                }
+                Some(TileClick::Inspect(tile_name)) => {
+                    // The intra-tile view shows the complex block of the tile's
+                    // first equivalent site.
+                    let pb_type = arch_index
+                        .tile(arch, &tile_name)
+                        .and_then(|tile| {
+                            tile.sub_tiles
+                                .iter()
+                                .flat_map(|sub_tile| sub_tile.equivalent_sites.iter())
+                                .next()
+                        })
+                        .map(|site| site.pb_type.clone());
+                    if let Some(pb_type) = pb_type {
+                        *selected_complex_block_name = Some(pb_type);
+                        *next_view_mode = ViewMode::ComplexBlock;
+                    } else {
+                        *selected_tile_name = Some(tile_name);
+                        *next_view_mode = ViewMode::Tile;
+                    }
+                }
+                Some(TileClick::ShowInfo {
+                    tile_name,
+                    col,
+                    row,
+                }) => {
+                    self.grid_state.info_tile = Some((tile_name, col, row));
+                }
+                None => {}
             }
         } else {
             // TODO: Render an error window
         }
     }
 
-    fn render_side_panel(&mut self, arch: &FPGAArch, ctx: &egui::Context) {
+    fn render_side_panel(&mut self, arch: &FPGAArch, arch_index: &ArchIndex, ctx: &egui::Context) {
         egui::SidePanel::right("grid_controls")
             .default_width(250.0)
             .show(ctx, |ui| {
@@ -250,28 +554,101 @@ impl GridView {
                         let grid_changed = render_grid_controls_panel(
                             ui,
                             arch,
+                            arch_index,
                             &mut self.grid_state,
                             self.device_grid.as_ref(),
                             &self.tile_colors,
+                            self.grid_renderer.hovered_tile.as_deref(),
                         );
                         if grid_changed {
+                            self.grid_state.rebuild_throttle.request();
+                        }
+                        let now = ui.input(|i| i.time);
+                        let dragging = self.grid_state.dimension_slider_dragged;
+                        if self.grid_state.rebuild_throttle.poll(now, dragging) {
                             self.rebuild_grid(arch);
+                        } else if self.grid_state.rebuild_throttle.is_pending() {
+                            // Come back for the held-back rebuild even if the
+                            // pointer stops moving.
+                            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                                DRAG_REBUILD_INTERVAL,
+                            ));
                         }
                     });
             });
     }
 }
 
+/// A box to type an (x, y) coordinate into, which scrolls the grid to that
+/// cell and briefly outlines it.
+fn render_jump_to_cell(ui: &mut egui::Ui, state: &mut GridState, grid: &DeviceGrid) {
+    ui.label("Go to cell:");
+    let mut go = false;
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.jump_text)
+                .hint_text("x, y")
+                .desired_width(80.0),
+        );
+        go |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        go |= ui.small_button("Go").clicked();
+    });
+    if go {
+        match parse_cell_coordinate(&state.jump_text, grid.width, grid.height) {
+            Ok((col, row)) => {
+                state.jump_error = None;
+                state.jump_to_cell = Some((col, row));
+                state.jump_highlight = Some((col, row, ui.input(|i| i.time)));
+            }
+            Err(e) => state.jump_error = Some(e),
+        }
+    }
+    if let Some(error) = &state.jump_error {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+}
+
+/// Parses an "x, y" coordinate, optionally in parentheses or separated by
+/// spaces, and checks it lies inside a grid of the given size.
+fn parse_cell_coordinate(
+    text: &str,
+    width: usize,
+    height: usize,
+) -> Result<(usize, usize), String> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .unwrap_or(text);
+    let parts: Vec<&str> = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let [x, y] = parts[..] else {
+        return Err("Enter two numbers, e.g. 3, 5".to_string());
+    };
+    let (Ok(x), Ok(y)) = (x.parse::<usize>(), y.parse::<usize>()) else {
+        return Err("Coordinates must be non-negative integers".to_string());
+    };
+    if x >= width || y >= height {
+        return Err(format!("({x}, {y}) is outside the {width}x{height} grid"));
+    }
+    Ok((x, y))
+}
+
 /// Renders the grid controls panel on the right side
 /// Returns true if grid dimensions changed
 fn render_grid_controls_panel(
     ui: &mut egui::Ui,
     arch: &FPGAArch,
+    arch_index: &ArchIndex,
     state: &mut GridState,
     device_grid: Option<&DeviceGrid>,
     tile_colors: &HashMap<String, egui::Color32>,
+    hovered_tile: Option<&str>,
 ) -> bool {
     let mut grid_changed = false;
+    state.dimension_slider_dragged = false;
 
     ui.heading("Grid Settings");
     ui.add_space(10.0);
@@ -314,8 +691,25 @@ fn render_grid_controls_panel(
 
     ui.label(if is_fixed_layout {
         "Dimensions (Fixed by layout):"
-    } else {
+    } else if state.lock_aspect_ratio {
         "Adjust dimensions while maintaining aspect ratio:"
+    } else {
+        "Adjust width and height independently:"
+    });
+    ui.add_space(5.0);
+    ui.add_enabled_ui(!is_fixed_layout, |ui| {
+        if ui
+            .checkbox(&mut state.lock_aspect_ratio, "Lock aspect ratio")
+            .on_hover_text(
+                "Keep the width and height at the layout's aspect ratio. \
+                 Turn off to set them separately.",
+            )
+            .changed()
+            && state.lock_aspect_ratio
+        {
+            // The rebuild restores the layout's aspect ratio.
+            grid_changed = true;
+        }
     });
     ui.add_space(10.0);
 
@@ -324,18 +718,16 @@ fn render_grid_controls_panel(
         ui.label("Width:");
         let mut temp_width = state.grid_width as f64;
         ui.add_enabled_ui(!is_fixed_layout, |ui| {
-            if ui
-                .add(
-                    egui::Slider::new(&mut temp_width, 1.0..=100.0)
-                        .step_by(1.0)
-                        .show_value(false),
-                )
-                .changed()
-            {
+            let response = ui.add(
+                egui::Slider::new(&mut temp_width, 1.0..=100.0)
+                    .step_by(1.0)
+                    .show_value(false),
+            );
+            state.dimension_slider_dragged |= response.dragged();
+            if response.changed() {
                 let new_width = temp_width.round() as usize;
                 if new_width != state.grid_width && new_width >= 1 {
-                    state.grid_width = new_width;
-                    update_grid_height_from_width(state);
+                    set_grid_width(state, new_width);
                     grid_changed = true;
                 }
             }
@@ -352,8 +744,7 @@ fn render_grid_controls_panel(
                 && (1..=100).contains(&new_width)
                 && new_width != state.grid_width
             {
-                state.grid_width = new_width;
-                update_grid_height_from_width(state);
+                set_grid_width(state, new_width);
                 grid_changed = true;
             }
         });
@@ -366,18 +757,16 @@ fn render_grid_controls_panel(
         ui.label("Height:");
         let mut temp_height = state.grid_height as f64;
         ui.add_enabled_ui(!is_fixed_layout, |ui| {
-            if ui
-                .add(
-                    egui::Slider::new(&mut temp_height, 1.0..=100.0)
-                        .step_by(1.0)
-                        .show_value(false),
-                )
-                .changed()
-            {
+            let response = ui.add(
+                egui::Slider::new(&mut temp_height, 1.0..=100.0)
+                    .step_by(1.0)
+                    .show_value(false),
+            );
+            state.dimension_slider_dragged |= response.dragged();
+            if response.changed() {
                 let new_height = temp_height.round() as usize;
                 if new_height != state.grid_height && new_height >= 1 {
-                    state.grid_height = new_height;
-                    update_grid_width_from_height(state);
+                    set_grid_height(state, new_height);
                     grid_changed = true;
                 }
             }
@@ -394,13 +783,41 @@ fn render_grid_controls_panel(
                 && (1..=100).contains(&new_height)
                 && new_height != state.grid_height
             {
-                state.grid_height = new_height;
-                update_grid_width_from_height(state);
+                set_grid_height(state, new_height);
                 grid_changed = true;
             }
         });
     });
 
+    if let Some(fpga_arch_parser::Layout::AutoLayout(auto_layout)) =
+        arch.layouts.layout_list.get(state.selected_layout_index)
+    {
+        ui.add_space(10.0);
+        let auto_size_hint = if state.lock_aspect_ratio {
+            "Pick a width large enough for the layout's single, col, row and region \
+             placements, and the height that keeps the locked aspect ratio"
+        } else {
+            "Pick a width and height large enough for the layout's single, col, row and \
+             region placements"
+        };
+        if ui
+            .button("Auto-size")
+            .on_hover_text(auto_size_hint)
+            .clicked()
+        {
+            let (width, height) = DeviceGrid::recommended_auto_layout_size(arch, auto_layout);
+            // The height follows from the width and aspect ratio, unless the
+            // two are edited separately.
+            state.grid_width = width.min(100);
+            if state.lock_aspect_ratio {
+                update_grid_height_from_width(state);
+            } else {
+                set_grid_height(state, height.min(100));
+            }
+            grid_changed = true;
+        }
+    }
+
     if let Some(layout) = arch.layouts.layout_list.get(state.selected_layout_index) {
         let layers = match layout {
             fpga_arch_parser::Layout::FixedLayout(fixed_layout) => &fixed_layout.layers,
@@ -435,6 +852,11 @@ fn render_grid_controls_panel(
         state.grid_width, state.grid_height
     ));
 
+    if let Some(grid) = device_grid {
+        ui.add_space(10.0);
+        render_jump_to_cell(ui, state, grid);
+    }
+
     if arch.noc.is_some() {
         ui.add_space(15.0);
         ui.separator();
@@ -443,6 +865,21 @@ fn render_grid_controls_panel(
         ui.checkbox(&mut state.show_noc, "Show NoC");
     }
 
+    if let Some(highlighted_tile) = &state.highlighted_tile {
+        ui.add_space(15.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        let mut clear = false;
+        ui.horizontal(|ui| {
+            ui.label(format!("Highlighting: {}", highlighted_tile.to_uppercase()));
+            clear = ui.small_button("Clear").clicked();
+        });
+        if clear {
+            state.highlighted_tile = None;
+        }
+    }
+
     ui.add_space(15.0);
     ui.separator();
     ui.add_space(10.0);
@@ -461,6 +898,22 @@ fn render_grid_controls_panel(
         }
     });
 
+    if cell_size_control(ui, &mut state.cell_size) {
+        state.zoom_changed = true;
+    }
+
+    if empty_cells_control(ui, &mut state.mark_empty_cells) {
+        state.grid_changed = true;
+    }
+
+    if cell_borders_control(ui, &mut state.show_cell_borders) {
+        state.grid_changed = true;
+    }
+
+    if y_origin_control(ui, &mut state.y_origin) {
+        state.grid_changed = true;
+    }
+
     ui.add_space(15.0);
     ui.separator();
     ui.add_space(10.0);
@@ -469,6 +922,8 @@ fn render_grid_controls_panel(
     ui.heading("Tile Counts");
     ui.add_space(10.0);
 
+    let mut legend_hovered_tile = None;
+
     if let Some(grid) = device_grid {
         let mut tile_counts: std::collections::BTreeMap<String, usize> =
             std::collections::BTreeMap::new();
@@ -482,12 +937,28 @@ fn render_grid_controls_panel(
 
         let sorted_counts: Vec<_> = tile_counts.into_iter().collect();
 
+        if !state.visible_tiles.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Showing {} of {} tile types",
+                    state.visible_tiles.len(),
+                    sorted_counts.len()
+                ));
+                if ui.small_button("Show all").clicked() {
+                    state.visible_tiles.clear();
+                    state.grid_changed = true;
+                }
+            });
+        }
+
         let table = egui_extras::TableBuilder::new(ui)
             .vscroll(false)
             .striped(true)
+            .column(egui_extras::Column::auto())
             .column(egui_extras::Column::auto().at_least(100.0))
             .column(egui_extras::Column::auto().at_least(50.0))
             .header(20.0, |mut header| {
+                header.col(|_| {});
                 header.col(|ui| {
                     ui.strong("Tile");
                 });
@@ -503,6 +974,21 @@ fn render_grid_controls_panel(
                     .copied()
                     .unwrap_or(egui::Color32::TRANSPARENT);
                 body.row(30.0, |mut row| {
+                    row.col(|ui| {
+                        let mut visible = state.visible_tiles.contains(&pb_type);
+                        if ui
+                            .checkbox(&mut visible, "")
+                            .on_hover_text("Show only the checked tile types")
+                            .changed()
+                        {
+                            if visible {
+                                state.visible_tiles.insert(pb_type.clone());
+                            } else {
+                                state.visible_tiles.remove(&pb_type);
+                            }
+                            state.grid_changed = true;
+                        }
+                    });
                     row.col(|ui| {
                         let rect = ui.available_rect_before_wrap();
                         ui.painter().rect_filled(rect, 0.0, color);
@@ -513,14 +999,308 @@ fn render_grid_controls_panel(
                         ui.painter().rect_filled(rect, 0.0, color);
                         ui.label(count.to_string());
                     });
+                    if row.response().hovered() {
+                        legend_hovered_tile = Some(pb_type.clone());
+                    }
                 });
             }
         });
     }
 
+    // Preview the tile under the pointer, in the grid or the table above,
+    // falling back to the highlighted tile type.
+    let preview_tile = legend_hovered_tile
+        .as_deref()
+        .or(hovered_tile)
+        .or(state.highlighted_tile.as_deref())
+        .and_then(|name| arch_index.tile(arch, name));
+    if let Some(tile) = preview_tile {
+        ui.add_space(15.0);
+        ui.separator();
+        ui.add_space(10.0);
+        render_tile_preview(ui, tile, tile_colors);
+    }
+
+    if let Some(grid) = device_grid {
+        ui.add_space(15.0);
+        ui.separator();
+        ui.add_space(10.0);
+        render_device_area(ui, arch, arch_index, grid);
+    }
+
+    if let Some(config) = &arch.layouts.tileable_config {
+        ui.add_space(15.0);
+        ui.separator();
+        ui.add_space(10.0);
+        render_tileable_config(ui, config);
+    }
+
     grid_changed
 }
 
+/// Draws the footprint of `tile`, `width`×`height` cells with its pins placed
+/// per its pin locations, small enough to fit in the side panel.
+fn render_tile_preview(
+    ui: &mut egui::Ui,
+    tile: &Tile,
+    tile_colors: &HashMap<String, egui::Color32>,
+) {
+    let dark_mode = ui.visuals().dark_mode;
+    ui.strong(format!(
+        "{} ({}x{})",
+        tile.name.to_uppercase(),
+        tile.width,
+        tile.height
+    ));
+    ui.add_space(5.0);
+
+    let cell_size = TILE_PREVIEW_SIZE / tile.width.max(tile.height).max(1) as f32;
+    let size = egui::vec2(tile.width as f32, tile.height as f32) * cell_size;
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let color = tile_colors
+        .get(&tile.name)
+        .copied()
+        .unwrap_or(color_scheme::grid_lb_color(dark_mode));
+    let tile_renderer = build_render_tile(tile, &rect, &color, dark_mode);
+    ui.painter().extend(tile_renderer.lb_shapes);
+    ui.painter().extend(tile_renderer.pin_shapes);
+    ui.label(
+        egui::RichText::new(format!("{} pins", tile.pin_mapper.num_pins_in_tile))
+            .weak()
+            .small(),
+    );
+}
+
+/// Shows the details of the placed tile picked from the grid context menu.
+fn render_tile_info_window(
+    arch: &FPGAArch,
+    arch_index: &ArchIndex,
+    info_tile: &mut Option<(String, usize, usize)>,
+    metadata: Option<&[Metadata]>,
+    ctx: &egui::Context,
+) {
+    let Some((tile_name, col, row)) = info_tile.as_ref() else {
+        return;
+    };
+    let mut open = true;
+    egui::Window::new("Tile Info")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.strong(tile_name.to_uppercase());
+            ui.label(format!("Location: [{}, {}]", col, row));
+            let Some(tile) = arch_index.tile(arch, tile_name) else {
+                return;
+            };
+            ui.label(format!("Size: {}x{}", tile.width, tile.height));
+            let (area, is_default) = tile_area(arch, tile);
+            if is_default {
+                ui.label(format!("Area: {:.2} (grid default)", area));
+            } else {
+                ui.label(format!("Area: {:.2}", area));
+            }
+            ui.label(format!("Pins: {}", tile.pin_mapper.num_pins_in_tile));
+            ui.add_space(5.0);
+            ui.label(format!("Sub-tiles ({}):", tile.sub_tiles.len()));
+            for sub_tile in &tile.sub_tiles {
+                let sites = sub_tile
+                    .equivalent_sites
+                    .iter()
+                    .map(|site| site.pb_type.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ui.label(format!(
+                    "  {} (capacity {}): {}",
+                    sub_tile.name, sub_tile.capacity, sites
+                ));
+            }
+            if let Some(metadata) = metadata {
+                common_ui::render_metadata(ui, metadata);
+            }
+        });
+    if !open {
+        *info_tile = None;
+    }
+}
+
+/// Number of blocks one instance of `tile` holds, summed over its sub-tiles.
+pub fn tile_capacity(tile: &Tile) -> usize {
+    tile.sub_tiles
+        .iter()
+        .map(|sub_tile| sub_tile.capacity.max(0) as usize)
+        .sum()
+}
+
+/// Area of one instance of `tile`, and whether it fell back to the device's
+/// `grid_logic_tile_area`. As in VPR, the fallback is charged once for every
+/// grid location the tile covers.
+pub fn tile_area(arch: &FPGAArch, tile: &Tile) -> (f32, bool) {
+    match tile.area {
+        Some(area) => (area, false),
+        None => {
+            let num_locations = (tile.width * tile.height) as f32;
+            (arch.device.area.grid_logic_tile_area * num_locations, true)
+        }
+    }
+}
+
+/// Shows the estimated area of the whole device: every tile instance on every
+/// layer, weighted by its tile's area.
+fn render_device_area(
+    ui: &mut egui::Ui,
+    arch: &FPGAArch,
+    arch_index: &ArchIndex,
+    grid: &DeviceGrid,
+) {
+    let mut tile_counts: HashMap<&str, usize> = HashMap::new();
+    for layer in &grid.grid_layers {
+        for cell in layer.cells.iter().flatten() {
+            if let GridCell::BlockAnchor { pb_type, .. } = cell {
+                *tile_counts.entry(pb_type.as_str()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut total_area = 0.0;
+    let mut defaulted_tiles = Vec::new();
+    for (tile_name, count) in tile_counts {
+        let Some(tile) = arch_index.tile(arch, tile_name) else {
+            continue;
+        };
+        let (area, is_default) = tile_area(arch, tile);
+        total_area += area * count as f32;
+        if is_default {
+            defaulted_tiles.push(tile_name);
+        }
+    }
+    defaulted_tiles.sort();
+
+    ui.heading("Device Area");
+    ui.add_space(10.0);
+    ui.label(format!("Estimated Total: {:e}", total_area));
+    if !defaulted_tiles.is_empty() {
+        // Only some tiles may set their own area, so call out the rest.
+        ui.label(
+            egui::RichText::new(format!(
+                "Using grid_logic_tile_area ({:e}) for: {}",
+                arch.device.area.grid_logic_tile_area,
+                defaulted_tiles.join(", ")
+            ))
+            .weak(),
+        );
+    }
+}
+
+/// Lists the tileable routing options set on `<layout>`, as used by
+/// OpenFPGA-style architectures.
+fn render_tileable_config(ui: &mut egui::Ui, config: &TileableLayoutConfig) {
+    let flags = [
+        (
+            "tileable",
+            config.tileable,
+            "Build the routing from identical tiles",
+        ),
+        (
+            "through_channel",
+            config.through_channel,
+            "Allow routing channels through multi-width and multi-height blocks",
+        ),
+        (
+            "shrink_boundary",
+            config.shrink_boundary,
+            "Remove routing channels beyond the outermost blocks",
+        ),
+        (
+            "perimeter_cb",
+            config.perimeter_cb,
+            "Add connection blocks around the perimeter of the device",
+        ),
+        (
+            "opin2all_sides",
+            config.opin2all_sides,
+            "Connect output pins to the routing on every side of the block",
+        ),
+        (
+            "concat_wire",
+            config.concat_wire,
+            "Join wires that end at a switch block into longer wires",
+        ),
+        (
+            "concat_pass_wire",
+            config.concat_pass_wire,
+            "Join wires that pass through a switch block",
+        ),
+    ];
+
+    ui.heading("Tileable Routing");
+    ui.add_space(10.0);
+    egui::Grid::new("tileable_config")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (name, enabled, description) in flags {
+                ui.monospace(name).on_hover_text(description);
+                ui.label(if enabled { "✔ on" } else { "✖ off" });
+                ui.end_row();
+            }
+        });
+}
+
+/// Draws the grid cell size control. Returns true if the cell size changed.
+pub fn cell_size_control(ui: &mut egui::Ui, cell_size: &mut Option<f32>) -> bool {
+    let mut changed = false;
+    let mut fit_to_view = cell_size.is_none();
+    ui.horizontal(|ui| {
+        ui.label("Cell Size:");
+        if ui.checkbox(&mut fit_to_view, "Fit to view").changed() {
+            *cell_size = if fit_to_view {
+                None
+            } else {
+                Some(DEFAULT_CELL_SIZE)
+            };
+            changed = true;
+        }
+    });
+    if let Some(size) = cell_size {
+        changed |= ui
+            .add(egui::Slider::new(size, MIN_CELL_SIZE..=MAX_CELL_SIZE).suffix(" px"))
+            .changed();
+    }
+    changed
+}
+
+pub fn empty_cells_control(ui: &mut egui::Ui, mark_empty_cells: &mut bool) -> bool {
+    ui.checkbox(mark_empty_cells, "Mark EMPTY cells")
+        .on_hover_text(
+            "Hatch cells that the layout explicitly fills with EMPTY, to tell them apart \
+             from cells where nothing was placed.",
+        )
+        .changed()
+}
+
+pub fn cell_borders_control(ui: &mut egui::Ui, show_cell_borders: &mut bool) -> bool {
+    ui.checkbox(show_cell_borders, "Show cell borders")
+        .on_hover_text(
+            "Outline every cell, and tiles that span several cells more heavily. \
+             Turn off to draw very large grids faster.",
+        )
+        .changed()
+}
+
+/// Draws the y origin control. Returns true if the origin changed.
+pub fn y_origin_control(ui: &mut egui::Ui, y_origin: &mut GridYOrigin) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Y origin:");
+        for origin in GridYOrigin::ALL {
+            changed |= ui
+                .selectable_value(y_origin, origin, origin.label())
+                .changed();
+        }
+    });
+    changed
+}
+
 pub fn get_layout_name(arch: &FPGAArch, index: usize) -> String {
     if let Some(layout) = arch.layouts.layout_list.get(index) {
         match &layout {
@@ -543,3 +1323,255 @@ fn update_grid_width_from_height(state: &mut GridState) {
         .round()
         .max(1.0) as usize;
 }
+
+/// Sets the grid width, moving the height with it when the aspect ratio is
+/// locked and updating the ratio when it is not.
+fn set_grid_width(state: &mut GridState, width: usize) {
+    state.grid_width = width;
+    if state.lock_aspect_ratio {
+        update_grid_height_from_width(state);
+    } else {
+        state.aspect_ratio = state.grid_width as f32 / state.grid_height as f32;
+    }
+}
+
+/// Sets the grid height, moving the width with it when the aspect ratio is
+/// locked and updating the ratio when it is not.
+fn set_grid_height(state: &mut GridState, height: usize) {
+    state.grid_height = height;
+    if state.lock_aspect_ratio {
+        update_grid_width_from_height(state);
+    } else {
+        state.aspect_ratio = state.grid_width as f32 / state.grid_height as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4_with;
+
+    #[test]
+    fn throttles_rebuilds_while_dragging() {
+        // A one second drag at 60 frames per second that changes the
+        // dimensions on every frame.
+        let frame_time = 1.0 / 60.0;
+        let mut throttle = GridRebuildThrottle::default();
+        let mut rebuilds = 0;
+        for frame in 0..60 {
+            throttle.request();
+            if throttle.poll(frame as f64 * frame_time, true) {
+                rebuilds += 1;
+            }
+        }
+        assert!(rebuilds <= (1.0 / DRAG_REBUILD_INTERVAL) as usize + 1);
+        assert!(rebuilds < 60);
+
+        // Releasing the slider builds the final dimensions right away.
+        assert!(throttle.is_pending());
+        assert!(throttle.poll(1.0, false));
+        assert!(!throttle.poll(1.0 + frame_time, false));
+
+        // Changes made without a drag are never held back.
+        throttle.request();
+        assert!(throttle.poll(1.0 + 2.0 * frame_time, false));
+    }
+
+    #[test]
+    fn y_origin_maps_rows_both_ways() {
+        let grid_height = 8;
+        for origin in GridYOrigin::ALL {
+            for row in 0..grid_height {
+                let screen_row = origin.screen_row(grid_height, row, 1);
+                assert_eq!(origin.grid_row(grid_height, screen_row), row);
+            }
+        }
+
+        // A 1x3 tile anchored at row 2 spans rows 2..5.
+        assert_eq!(GridYOrigin::Bottom.screen_row(grid_height, 2, 3), 3);
+        assert_eq!(GridYOrigin::Top.screen_row(grid_height, 2, 3), 2);
+        assert_eq!(GridYOrigin::Bottom.screen_y(8.0, 2.5), 5.5);
+        assert_eq!(GridYOrigin::Top.screen_y(8.0, 2.5), 2.5);
+    }
+
+    #[test]
+    fn recommends_auto_layout_size_from_placements() {
+        let size_of = |layout_xml: &str| {
+            let arch = k4_n4_with(&[(
+                r#"<fill type="clb" priority="10"/>"#,
+                &format!(r#"<fill type="clb" priority="10"/>{layout_xml}"#),
+            )]);
+            let Some(fpga_arch_parser::Layout::AutoLayout(auto_layout)) =
+                arch.layouts.layout_list.first()
+            else {
+                panic!("k4_N4_90nm.xml has an auto layout");
+            };
+            DeviceGrid::recommended_auto_layout_size(&arch, auto_layout)
+        };
+
+        // Perimeter and fill alone keep the default size.
+        assert_eq!(size_of(""), (10, 10));
+        // A clb placed at (20, 3) needs its own column and the perimeter.
+        assert_eq!(
+            size_of(r#"<single type="clb" x="20" y="3" priority="20"/>"#),
+            (22, 22)
+        );
+        // Columns that repeat every 15 show at least twice.
+        assert_eq!(
+            size_of(r#"<col type="clb" startx="4" repeatx="15" starty="1" priority="20"/>"#),
+            (21, 21)
+        );
+        // Placements relative to the device size fit any size.
+        assert_eq!(
+            size_of(r#"<single type="clb" x="W-2" y="H/2" priority="20"/>"#),
+            (10, 10)
+        );
+    }
+
+    #[test]
+    fn records_grid_location_metadata() {
+        let arch = k4_n4_with(&[(
+            r#"<fill type="clb" priority="10"/>"#,
+            r#"<fill type="clb" priority="10"/>
+                <single type="clb" x="3" y="4" priority="20">
+                  <metadata><meta name="fasm_prefix">CLB_X3Y4</meta></metadata>
+                </single>
+                <single type="clb" x="5" y="5" priority="20">
+                  <metadata><meta name="fasm_prefix">CLB_X5Y5</meta></metadata>
+                </single>
+                <single type="clb" x="5" y="5" priority="30"/>"#,
+        )]);
+        let grid = DeviceGrid::from_auto_layout_with_dimensions(&arch, 10, 10);
+        let metadata = grid.metadata(&arch, 0, 4, 3).expect("(3, 4) has metadata");
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[0].name, "fasm_prefix");
+        assert_eq!(metadata[0].value, "CLB_X3Y4");
+        // Tiles placed by the fill carry none, and neither do tiles that
+        // replaced one that did.
+        assert!(grid.metadata(&arch, 0, 2, 2).is_none());
+        assert!(grid.metadata(&arch, 0, 5, 5).is_none());
+    }
+
+    #[test]
+    fn places_tiles_with_every_expression_the_parser_accepts() {
+        let arch = k4_n4_with(&[(
+            r#"<fill type="clb" priority="10"/>"#,
+            r#"<fill type="clb" priority="10"/>
+                <single type="io" x="max(W - 8, 1)" y="min(H, 3) % 2" priority="20"/>
+                <single type="io" x="(W > 5) * 4" y="gcd(W, 6) + (h == 1)" priority="20"/>"#,
+        )]);
+        let grid = DeviceGrid::from_auto_layout_with_dimensions(&arch, 10, 10);
+        for (x, y) in [(2, 1), (4, 3)] {
+            assert!(
+                matches!(grid.get(y, x, 0), Some(GridCell::BlockAnchor { pb_type, .. }) if pb_type == "io"),
+                "no io at ({x}, {y})"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_equal_priority_placement_conflicts() {
+        let arch = k4_n4_with(&[
+            (
+                r#"<auto_layout aspect_ratio="1.000000">"#,
+                r#"<fixed_layout name="fixed" width="8" height="8">
+                <single type="io" x="3" y="4" priority="20"/>
+                <single type="clb" x="3" y="4" priority="20"/>
+                <single type="EMPTY" x="5" y="5" priority="30"/>
+                <single type="io" x="5" y="5" priority="20"/>"#,
+            ),
+            ("</auto_layout>", "</fixed_layout>"),
+        ]);
+        let grid = DeviceGrid::from_fixed_layout(&arch, 0);
+
+        // Only the tie at (3, 4) is ambiguous: the perimeter, corners, and
+        // fill all have distinct priorities, and the io at (5, 5) loses.
+        assert_eq!(grid.placement_conflicts.len(), 1);
+        let conflict = &grid.placement_conflicts[0];
+        assert_eq!((conflict.x, conflict.y, conflict.priority), (3, 4, 20));
+        assert_eq!(conflict.replaced, "io");
+        assert_eq!(conflict.placed, "clb");
+        assert_eq!(
+            conflict.to_string(),
+            "Layout 'fixed': 'io' and 'clb' are both placed at (3, 4) with priority 20; \
+             the later 'clb' was kept"
+        );
+
+        // A tile taller than one cell is reported at the cell that clashes,
+        // not at its anchor.
+        let arch = k4_n4_with(&[
+            (r#"<tile name="clb">"#, r#"<tile name="clb" height="2">"#),
+            (
+                r#"<auto_layout aspect_ratio="1.000000">"#,
+                r#"<fixed_layout name="fixed" width="8" height="8">
+                <single type="io" x="3" y="4" priority="20"/>
+                <single type="clb" x="3" y="3" priority="20"/>"#,
+            ),
+            ("</auto_layout>", "</fixed_layout>"),
+        ]);
+        let grid = DeviceGrid::from_fixed_layout(&arch, 0);
+        assert_eq!(grid.placement_conflicts.len(), 1);
+        let conflict = &grid.placement_conflicts[0];
+        assert_eq!((conflict.x, conflict.y), (3, 4));
+
+        // Auto layouts are not checked.
+        let arch = k4_n4_with(&[(
+            r#"<auto_layout aspect_ratio="1.000000">"#,
+            r#"<auto_layout aspect_ratio="1.000000">
+                <single type="io" x="3" y="4" priority="20"/>
+                <single type="clb" x="3" y="4" priority="20"/>"#,
+        )]);
+        let grid = DeviceGrid::from_auto_layout_with_dimensions(&arch, 8, 8);
+        assert!(grid.placement_conflicts.is_empty());
+    }
+
+    #[test]
+    fn aspect_ratio_lock_links_width_and_height() {
+        let mut state = GridState {
+            aspect_ratio: 2.0,
+            ..GridState::default()
+        };
+        set_grid_width(&mut state, 30);
+        assert_eq!((state.grid_width, state.grid_height), (30, 15));
+        set_grid_height(&mut state, 20);
+        assert_eq!((state.grid_width, state.grid_height), (40, 20));
+
+        state.lock_aspect_ratio = false;
+        set_grid_width(&mut state, 10);
+        assert_eq!((state.grid_width, state.grid_height), (10, 20));
+        assert_eq!(state.aspect_ratio, 0.5);
+        set_grid_height(&mut state, 40);
+        assert_eq!((state.grid_width, state.grid_height), (10, 40));
+        assert_eq!(state.aspect_ratio, 0.25);
+    }
+
+    #[test]
+    fn dims_tiles_left_out_of_the_filter() {
+        let mut state = GridState::default();
+        assert!(!state.is_tile_dimmed("clb"));
+
+        state.visible_tiles.insert("memory".to_string());
+        state.visible_tiles.insert("mult_36".to_string());
+        assert!(state.is_tile_dimmed("clb"));
+        assert!(!state.is_tile_dimmed("memory"));
+        assert!(!state.is_tile_dimmed("mult_36"));
+
+        state.visible_tiles.clear();
+        assert!(!state.is_tile_dimmed("clb"));
+    }
+
+    #[test]
+    fn parses_cell_coordinates_within_the_grid() {
+        assert_eq!(parse_cell_coordinate("3, 5", 10, 8), Ok((3, 5)));
+        assert_eq!(parse_cell_coordinate(" (0,7) ", 10, 8), Ok((0, 7)));
+        assert_eq!(parse_cell_coordinate("9 0", 10, 8), Ok((9, 0)));
+
+        assert!(parse_cell_coordinate("3", 10, 8).is_err());
+        assert!(parse_cell_coordinate("3, -1", 10, 8).is_err());
+        assert_eq!(
+            parse_cell_coordinate("10, 2", 10, 8),
+            Err("(10, 2) is outside the 10x8 grid".to_string())
+        );
+        assert!(parse_cell_coordinate("2, 8", 10, 8).is_err());
+    }
+}
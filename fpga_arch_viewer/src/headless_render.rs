@@ -0,0 +1,629 @@
+//! Headless Rendering
+//!
+//! Renders the grid and the complex block of every tile to SVG files without
+//! opening a window. Used by the `--render` command line mode to generate
+//! documentation images in batch.
+//!
+//! The views are drawn with egui exactly as they are in the GUI, but instead
+//! of being rasterized, the resulting shapes are written out as SVG elements.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use egui::epaint::{ClippedShape, ColorMode, PathStroke, Shape};
+use fpga_arch_parser::prelude::*;
+
+use crate::arch_index::ArchIndex;
+use crate::color_scheme::Palette;
+use crate::grid_view::GridView;
+use crate::intra_tile::{self, IntraTileState};
+
+/// Pixel size of a grid cell in the rendered grid image. Large enough for the
+/// tile names to be drawn.
+const GRID_CELL_SIZE: f32 = 60.0;
+
+/// Margin, in pixels, kept around a block exported on its own.
+const BLOCK_EXPORT_MARGIN: f32 = 10.0;
+
+/// Renders the grid of the first layout to `grid.svg`, and the complex block
+/// of each tile to `tile_<name>.svg`, inside `out_dir`, in the colors of
+/// `palette`. Coordinates are rounded to multiples of `snap_step`, if given.
+/// Returns the paths of the files that were written.
+pub fn render_arch_to_dir(
+    arch: &FPGAArch,
+    out_dir: &Path,
+    snap_step: Option<f32>,
+    palette: Palette,
+) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(out_dir)?;
+    let mut written = Vec::new();
+
+    let grid_path = out_dir.join("grid.svg");
+    std::fs::write(&grid_path, render_grid_svg(arch, snap_step, palette))?;
+    written.push(grid_path);
+
+    for tile in &arch.tiles {
+        let Some(root_pb) = tile_root_pb(arch, tile) else {
+            log::warn!("Tile '{}' has no complex block to render", tile.name);
+            continue;
+        };
+
+        let mut state = IntraTileState::default();
+        intra_tile::expand_all_blocks(&mut state, root_pb, &root_pb.name);
+        let size = intra_tile::canvas_size(root_pb, &mut state);
+        let shapes = run_offscreen(size, |ui| {
+            intra_tile::render_intra_tile_canvas(ui, root_pb, &mut state, true, palette, false);
+        });
+
+        let tile_path = out_dir.join(format!("tile_{}.svg", tile.name));
+        std::fs::write(&tile_path, shapes_to_svg(size, &shapes, snap_step))?;
+        written.push(tile_path);
+    }
+
+    Ok(written)
+}
+
+/// Renders the block at `instance_path` and what is drawn inside it to SVG,
+/// with the expansion and modes of `view`, in the colors of `palette`. The image is bounded by the block's
+/// rect, so the surrounding blocks are left out. Coordinates are rounded to
+/// multiples of `snap_step`, if given. Returns None if the block is not
+/// drawn, e.g. because its parent is collapsed.
+pub fn render_block_svg(
+    root_pb: &PBType,
+    view: &IntraTileState,
+    instance_path: &str,
+    draw_interconnects: bool,
+    palette: Palette,
+    snap_step: Option<f32>,
+) -> Option<String> {
+    // Draw at the default zoom, whatever the view is zoomed to.
+    let mut state = view.drawing_settings();
+    state.selected_modes = view.selected_modes.clone();
+    state.expanded_blocks = view.expanded_blocks.clone();
+    state.expanded_arrays = view.expanded_arrays.clone();
+    let size = intra_tile::canvas_size(root_pb, &mut state);
+    let shapes = run_offscreen(size, |ui| {
+        intra_tile::render_intra_tile_canvas(
+            ui,
+            root_pb,
+            &mut state,
+            draw_interconnects,
+            palette,
+            false,
+        );
+    });
+    let rect = state.instance_rect(instance_path)?;
+    // Leave room for the pins, which sit on the edge of the block.
+    Some(shapes_to_svg_in(
+        rect.expand(BLOCK_EXPORT_MARGIN),
+        &shapes,
+        snap_step,
+    ))
+}
+
+/// Lists the measured size of the complex block of `tile_name`, fully
+/// expanded, as one `<instance path> <width>x<height>` line per block, sorted
+/// by instance path. Used by the hidden `--layout-metrics` mode to catch
+/// layout regressions.
+pub fn layout_metrics_report(arch: &FPGAArch, tile_name: &str) -> Result<String, String> {
+    let tile = arch
+        .tiles
+        .iter()
+        .find(|tile| tile.name == tile_name)
+        .ok_or_else(|| format!("No tile named '{tile_name}'"))?;
+    let root_pb = tile_root_pb(arch, tile)
+        .ok_or_else(|| format!("Tile '{tile_name}' has no complex block"))?;
+
+    let mut state = IntraTileState::default();
+    intra_tile::expand_all_blocks(&mut state, root_pb, &root_pb.name);
+    let mut report = String::new();
+    for (instance_path, size) in intra_tile::layout_metrics(root_pb, &mut state) {
+        let _ = writeln!(report, "{instance_path} {:.1}x{:.1}", size.x, size.y);
+    }
+    Ok(report)
+}
+
+/// A tile is shown by the complex block of its first equivalent site.
+fn tile_root_pb<'a>(arch: &'a FPGAArch, tile: &Tile) -> Option<&'a PBType> {
+    tile.sub_tiles
+        .iter()
+        .flat_map(|sub_tile| sub_tile.equivalent_sites.iter())
+        .find_map(|site| {
+            arch.complex_block_list
+                .iter()
+                .find(|pb| pb.name == site.pb_type)
+        })
+}
+
+fn render_grid_svg(arch: &FPGAArch, snap_step: Option<f32>, palette: Palette) -> String {
+    let mut grid_view = GridView::default();
+    grid_view.on_architecture_load(arch);
+    grid_view.update_tile_colors(palette, false);
+    let Some(grid) = &grid_view.device_grid else {
+        return shapes_to_svg(egui::Vec2::ZERO, &[], snap_step);
+    };
+
+    let size = egui::vec2(
+        grid.width as f32 * GRID_CELL_SIZE,
+        grid.height as f32 * GRID_CELL_SIZE,
+    );
+    let mut grid_state = grid_view.grid_state.clone();
+    grid_state.cell_size = Some(GRID_CELL_SIZE);
+    let arch_index = ArchIndex::new(arch);
+    let shapes = run_offscreen(size, |ui| {
+        grid_view.grid_renderer.prerender_grid(
+            grid,
+            &grid_view.tile_colors,
+            &grid_view.tile_capacities,
+            &grid_state,
+            false,
+            ui,
+        );
+        let _ = grid_view.grid_renderer.render_grid(
+            ui,
+            grid,
+            arch,
+            &arch_index,
+            &grid_state,
+            None,
+            false,
+        );
+    });
+    shapes_to_svg(size, &shapes, snap_step)
+}
+
+/// Runs a single egui frame of the given size without a window, and returns
+/// the shapes that were painted.
+fn run_offscreen(size: egui::Vec2, mut draw: impl FnMut(&mut egui::Ui)) -> Vec<ClippedShape> {
+    let ctx = egui::Context::default();
+    ctx.set_visuals(egui::Visuals::light());
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size)),
+        ..Default::default()
+    };
+    let output = ctx.run(input, |ctx| {
+        let frame = egui::Frame::NONE.fill(ctx.style().visuals.panel_fill);
+        egui::CentralPanel::default()
+            .frame(frame)
+            .show(ctx, |ui| draw(ui));
+    });
+    output.shapes
+}
+
+/// Writes the painted shapes out as an SVG document of the given size.
+fn shapes_to_svg(size: egui::Vec2, shapes: &[ClippedShape], snap_step: Option<f32>) -> String {
+    shapes_to_svg_in(
+        egui::Rect::from_min_size(egui::Pos2::ZERO, size),
+        shapes,
+        snap_step,
+    )
+}
+
+/// Writes the painted shapes that fall within `bounds` out as an SVG document
+/// showing just that part of the frame.
+///
+/// The layout puts blocks and wires at fractional pixels, which blurs their
+/// edges once the image is rasterized. With a `snap_step`, every coordinate,
+/// including the view box, is rounded to a multiple of it.
+fn shapes_to_svg_in(bounds: egui::Rect, shapes: &[ClippedShape], snap_step: Option<f32>) -> String {
+    let snap = Snap(snap_step);
+    let view_box = snap.rect(bounds);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"{x} {y} {w} {h}\">\n",
+        x = view_box.min.x,
+        y = view_box.min.y,
+        w = view_box.width(),
+        h = view_box.height()
+    );
+    for clipped in shapes {
+        // Anything scrolled out of view or outside the bounds is not part of
+        // the image.
+        let shape_rect = clipped.shape.visual_bounding_rect();
+        if !clipped.clip_rect.intersects(shape_rect) || !bounds.intersects(shape_rect) {
+            continue;
+        }
+        write_shape(&mut svg, &clipped.shape, snap);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Rounds coordinates to multiples of a grid step, or leaves them as they
+/// are without one.
+#[derive(Clone, Copy)]
+struct Snap(Option<f32>);
+
+impl Snap {
+    fn coord(self, value: f32) -> f32 {
+        match self.0 {
+            Some(step) => (value / step).round() * step,
+            None => value,
+        }
+    }
+
+    fn pos(self, pos: egui::Pos2) -> egui::Pos2 {
+        egui::pos2(self.coord(pos.x), self.coord(pos.y))
+    }
+
+    fn rect(self, rect: egui::Rect) -> egui::Rect {
+        egui::Rect::from_min_max(self.pos(rect.min), self.pos(rect.max))
+    }
+}
+
+fn write_shape(svg: &mut String, shape: &Shape, snap: Snap) {
+    match shape {
+        Shape::Noop | Shape::Callback(_) => {}
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                write_shape(svg, shape, snap);
+            }
+        }
+        Shape::Circle(circle) => {
+            let center = snap.pos(circle.center);
+            let _ = writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {} {}/>",
+                center.x,
+                center.y,
+                circle.radius,
+                fill_attrs(circle.fill),
+                stroke_attrs(circle.stroke.width, circle.stroke.color)
+            );
+        }
+        Shape::Ellipse(ellipse) => {
+            let center = snap.pos(ellipse.center);
+            let _ = writeln!(
+                svg,
+                "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {} {}/>",
+                center.x,
+                center.y,
+                ellipse.radius.x,
+                ellipse.radius.y,
+                fill_attrs(ellipse.fill),
+                stroke_attrs(ellipse.stroke.width, ellipse.stroke.color)
+            );
+        }
+        Shape::LineSegment { points, stroke } => {
+            let points = points.map(|p| snap.pos(p));
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+                points[0].x,
+                points[0].y,
+                points[1].x,
+                points[1].y,
+                stroke_attrs(stroke.width, stroke.color)
+            );
+        }
+        Shape::Path(path) => {
+            let tag = if path.closed { "polygon" } else { "polyline" };
+            let fill = if path.closed {
+                fill_attrs(path.fill)
+            } else {
+                fill_attrs(egui::Color32::TRANSPARENT)
+            };
+            let _ = writeln!(
+                svg,
+                "<{} points=\"{}\" {} {}/>",
+                tag,
+                points_attr(&path.points, snap),
+                fill,
+                path_stroke_attrs(&path.stroke)
+            );
+        }
+        Shape::Rect(rect_shape) => {
+            // SVG strokes are centered on the outline, so move the outline to
+            // where egui would draw the stroke.
+            let rect = snap.rect(rect_shape.rect);
+            let half_width = rect_shape.stroke.width / 2.0;
+            let stroke_rect = match rect_shape.stroke_kind {
+                egui::StrokeKind::Inside => rect.shrink(half_width),
+                egui::StrokeKind::Middle => rect,
+                egui::StrokeKind::Outside => rect.expand(half_width),
+            };
+            let rounding = rect_shape.corner_radius.nw as f32;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {}/>",
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                rect.height(),
+                rounding,
+                fill_attrs(rect_shape.fill)
+            );
+            if rect_shape.stroke.width > 0.0 {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"none\" {}/>",
+                    stroke_rect.min.x,
+                    stroke_rect.min.y,
+                    stroke_rect.width(),
+                    stroke_rect.height(),
+                    rounding,
+                    stroke_attrs(rect_shape.stroke.width, rect_shape.stroke.color)
+                );
+            }
+        }
+        Shape::Text(text_shape) => {
+            let Some(section) = text_shape.galley.job.sections.first() else {
+                return;
+            };
+            let color = text_shape.override_text_color.unwrap_or(
+                if section.format.color == egui::Color32::PLACEHOLDER {
+                    text_shape.fallback_color
+                } else {
+                    section.format.color
+                },
+            );
+            let pos = snap.pos(text_shape.pos);
+            let transform = if text_shape.angle != 0.0 {
+                format!(
+                    " transform=\"rotate({} {} {})\"",
+                    text_shape.angle.to_degrees(),
+                    pos.x,
+                    pos.y
+                )
+            } else {
+                String::new()
+            };
+            for placed_row in &text_shape.galley.rows {
+                let Some(first_glyph) = placed_row.row.glyphs.first() else {
+                    continue;
+                };
+                let text: String = placed_row.row.glyphs.iter().map(|g| g.chr).collect();
+                let baseline =
+                    snap.pos(text_shape.pos + placed_row.pos.to_vec2() + first_glyph.pos.to_vec2());
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" {}{}>{}</text>",
+                    baseline.x,
+                    baseline.y,
+                    section.format.font_id.size,
+                    fill_attrs(color),
+                    transform,
+                    escape_xml(&text)
+                );
+            }
+        }
+        Shape::Mesh(mesh) => {
+            for triangle in mesh.indices.chunks_exact(3) {
+                let points: Vec<egui::Pos2> = triangle
+                    .iter()
+                    .map(|&i| mesh.vertices[i as usize].pos)
+                    .collect();
+                let color = mesh.vertices[triangle[0] as usize].color;
+                let _ = writeln!(
+                    svg,
+                    "<polygon points=\"{}\" {}/>",
+                    points_attr(&points, snap),
+                    fill_attrs(color)
+                );
+            }
+        }
+        Shape::QuadraticBezier(bezier) => {
+            let [p0, p1, p2] = bezier.points.map(|p| snap.pos(p));
+            let _ = writeln!(
+                svg,
+                "<path d=\"M {} {} Q {} {} {} {}{}\" {} {}/>",
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                p2.x,
+                p2.y,
+                if bezier.closed { " Z" } else { "" },
+                fill_attrs(bezier.fill),
+                path_stroke_attrs(&bezier.stroke)
+            );
+        }
+        Shape::CubicBezier(bezier) => {
+            let [p0, p1, p2, p3] = bezier.points.map(|p| snap.pos(p));
+            let _ = writeln!(
+                svg,
+                "<path d=\"M {} {} C {} {} {} {} {} {}{}\" {} {}/>",
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                p2.x,
+                p2.y,
+                p3.x,
+                p3.y,
+                if bezier.closed { " Z" } else { "" },
+                fill_attrs(bezier.fill),
+                path_stroke_attrs(&bezier.stroke)
+            );
+        }
+    }
+}
+
+fn points_attr(points: &[egui::Pos2], snap: Snap) -> String {
+    points
+        .iter()
+        .map(|&p| snap.pos(p))
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn color_attr(color: egui::Color32) -> (String, f32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    (format!("rgb({r},{g},{b})"), a as f32 / 255.0)
+}
+
+fn fill_attrs(color: egui::Color32) -> String {
+    if color.a() == 0 {
+        return "fill=\"none\"".to_string();
+    }
+    let (rgb, opacity) = color_attr(color);
+    format!("fill=\"{rgb}\" fill-opacity=\"{opacity}\"")
+}
+
+fn stroke_attrs(width: f32, color: egui::Color32) -> String {
+    if width <= 0.0 || color.a() == 0 {
+        return String::new();
+    }
+    let (rgb, opacity) = color_attr(color);
+    format!("stroke=\"{rgb}\" stroke-opacity=\"{opacity}\" stroke-width=\"{width}\"")
+}
+
+fn path_stroke_attrs(stroke: &PathStroke) -> String {
+    match &stroke.color {
+        ColorMode::Solid(color) => stroke_attrs(stroke.width, *color),
+        // Gradient strokes are not used by the views; skip them.
+        ColorMode::UV(_) => String::new(),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{k4_n4_clb, k4_n4_with};
+
+    #[test]
+    fn exports_a_single_block_within_its_rect() {
+        let clb = &k4_n4_clb();
+
+        let mut view = IntraTileState::default();
+        intra_tile::expand_all_blocks(&mut view, clb, "clb");
+        let full = render_block_svg(clb, &view, "clb", true, Palette::Standard, None)
+            .expect("the root is drawn");
+        let fle = render_block_svg(clb, &view, "clb.fle[0]", true, Palette::Standard, None)
+            .expect("fle[0] is drawn");
+        assert!(fle.starts_with("<svg "));
+        // Only the fle and what is inside it is drawn.
+        assert!(fle.len() < full.len() / 2);
+
+        // Blocks inside a collapsed block are not drawn.
+        view.expanded_blocks.remove("clb");
+        assert!(
+            render_block_svg(clb, &view, "clb.fle[0]", true, Palette::Standard, None).is_none()
+        );
+    }
+
+    /// The numbers in the coordinate attributes of an SVG document. Stroke
+    /// outlines are left out, as they sit half a stroke width inside the rect
+    /// they outline.
+    fn coordinates(svg: &str) -> Vec<f32> {
+        let svg: String = svg
+            .lines()
+            .filter(|line| !(line.starts_with("<rect") && line.contains("fill=\"none\" stroke")))
+            .collect();
+        let mut values = Vec::new();
+        for attr in [
+            " x=\"",
+            " y=\"",
+            " x1=\"",
+            " y1=\"",
+            " x2=\"",
+            " y2=\"",
+            " cx=\"",
+            " cy=\"",
+            " width=\"",
+            " height=\"",
+        ] {
+            for part in svg.split(attr).skip(1) {
+                let value = &part[..part.find('"').unwrap()];
+                values.push(value.parse().unwrap());
+            }
+        }
+        for part in svg.split(" points=\"").skip(1) {
+            let points = &part[..part.find('"').unwrap()];
+            for point in points.split([' ', ',']) {
+                values.push(point.parse().unwrap());
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn draws_cell_borders_only_when_enabled() {
+        let arch = k4_n4_with(&[(
+            r#"<tile name="clb">"#,
+            r#"<tile name="clb" width="2" height="2">"#,
+        )]);
+
+        let mut grid_view = GridView::default();
+        grid_view.on_architecture_load(&arch);
+        grid_view.update_tile_colors(Palette::Standard, false);
+        let grid = grid_view
+            .device_grid
+            .as_ref()
+            .expect("the layout has a grid");
+        let arch_index = ArchIndex::new(&arch);
+        // (outlines, heavy outlines, lines between cells) drawn in the grid.
+        let mut count_borders = |show_cell_borders: bool| {
+            let mut grid_state = grid_view.grid_state.clone();
+            grid_state.cell_size = Some(GRID_CELL_SIZE);
+            grid_state.show_cell_borders = show_cell_borders;
+            let size = egui::vec2(
+                grid.width as f32 * GRID_CELL_SIZE,
+                grid.height as f32 * GRID_CELL_SIZE,
+            );
+            let shapes = run_offscreen(size, |ui| {
+                grid_view.grid_renderer.prerender_grid(
+                    grid,
+                    &grid_view.tile_colors,
+                    &grid_view.tile_capacities,
+                    &grid_state,
+                    false,
+                    ui,
+                );
+                let _ = grid_view.grid_renderer.render_grid(
+                    ui,
+                    grid,
+                    &arch,
+                    &arch_index,
+                    &grid_state,
+                    None,
+                    false,
+                );
+            });
+            let mut counts = (0, 0, 0);
+            for clipped in &shapes {
+                match &clipped.shape {
+                    egui::Shape::Rect(rect) if rect.stroke.width >= 2.0 => counts.1 += 1,
+                    egui::Shape::Rect(rect) if rect.stroke.width > 0.0 => counts.0 += 1,
+                    egui::Shape::LineSegment { .. } => counts.2 += 1,
+                    _ => {}
+                }
+            }
+            counts
+        };
+
+        let (outlines, heavy_outlines, cell_lines) = count_borders(true);
+        assert!(outlines > 0);
+        assert!(heavy_outlines > 0);
+        // Each 2x2 clb is split by one vertical and one horizontal line.
+        assert_eq!(cell_lines, 2 * heavy_outlines);
+        assert_eq!(count_borders(false), (0, 0, 0));
+    }
+
+    #[test]
+    fn snaps_exported_coordinates_to_the_grid() {
+        let clb = &k4_n4_clb();
+        let mut view = IntraTileState::default();
+        intra_tile::expand_all_blocks(&mut view, clb, "clb");
+
+        // The layout puts some of the blocks and wires at fractional pixels.
+        let exact =
+            render_block_svg(clb, &view, "clb.fle[1]", true, Palette::Standard, None).unwrap();
+        assert!(coordinates(&exact).iter().any(|v| v.fract() != 0.0));
+
+        let snapped =
+            render_block_svg(clb, &view, "clb.fle[1]", true, Palette::Standard, Some(1.0)).unwrap();
+        assert!(coordinates(&snapped).iter().all(|v| v.fract() == 0.0));
+
+        let coarse =
+            render_block_svg(clb, &view, "clb.fle[1]", true, Palette::Standard, Some(5.0)).unwrap();
+        assert!(coordinates(&coarse).iter().all(|v| v % 5.0 == 0.0));
+    }
+}
@@ -4,10 +4,12 @@
 //! (LUT, FlipFlop, Memory, Generic, BLIF) and their ports.
 
 use eframe::egui;
-use fpga_arch_parser::{PBType, Port};
+use fpga_arch_parser::prelude::*;
 use std::collections::HashMap;
+use std::ops::Range;
 
-use super::color_scheme;
+use super::block_style::{self, HeaderClass};
+use super::color_scheme::{self, Palette};
 use super::intra_tile::IntraTileState;
 
 // Constants
@@ -15,6 +17,11 @@ const HEADER_HEIGHT: f32 = 35.0;
 const PORT_LENGTH: f32 = 15.0;
 const MIN_PIN_SPACING: f32 = 25.0;
 const PIN_SQUARE_SIZE: f32 = 6.0;
+const EQUIVALENCE_BRACKET_GAP: f32 = 4.0;
+const EQUIVALENCE_BRACKET_TICK: f32 = 4.0;
+const PIN_INDEX_FONT_SIZE: f32 = 9.0;
+/// Pin index labels smaller than this, in points, are not drawn.
+const MIN_PIN_INDEX_FONT_SIZE: f32 = 5.0;
 
 //-----------------------------------------------------------
 // Draw Pin
@@ -57,14 +64,24 @@ fn draw_pin(
     });
 }
 
-#[allow(dead_code)]
+#[derive(Debug, PartialEq)]
 enum PinSide {
     Left,
     Right,
-    Top,
     Bottom,
 }
 
+/// The edge of a block a port's pins are drawn along: inputs on the left,
+/// outputs on the right, and ports declared with `<clock>` along the bottom,
+/// apart from the data pins, whatever they are named.
+fn pin_side(port: &Port) -> PinSide {
+    match port {
+        Port::Input(_) => PinSide::Left,
+        Port::Output(_) => PinSide::Right,
+        Port::Clock(_) => PinSide::Bottom,
+    }
+}
+
 /// Draws pins along a side of the rectangle.
 fn draw_pins_on_side(
     pins: &[PinInfo],
@@ -74,6 +91,7 @@ fn draw_pins_on_side(
     port_map: &mut HashMap<String, egui::Pos2>,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    dark_mode: bool,
 ) {
     if pins.is_empty() {
         return;
@@ -98,21 +116,6 @@ fn draw_pins_on_side(
             };
             (spacing, (x_pos, start_y), color_scheme::PIN_COLOR)
         }
-        PinSide::Top => {
-            let min_required_width = (total_pins + 1.0) * (MIN_PIN_SPACING * zoom);
-            let spacing = if rect.width() >= min_required_width {
-                rect.width() / (total_pins + 1.0)
-            } else {
-                MIN_PIN_SPACING * zoom
-            };
-            let total_pin_width = spacing * (total_pins - 1.0);
-            let start_x = rect.min.x + (rect.width() - total_pin_width) / 2.0;
-            (
-                spacing,
-                (start_x, rect.min.y),
-                color_scheme::CLOCK_PIN_COLOR,
-            )
-        }
         PinSide::Bottom => {
             let min_required_width = (total_pins + 1.0) * (MIN_PIN_SPACING * zoom);
             let spacing = if rect.width() >= min_required_width {
@@ -130,6 +133,7 @@ fn draw_pins_on_side(
         }
     };
 
+    let mut port_positions: Vec<egui::Pos2> = Vec::with_capacity(pins.len());
     for (i, pin) in pins.iter().enumerate() {
         let (start, end, port_pos) = match side {
             PinSide::Left => {
@@ -146,13 +150,6 @@ fn draw_pins_on_side(
                 let end = egui::pos2(x_pos + PORT_LENGTH * zoom, y_pos);
                 (start, end, end)
             }
-            PinSide::Top => {
-                let (start_x, _) = start_pos;
-                let x_pos = start_x + spacing * i as f32;
-                let start = egui::pos2(x_pos, rect.min.y);
-                let end = egui::pos2(x_pos, rect.min.y - PORT_LENGTH * zoom);
-                (start, end, end)
-            }
             PinSide::Bottom => {
                 let (start_x, _) = start_pos;
                 let x_pos = start_x + spacing * i as f32;
@@ -168,11 +165,14 @@ fn draw_pins_on_side(
             .any(|p| p.distance(port_pos) < 1.0);
 
         let stroke_color = if is_highlighted {
-            color_scheme::HIGHLIGHT_COLOR
+            state.wire_style.highlight_color
+        } else if pin.global {
+            color_scheme::GLOBAL_PIN_COLOR
         } else {
             default_color
         };
 
+        port_positions.push(port_pos);
         let pin_name = format!("{}[{}]", pin.name, pin.index);
         draw_pin(
             painter,
@@ -187,11 +187,175 @@ fn draw_pins_on_side(
             is_highlighted,
         );
     }
+
+    // Bracket each run of logically-equivalent pins of the same port.
+    let runs = port_runs(pins);
+    for run in &runs {
+        if pins[run.start].equivalent && run.len() > 1 {
+            draw_equivalence_bracket(
+                painter,
+                port_positions[run.start],
+                port_positions[run.end - 1],
+                &side,
+                zoom,
+                default_color,
+            );
+        }
+    }
+
+    if state.show_pin_indices {
+        draw_pin_indices(
+            painter,
+            pins,
+            &runs,
+            &port_positions,
+            &side,
+            spacing,
+            zoom,
+            dark_mode,
+        );
+    }
+}
+
+/// Splits `pins` into runs of consecutive pins belonging to the same port.
+fn port_runs(pins: &[PinInfo]) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    while run_start < pins.len() {
+        let mut run_end = run_start + 1;
+        while run_end < pins.len() && pins[run_end].name == pins[run_start].name {
+            run_end += 1;
+        }
+        runs.push(run_start..run_end);
+        run_start = run_end;
+    }
+    runs
+}
+
+/// Whether index labels as long as `widest_label` characters fit between
+/// pins `spacing` apart on `side` without overlapping.
+fn pin_indices_fit(side: &PinSide, spacing: f32, font_size: f32, widest_label: usize) -> bool {
+    let needed = match side {
+        PinSide::Left | PinSide::Right => font_size * 1.2,
+        // Monospace glyphs are a little over half as wide as they are tall.
+        PinSide::Bottom => font_size * 0.6 * widest_label as f32 + 2.0,
+    };
+    spacing >= needed
+}
+
+/// Labels the pins of each bus port just inside the block edge: every pin
+/// with its index when there is room between them, otherwise the whole run
+/// with its index range, e.g. `in[3:0]`. Single-pin ports are left alone.
+fn draw_pin_indices(
+    painter: &egui::Painter,
+    pins: &[PinInfo],
+    runs: &[Range<usize>],
+    port_positions: &[egui::Pos2],
+    side: &PinSide,
+    spacing: f32,
+    zoom: f32,
+    dark_mode: bool,
+) {
+    let font_size = PIN_INDEX_FONT_SIZE * zoom;
+    if font_size < MIN_PIN_INDEX_FONT_SIZE {
+        return;
+    }
+    let font = egui::FontId::monospace(font_size);
+    let color = color_scheme::theme_text_color(dark_mode);
+    // Labels sit just inside the block edge, clear of the wires at the pin
+    // ends.
+    let inset = 3.0 * zoom;
+    let (inward, anchor) = match side {
+        PinSide::Left => (egui::vec2(inset, 0.0), egui::Align2::LEFT_CENTER),
+        PinSide::Right => (egui::vec2(-inset, 0.0), egui::Align2::RIGHT_CENTER),
+        PinSide::Bottom => (egui::vec2(0.0, -inset), egui::Align2::CENTER_BOTTOM),
+    };
+    let outward_offset = match side {
+        PinSide::Left => egui::vec2(-PORT_LENGTH * zoom, 0.0),
+        PinSide::Right => egui::vec2(PORT_LENGTH * zoom, 0.0),
+        PinSide::Bottom => egui::vec2(0.0, PORT_LENGTH * zoom),
+    };
+
+    let bus_runs = runs.iter().filter(|run| run.len() > 1);
+    let widest_label = bus_runs
+        .clone()
+        .map(|run| format!("[{}]", pins[run.end - 1].index).len())
+        .max();
+    let Some(widest_label) = widest_label else {
+        return;
+    };
+
+    if pin_indices_fit(side, spacing, font_size, widest_label) {
+        for run in bus_runs {
+            for i in run.clone() {
+                // Port positions are the outer pin ends; step back to the edge.
+                let edge = port_positions[i] - outward_offset;
+                painter.text(
+                    edge + inward,
+                    anchor,
+                    format!("[{}]", pins[i].index),
+                    font.clone(),
+                    color,
+                );
+            }
+        }
+    } else {
+        for run in bus_runs {
+            let first = port_positions[run.start] - outward_offset;
+            let last = port_positions[run.end - 1] - outward_offset;
+            let label = format!(
+                "{}[{}:{}]",
+                pins[run.start].name,
+                pins[run.end - 1].index,
+                pins[run.start].index
+            );
+            painter.text(
+                first.lerp(last, 0.5) + inward,
+                anchor,
+                label,
+                font.clone(),
+                color,
+            );
+        }
+    }
+}
+
+/// Draws a bracket just outside the pin ends from `first` to `last`, marking
+/// the pins in between as swappable.
+fn draw_equivalence_bracket(
+    painter: &egui::Painter,
+    first: egui::Pos2,
+    last: egui::Pos2,
+    side: &PinSide,
+    zoom: f32,
+    color: egui::Color32,
+) {
+    let outward = match side {
+        PinSide::Left => egui::vec2(-1.0, 0.0),
+        PinSide::Right => egui::vec2(1.0, 0.0),
+        PinSide::Bottom => egui::vec2(0.0, 1.0),
+    };
+    let gap = outward * (EQUIVALENCE_BRACKET_GAP * zoom);
+    let tick = outward * (EQUIVALENCE_BRACKET_TICK * zoom);
+    let stroke = egui::Stroke::new(1.0 * zoom, color);
+    painter.line(
+        vec![
+            first + gap,
+            first + gap + tick,
+            last + gap + tick,
+            last + gap,
+        ],
+        stroke,
+    );
 }
 
 struct PinInfo<'a> {
     name: &'a str,
     index: usize,
+    /// Whether the pins of this port are logically equivalent (swappable).
+    equivalent: bool,
+    /// Whether this is a non-clock global input, such as a reset.
+    global: bool,
 }
 
 //-----------------------------------------------------------
@@ -204,67 +368,38 @@ pub fn draw_ports(
     port_map: &mut HashMap<String, egui::Pos2>,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    dark_mode: bool,
 ) {
-    let mut input_pins: Vec<PinInfo> = Vec::new();
-    let mut output_pins: Vec<PinInfo> = Vec::new();
-    let mut clock_pins: Vec<PinInfo> = Vec::new();
+    let mut left_pins: Vec<PinInfo> = Vec::new();
+    let mut right_pins: Vec<PinInfo> = Vec::new();
+    let mut bottom_pins: Vec<PinInfo> = Vec::new();
 
     for port in &pb_type.ports {
-        match port {
-            Port::Input(p) => {
-                for i in 0..p.num_pins {
-                    input_pins.push(PinInfo {
-                        name: &p.name,
-                        index: i,
-                    });
-                }
-            }
-            Port::Output(p) => {
-                for i in 0..p.num_pins {
-                    output_pins.push(PinInfo {
-                        name: &p.name,
-                        index: i,
-                    });
-                }
-            }
-            Port::Clock(p) => {
-                for i in 0..p.num_pins {
-                    clock_pins.push(PinInfo {
-                        name: &p.name,
-                        index: i,
-                    });
-                }
-            }
-        }
+        let (name, num_pins, equivalent, global) = match port {
+            Port::Input(p) => (&p.name, p.num_pins, &p.equivalent, p.is_non_clock_global),
+            Port::Output(p) => (&p.name, p.num_pins, &p.equivalent, false),
+            Port::Clock(p) => (&p.name, p.num_pins, &p.equivalent, false),
+        };
+        let pins = match pin_side(port) {
+            PinSide::Left => &mut left_pins,
+            PinSide::Right => &mut right_pins,
+            PinSide::Bottom => &mut bottom_pins,
+        };
+        pins.extend((0..num_pins).map(|index| PinInfo {
+            name,
+            index,
+            equivalent: !matches!(equivalent, PinEquivalence::None),
+            global,
+        }));
     }
 
-    draw_pins_on_side(
-        &input_pins,
-        rect,
-        PinSide::Left,
-        painter,
-        port_map,
-        state,
-        ui,
-    );
-    draw_pins_on_side(
-        &output_pins,
-        rect,
-        PinSide::Right,
-        painter,
-        port_map,
-        state,
-        ui,
-    );
-    draw_pins_on_side(
-        &clock_pins,
-        rect,
-        PinSide::Bottom,
-        painter,
-        port_map,
-        state,
-        ui,
-    );
+    for (pins, side) in [
+        (&left_pins, PinSide::Left),
+        (&right_pins, PinSide::Right),
+        (&bottom_pins, PinSide::Bottom),
+    ] {
+        draw_pins_on_side(pins, rect, side, painter, port_map, state, ui, dark_mode);
+    }
 }
 
 //-----------------------------------------------------------
@@ -276,6 +411,7 @@ pub fn draw_generic_block(
     pb_type: &PBType,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
@@ -290,10 +426,11 @@ pub fn draw_generic_block(
     // Title bar
     let title_rect =
         egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), HEADER_HEIGHT * zoom));
+    let title_bg = block_style::header_color(HeaderClass::of(pb_type), palette, dark_mode);
     painter.rect(
         title_rect,
         egui::CornerRadius::ZERO,
-        color_scheme::theme_header_bg(dark_mode),
+        title_bg,
         egui::Stroke::NONE,
         egui::epaint::StrokeKind::Inside,
     );
@@ -303,11 +440,11 @@ pub fn draw_generic_block(
         egui::Align2::LEFT_TOP,
         &pb_type.name,
         egui::FontId::proportional(14.0 * zoom),
-        color_scheme::theme_text_color(dark_mode),
+        block_style::contrasting_text_color(title_bg),
     );
 
     let mut port_map = HashMap::new();
-    draw_ports(painter, rect, pb_type, &mut port_map, state, ui);
+    draw_ports(painter, rect, pb_type, &mut port_map, state, ui, dark_mode);
     port_map
 }
 
@@ -320,10 +457,11 @@ pub fn draw_lut(
     pb_type: &PBType,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
-    let colors = color_scheme::lut_colors(dark_mode);
+    let colors = color_scheme::lut_colors(palette, dark_mode);
     painter.rect(
         rect,
         egui::CornerRadius::ZERO,
@@ -349,7 +487,7 @@ pub fn draw_lut(
     );
 
     let mut port_map = HashMap::new();
-    draw_ports(painter, rect, pb_type, &mut port_map, state, ui);
+    draw_ports(painter, rect, pb_type, &mut port_map, state, ui, dark_mode);
     port_map
 }
 
@@ -362,10 +500,11 @@ pub fn draw_flip_flop(
     pb_type: &PBType,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
-    let colors = color_scheme::flip_flop_colors(dark_mode);
+    let colors = color_scheme::flip_flop_colors(palette, dark_mode);
     painter.rect(
         rect,
         egui::CornerRadius::ZERO,
@@ -404,7 +543,7 @@ pub fn draw_flip_flop(
     );
 
     let mut port_map = HashMap::new();
-    draw_ports(painter, rect, pb_type, &mut port_map, state, ui);
+    draw_ports(painter, rect, pb_type, &mut port_map, state, ui, dark_mode);
     port_map
 }
 
@@ -417,10 +556,11 @@ pub fn draw_memory(
     pb_type: &PBType,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
-    let colors = color_scheme::memory_colors(dark_mode);
+    let colors = color_scheme::memory_colors(palette, dark_mode);
     painter.rect(
         rect,
         egui::CornerRadius::ZERO,
@@ -459,7 +599,7 @@ pub fn draw_memory(
     );
 
     let mut port_map = HashMap::new();
-    draw_ports(painter, rect, pb_type, &mut port_map, state, ui);
+    draw_ports(painter, rect, pb_type, &mut port_map, state, ui, dark_mode);
     port_map
 }
 
@@ -472,10 +612,11 @@ pub fn draw_blif_block(
     pb_type: &PBType,
     state: &mut IntraTileState,
     ui: &mut egui::Ui,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
-    let colors = color_scheme::blif_colors(dark_mode);
+    let colors = color_scheme::blif_colors(palette, dark_mode);
     painter.rect(
         rect,
         egui::CornerRadius::ZERO,
@@ -500,7 +641,7 @@ pub fn draw_blif_block(
         painter.text(
             rect.center(),
             egui::Align2::CENTER_CENTER,
-            blif_model,
+            blif_model_label(blif_model),
             egui::FontId::monospace(14.0 * zoom),
             colors.text,
         );
@@ -515,6 +656,85 @@ pub fn draw_blif_block(
     );
 
     let mut port_map = HashMap::new();
-    draw_ports(painter, rect, pb_type, &mut port_map, state, ui);
+    draw_ports(painter, rect, pb_type, &mut port_map, state, ui, dark_mode);
     port_map
 }
+
+/// Text shown in the middle of a BLIF primitive: the model name of a
+/// `.subckt`, or the model as written for built-in models.
+pub fn blif_model_label(blif_model: &BlifModel) -> String {
+    match blif_model {
+        BlifModel::Subckt(model) => model.clone(),
+        _ => blif_model.raw(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4_clb;
+
+    fn pin(name: &str, index: usize) -> PinInfo<'_> {
+        PinInfo {
+            name,
+            index,
+            equivalent: false,
+            global: false,
+        }
+    }
+
+    #[test]
+    fn splits_pins_into_port_runs() {
+        let pins = [pin("in", 0), pin("in", 1), pin("in", 2), pin("cin", 0)];
+        assert_eq!(port_runs(&pins), vec![0..3, 3..4]);
+        assert!(port_runs(&[]).is_empty());
+    }
+
+    #[test]
+    fn pin_indices_need_room_between_pins() {
+        // Stacked pins only need the height of a line between them.
+        assert!(pin_indices_fit(&PinSide::Left, 25.0, 9.0, 4));
+        assert!(!pin_indices_fit(&PinSide::Right, 8.0, 9.0, 4));
+        // Side by side, the whole label has to fit.
+        assert!(pin_indices_fit(&PinSide::Bottom, 25.0, 9.0, 3));
+        assert!(!pin_indices_fit(&PinSide::Bottom, 25.0, 9.0, 5));
+    }
+
+    #[test]
+    fn draws_clock_pins_on_their_own_edge() {
+        let clb = k4_n4_clb();
+        let rect = egui::Rect::from_min_size(egui::pos2(100.0, 100.0), egui::vec2(400.0, 400.0));
+        let mut port_map = HashMap::new();
+        let mut state = IntraTileState::default();
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let painter = ui.painter().clone();
+                draw_ports(&painter, rect, &clb, &mut port_map, &mut state, ui, false);
+            });
+        });
+
+        // The clb has inputs I, outputs O, and the clock clk.
+        for (pin, pos) in &port_map {
+            match pin.split('[').next() {
+                Some("I") => assert!(pos.x < rect.min.x, "{pin} is not on the left"),
+                Some("O") => assert!(pos.x > rect.max.x, "{pin} is not on the right"),
+                Some("clk") => {
+                    assert!(pos.y > rect.max.y, "{pin} is not on the bottom");
+                    assert!(rect.x_range().contains(pos.x));
+                }
+                _ => panic!("unexpected pin {pin}"),
+            }
+        }
+        assert!(port_map.contains_key("clk[0]"));
+
+        // Only the declared port type decides the edge, not the name.
+        let trigger = Port::Clock(ClockPort {
+            name: "trigger".to_string(),
+            num_pins: 1,
+            equivalent: PinEquivalence::None,
+            port_class: PortClass::Clock,
+        });
+        assert_eq!(pin_side(&trigger), PinSide::Bottom);
+    }
+}
@@ -3,12 +3,32 @@
 //! Part of the Intra Tile Visualization, this module renders the textualhierarchy tree of an FPGA tile.
 
 use eframe::egui;
-use fpga_arch_parser::{FPGAArch, PBType, PBTypeClass, Port, Tile};
+use fpga_arch_parser::prelude::*;
+
+use crate::arch_diff::type_path;
+use crate::arch_index::ArchIndex;
+use crate::color_scheme;
+use crate::intra_tile::{
+    IntraTileState, generate_child_instance_name, get_children_for_mode, pb_type_class_name,
+};
 
 // ------------------------------------------------------------
 // Hierarchy Tree Rendering
 // ------------------------------------------------------------
-pub fn render_hierarchy_tree(ui: &mut egui::Ui, arch: &FPGAArch, tile: &Tile) {
+pub fn render_hierarchy_tree(
+    ui: &mut egui::Ui,
+    arch: &FPGAArch,
+    arch_index: &ArchIndex,
+    tile: &Tile,
+) {
+    if tile.sub_tiles.is_empty() {
+        ui.label(
+            egui::RichText::new("This tile has no sub-tiles.")
+                .italics()
+                .weak(),
+        );
+        return;
+    }
     for sub_tile in &tile.sub_tiles {
         egui::CollapsingHeader::new(format!("SubTile: {}", sub_tile.name))
             .default_open(true)
@@ -17,11 +37,17 @@ pub fn render_hierarchy_tree(ui: &mut egui::Ui, arch: &FPGAArch, tile: &Tile) {
                 for site in &sub_tile.equivalent_sites {
                     ui.label(format!("Equivalent Site PB Type: {}", site.pb_type));
                     // Find the PBType definition
-                    if let Some(pb_type) = arch
-                        .complex_block_list
-                        .iter()
-                        .find(|pb| pb.name == site.pb_type)
-                    {
+                    if let Some(pb_type) = arch_index.complex_block(arch, &site.pb_type) {
+                        // A primitive site would otherwise show just its own
+                        // node, which reads like a tree that failed to load.
+                        if is_leaf(pb_type) {
+                            ui.label(
+                                egui::RichText::new("This tile has no sub-blocks.")
+                                    .italics()
+                                    .weak(),
+                            );
+                            ui.label(format!("Class: {}", pb_type_class_name(&pb_type.class)));
+                        }
                         ui.push_id(format!("pb_{}", pb_type.name), |ui| {
                             render_pb_type_tree_node(ui, pb_type);
                         });
@@ -33,6 +59,11 @@ pub fn render_hierarchy_tree(ui: &mut egui::Ui, arch: &FPGAArch, tile: &Tile) {
     }
 }
 
+/// Whether a pb_type has no children in any of its modes.
+fn is_leaf(pb_type: &PBType) -> bool {
+    pb_type.pb_types.is_empty() && pb_type.modes.iter().all(|mode| mode.pb_types.is_empty())
+}
+
 fn render_interconnects(ui: &mut egui::Ui, interconnects: &[fpga_arch_parser::Interconnect]) {
     for inter in interconnects {
         let kind = match inter.interconnect_type {
@@ -131,3 +162,100 @@ fn render_pb_type_tree_node(ui: &mut egui::Ui, pb_type: &PBType) {
         }
     });
 }
+
+// ------------------------------------------------------------
+// Instance Tree
+// ------------------------------------------------------------
+/// Renders the block instances of a complex block as they are drawn on the
+/// canvas, following the selected mode of each block. Returns the instance
+/// path of the node that was clicked, if any.
+pub fn render_instance_tree(
+    ui: &mut egui::Ui,
+    root_pb: &PBType,
+    state: &IntraTileState,
+) -> Option<String> {
+    let mut clicked_path = None;
+    render_instance_tree_node(
+        ui,
+        root_pb,
+        &root_pb.name,
+        &root_pb.name,
+        state,
+        &mut clicked_path,
+    );
+    clicked_path
+}
+
+fn render_instance_tree_node(
+    ui: &mut egui::Ui,
+    pb_type: &PBType,
+    instance_name: &str,
+    instance_path: &str,
+    state: &IntraTileState,
+    clicked_path: &mut Option<String>,
+) {
+    let is_selected = state.selected_block_path.as_deref() == Some(instance_path);
+    let children = get_children_for_mode(pb_type, state.selected_mode(pb_type, instance_path));
+
+    let label = instance_label(instance_name, instance_path, state);
+
+    if children.is_empty() {
+        if ui.selectable_label(is_selected, label).clicked() {
+            *clicked_path = Some(instance_path.to_string());
+        }
+        return;
+    }
+
+    let id = ui.make_persistent_id(("instance_tree", instance_path));
+    // Only the root starts open, so large blocks do not flood the panel.
+    let default_open = !instance_path.contains('.');
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, default_open)
+        .show_header(ui, |ui| {
+            if ui.selectable_label(is_selected, label).clicked() {
+                *clicked_path = Some(instance_path.to_string());
+            }
+        })
+        .body(|ui| {
+            for child_pb in children {
+                for i in 0..child_pb.num_pb {
+                    let child_name = generate_child_instance_name(child_pb, i);
+                    let child_path = format!("{}.{}", instance_path, child_name);
+                    render_instance_tree_node(
+                        ui,
+                        child_pb,
+                        &child_name,
+                        &child_path,
+                        state,
+                        clicked_path,
+                    );
+                }
+            }
+        });
+}
+
+/// The name of an instance tree node, marked if its pb_type changed in the
+/// last reload. Blocks containing a change are marked more faintly, so
+/// changes inside collapsed nodes can still be found.
+fn instance_label(
+    instance_name: &str,
+    instance_path: &str,
+    state: &IntraTileState,
+) -> egui::RichText {
+    let label = egui::RichText::new(instance_name);
+    let Some(changes) = &state.recent_changes else {
+        return label;
+    };
+    let path = type_path(instance_path);
+    let strength = if changes.names.contains(&path) {
+        changes.strength
+    } else if changes
+        .names
+        .iter()
+        .any(|changed| changed.starts_with(&format!("{path}.")))
+    {
+        changes.strength * 0.4
+    } else {
+        return label;
+    };
+    label.background_color(color_scheme::CHANGE_HIGHLIGHT_COLOR.gamma_multiply(strength))
+}
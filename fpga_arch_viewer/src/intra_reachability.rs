@@ -0,0 +1,197 @@
+//! Port Reachability
+//!
+//! Part of the Intra Tile Visualization, this module answers whether one port
+//! of a block can reach another through the interconnects of a mode, by a
+//! breadth-first search over the pins of the block and its children.
+
+use fpga_arch_parser::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use crate::intra_tile::{
+    align_direct_wires, bus_name, expand_port_list, generate_child_instance_name,
+    get_children_for_mode, get_interconnects_for_mode, resolve_bus_list, resolve_port_pos,
+};
+
+/// The pins of a block and of its children in one mode, with an edge for
+/// every connection an interconnect makes between two of them.
+pub struct PinGraph {
+    /// Pin references, e.g. "clb.I[0]" or "fle[0].in[0]".
+    pins: Vec<String>,
+    /// Pins each pin drives, by index into `pins`.
+    edges: Vec<Vec<usize>>,
+}
+
+impl PinGraph {
+    /// Builds the graph of `pb_type` in the mode at `mode_index`. With
+    /// `through_children`, every input and clock pin of a child drives all
+    /// of its outputs, since what the child connects inside depends on its
+    /// own mode.
+    pub fn build(pb_type: &PBType, mode_index: usize, through_children: bool) -> Self {
+        let mut pins = Vec::new();
+
+        // Pins are keyed the way the canvas keys their positions.
+        let mut my_ports = HashMap::new();
+        for port in &pb_type.ports {
+            let (name, num_pins) = port_name_and_width(port);
+            for pin in 0..num_pins {
+                my_ports.insert(format!("{name}[{pin}]"), pins.len());
+                pins.push(format!("{}.{name}[{pin}]", pb_type.name));
+            }
+        }
+
+        let mut children_ports = HashMap::new();
+        let mut child_instances = Vec::new();
+        for child in get_children_for_mode(pb_type, mode_index) {
+            for index in 0..child.num_pb {
+                let instance_name = generate_child_instance_name(child, index);
+                let mut inputs = Vec::new();
+                let mut outputs = Vec::new();
+                for port in &child.ports {
+                    let (name, num_pins) = port_name_and_width(port);
+                    for pin in 0..num_pins {
+                        let pin_ref = format!("{instance_name}.{name}[{pin}]");
+                        match port {
+                            Port::Output(_) => outputs.push(pins.len()),
+                            Port::Input(_) | Port::Clock(_) => inputs.push(pins.len()),
+                        }
+                        children_ports.insert(pin_ref.clone(), pins.len());
+                        pins.push(pin_ref);
+                    }
+                }
+                child_instances.push((inputs, outputs));
+            }
+        }
+
+        let mut edges = vec![Vec::new(); pins.len()];
+        let resolve =
+            |pin_ref: &str| resolve_port_pos(pin_ref, &pb_type.name, &my_ports, &children_ports);
+        for inter in get_interconnects_for_mode(pb_type, mode_index) {
+            let sources = resolve_bus_list(
+                &expand_port_list(&inter.input),
+                &pb_type.name,
+                &my_ports,
+                &children_ports,
+            );
+            let sinks = resolve_bus_list(
+                &expand_port_list(&inter.output),
+                &pb_type.name,
+                &my_ports,
+                &children_ports,
+            );
+            match inter.interconnect_type {
+                InterconnectType::Direct => {
+                    let (sources, sinks) = align_direct_wires(sources, sinks);
+                    for (src, dst) in sources.iter().zip(&sinks) {
+                        if let (Some(src), Some(dst)) = (resolve(src), resolve(dst)) {
+                            edges[src].push(dst);
+                        }
+                    }
+                }
+                InterconnectType::Mux | InterconnectType::Complete => {
+                    for src in sources.iter().filter_map(|src| resolve(src)) {
+                        edges[src].extend(sinks.iter().filter_map(|dst| resolve(dst)));
+                    }
+                }
+            }
+        }
+
+        if through_children {
+            for (inputs, outputs) in child_instances {
+                for input in inputs {
+                    edges[input].extend(&outputs);
+                }
+            }
+        }
+
+        Self { pins, edges }
+    }
+
+    /// Every port of the block and of its child instances, e.g. "clb.I" or
+    /// "fle[0].in", in the order their pins were added.
+    pub fn ports(&self) -> Vec<&str> {
+        let mut ports: Vec<&str> = Vec::new();
+        for pin in &self.pins {
+            let port = bus_name(pin);
+            if ports.last() != Some(&port) {
+                ports.push(port);
+            }
+        }
+        ports
+    }
+
+    /// The shortest chain of pins from any pin of the `source` port to any
+    /// pin of the `sink` port, or None if no pin of `sink` can be reached.
+    pub fn find_path(&self, source: &str, sink: &str) -> Option<Vec<String>> {
+        let mut previous: Vec<Option<usize>> = vec![None; self.pins.len()];
+        let mut visited = vec![false; self.pins.len()];
+        let mut queue = VecDeque::new();
+        for (index, pin) in self.pins.iter().enumerate() {
+            if bus_name(pin) == source {
+                visited[index] = true;
+                queue.push_back(index);
+            }
+        }
+
+        while let Some(index) = queue.pop_front() {
+            if bus_name(&self.pins[index]) == sink {
+                let mut path = vec![self.pins[index].clone()];
+                let mut current = index;
+                while let Some(prev) = previous[current] {
+                    path.push(self.pins[prev].clone());
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in &self.edges[index] {
+                if !visited[next] {
+                    visited[next] = true;
+                    previous[next] = Some(index);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+fn port_name_and_width(port: &Port) -> (&str, usize) {
+    match port {
+        Port::Input(p) => (&p.name, p.num_pins),
+        Port::Output(p) => (&p.name, p.num_pins),
+        Port::Clock(p) => (&p.name, p.num_pins),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4_clb;
+
+    #[test]
+    fn finds_paths_through_interconnects() {
+        let clb = k4_n4_clb();
+
+        let graph = PinGraph::build(&clb, 0, true);
+        assert!(graph.ports().contains(&"clb.I"));
+        assert!(graph.ports().contains(&"fle[3].out"));
+
+        // The crossbar feeds the fle inputs, and the fles drive the outputs.
+        let path = graph
+            .find_path("clb.I", "fle[0].in")
+            .expect("clb.I reaches the fle inputs");
+        assert_eq!(path.len(), 2);
+        assert!(path[0].starts_with("clb.I["));
+        assert!(path[1].starts_with("fle[0].in["));
+        let path = graph
+            .find_path("clb.I", "clb.O")
+            .expect("clb.I reaches clb.O through a fle");
+        assert!(path.last().is_some_and(|pin| pin.starts_with("clb.O[")));
+
+        // Without passing through the fles, the outputs cannot be reached.
+        let graph = PinGraph::build(&clb, 0, false);
+        assert!(graph.find_path("clb.I", "clb.O").is_none());
+        // Nothing drives an input of the block.
+        assert!(graph.find_path("fle[0].out", "clb.I").is_none());
+    }
+}
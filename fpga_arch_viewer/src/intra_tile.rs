@@ -3,17 +3,26 @@
 //! Part of the FPGA Visualizer, this module renders the intra-tile view of an FPGA tile.
 
 use eframe::egui;
-use fpga_arch_parser::{PBType, PBTypeClass, Port};
+use fpga_arch_parser::prelude::*;
 use log::warn;
 use std::collections::{HashMap, HashSet};
 
-use crate::color_scheme;
+use crate::arch_diff::ChangeHighlight;
+use crate::block_style::{self, HeaderClass};
+use crate::color_scheme::{self, Palette};
+use crate::common_ui::{self, truncate_label};
+use crate::delay_matrix_view::{self, DelayMatrixPopup};
 use crate::intra_block_drawing;
+use crate::intra_reachability::PinGraph;
+use crate::perf_overlay::DrawStats;
 
 // ------------------------------------------------------------
 // Constants
 // ------------------------------------------------------------
-const PADDING: f32 = 50.0;
+// Default spacing around and between child blocks; adjustable at runtime.
+const DEFAULT_PADDING: f32 = 50.0;
+const MIN_PADDING: f32 = 20.0;
+const MAX_PADDING: f32 = 150.0;
 const HEADER_HEIGHT: f32 = 35.0;
 const MIN_BLOCK_SIZE: egui::Vec2 = egui::vec2(80.0, 120.0);
 const MIN_PIN_SPACING: f32 = 25.0;
@@ -23,18 +32,222 @@ const MUX_GUTTER: f32 = 70.0;
 // Only enable the mux gutter for "dense" mux regions; a single mux (e.g. k4_N4 mux1)
 // shouldn't push children around and change local routing aesthetics.
 const MUX_GUTTER_MIN_MUXES: usize = 4;
+// Child arrays with more instances than this are collapsed to their first
+// instance plus a summary badge until the user asks to see all of them.
+const ARRAY_COLLAPSE_THRESHOLD: usize = 16;
+const ARRAY_BADGE_HEIGHT: f32 = 24.0;
+const ARRAY_BADGE_GAP: f32 = 10.0;
+// Room for the "max depth" marker in the header of a block past the render
+// depth, at 100% zoom.
+const MAX_DEPTH_MARKER_WIDTH: f32 = 55.0;
+// Bundled buses are drawn as their individual wires from this zoom up.
+pub(crate) const BUNDLE_EXPAND_ZOOM: f32 = 2.0;
+// Distance between the dots of the background grid at 100% zoom.
+const CANVAS_GRID_SPACING: f32 = 50.0;
+// Blocks nested deeper than this are drawn collapsed, so that a file with a
+// huge hierarchy cannot stall the UI. Configurable in the settings.
+pub const DEFAULT_MAX_RENDER_DEPTH: usize = 12;
+pub const MAX_RENDER_DEPTH_LIMIT: usize = 64;
 
 // ------------------------------------------------------------
 // Intra Tile Drawing Entry Point
 // ------------------------------------------------------------
+/// An interconnect port reference that names a block or port that does not
+/// exist, so its wire cannot be drawn.
+pub struct UnresolvedInterconnectRef {
+    pub instance_path: String,
+    pub interconnect_name: String,
+    pub port_ref: String,
+}
+
+/// How interconnect wires are stroked, so they can be made easier to see on
+/// high-DPI displays or projectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WireStyle {
+    /// Width of a wire at 100% zoom.
+    pub thickness: f32,
+    /// Width of a hovered or selected wire at 100% zoom.
+    pub highlight_thickness: f32,
+    /// Color of hovered or selected wires and interconnect blocks.
+    pub highlight_color: egui::Color32,
+}
+
+impl WireStyle {
+    pub const MAX_THICKNESS: f32 = 8.0;
+
+    /// How many times wider than one of its wires a bus bundle is drawn.
+    pub const BUNDLE_WIDTH_SCALE: f32 = 2.5;
+
+    /// Stroke of a wire drawn in `color`, or in the highlight style if it is
+    /// highlighted.
+    pub fn stroke(&self, highlighted: bool, color: egui::Color32, zoom: f32) -> egui::Stroke {
+        if highlighted {
+            egui::Stroke::new(self.highlight_thickness * zoom, self.highlight_color)
+        } else {
+            egui::Stroke::new(self.thickness * zoom, color)
+        }
+    }
+
+    /// Stroke of a bus bundle, a wider version of `stroke`.
+    pub fn bundle_stroke(
+        &self,
+        highlighted: bool,
+        color: egui::Color32,
+        zoom: f32,
+    ) -> egui::Stroke {
+        let stroke = self.stroke(highlighted, color, zoom);
+        egui::Stroke::new(stroke.width * Self::BUNDLE_WIDTH_SCALE, stroke.color)
+    }
+}
+
+impl Default for WireStyle {
+    fn default() -> Self {
+        Self {
+            thickness: 1.5,
+            highlight_thickness: 2.5,
+            highlight_color: color_scheme::HIGHLIGHT_COLOR,
+        }
+    }
+}
+
+/// Block, source and sink ports picked in the reachability check, e.g.
+/// "clb.I", and the result of the last check.
+pub struct ReachabilityCheck {
+    /// Instance path of the block whose interconnects are checked; None
+    /// checks the root block.
+    pub block_path: Option<String>,
+    pub source: Option<String>,
+    pub sink: Option<String>,
+    /// Let child blocks connect each of their inputs to all of their outputs.
+    pub through_children: bool,
+    pub result: Option<ReachabilityResult>,
+}
+
+/// The outcome of a reachability check in one block and mode.
+pub struct ReachabilityResult {
+    pub instance_path: String,
+    pub mode_index: usize,
+    /// Pins along the path that was found, or None if there is no path.
+    pub path: Option<Vec<String>>,
+}
+
+impl ReachabilityResult {
+    fn is_for(&self, instance_path: &str, mode_index: usize) -> bool {
+        self.instance_path == instance_path && self.mode_index == mode_index
+    }
+}
+
+impl Default for ReachabilityCheck {
+    fn default() -> Self {
+        Self {
+            block_path: None,
+            source: None,
+            sink: None,
+            through_children: true,
+            result: None,
+        }
+    }
+}
+
 pub struct IntraTileState {
     pub selected_modes: HashMap<String, usize>,
     pub highlighted_positions_this_frame: Vec<egui::Pos2>,
     pub highlighted_positions_next_frame: Vec<egui::Pos2>,
     pub expanded_blocks: HashSet<String>,
+    /// Large child arrays (keyed by "parent_path.child_name") shown in full.
+    pub expanded_arrays: HashSet<String>,
+    /// Screen rect of every block instance drawn in the last rendered frame,
+    /// keyed by instance path (e.g. "clb.fle[0].ble4"). Children of collapsed
+    /// blocks are not drawn, so they have no entry. Cleared at the start of
+    /// each frame and refilled as the blocks are drawn.
     pub pb_rects: HashMap<String, egui::Rect>,
+    /// Interconnect port references that do not match any port, gathered
+    /// while drawing the last frame.
+    pub unresolved_refs: Vec<UnresolvedInterconnectRef>,
     /// Zoom factor for the intra-tile canvas (1.0 = 100%).
     pub zoom: f32,
+    /// Spacing around and between child blocks, before zoom.
+    pub padding: f32,
+    /// Instance path whose interconnects are listed in the interconnect
+    /// table; None lists the root block.
+    pub interconnect_table_path: Option<String>,
+    /// Interconnect picked in the table, as (instance_path, interconnect name).
+    /// Its wires stay highlighted on the canvas.
+    pub selected_interconnect: Option<(String, String)>,
+    /// Ports picked in the reachability check and its last result.
+    pub reachability: ReachabilityCheck,
+    /// Blocks and wires drawn in the last rendered frame.
+    pub draw_stats: DrawStats,
+    /// Delay matrices shown in the heatmap window, if it is open.
+    pub delay_matrix_popup: Option<DelayMatrixPopup>,
+    /// Sorted names of every pack pattern in the current complex block. A
+    /// pattern's index here picks its color.
+    pub pack_pattern_names: Vec<String>,
+    /// Instance path picked in the hierarchy tree. It is outlined on the canvas.
+    pub selected_block_path: Option<String>,
+    /// Instance path of the block whose header was clicked or that was picked
+    /// in the hierarchy tree last, shown as a breadcrumb above the canvas.
+    pub breadcrumb_path: Option<String>,
+    /// Instance path of the block that has the keyboard focus. The arrow keys
+    /// move it through the hierarchy and Enter expands or collapses it.
+    pub focused_block_path: Option<String>,
+    /// Zoom the canvas so a block picked in the hierarchy tree fills the view.
+    pub zoom_to_selection: bool,
+    /// Annotate collapsed block headers with their input, output and clock
+    /// pin counts.
+    pub show_port_widths: bool,
+    /// Label the pins of bus ports with their indices, or with the bus range
+    /// when zoomed out too far to tell the pins apart.
+    pub show_pin_indices: bool,
+    /// Tint each child block by how many interconnect pins connect to it.
+    pub show_connection_heat: bool,
+    /// pb_types, by type path, that changed in the last reload. They are
+    /// marked in the hierarchy tree until the highlight fades.
+    pub recent_changes: Option<ChangeHighlight>,
+    /// Draw direct connections between two buses as one thick line with a
+    /// width annotation. The bundle expands into its wires on hover or when
+    /// zoomed in.
+    pub bundle_buses: bool,
+    /// Wire widths and highlight color, from the settings.
+    pub wire_style: WireStyle,
+    /// Draw a dotted grid behind the block diagram, from the settings.
+    pub show_canvas_grid: bool,
+    /// Blocks this many levels below the root, or deeper, are drawn collapsed
+    /// even if expanded. From the settings.
+    pub max_render_depth: usize,
+    /// Instance path of a block picked from its header menu to be exported on
+    /// its own. The viewer takes it once the frame is drawn.
+    pub export_block_request: Option<String>,
+    /// Clicks on the canvas place ruler points instead of expanding or
+    /// collapsing blocks.
+    pub ruler_mode: bool,
+    /// Points placed with the ruler, relative to the canvas origin at 100%
+    /// zoom. A third click starts a new measurement.
+    pub ruler_points: Vec<egui::Pos2>,
+    /// Text typed in the find bar. Blocks whose instance name contains it
+    /// are matches.
+    pub find_query: String,
+    /// Instance paths matching `find_query`, in drawing order.
+    pub find_matches: Vec<String>,
+    /// Index in `find_matches` of the match last stepped to, if any.
+    pub find_current: Option<usize>,
+    /// Set by Ctrl+F; the find bar takes the keyboard focus on the next frame.
+    focus_find_field: bool,
+    /// When set, the canvas scrolls back to its origin on the next frame.
+    scroll_to_origin: bool,
+    /// Set when a block is picked; the canvas brings it into view once it has
+    /// been laid out.
+    focus_selected_block: bool,
+    /// Set when the keyboard focus moves; the canvas scrolls the focused block
+    /// into view once it has been laid out.
+    scroll_to_focused: bool,
+    /// Set while an interconnect is drawn if the pointer is over one of its
+    /// wires or its mux or crossbar block.
+    interconnect_hovered: bool,
+    /// Scroll offset the canvas moves to on the next frame.
+    pending_scroll_offset: Option<egui::Vec2>,
+    /// Scroll offset of the canvas in the last rendered frame.
+    scroll_offset: egui::Vec2,
     // Cache for PBType measurements: (instance_path, is_expanded, mode_index) -> size
     measurement_cache: HashMap<(String, bool, usize), egui::Vec2>,
 }
@@ -46,8 +259,42 @@ impl Default for IntraTileState {
             highlighted_positions_this_frame: Vec::new(),
             highlighted_positions_next_frame: Vec::new(),
             expanded_blocks: HashSet::new(),
+            expanded_arrays: HashSet::new(),
             pb_rects: HashMap::new(),
+            unresolved_refs: Vec::new(),
             zoom: 1.0,
+            padding: DEFAULT_PADDING,
+            interconnect_table_path: None,
+            selected_interconnect: None,
+            reachability: ReachabilityCheck::default(),
+            draw_stats: DrawStats::default(),
+            delay_matrix_popup: None,
+            pack_pattern_names: Vec::new(),
+            selected_block_path: None,
+            breadcrumb_path: None,
+            focused_block_path: None,
+            zoom_to_selection: false,
+            show_port_widths: false,
+            show_pin_indices: false,
+            show_connection_heat: false,
+            recent_changes: None,
+            bundle_buses: false,
+            wire_style: WireStyle::default(),
+            show_canvas_grid: false,
+            max_render_depth: DEFAULT_MAX_RENDER_DEPTH,
+            export_block_request: None,
+            ruler_mode: false,
+            ruler_points: Vec::new(),
+            find_query: String::new(),
+            find_matches: Vec::new(),
+            find_current: None,
+            focus_find_field: false,
+            scroll_to_origin: false,
+            focus_selected_block: false,
+            scroll_to_focused: false,
+            interconnect_hovered: false,
+            pending_scroll_offset: None,
+            scroll_offset: egui::Vec2::ZERO,
             measurement_cache: HashMap::new(),
         }
     }
@@ -57,6 +304,96 @@ impl IntraTileState {
     pub(crate) fn zoom_clamped(&self) -> f32 {
         self.zoom.clamp(0.2, 4.0)
     }
+
+    /// Whether the block at `instance_path` is too deep in the hierarchy to be
+    /// drawn expanded.
+    pub fn at_max_render_depth(&self, instance_path: &str) -> bool {
+        instance_path.matches('.').count() >= self.max_render_depth
+    }
+
+    /// The mode the block at `instance_path` is drawn in: the one picked in its
+    /// mode selector, or else the block's physical mode, or its first mode.
+    pub fn selected_mode(&self, pb_type: &PBType, instance_path: &str) -> usize {
+        let mode_index = self
+            .selected_modes
+            .get(instance_path)
+            .copied()
+            .unwrap_or_else(|| pb_type.default_mode_index());
+        validate_mode_index(pb_type, mode_index)
+    }
+
+    /// Whether the block at `instance_path` is drawn with its children: it is
+    /// expanded and not past the maximum render depth.
+    fn is_drawn_expanded(&self, instance_path: &str) -> bool {
+        self.expanded_blocks.contains(instance_path) && !self.at_max_render_depth(instance_path)
+    }
+
+    /// Where the block at `instance_path` was drawn in the last rendered frame,
+    /// or None if it was not drawn.
+    pub fn instance_rect(&self, instance_path: &str) -> Option<egui::Rect> {
+        self.pb_rects.get(instance_path).copied()
+    }
+
+    /// A fresh state that draws blocks and wires the way this one does: the
+    /// same labels, wire style and depth limit. Used for exports,
+    /// and kept by `reset`.
+    pub fn drawing_settings(&self) -> Self {
+        Self {
+            show_port_widths: self.show_port_widths,
+            show_pin_indices: self.show_pin_indices,
+            bundle_buses: self.bundle_buses,
+            wire_style: self.wire_style,
+            max_render_depth: self.max_render_depth,
+            ..Self::default()
+        }
+    }
+
+    /// Clears expansion, mode selection, and zoom, and scrolls the canvas back
+    /// to its origin. Display options are kept.
+    pub fn reset(&mut self) {
+        *self = Self {
+            zoom_to_selection: self.zoom_to_selection,
+            show_connection_heat: self.show_connection_heat,
+            recent_changes: self.recent_changes.clone(),
+            show_canvas_grid: self.show_canvas_grid,
+            ruler_mode: self.ruler_mode,
+            ..self.drawing_settings()
+        };
+        self.scroll_to_origin = true;
+    }
+
+    /// Captures the modes, expansion, zoom and scroll of the canvas.
+    pub fn snapshot(&self) -> IntraTileSnapshot {
+        IntraTileSnapshot {
+            selected_modes: self.selected_modes.clone(),
+            expanded_blocks: self.expanded_blocks.clone(),
+            expanded_arrays: self.expanded_arrays.clone(),
+            zoom: self.zoom,
+            scroll_offset: self.scroll_offset,
+        }
+    }
+
+    /// Puts the canvas back exactly as it was when `snapshot` was taken. Other
+    /// selections are cleared as by `reset`.
+    pub fn restore(&mut self, snapshot: &IntraTileSnapshot) {
+        self.reset();
+        self.selected_modes = snapshot.selected_modes.clone();
+        self.expanded_blocks = snapshot.expanded_blocks.clone();
+        self.expanded_arrays = snapshot.expanded_arrays.clone();
+        self.zoom = snapshot.zoom;
+        self.scroll_to_origin = false;
+        self.pending_scroll_offset = Some(snapshot.scroll_offset);
+    }
+}
+
+/// The parts of an `IntraTileState` that decide what the canvas shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntraTileSnapshot {
+    pub selected_modes: HashMap<String, usize>,
+    pub expanded_blocks: HashSet<String>,
+    pub expanded_arrays: HashSet<String>,
+    pub zoom: f32,
+    pub scroll_offset: egui::Vec2,
 }
 
 fn apply_local_zoom_style(ui: &mut egui::Ui, zoom: f32) -> std::sync::Arc<egui::Style> {
@@ -97,11 +434,18 @@ fn render_visual_layout_canvas(
     state: &mut IntraTileState,
     expand_all: bool,
     draw_interconnects: bool,
+    palette: Palette,
     dark_mode: bool,
 ) {
-    egui::ScrollArea::both()
+    let mut scroll_area = egui::ScrollArea::both()
         .id_salt("intra_tile_canvas")
-        .auto_shrink([false, false])
+        .auto_shrink([false, false]);
+    if std::mem::take(&mut state.scroll_to_origin) {
+        scroll_area = scroll_area.scroll_offset(egui::Vec2::ZERO);
+    } else if let Some(offset) = state.pending_scroll_offset.take() {
+        scroll_area = scroll_area.scroll_offset(offset);
+    }
+    let output = scroll_area
         // Enable "click + drag" panning within the canvas area.
         // This remains confined to the ScrollArea viewport, so it won't overlap other UI panels.
         .show(ui, |ui| {
@@ -145,27 +489,251 @@ fn render_visual_layout_canvas(
 
             // Draw pbtype here
             let zoom = state.zoom_clamped();
-            let total_size = measure_pb_type(root_pb, state, &root_pb.name);
-            let (response, painter) = ui.allocate_painter(
-                total_size + egui::vec2(40.0, 40.0) * zoom,
-                // Important: don't capture drags here, otherwise it prevents the
-                // ScrollArea from receiving drag-to-pan gestures.
-                egui::Sense::hover(),
-            );
-            let start_pos = response.rect.min + egui::vec2(20.0, 20.0) * zoom;
-
-            let _ = draw_pb_type(
-                &painter,
+            let (response, painter) = draw_canvas(
+                ui,
                 root_pb,
-                start_pos,
                 state,
-                &root_pb.name,
-                ui,
                 expand_all,
                 draw_interconnects,
+                palette,
                 dark_mode,
             );
+
+            if state.ruler_mode {
+                handle_ruler_clicks(ui, state, response.rect, zoom);
+            }
+            draw_ruler(&painter, state, response.rect.min, zoom, dark_mode);
+
+            let selected_rect = state
+                .selected_block_path
+                .as_deref()
+                .and_then(|path| state.instance_rect(path));
+            if let Some(selected_rect) = selected_rect {
+                painter.rect_stroke(
+                    selected_rect,
+                    egui::CornerRadius::ZERO,
+                    egui::Stroke::new(3.0 * zoom, color_scheme::GRID_HIGHLIGHT_COLOR),
+                    egui::epaint::StrokeKind::Outside,
+                );
+                if std::mem::take(&mut state.focus_selected_block) {
+                    focus_canvas_on_rect(state, selected_rect, response.rect.min, zoom_viewport);
+                }
+            }
+
+            let focused_rect = state
+                .focused_block_path
+                .as_deref()
+                .and_then(|path| state.instance_rect(path));
+            if state.scroll_to_focused
+                && let Some(focused_rect) = focused_rect
+            {
+                state.scroll_to_focused = false;
+                ui.scroll_to_rect(focused_rect, None);
+            }
         });
+    state.scroll_offset = output.state.offset;
+}
+
+/// Places a ruler point where the canvas is clicked. The click area is laid
+/// over the blocks so their headers don't expand or collapse meanwhile.
+fn handle_ruler_clicks(
+    ui: &egui::Ui,
+    state: &mut IntraTileState,
+    canvas_rect: egui::Rect,
+    zoom: f32,
+) {
+    let response = ui
+        .interact(canvas_rect, ui.id().with("ruler"), egui::Sense::click())
+        .on_hover_cursor(egui::CursorIcon::Crosshair);
+    if !response.clicked() {
+        return;
+    }
+    let Some(pos) = response.interact_pointer_pos() else {
+        return;
+    };
+    if state.ruler_points.len() >= 2 {
+        state.ruler_points.clear();
+    }
+    state
+        .ruler_points
+        .push(egui::pos2(0.0, 0.0) + (pos - canvas_rect.min) / zoom);
+}
+
+/// Label of a measurement between two points `unscaled_distance` apart at
+/// 100% zoom, e.g. "240 px (120 at 100%)".
+fn ruler_label(unscaled_distance: f32, zoom: f32) -> String {
+    format!(
+        "{:.0} px ({:.0} at 100%)",
+        unscaled_distance * zoom,
+        unscaled_distance
+    )
+}
+
+/// Draws the ruler points and, once both are placed, a dimension line
+/// between them labelled with the distance.
+fn draw_ruler(
+    painter: &egui::Painter,
+    state: &IntraTileState,
+    canvas_origin: egui::Pos2,
+    zoom: f32,
+    dark_mode: bool,
+) {
+    let color = color_scheme::theme_ruler_color(dark_mode);
+    let points: Vec<egui::Pos2> = state
+        .ruler_points
+        .iter()
+        .map(|p| canvas_origin + p.to_vec2() * zoom)
+        .collect();
+    for &point in &points {
+        painter.circle_filled(point, 3.0, color);
+    }
+    let [start, end] = points[..] else {
+        return;
+    };
+    let stroke = egui::Stroke::new(1.5, color);
+    painter.line_segment([start, end], stroke);
+    // End ticks across the line, as on a dimension line.
+    let direction = (end - start).normalized();
+    if direction.length() > 0.0 {
+        let tick = direction.rot90() * 6.0;
+        for point in [start, end] {
+            painter.line_segment([point - tick, point + tick], stroke);
+        }
+    }
+
+    let unscaled_distance = (state.ruler_points[1] - state.ruler_points[0]).length();
+    let label_pos = egui::pos2((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+    let galley = painter.layout_no_wrap(
+        ruler_label(unscaled_distance, zoom),
+        egui::FontId::proportional(12.0),
+        color,
+    );
+    let text_rect =
+        egui::Align2::CENTER_BOTTOM.anchor_size(label_pos - egui::vec2(0.0, 4.0), galley.size());
+    painter.rect_filled(
+        text_rect.expand(2.0),
+        2.0,
+        color_scheme::theme_ruler_label_bg(dark_mode),
+    );
+    painter.galley(text_rect.min, galley, color);
+}
+
+/// Allocates a canvas large enough for `root_pb` and draws the block on it.
+fn draw_canvas(
+    ui: &mut egui::Ui,
+    root_pb: &PBType,
+    state: &mut IntraTileState,
+    expand_all: bool,
+    draw_interconnects: bool,
+    palette: Palette,
+    dark_mode: bool,
+) -> (egui::Response, egui::Painter) {
+    let zoom = state.zoom_clamped();
+    let (response, painter) = ui.allocate_painter(
+        canvas_size(root_pb, state),
+        // Important: don't capture drags here, otherwise it prevents the
+        // ScrollArea from receiving drag-to-pan gestures.
+        egui::Sense::hover(),
+    );
+    if state.show_canvas_grid {
+        draw_canvas_grid(&painter, response.rect, zoom, dark_mode);
+    }
+    let start_pos = response.rect.min + egui::vec2(20.0, 20.0) * zoom;
+
+    let _ = draw_pb_type(
+        &painter,
+        root_pb,
+        start_pos,
+        state,
+        &root_pb.name,
+        ui,
+        expand_all,
+        draw_interconnects,
+        palette,
+        dark_mode,
+    );
+    (response, painter)
+}
+
+/// Dots every CANVAS_GRID_SPACING pixels, at 100% zoom, from the canvas
+/// origin. Only the visible part of the canvas is dotted.
+fn draw_canvas_grid(painter: &egui::Painter, canvas_rect: egui::Rect, zoom: f32, dark_mode: bool) {
+    let spacing = CANVAS_GRID_SPACING * zoom;
+    let visible = canvas_rect.intersect(painter.clip_rect());
+    if !visible.is_positive() {
+        return;
+    }
+    let first = ((visible.min - canvas_rect.min) / spacing).ceil();
+    let last = ((visible.max - canvas_rect.min) / spacing).floor();
+    let color = color_scheme::theme_canvas_grid_color(dark_mode);
+    let radius = (1.0 * zoom).max(0.75);
+    let mut dots = Vec::new();
+    for row in first.y as usize..=last.y as usize {
+        for col in first.x as usize..=last.x as usize {
+            let center = canvas_rect.min + egui::vec2(col as f32, row as f32) * spacing;
+            dots.push(egui::Shape::circle_filled(center, radius, color));
+        }
+    }
+    painter.extend(dots);
+}
+
+/// Size of the canvas needed to draw `root_pb` with the current expansion,
+/// modes, and zoom, including the margin around the block.
+pub fn canvas_size(root_pb: &PBType, state: &mut IntraTileState) -> egui::Vec2 {
+    let zoom = state.zoom_clamped();
+    measure_pb_type(root_pb, state, &root_pb.name) + egui::vec2(40.0, 40.0) * zoom
+}
+
+/// Measured size of `root_pb` and of every child instance laid out inside an
+/// expanded block, keyed by instance path and sorted by it.
+pub fn layout_metrics(root_pb: &PBType, state: &mut IntraTileState) -> Vec<(String, egui::Vec2)> {
+    let mut metrics = Vec::new();
+    collect_layout_metrics(root_pb, state, &root_pb.name, &mut metrics);
+    metrics.sort_by(|a, b| a.0.cmp(&b.0));
+    metrics
+}
+
+fn collect_layout_metrics(
+    pb_type: &PBType,
+    state: &mut IntraTileState,
+    instance_path: &str,
+    metrics: &mut Vec<(String, egui::Vec2)>,
+) {
+    let size = measure_pb_type(pb_type, state, instance_path);
+    metrics.push((instance_path.to_string(), size));
+    if !state.is_drawn_expanded(instance_path) {
+        return;
+    }
+
+    let mode_index = state.selected_mode(pb_type, instance_path);
+    for child_pb in get_children_for_mode(pb_type, mode_index) {
+        for i in 0..visible_instance_count(state, instance_path, child_pb) {
+            let instance_name = generate_child_instance_name(child_pb, i);
+            let child_path = format!("{}.{}", instance_path, instance_name);
+            collect_layout_metrics(child_pb, state, &child_path, metrics);
+        }
+    }
+}
+
+/// Works out the zoom and scroll offset that center `rect` in the viewport on
+/// the next frame. The layout scales linearly with zoom, so the rect's position
+/// at the new zoom can be found without laying the block out again.
+fn focus_canvas_on_rect(
+    state: &mut IntraTileState,
+    rect: egui::Rect,
+    canvas_origin: egui::Pos2,
+    viewport: egui::Rect,
+) {
+    let zoom = state.zoom_clamped();
+    let mut new_zoom = zoom;
+    if state.zoom_to_selection && rect.width() > 0.0 && rect.height() > 0.0 {
+        // Leave a margin around the block so it is comfortably sized.
+        let fit = (viewport.width() / rect.width()).min(viewport.height() / rect.height()) * 0.8;
+        new_zoom = (zoom * fit).clamp(0.2, 4.0);
+        state.zoom = new_zoom;
+    }
+    let center = (rect.center() - canvas_origin) * (new_zoom / zoom);
+    state.pending_scroll_offset = Some((center - viewport.size() / 2.0).max(egui::Vec2::ZERO));
 }
 
 fn render_visual_layout_controls(ui: &mut egui::Ui, state: &mut IntraTileState) {
@@ -181,25 +749,80 @@ fn render_visual_layout_controls(ui: &mut egui::Ui, state: &mut IntraTileState)
         if ui.small_button("Reset").clicked() {
             state.zoom = 1.0;
         }
+
+        ui.add_space(20.0);
+        ui.label("Spacing:");
+        ui.add(egui::Slider::new(&mut state.padding, MIN_PADDING..=MAX_PADDING).suffix(" px"))
+            .on_hover_text("Space around and between child blocks");
+        if ui.small_button("Reset").clicked() {
+            state.padding = DEFAULT_PADDING;
+        }
+
+        ui.add_space(20.0);
+        if ui
+            .toggle_value(&mut state.ruler_mode, "📏 Ruler")
+            .on_hover_text(
+                "Click two points on the canvas to measure the distance between them. \
+                 A third click starts over and Escape clears.",
+            )
+            .changed()
+        {
+            state.ruler_points.clear();
+        }
     });
 }
 
-pub fn render_intra_tile_view(
-    ui: &mut egui::Ui,
-    root_pb: &PBType,
-    state: &mut IntraTileState,
-    expand_all: bool,
-    draw_interconnects: bool,
-    dark_mode: bool,
-) {
+/// Resets the per-frame state before `root_pb` is drawn.
+fn begin_frame(root_pb: &PBType, state: &mut IntraTileState) {
     // Clear per-frame PB rects before drawing
     state.pb_rects.clear();
+    state.unresolved_refs.clear();
+    state.draw_stats = DrawStats::default();
     // Clear measurement cache at start of each frame to ensure fresh calculations
     // when expanded_blocks or selected_modes change
     state.measurement_cache.clear();
 
     state.highlighted_positions_this_frame =
         std::mem::take(&mut state.highlighted_positions_next_frame);
+    state.pack_pattern_names.clear();
+    collect_pack_pattern_names(root_pb, &mut state.pack_pattern_names);
+    state.pack_pattern_names.sort();
+    state.pack_pattern_names.dedup();
+}
+
+/// Draws only the block canvas of `root_pb`, without the controls, the
+/// interconnect table, or scrolling. Used to render a complex block offscreen.
+pub fn render_intra_tile_canvas(
+    ui: &mut egui::Ui,
+    root_pb: &PBType,
+    state: &mut IntraTileState,
+    draw_interconnects: bool,
+    palette: Palette,
+    dark_mode: bool,
+) {
+    begin_frame(root_pb, state);
+    let _ = draw_canvas(
+        ui,
+        root_pb,
+        state,
+        false,
+        draw_interconnects,
+        palette,
+        dark_mode,
+    );
+}
+
+pub fn render_intra_tile_view(
+    ui: &mut egui::Ui,
+    root_pb: &PBType,
+    state: &mut IntraTileState,
+    expand_all: bool,
+    draw_interconnects: bool,
+    palette: Palette,
+    dark_mode: bool,
+) {
+    handle_focus_keys(ui, root_pb, state);
+    begin_frame(root_pb, state);
     ui.heading(format!("Complex Block: {}", root_pb.name));
     ui.separator();
 
@@ -209,30 +832,506 @@ pub fn render_intra_tile_view(
     ui.set_width(available_rect.width());
     ui.heading("Visual Layout");
     render_visual_layout_controls(ui, state);
+    render_find_bar(ui, root_pb, state);
+    render_breadcrumbs(ui, root_pb, state);
+    egui::TopBottomPanel::bottom("interconnect_table_panel")
+        .resizable(true)
+        .show_inside(ui, |ui| {
+            egui::CollapsingHeader::new("Interconnects")
+                .id_salt("interconnect_table")
+                .show(ui, |ui| {
+                    render_interconnect_table(ui, root_pb, state);
+                });
+            egui::CollapsingHeader::new("Reachability")
+                .id_salt("reachability_check")
+                .show(ui, |ui| {
+                    render_reachability_check(ui, root_pb, state);
+                });
+        });
+    delay_matrix_view::render_delay_matrix_window(
+        ui.ctx(),
+        &mut state.delay_matrix_popup,
+        dark_mode,
+    );
     render_visual_layout_canvas(
         ui,
         root_pb,
         state,
         expand_all,
         draw_interconnects,
+        palette,
         dark_mode,
     );
 }
 
+/// Shows the path to the last block the user interacted with, e.g.
+/// "clb > fle[2] > ble4". Clicking a segment collapses everything below that
+/// block and brings it into view.
+fn render_breadcrumbs(ui: &mut egui::Ui, root_pb: &PBType, state: &mut IntraTileState) {
+    let path = state
+        .breadcrumb_path
+        .clone()
+        .unwrap_or_else(|| root_pb.name.clone());
+
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.0;
+        let mut segment_path = String::new();
+        for (idx, segment) in path.split('.').enumerate() {
+            if idx > 0 {
+                segment_path.push('.');
+            }
+            segment_path.push_str(segment);
+            // A mode change can leave the tail of the path pointing nowhere.
+            if find_pb_type_by_path(root_pb, state, &segment_path).is_none() {
+                break;
+            }
+            if idx > 0 {
+                ui.weak(">");
+            }
+            if ui
+                .link(segment)
+                .on_hover_text("Collapse everything below this block")
+                .clicked()
+            {
+                clicked = Some(segment_path.clone());
+            }
+        }
+    });
+
+    if let Some(segment_path) = clicked {
+        collapse_below(root_pb, state, &segment_path);
+    }
+}
+
+/// Leaves the block at `instance_path` open with everything below it
+/// collapsed, and brings it into view.
+fn collapse_below(root_pb: &PBType, state: &mut IntraTileState, instance_path: &str) {
+    if find_pb_type_by_path(root_pb, state, instance_path).is_none() {
+        return;
+    }
+    collapse_subtree(state, instance_path);
+    state.expanded_blocks.insert(instance_path.to_string());
+    focus_block(state, instance_path);
+}
+
+/// Gathers the names of the pack patterns on every interconnect in the
+/// hierarchy, across all modes.
+fn collect_pack_pattern_names(pb_type: &PBType, names: &mut Vec<String>) {
+    let interconnects = pb_type.interconnects.iter().chain(
+        pb_type
+            .modes
+            .iter()
+            .flat_map(|mode| mode.interconnects.iter()),
+    );
+    for inter in interconnects {
+        names.extend(inter.pack_patterns.iter().map(|pp| pp.name.clone()));
+    }
+
+    let children = pb_type
+        .pb_types
+        .iter()
+        .chain(pb_type.modes.iter().flat_map(|mode| mode.pb_types.iter()));
+    for child in children {
+        collect_pack_pattern_names(child, names);
+    }
+}
+
+// ------------------------------------------------------------
+// Interconnect Table
+// ------------------------------------------------------------
+/// Lists the interconnects of one block's selected mode. Clicking a row
+/// highlights that interconnect's wires on the canvas.
+fn render_interconnect_table(ui: &mut egui::Ui, root_pb: &PBType, state: &mut IntraTileState) {
+    let table_path = interconnect_block_picker(
+        ui,
+        root_pb,
+        state,
+        state.interconnect_table_path.as_deref(),
+        "interconnect_table_block",
+    );
+    state.interconnect_table_path = Some(table_path.clone());
+
+    let Some(pb_type) = find_pb_type_by_path(root_pb, state, &table_path) else {
+        return;
+    };
+    let mode_index = state.selected_mode(pb_type, &table_path);
+    if let Some(mode) = pb_type.modes.get(mode_index) {
+        ui.label(format!("Mode: {}", mode.name));
+    }
+
+    // Leaf blocks usually carry the delay matrices, so offer those of the
+    // children alongside the interconnects.
+    let matrix_children: Vec<&PBType> = get_children_for_mode(pb_type, mode_index)
+        .iter()
+        .filter(|child| delay_matrix_view::has_delay_matrix(&child.delays))
+        .collect();
+    if !matrix_children.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Block delay matrices:");
+            for child in matrix_children {
+                if ui.small_button(&child.name).clicked() {
+                    state.delay_matrix_popup =
+                        DelayMatrixPopup::from_delays(child.name.clone(), &child.delays);
+                }
+            }
+        });
+    }
+
+    let interconnects = get_interconnects_for_mode(pb_type, mode_index);
+    if interconnects.is_empty() {
+        ui.label("No interconnects in this mode.");
+        return;
+    }
+
+    let mut clicked_interconnect = None;
+    let mut opened_matrix = None;
+    egui_extras::TableBuilder::new(ui)
+        .striped(true)
+        .sense(egui::Sense::click())
+        .column(egui_extras::Column::auto().at_least(80.0))
+        .column(egui_extras::Column::auto().at_least(60.0))
+        .column(egui_extras::Column::auto().at_least(120.0).clip(true))
+        .column(egui_extras::Column::auto().at_least(120.0).clip(true))
+        .column(egui_extras::Column::auto().at_least(120.0).clip(true))
+        .column(egui_extras::Column::remainder())
+        .header(20.0, |mut header| {
+            for title in INTERCONNECT_COLUMNS {
+                header.col(|ui| {
+                    ui.strong(title);
+                });
+            }
+        })
+        .body(|mut body| {
+            for inter in interconnects {
+                let is_selected = state
+                    .selected_interconnect
+                    .as_ref()
+                    .is_some_and(|(path, name)| *path == table_path && *name == inter.name);
+                body.row(20.0, |mut row| {
+                    row.set_selected(is_selected);
+                    for text in interconnect_row_text(inter) {
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(&text).selectable(false))
+                                .on_hover_text(text);
+                        });
+                    }
+                    row.col(|ui| {
+                        if delay_matrix_view::has_delay_matrix(&inter.delays) {
+                            if ui.small_button("Show matrix").clicked() {
+                                opened_matrix = Some(inter);
+                            }
+                        } else {
+                            let text = constant_delays_text(&inter.delays);
+                            ui.add(egui::Label::new(&text).selectable(false))
+                                .on_hover_text(&text);
+                        }
+                    });
+                    if row.response().clicked() {
+                        clicked_interconnect = Some(inter.name.clone());
+                    }
+                });
+            }
+        });
+
+    if let Some(inter) = opened_matrix {
+        state.delay_matrix_popup =
+            DelayMatrixPopup::from_delays(format!("{table_path} / {}", inter.name), &inter.delays);
+    }
+
+    if let Some(name) = clicked_interconnect {
+        let clicked = Some((table_path, name));
+        state.selected_interconnect = if state.selected_interconnect == clicked {
+            None
+        } else {
+            clicked
+        };
+        // The highlight is applied on the frame after it is requested.
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Lets the user pick two ports of a block and reports whether the first can
+/// reach the second through the interconnects of the block's selected mode.
+/// A path that is found stays highlighted on the canvas.
+fn render_reachability_check(ui: &mut egui::Ui, root_pb: &PBType, state: &mut IntraTileState) {
+    let block_path = interconnect_block_picker(
+        ui,
+        root_pb,
+        state,
+        state.reachability.block_path.as_deref(),
+        "reachability_block",
+    );
+    state.reachability.block_path = Some(block_path.clone());
+    let Some(pb_type) = find_pb_type_by_path(root_pb, state, &block_path) else {
+        return;
+    };
+    let mode_index = state.selected_mode(pb_type, &block_path);
+    if let Some(mode) = pb_type.modes.get(mode_index) {
+        ui.label(format!("Mode: {}", mode.name));
+    }
+
+    let check = &mut state.reachability;
+    let picked_before = (
+        check.source.clone(),
+        check.sink.clone(),
+        check.through_children,
+    );
+    let graph = PinGraph::build(pb_type, mode_index, check.through_children);
+    let ports = graph.ports();
+    for (label, port) in [("From:", &mut check.source), ("To:", &mut check.sink)] {
+        if port.as_deref().is_some_and(|name| !ports.contains(&name)) {
+            *port = None;
+        }
+        ui.horizontal(|ui| {
+            ui.label(label);
+            egui::ComboBox::from_id_salt(("reachability_port", label))
+                .selected_text(port.as_deref().unwrap_or("Pick a port"))
+                .show_ui(ui, |ui| {
+                    for name in &ports {
+                        ui.selectable_value(port, Some(name.to_string()), *name);
+                    }
+                });
+        });
+    }
+    ui.checkbox(&mut check.through_children, "Pass through child blocks")
+        .on_hover_text("Treat each child block as connecting every input to every output");
+    // A result no longer applies once the ports or options change.
+    if (
+        check.source.clone(),
+        check.sink.clone(),
+        check.through_children,
+    ) != picked_before
+    {
+        check.result = None;
+    }
+
+    let (Some(source), Some(sink)) = (&check.source, &check.sink) else {
+        return;
+    };
+    if ui.button("Check").clicked() {
+        let path = graph.find_path(source, sink);
+        check.result = Some(ReachabilityResult {
+            instance_path: block_path.clone(),
+            mode_index,
+            path,
+        });
+        // The highlight is applied on the frame after it is requested.
+        ui.ctx().request_repaint();
+    }
+
+    // Only report a result for the block and mode it was found in.
+    let Some(result) = check
+        .result
+        .as_ref()
+        .filter(|result| result.is_for(&block_path, mode_index))
+    else {
+        return;
+    };
+    match &result.path {
+        Some(path) => {
+            ui.label(format!(
+                "Path found ({} hops):",
+                path.len().saturating_sub(1)
+            ));
+            ui.add(egui::Label::new(egui::RichText::new(path.join(" → ")).monospace()).wrap());
+        }
+        None => {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("No path from {source} to {sink} in this mode."),
+            );
+        }
+    }
+}
+
+/// Lets the user pick the root or an expanded block, whose selected mode the
+/// interconnect table or the reachability check looks at. `picked` is the
+/// block picked before. Returns the block's instance path.
+fn interconnect_block_picker(
+    ui: &mut egui::Ui,
+    root_pb: &PBType,
+    state: &IntraTileState,
+    picked: Option<&str>,
+    id_salt: &str,
+) -> String {
+    // Offer the root and every expanded block, since only those show wires.
+    let mut block_paths = vec![root_pb.name.clone()];
+    let mut expanded_paths: Vec<&String> = state
+        .expanded_blocks
+        .iter()
+        .filter(|path| **path != root_pb.name)
+        .filter(|path| find_pb_type_by_path(root_pb, state, path).is_some())
+        .collect();
+    expanded_paths.sort();
+    block_paths.extend(expanded_paths.into_iter().cloned());
+
+    let mut block_path = picked
+        .filter(|path| block_paths.iter().any(|p| p == path))
+        .unwrap_or(&root_pb.name)
+        .to_string();
+    ui.horizontal(|ui| {
+        ui.label("Block:");
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(&block_path)
+            .show_ui(ui, |ui| {
+                for path in &block_paths {
+                    ui.selectable_value(&mut block_path, path.clone(), path);
+                }
+            });
+    });
+    block_path
+}
+
+/// Column titles of the interconnect table and its CSV export.
+const INTERCONNECT_COLUMNS: [&str; 6] =
+    ["Name", "Type", "Input", "Output", "Pack Patterns", "Delays"];
+
+/// Text of every column of an interconnect's row except the delays.
+fn interconnect_row_text(inter: &fpga_arch_parser::Interconnect) -> [String; 5] {
+    let kind = match inter.interconnect_type {
+        fpga_arch_parser::InterconnectType::Direct => "direct",
+        fpga_arch_parser::InterconnectType::Mux => "mux",
+        fpga_arch_parser::InterconnectType::Complete => "complete",
+    };
+    let pack_patterns = inter
+        .pack_patterns
+        .iter()
+        .map(|pp| pp.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    [
+        inter.name.clone(),
+        kind.to_string(),
+        inter.input.clone(),
+        inter.output.clone(),
+        pack_patterns,
+    ]
+}
+
+/// Writes the interconnects of the block the interconnect table shows as
+/// CSV. Returns the block's instance path along with the text.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn interconnect_table_csv(root_pb: &PBType, state: &IntraTileState) -> (String, String) {
+    let (table_path, pb_type) = state
+        .interconnect_table_path
+        .as_ref()
+        .and_then(|path| Some((path.clone(), find_pb_type_by_path(root_pb, state, path)?)))
+        .unwrap_or_else(|| (root_pb.name.clone(), root_pb));
+    let mode_index = state.selected_mode(pb_type, &table_path);
+
+    let mut csv = INTERCONNECT_COLUMNS.join(",");
+    csv.push('\n');
+    for inter in get_interconnects_for_mode(pb_type, mode_index) {
+        let mut delays = constant_delays_text(&inter.delays);
+        if delay_matrix_view::has_delay_matrix(&inter.delays) {
+            if !delays.is_empty() {
+                delays.push_str(", ");
+            }
+            delays.push_str("matrix");
+        }
+        let fields: Vec<String> = interconnect_row_text(inter)
+            .into_iter()
+            .chain(std::iter::once(delays))
+            .map(|field| csv_field(&field))
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    (table_path, csv)
+}
+
+/// Quotes a CSV field if it contains a separator, quote, or line break.
+#[cfg(not(target_arch = "wasm32"))]
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Short summary of the constant delays of an interconnect, e.g. "max 1e-10".
+fn constant_delays_text(delays: &[fpga_arch_parser::DelayInfo]) -> String {
+    delays
+        .iter()
+        .filter_map(|delay| match delay {
+            fpga_arch_parser::DelayInfo::Constant { min, max, .. } if min == max => {
+                Some(format!("{max:e}"))
+            }
+            fpga_arch_parser::DelayInfo::Constant { min, max, .. } => {
+                Some(format!("{min:e}..{max:e}"))
+            }
+            fpga_arch_parser::DelayInfo::Matrix { .. } => None,
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Finds the pb_type drawn at `instance_path`, following the selected mode of
+/// each block along the way.
+fn find_pb_type_by_path<'a>(
+    root_pb: &'a PBType,
+    state: &IntraTileState,
+    instance_path: &str,
+) -> Option<&'a PBType> {
+    let mut segments = instance_path.split('.');
+    if segments.next()? != root_pb.name {
+        return None;
+    }
+
+    let mut pb_type = root_pb;
+    let mut path = root_pb.name.clone();
+    for segment in segments {
+        let mode_index = state.selected_mode(pb_type, &path);
+        pb_type = get_children_for_mode(pb_type, mode_index)
+            .iter()
+            .find(|child| {
+                (0..child.num_pb).any(|i| generate_child_instance_name(child, i) == segment)
+            })?;
+        path = format!("{path}.{segment}");
+    }
+    Some(pb_type)
+}
+
+/// The tag and source line of what is selected in the complex block view:
+/// the picked interconnect, else the picked block, else the root block.
+pub fn selected_source_element(
+    root_pb: &PBType,
+    state: &IntraTileState,
+) -> Option<(&'static str, usize)> {
+    if let Some((instance_path, name)) = &state.selected_interconnect {
+        let pb_type = find_pb_type_by_path(root_pb, state, instance_path)?;
+        let mode_index = state.selected_mode(pb_type, instance_path);
+        let interconnect = get_interconnects_for_mode(pb_type, mode_index)
+            .iter()
+            .find(|interconnect| interconnect.name == *name)?;
+        let tag = match interconnect.interconnect_type {
+            fpga_arch_parser::InterconnectType::Direct => "direct",
+            fpga_arch_parser::InterconnectType::Mux => "mux",
+            fpga_arch_parser::InterconnectType::Complete => "complete",
+        };
+        return Some((tag, interconnect.source_line.0?));
+    }
+    let pb_type = match &state.selected_block_path {
+        Some(instance_path) => find_pb_type_by_path(root_pb, state, instance_path)?,
+        None => root_pb,
+    };
+    Some(("pb_type", pb_type.source_line.0?))
+}
+
 // ------------------------------------------------------------
 // Expand Block Feature
 // ------------------------------------------------------------
 pub fn expand_all_blocks(state: &mut IntraTileState, pb_type: &PBType, instance_path: &str) {
+    // Blocks past the render depth are drawn collapsed anyway, and walking
+    // every instance of a huge hierarchy would stall the UI.
+    if state.at_max_render_depth(instance_path) {
+        return;
+    }
     state.expanded_blocks.insert(instance_path.to_string());
 
-    let mode_index = *state.selected_modes.get(instance_path).unwrap_or(&0);
-    let mode_index = validate_mode_index(pb_type, mode_index);
-    // Update state with validated mode index if it was corrected
-    if mode_index != *state.selected_modes.get(instance_path).unwrap_or(&0) {
-        state
-            .selected_modes
-            .insert(instance_path.to_string(), mode_index);
-    }
+    let mode_index = state.selected_mode(pb_type, instance_path);
     let children = get_children_for_mode(pb_type, mode_index);
 
     for child_pb in children {
@@ -244,8 +1343,369 @@ pub fn expand_all_blocks(state: &mut IntraTileState, pb_type: &PBType, instance_
     }
 }
 
+/// Collapses the block at `instance_path` and every block below it, under
+/// any mode, leaving the rest of the tree as it is.
+pub fn collapse_subtree(state: &mut IntraTileState, instance_path: &str) {
+    let prefix = format!("{}.", instance_path);
+    state
+        .expanded_blocks
+        .retain(|path| path != instance_path && !path.starts_with(&prefix));
+    state
+        .expanded_arrays
+        .retain(|path| !path.starts_with(&prefix));
+}
+
+/// Expands the root block and its descendants down to `depth` levels, so a
+/// depth of 1 shows the root's children without opening them.
+pub fn expand_blocks_to_depth(state: &mut IntraTileState, root_pb: &PBType, depth: usize) {
+    expand_to_depth(state, root_pb, &root_pb.name, depth);
+}
+
+fn expand_to_depth(
+    state: &mut IntraTileState,
+    pb_type: &PBType,
+    instance_path: &str,
+    depth: usize,
+) {
+    if depth == 0 || state.at_max_render_depth(instance_path) {
+        return;
+    }
+    state.expanded_blocks.insert(instance_path.to_string());
+
+    let mode_index = state.selected_mode(pb_type, instance_path);
+    for child_pb in get_children_for_mode(pb_type, mode_index) {
+        for i in 0..child_pb.num_pb {
+            let instance_name = generate_child_instance_name(child_pb, i);
+            let child_path = format!("{}.{}", instance_path, instance_name);
+            expand_to_depth(state, child_pb, &child_path, depth - 1);
+        }
+    }
+}
+
+/// Expands a block and collapses every sibling instance under the same parent,
+/// leaving only one branch open at that level.
+pub fn expand_block_collapsing_siblings(state: &mut IntraTileState, instance_path: &str) {
+    // The root block has no parent, so it has no siblings.
+    if let Some((parent_path, _)) = instance_path.rsplit_once('.') {
+        state
+            .expanded_blocks
+            .retain(|path| match path.rsplit_once('.') {
+                Some((path_parent, _)) => path_parent != parent_path,
+                None => true,
+            });
+    }
+    state.expanded_blocks.insert(instance_path.to_string());
+}
+
+/// Selects the block at `instance_path` and expands every block above it, so
+/// the canvas can bring it into view on the next frame it is drawn.
+pub fn focus_block(state: &mut IntraTileState, instance_path: &str) {
+    let mut parent_path: Option<String> = None;
+    for segment in instance_path.split('.') {
+        if let Some(parent_path) = &parent_path {
+            state.expanded_blocks.insert(parent_path.clone());
+            // Only the first instance of a collapsed array is drawn.
+            if let Some((child_name, index)) = segment.split_once('[')
+                && index.trim_end_matches(']') != "0"
+            {
+                state
+                    .expanded_arrays
+                    .insert(format!("{}.{}", parent_path, child_name));
+            }
+        }
+        parent_path = Some(match parent_path {
+            Some(parent_path) => format!("{}.{}", parent_path, segment),
+            None => segment.to_string(),
+        });
+    }
+    state.selected_block_path = Some(instance_path.to_string());
+    state.breadcrumb_path = Some(instance_path.to_string());
+    state.focused_block_path = Some(instance_path.to_string());
+    state.focus_selected_block = true;
+}
+
+// ------------------------------------------------------------
+// Keyboard Navigation
+// ------------------------------------------------------------
+
+/// A step of the keyboard focus through the block hierarchy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusMove {
+    Parent,
+    FirstChild,
+    PreviousSibling,
+    NextSibling,
+}
+
+/// Moves the keyboard focus with the arrow keys and toggles the focused
+/// block's expansion with Enter. Ctrl+F focuses the find bar. Other keys are
+/// left alone while a text field has focus.
+fn handle_focus_keys(ui: &egui::Ui, root_pb: &PBType, state: &mut IntraTileState) {
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)) {
+        state.focus_find_field = true;
+    }
+    if ui.ctx().wants_keyboard_input() {
+        return;
+    }
+    let key_moves = [
+        (egui::Key::ArrowUp, FocusMove::Parent),
+        (egui::Key::ArrowDown, FocusMove::FirstChild),
+        (egui::Key::ArrowLeft, FocusMove::PreviousSibling),
+        (egui::Key::ArrowRight, FocusMove::NextSibling),
+    ];
+    for (key, focus_move) in key_moves {
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key)) {
+            move_focus(root_pb, state, focus_move);
+        }
+    }
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)) {
+        toggle_focused_block(root_pb, state);
+    }
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+        // Escape clears a measurement first, then the focus.
+        if state.ruler_points.is_empty() {
+            state.focused_block_path = None;
+        } else {
+            state.ruler_points.clear();
+        }
+    }
+}
+
+/// Moves the keyboard focus one step through the hierarchy. Moving to a
+/// child expands the focused block, and siblings are limited to the
+/// instances that are drawn. With nothing focused, the root block takes the
+/// focus.
+pub fn move_focus(root_pb: &PBType, state: &mut IntraTileState, focus_move: FocusMove) {
+    let focused = state
+        .focused_block_path
+        .clone()
+        .filter(|path| find_pb_type_by_path(root_pb, state, path).is_some());
+    let Some(focused) = focused else {
+        set_focus(state, &root_pb.name);
+        return;
+    };
+
+    let target = match focus_move {
+        FocusMove::Parent => focused
+            .rsplit_once('.')
+            .map(|(parent_path, _)| parent_path.to_string()),
+        FocusMove::FirstChild if state.at_max_render_depth(&focused) => None,
+        FocusMove::FirstChild => {
+            let first_child = find_pb_type_by_path(root_pb, state, &focused).and_then(|pb_type| {
+                child_instance_paths(state, pb_type, &focused)
+                    .first()
+                    .cloned()
+            });
+            if first_child.is_some() {
+                state.expanded_blocks.insert(focused.clone());
+            }
+            first_child
+        }
+        FocusMove::PreviousSibling | FocusMove::NextSibling => {
+            focused.rsplit_once('.').and_then(|(parent_path, _)| {
+                let parent_pb = find_pb_type_by_path(root_pb, state, parent_path)?;
+                let siblings = child_instance_paths(state, parent_pb, parent_path);
+                let index = siblings.iter().position(|path| *path == focused)?;
+                let index = match focus_move {
+                    FocusMove::PreviousSibling => index.checked_sub(1)?,
+                    _ => index + 1,
+                };
+                siblings.get(index).cloned()
+            })
+        }
+    };
+    if let Some(target) = target {
+        set_focus(state, &target);
+    }
+}
+
+/// Expands the focused block, or collapses it if it is open.
+pub fn toggle_focused_block(root_pb: &PBType, state: &mut IntraTileState) {
+    let Some(focused) = state.focused_block_path.clone() else {
+        return;
+    };
+    let Some(pb_type) = find_pb_type_by_path(root_pb, state, &focused) else {
+        return;
+    };
+    if child_instance_paths(state, pb_type, &focused).is_empty()
+        || state.at_max_render_depth(&focused)
+    {
+        return;
+    }
+    if !state.expanded_blocks.remove(&focused) {
+        state.expanded_blocks.insert(focused.clone());
+    }
+    state.scroll_to_focused = true;
+}
+
+fn set_focus(state: &mut IntraTileState, instance_path: &str) {
+    state.focused_block_path = Some(instance_path.to_string());
+    state.breadcrumb_path = Some(instance_path.to_string());
+    state.scroll_to_focused = true;
+}
+
+/// Paths of the child instances drawn inside the block at `instance_path`
+/// when it is expanded, in drawing order.
+fn child_instance_paths(
+    state: &IntraTileState,
+    pb_type: &PBType,
+    instance_path: &str,
+) -> Vec<String> {
+    let mode_index = state.selected_mode(pb_type, instance_path);
+    get_children_for_mode(pb_type, mode_index)
+        .iter()
+        .flat_map(|child_pb| {
+            (0..visible_instance_count(state, instance_path, child_pb)).map(move |i| {
+                format!(
+                    "{}.{}",
+                    instance_path,
+                    generate_child_instance_name(child_pb, i)
+                )
+            })
+        })
+        .collect()
+}
+
+// ------------------------------------------------------------
+// Find
+// ------------------------------------------------------------
+
+/// A search field for blocks by instance name. Enter steps to the next match
+/// and Shift+Enter to the previous one, expanding the hierarchy down to it and
+/// scrolling it into view.
+fn render_find_bar(ui: &mut egui::Ui, root_pb: &PBType, state: &mut IntraTileState) {
+    let mut step = None;
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.find_query)
+                .hint_text("Find block (Ctrl+F)")
+                .desired_width(200.0),
+        );
+        if std::mem::take(&mut state.focus_find_field) {
+            response.request_focus();
+        }
+        if response.changed() {
+            state.find_matches = find_in_hierarchy(root_pb, state, &state.find_query);
+            state.find_current = None;
+        }
+        // A single line field gives up the focus on Enter; take it back so
+        // Enter can be pressed again for the next match.
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            step = Some(!ui.input(|i| i.modifiers.shift));
+            response.request_focus();
+        }
+
+        let has_matches = !state.find_matches.is_empty();
+        if ui
+            .add_enabled(has_matches, egui::Button::new("⏶").small())
+            .on_hover_text("Previous match (Shift+Enter)")
+            .clicked()
+        {
+            step = Some(false);
+        }
+        if ui
+            .add_enabled(has_matches, egui::Button::new("⏷").small())
+            .on_hover_text("Next match (Enter)")
+            .clicked()
+        {
+            step = Some(true);
+        }
+        if let Some(status) = find_status(state) {
+            ui.weak(status);
+        }
+    });
+
+    if let Some(forward) = step {
+        step_find(root_pb, state, forward);
+    }
+}
+
+/// "match 2 of 7" once a match is stepped to, the number of matches before
+/// that, and None with nothing to find.
+fn find_status(state: &IntraTileState) -> Option<String> {
+    if state.find_query.trim().is_empty() {
+        return None;
+    }
+    let count = state.find_matches.len();
+    Some(match (state.find_current, count) {
+        (_, 0) => "no matches".to_string(),
+        (Some(current), _) => format!("match {} of {count}", current + 1),
+        (None, 1) => "1 match".to_string(),
+        (None, _) => format!("{count} matches"),
+    })
+}
+
+/// Paths of the block instances under `root_pb` whose instance name contains
+/// `query`, ignoring case, in drawing order. Blocks follow their selected
+/// modes, and blocks below the max render depth are left out as they are
+/// never drawn.
+pub fn find_in_hierarchy(root_pb: &PBType, state: &IntraTileState, query: &str) -> Vec<String> {
+    let query = query.trim().to_lowercase();
+    let mut matches = Vec::new();
+    if !query.is_empty() {
+        collect_find_matches(state, root_pb, &root_pb.name, &query, &mut matches);
+    }
+    matches
+}
+
+fn collect_find_matches(
+    state: &IntraTileState,
+    pb_type: &PBType,
+    instance_path: &str,
+    query: &str,
+    matches: &mut Vec<String>,
+) {
+    let instance_name = instance_path.rsplit('.').next().unwrap_or(instance_path);
+    if instance_name.to_lowercase().contains(query) {
+        matches.push(instance_path.to_string());
+    }
+    if state.at_max_render_depth(instance_path) {
+        return;
+    }
+
+    let mode_index = state.selected_mode(pb_type, instance_path);
+    for child_pb in get_children_for_mode(pb_type, mode_index) {
+        for i in 0..child_pb.num_pb {
+            let child_path = format!(
+                "{}.{}",
+                instance_path,
+                generate_child_instance_name(child_pb, i)
+            );
+            collect_find_matches(state, child_pb, &child_path, query, matches);
+        }
+    }
+}
+
+/// Steps to the next match, or the previous one, wrapping around at either
+/// end, and brings it into view. The matches are gathered again first, since
+/// a mode change can add or remove blocks.
+pub fn step_find(root_pb: &PBType, state: &mut IntraTileState, forward: bool) {
+    let current_path = state
+        .find_current
+        .and_then(|index| state.find_matches.get(index).cloned());
+    state.find_matches = find_in_hierarchy(root_pb, state, &state.find_query);
+    let count = state.find_matches.len();
+    if count == 0 {
+        state.find_current = None;
+        return;
+    }
+
+    let index =
+        match current_path.and_then(|path| state.find_matches.iter().position(|m| *m == path)) {
+            Some(index) if forward => (index + 1) % count,
+            Some(index) => (index + count - 1) % count,
+            None if forward => 0,
+            None => count - 1,
+        };
+    state.find_current = Some(index);
+    let path = state.find_matches[index].clone();
+    focus_block(state, &path);
+}
+
 pub fn collapse_all_blocks(state: &mut IntraTileState) {
     state.expanded_blocks.clear();
+    state.expanded_arrays.clear();
 }
 
 // ------------------------------------------------------------
@@ -272,28 +1732,6 @@ fn estimate_text_width(font: &egui::FontId, text: &str) -> f32 {
     text.len() as f32 * char_width
 }
 
-/// Represents the type of port to count.
-#[derive(Clone, Copy)]
-enum PortType {
-    Input,
-    Output,
-    Clock,
-}
-
-/// Counts pins of a specific type in a PBType.
-fn count_pins(pb_type: &PBType, port_type: PortType) -> usize {
-    pb_type
-        .ports
-        .iter()
-        .filter_map(|p| match (port_type, p) {
-            (PortType::Input, Port::Input(ip)) => Some(ip.num_pins),
-            (PortType::Output, Port::Output(op)) => Some(op.num_pins),
-            (PortType::Clock, Port::Clock(cp)) => Some(cp.num_pins),
-            _ => None,
-        })
-        .sum()
-}
-
 /// Calculates the header name width
 fn calculate_header_name_width(pb_type: &PBType, has_children: bool, zoom: f32) -> f32 {
     let font = egui::FontId::proportional(14.0 * zoom);
@@ -316,7 +1754,10 @@ fn calculate_blif_model_width(pb_type: &PBType, zoom: f32) -> f32 {
         PBTypeClass::None => {
             if let Some(blif_model) = &pb_type.blif_model {
                 let blif_font = egui::FontId::monospace(14.0 * zoom);
-                estimate_text_width(&blif_font, blif_model) + 20.0 * zoom
+                estimate_text_width(
+                    &blif_font,
+                    &intra_block_drawing::blif_model_label(blif_model),
+                ) + 20.0 * zoom
             } else {
                 0.0
             }
@@ -325,7 +1766,7 @@ fn calculate_blif_model_width(pb_type: &PBType, zoom: f32) -> f32 {
     }
 }
 
-fn generate_child_instance_name(child_pb: &PBType, index: usize) -> String {
+pub(crate) fn generate_child_instance_name(child_pb: &PBType, index: usize) -> String {
     if child_pb.num_pb == 1 {
         child_pb.name.clone()
     } else {
@@ -333,9 +1774,27 @@ fn generate_child_instance_name(child_pb: &PBType, index: usize) -> String {
     }
 }
 
+fn array_key(instance_path: &str, child_pb: &PBType) -> String {
+    format!("{}.{}", instance_path, child_pb.name)
+}
+
+/// Number of instances of `child_pb` to lay out. Large arrays only show their
+/// first instance until they are expanded.
+fn visible_instance_count(state: &IntraTileState, instance_path: &str, child_pb: &PBType) -> usize {
+    if child_pb.num_pb > ARRAY_COLLAPSE_THRESHOLD
+        && !state
+            .expanded_arrays
+            .contains(&array_key(instance_path, child_pb))
+    {
+        1
+    } else {
+        child_pb.num_pb
+    }
+}
+
 /// Validates and corrects a mode index for a PBType, ensuring it's within bounds.
 /// Returns a valid mode index (defaults to 0 if out of bounds).
-fn validate_mode_index(pb_type: &PBType, mode_index: usize) -> usize {
+pub(crate) fn validate_mode_index(pb_type: &PBType, mode_index: usize) -> usize {
     if pb_type.modes.is_empty() {
         0 // No modes, index doesn't matter
     } else if mode_index < pb_type.modes.len() {
@@ -345,7 +1804,7 @@ fn validate_mode_index(pb_type: &PBType, mode_index: usize) -> usize {
     }
 }
 
-fn get_children_for_mode(pb_type: &PBType, mode_index: usize) -> &[PBType] {
+pub(crate) fn get_children_for_mode(pb_type: &PBType, mode_index: usize) -> &[PBType] {
     if !pb_type.modes.is_empty() {
         if mode_index < pb_type.modes.len() {
             &pb_type.modes[mode_index].pb_types
@@ -357,7 +1816,58 @@ fn get_children_for_mode(pb_type: &PBType, mode_index: usize) -> &[PBType] {
     }
 }
 
-fn get_interconnects_for_mode(
+/// Pairs the pins of a direct interconnect. Buses are aligned by numeric
+/// index when both sides are indexed, so fle[0] maps to O[0], fle[1] to
+/// O[1], etc. An outer index (before the dot) is compared first, then an
+/// inner bit index. If either side lacks indices or lengths differ, the
+/// order is kept.
+pub(crate) fn align_direct_wires(
+    mut sources: Vec<String>,
+    mut sinks: Vec<String>,
+) -> (Vec<String>, Vec<String>) {
+    let parse_indices = |s: &str| {
+        // outer like "fle[3].out[0]"
+        let outer = s.find('[').and_then(|start| {
+            s[start + 1..]
+                .find(']')
+                .and_then(|e| s[start + 1..start + 1 + e].parse::<i32>().ok())
+        });
+        // inner like ".out[0]"
+        let inner = s.rfind('[').and_then(|start| {
+            s[start + 1..]
+                .find(']')
+                .and_then(|e| s[start + 1..start + 1 + e].parse::<i32>().ok())
+        });
+        (outer, inner)
+    };
+
+    let src_all_idx = sources.iter().all(|s| parse_indices(s).0.is_some());
+    let dst_all_idx = sinks.iter().all(|s| parse_indices(s).0.is_some());
+
+    if src_all_idx && dst_all_idx && sources.len() == sinks.len() {
+        let mut src_with = sources
+            .into_iter()
+            .filter_map(|s| {
+                let (o, i) = parse_indices(&s);
+                Some(((o?, i.unwrap_or(0)), s))
+            })
+            .collect::<Vec<_>>();
+        let mut dst_with = sinks
+            .into_iter()
+            .filter_map(|d| {
+                let (o, i) = parse_indices(&d);
+                Some(((o?, i.unwrap_or(0)), d))
+            })
+            .collect::<Vec<_>>();
+        src_with.sort_by_key(|(k, _)| *k);
+        dst_with.sort_by_key(|(k, _)| *k);
+        sources = src_with.into_iter().map(|(_, s)| s).collect();
+        sinks = dst_with.into_iter().map(|(_, d)| d).collect();
+    }
+    (sources, sinks)
+}
+
+pub(crate) fn get_interconnects_for_mode(
     pb_type: &PBType,
     mode_index: usize,
 ) -> &[fpga_arch_parser::Interconnect] {
@@ -378,15 +1888,8 @@ fn measure_pb_type(
     instance_path: &str,
 ) -> egui::Vec2 {
     let zoom = state.zoom_clamped();
-    let is_expanded = state.expanded_blocks.contains(instance_path);
-    let mut mode_index = *state.selected_modes.get(instance_path).unwrap_or(&0);
-    mode_index = validate_mode_index(pb_type, mode_index);
-    // Update state with validated mode index if it was corrected
-    if mode_index != *state.selected_modes.get(instance_path).unwrap_or(&0) {
-        state
-            .selected_modes
-            .insert(instance_path.to_string(), mode_index);
-    }
+    let is_expanded = state.is_drawn_expanded(instance_path);
+    let mode_index = state.selected_mode(pb_type, instance_path);
 
     // Check cache first
     let cache_key = (instance_path.to_string(), is_expanded, mode_index);
@@ -400,16 +1903,19 @@ fn measure_pb_type(
         let header_name_width_with_selector = calculate_header_name_width(pb_type, true, zoom);
         let blif_model_width = calculate_blif_model_width(pb_type, zoom);
 
-        let min_width = (MIN_BLOCK_SIZE.x * zoom)
+        let mut min_width = (MIN_BLOCK_SIZE.x * zoom)
             .max(header_name_width_with_selector)
             .max(blif_model_width);
+        if state.at_max_render_depth(instance_path) {
+            min_width += MAX_DEPTH_MARKER_WIDTH * zoom;
+        }
         return egui::vec2(min_width, HEADER_HEIGHT * zoom);
     }
 
     if children.is_empty() {
-        let total_input_pins = count_pins(pb_type, PortType::Input);
-        let total_output_pins = count_pins(pb_type, PortType::Output);
-        let total_clock_pins = count_pins(pb_type, PortType::Clock);
+        let total_input_pins = pb_type.num_input_pins();
+        let total_output_pins = pb_type.num_output_pins();
+        let total_clock_pins = pb_type.num_clock_pins();
 
         let max_side_pins = total_input_pins.max(total_output_pins) as f32;
         let min_height_for_pins = if max_side_pins > 0.0 {
@@ -448,25 +1954,30 @@ fn measure_pb_type(
             let mut current_h: f32 = 0.0;
 
             for child_pb in children {
-                let num = child_pb.num_pb as f32;
-                let gaps = child_pb.num_pb.saturating_sub(1) as f32;
+                let num_visible = visible_instance_count(state, instance_path, child_pb);
+                let num = num_visible as f32;
+                let gaps = num_visible.saturating_sub(1) as f32;
 
                 let mut max_instance_size = egui::vec2(0.0, 0.0);
 
-                for i in 0..child_pb.num_pb {
+                for i in 0..num_visible {
                     let child_instance_name = generate_child_instance_name(child_pb, i);
                     let child_path = format!("{}.{}", instance_path, child_instance_name);
                     let s = measure_pb_type(child_pb, state, &child_path);
                     max_instance_size = max_instance_size.max(s);
                 }
 
-                let total_instances_h = max_instance_size.y * num + (PADDING * zoom) * gaps;
+                let mut total_instances_h =
+                    max_instance_size.y * num + (state.padding * zoom) * gaps;
+                if num_visible < child_pb.num_pb {
+                    total_instances_h += (ARRAY_BADGE_GAP + ARRAY_BADGE_HEIGHT) * zoom;
+                }
 
                 max_child_w = max_child_w.max(max_instance_size.x);
-                current_h += total_instances_h + (PADDING * zoom);
+                current_h += total_instances_h + (state.padding * zoom);
             }
             if !children.is_empty() {
-                current_h -= PADDING * zoom;
+                current_h -= state.padding * zoom;
             }
 
             total_w = max_child_w;
@@ -477,26 +1988,31 @@ fn measure_pb_type(
             let mut current_w: f32 = 0.0;
 
             for child_pb in children {
-                let num = child_pb.num_pb as f32;
-                let gaps = child_pb.num_pb.saturating_sub(1) as f32;
+                let num_visible = visible_instance_count(state, instance_path, child_pb);
+                let num = num_visible as f32;
+                let gaps = num_visible.saturating_sub(1) as f32;
 
                 let mut max_instance_size = egui::vec2(0.0, 0.0);
-                for i in 0..child_pb.num_pb {
+                for i in 0..num_visible {
                     let child_instance_name = generate_child_instance_name(child_pb, i);
                     let child_path = format!("{}.{}", instance_path, child_instance_name);
                     let s = measure_pb_type(child_pb, state, &child_path);
                     max_instance_size = max_instance_size.max(s);
                 }
 
-                let child_instances_h = max_instance_size.y * num + (PADDING * zoom) * gaps;
+                let mut child_instances_h =
+                    max_instance_size.y * num + (state.padding * zoom) * gaps;
+                if num_visible < child_pb.num_pb {
+                    child_instances_h += (ARRAY_BADGE_GAP + ARRAY_BADGE_HEIGHT) * zoom;
+                }
                 let child_instances_w = max_instance_size.x;
 
                 max_child_h = max_child_h.max(child_instances_h);
-                current_w += child_instances_w + (PADDING * zoom);
+                current_w += child_instances_w + (state.padding * zoom);
             }
 
             if !children.is_empty() {
-                current_w -= PADDING * zoom;
+                current_w -= state.padding * zoom;
             }
 
             total_w = current_w;
@@ -504,8 +2020,8 @@ fn measure_pb_type(
         }
     }
 
-    let total_input_pins = count_pins(pb_type, PortType::Input);
-    let total_output_pins = count_pins(pb_type, PortType::Output);
+    let total_input_pins = pb_type.num_input_pins();
+    let total_output_pins = pb_type.num_output_pins();
     let max_pins = total_input_pins.max(total_output_pins) as f32;
     let min_port_height = (max_pins + 1.0) * (MIN_PIN_SPACING * zoom);
 
@@ -516,10 +2032,11 @@ fn measure_pb_type(
         )
     });
 
+    let clock_ports = ClockPorts::new(pb_type, children);
     let is_clock_complete = pb_type
         .interconnects
         .iter()
-        .any(|i| is_clock_complete_interconnect(i, pb_type, children));
+        .any(|i| is_clock_complete_interconnect(i, &clock_ports));
 
     let mux_count = get_interconnects_for_mode(pb_type, mode_index)
         .iter()
@@ -553,13 +2070,13 @@ fn measure_pb_type(
         calculate_header_name_width(pb_type, !children.is_empty(), zoom);
     let blif_model_width = calculate_blif_model_width(pb_type, zoom);
 
-    let w = (total_w + (PADDING * zoom) * 2.0 + interconnect_width + clock_padding_right)
+    let w = (total_w + (state.padding * zoom) * 2.0 + interconnect_width + clock_padding_right)
         .max(MIN_BLOCK_SIZE.x * zoom)
         .max(header_name_width_with_selector)
         .max(blif_model_width)
-        .max(total_w + complete_spacing + mux_gutter + (PADDING * zoom) * 2.0)
-        .max(total_w + mux_gutter + (PADDING * zoom) * 2.0);
-    let h = ((HEADER_HEIGHT * zoom) + (PADDING * zoom) + total_h + (PADDING * zoom))
+        .max(total_w + complete_spacing + mux_gutter + (state.padding * zoom) * 2.0)
+        .max(total_w + mux_gutter + (state.padding * zoom) * 2.0);
+    let h = ((HEADER_HEIGHT * zoom) + (state.padding * zoom) + total_h + (state.padding * zoom))
         .max(MIN_BLOCK_SIZE.y * zoom)
         .max(min_port_height);
     let size = egui::vec2(w, h);
@@ -591,139 +2108,58 @@ fn draw_expand_indicator(
     );
 }
 
-/// Checks if a port reference refers to a clock port.
-/// Handles both current PBType ports and child instance ports.
-/// Extracts the port name from references like "clk[0]", "clb.clk[0]", or "fle[0].clk[0]".
-fn is_clock_port(port_ref: &str, current_pb: &PBType, children: &[PBType]) -> bool {
-    use fpga_arch_parser::{Port, PortClass};
-
-    // Extract port name and instance from various formats:
-    // "clk[0]" -> (None, "clk")
-    // "clb.clk[0]" -> (Some("clb"), "clk")
-    // "fle[0].clk[0]" -> (Some("fle[0]"), "clk")
-    let (instance_prefix, port_name) = if let Some(dot_idx) = port_ref.rfind('.') {
-        // Has instance prefix
-        let instance_part = &port_ref[..dot_idx];
-        let port_part = &port_ref[dot_idx + 1..];
-        let port_name = if let Some(bracket_idx) = port_part.find('[') {
-            &port_part[..bracket_idx]
-        } else {
-            port_part
-        };
-        (Some(instance_part), port_name)
-    } else if let Some(bracket_idx) = port_ref.find('[') {
-        // Just port name with index
-        (None, &port_ref[..bracket_idx])
-    } else {
-        (None, port_ref)
-    };
-
-    // If there's an instance prefix, check child PBTypes
-    if let Some(instance) = instance_prefix {
-        // Extract child name (e.g., "fle" from "fle[0]")
-        let child_name = if let Some(bracket_idx) = instance.find('[') {
-            &instance[..bracket_idx]
-        } else {
-            instance
-        };
+/// The clock ports visible from inside a pb_type: its own `<clock>` ports and
+/// those of its children in the current mode. Gathered once per block so that
+/// wiring style follows the declared port type instead of the port name.
+struct ClockPorts {
+    current_pb_name: String,
+    // (pb_type name, port name)
+    ports: HashSet<(String, String)>,
+}
 
-        // Find matching child PBType
-        for child_pb in children {
-            if child_pb.name == child_name {
-                // Check ports in this child PBType
-                for port in &child_pb.ports {
-                    match port {
-                        Port::Clock(c) => {
-                            if c.name == port_name {
-                                return true;
-                            }
-                        }
-                        Port::Input(p) => {
-                            if p.name == port_name {
-                                // Check port_class first, then fall back to name matching for backward compatibility
-                                if matches!(p.port_class, PortClass::Clock) {
-                                    return true;
-                                }
-                                // Fallback: check if port name contains "clk" or "clock"
-                                let name_lower = p.name.to_lowercase();
-                                if name_lower.contains("clk") || name_lower.contains("clock") {
-                                    return true;
-                                }
-                            }
-                        }
-                        Port::Output(p) => {
-                            if p.name == port_name {
-                                // Check port_class first, then fall back to name matching for backward compatibility
-                                if matches!(p.port_class, PortClass::Clock) {
-                                    return true;
-                                }
-                                // Fallback: check if port name contains "clk" or "clock"
-                                let name_lower = p.name.to_lowercase();
-                                if name_lower.contains("clk") || name_lower.contains("clock") {
-                                    return true;
-                                }
-                            }
-                        }
-                    }
+impl ClockPorts {
+    fn new(current_pb: &PBType, children: &[PBType]) -> Self {
+        let mut ports = HashSet::new();
+        for pb_type in std::iter::once(current_pb).chain(children) {
+            for port in &pb_type.ports {
+                if let Port::Clock(c) = port {
+                    ports.insert((pb_type.name.clone(), c.name.clone()));
                 }
-                break;
             }
         }
-    } else {
-        // Check current PBType ports
-        for port in &current_pb.ports {
-            match port {
-                Port::Clock(c) => {
-                    if c.name == port_name {
-                        return true;
-                    }
-                }
-                Port::Input(p) => {
-                    if p.name == port_name {
-                        // Check port_class first, then fall back to name matching for backward compatibility
-                        if matches!(p.port_class, PortClass::Clock) {
-                            return true;
-                        }
-                        // Fallback: check if port name contains "clk" or "clock"
-                        let name_lower = p.name.to_lowercase();
-                        if name_lower.contains("clk") || name_lower.contains("clock") {
-                            return true;
-                        }
-                    }
-                }
-                Port::Output(p) => {
-                    if p.name == port_name {
-                        // Check port_class first, then fall back to name matching for backward compatibility
-                        if matches!(p.port_class, PortClass::Clock) {
-                            return true;
-                        }
-                        // Fallback: check if port name contains "clk" or "clock"
-                        let name_lower = p.name.to_lowercase();
-                        if name_lower.contains("clk") || name_lower.contains("clock") {
-                            return true;
-                        }
-                    }
-                }
-            }
+        Self {
+            current_pb_name: current_pb.name.clone(),
+            ports,
         }
     }
 
-    // Final fallback: if port name itself contains "clk" or "clock", treat as clock
-    // This handles cases where the port might not be found in the PBType but the name suggests it's a clock
-    let port_name_lower = port_name.to_lowercase();
-    if port_name_lower.contains("clk") || port_name_lower.contains("clock") {
-        return true;
+    /// Checks if a port reference refers to a clock port.
+    /// Handles references like "clk[0]", "clb.clk[0]", or "fle[0].clk[0]".
+    fn contains(&self, port_ref: &str) -> bool {
+        let (pb_name, port_name) = split_port_ref(port_ref, &self.current_pb_name);
+        self.ports
+            .contains(&(pb_name.to_string(), port_name.to_string()))
     }
+}
 
-    false
+/// Splits a port reference into its pb_type and port names, without any
+/// instance or pin indices. "fle[0].clk[0]" gives ("fle", "clk"), and a bare
+/// "clk[0]" belongs to the current pb_type.
+fn split_port_ref<'a>(port_ref: &'a str, current_pb_name: &'a str) -> (&'a str, &'a str) {
+    let (instance, port_part) = match port_ref.rfind('.') {
+        Some(dot_idx) => (&port_ref[..dot_idx], &port_ref[dot_idx + 1..]),
+        None => (current_pb_name, port_ref),
+    };
+    let pb_name = instance.split('[').next().unwrap_or(instance);
+    let port_name = port_part.split('[').next().unwrap_or(port_part);
+    (pb_name, port_name)
 }
 
 /// Checks if a Complete interconnect is a clock interconnect by verifying
 /// that all ports in both input and output lists are clock ports.
 fn is_clock_complete_interconnect(
     interconnect: &fpga_arch_parser::Interconnect,
-    current_pb: &PBType,
-    children: &[PBType],
+    clock_ports: &ClockPorts,
 ) -> bool {
     if matches!(
         interconnect.interconnect_type,
@@ -736,12 +2172,12 @@ fn is_clock_complete_interconnect(
         // Check if all input ports are clock ports
         let all_inputs_clock = raw_inputs
             .iter()
-            .all(|port_ref| is_clock_port(port_ref, current_pb, children));
+            .all(|port_ref| clock_ports.contains(port_ref));
 
         // Check if all output ports are clock ports
         let all_outputs_clock = raw_outputs
             .iter()
-            .all(|port_ref| is_clock_port(port_ref, current_pb, children));
+            .all(|port_ref| clock_ports.contains(port_ref));
 
         all_inputs_clock && all_outputs_clock
     } else {
@@ -749,12 +2185,15 @@ fn is_clock_complete_interconnect(
     }
 }
 
-fn resolve_port_pos(
+/// Looks up a single port pin reference in the pins of the current block,
+/// keyed like "I[0]", and of its children, keyed like "fle[0].in[0]". Usually
+/// the maps hold pin positions, but any per-pin value can be looked up.
+pub(crate) fn resolve_port_pos<V: Copy>(
     port_ref: &str,
     current_pb_name: &str,
-    my_ports: &HashMap<String, egui::Pos2>,
-    children_ports: &HashMap<String, egui::Pos2>,
-) -> Option<egui::Pos2> {
+    my_ports: &HashMap<String, V>,
+    children_ports: &HashMap<String, V>,
+) -> Option<V> {
     // clb.I -> I
     if let Some(stripped) = port_ref.strip_prefix(&format!("{}.", current_pb_name))
         && let Some(pos) = my_ports.get(stripped)
@@ -792,11 +2231,11 @@ fn resolve_port_pos(
 }
 
 // "in" -> tries "in[0]", "in[1]", ... until "in[4]"
-fn resolve_bus_list(
+pub(crate) fn resolve_bus_list<V: Copy>(
     port_list: &[String],
     current_pb_name: &str,
-    my_ports: &HashMap<String, egui::Pos2>,
-    children_ports: &HashMap<String, egui::Pos2>,
+    my_ports: &HashMap<String, V>,
+    children_ports: &HashMap<String, V>,
 ) -> Vec<String> {
     let mut resolved = Vec::new();
     for port_ref in port_list {
@@ -834,9 +2273,54 @@ fn resolve_bus_list(
     resolved
 }
 
+/// Checks a single (already expanded) port reference like "fle[2].in[3]"
+/// against the pb_type definitions, independent of what is drawn.
+fn port_ref_exists(port_ref: &str, current_pb: &PBType, children: &[PBType]) -> bool {
+    // Splits "name[idx]" into ("name", Some(idx)).
+    fn split_index(s: &str) -> Option<(&str, Option<usize>)> {
+        match s.split_once('[') {
+            Some((name, rest)) => {
+                let idx = rest.strip_suffix(']')?.parse().ok()?;
+                Some((name, Some(idx)))
+            }
+            None => Some((s, None)),
+        }
+    }
+
+    let Some((block_part, port_part)) = port_ref.rsplit_once('.') else {
+        return false;
+    };
+    let (Some((block_name, block_idx)), Some((port_name, pin_idx))) =
+        (split_index(block_part), split_index(port_part))
+    else {
+        return false;
+    };
+
+    let pb_type = if block_name == current_pb.name {
+        // The parent itself is a single instance from its own point of view.
+        block_idx.is_none_or(|idx| idx == 0).then_some(current_pb)
+    } else {
+        children
+            .iter()
+            .find(|child| child.name == block_name)
+            .filter(|child| block_idx.is_none_or(|idx| idx < child.num_pb))
+    };
+
+    pb_type.is_some_and(|pb_type| {
+        pb_type.ports.iter().any(|port| {
+            let (name, num_pins) = match port {
+                Port::Input(p) => (&p.name, p.num_pins),
+                Port::Output(p) => (&p.name, p.num_pins),
+                Port::Clock(p) => (&p.name, p.num_pins),
+            };
+            *name == port_name && pin_idx.is_none_or(|idx| idx < num_pins)
+        })
+    })
+}
+
 // "fle[3:0].out" -> ["fle[3].out", "fle[2].out", ...]
 // "lut5[0:0].in[4:0]" -> ["lut5[0].in[4]", "lut5[0].in[3]", ..., "lut5[0].in[0]"]
-fn expand_port_list(port_list_str: &str) -> Vec<String> {
+pub(crate) fn expand_port_list(port_list_str: &str) -> Vec<String> {
     let mut parts: Vec<String> = port_list_str
         .split_whitespace()
         .map(|s| s.to_string())
@@ -889,6 +2373,18 @@ fn expand_port_list(port_list_str: &str) -> Vec<String> {
     parts
 }
 
+pub(crate) fn pb_type_class_name(class: &PBTypeClass) -> &'static str {
+    match class {
+        PBTypeClass::None => "none",
+        PBTypeClass::Lut => "lut",
+        PBTypeClass::FlipFlop => "flipflop",
+        PBTypeClass::Memory => "memory",
+        PBTypeClass::InterconnectDirect => "direct",
+        PBTypeClass::InterconnectMux => "mux",
+        PBTypeClass::InterconnectComplete => "complete",
+    }
+}
+
 fn draw_pb_type(
     painter: &egui::Painter,
     pb_type: &PBType,
@@ -898,6 +2394,7 @@ fn draw_pb_type(
     ui: &mut egui::Ui,
     expand_all: bool,
     draw_interconnects: bool,
+    palette: Palette,
     dark_mode: bool,
 ) -> HashMap<String, egui::Pos2> {
     let zoom = state.zoom_clamped();
@@ -906,14 +2403,8 @@ fn draw_pb_type(
 
     // Record this PB's rect for downstream placement (e.g., interconnect boxes)
     state.pb_rects.insert(instance_path.to_string(), rect);
-    let mut mode_index = *state.selected_modes.get(instance_path).unwrap_or(&0);
-    mode_index = validate_mode_index(pb_type, mode_index);
-    // Update state with validated mode index if it was corrected
-    if mode_index != *state.selected_modes.get(instance_path).unwrap_or(&0) {
-        state
-            .selected_modes
-            .insert(instance_path.to_string(), mode_index);
-    }
+    state.draw_stats.blocks += 1;
+    let mode_index = state.selected_mode(pb_type, instance_path);
     let children = get_children_for_mode(pb_type, mode_index);
 
     let has_children = !children.is_empty();
@@ -923,11 +2414,13 @@ fn draw_pb_type(
             fpga_arch_parser::InterconnectType::Complete
         )
     });
+    let clock_ports = ClockPorts::new(pb_type, children);
     let _is_clock_complete = pb_type
         .interconnects
         .iter()
-        .any(|i| is_clock_complete_interconnect(i, pb_type, children));
-    let is_expanded = state.expanded_blocks.contains(instance_path);
+        .any(|i| is_clock_complete_interconnect(i, &clock_ports));
+    let is_expanded = state.is_drawn_expanded(instance_path);
+    let depth_limited = has_children && state.at_max_render_depth(instance_path);
 
     // Draw header with expand/collapse indicator
     let header_rect =
@@ -941,21 +2434,80 @@ fn draw_pb_type(
             });
 
         if header_response.inner.clicked() {
-            if is_expanded {
+            state.breadcrumb_path = Some(instance_path.to_string());
+            state.focused_block_path = Some(instance_path.to_string());
+            if depth_limited {
+                // Past the render depth the block stays collapsed.
+            } else if ui.input(|i| i.modifiers.alt) {
+                // Alt+click focuses on this branch of the hierarchy.
+                expand_block_collapsing_siblings(state, instance_path);
+            } else if is_expanded {
                 state.expanded_blocks.remove(instance_path);
             } else {
                 state.expanded_blocks.insert(instance_path.to_string());
             }
         }
+        header_response.inner.context_menu(|ui| {
+            if ui.button("Collapse subtree").clicked() {
+                collapse_subtree(state, instance_path);
+                state.breadcrumb_path = Some(instance_path.to_string());
+                ui.close();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button("Export this block as SVG...")
+                .on_hover_text("PNG export is not supported yet")
+                .clicked()
+            {
+                state.export_block_request = Some(instance_path.to_string());
+                ui.close();
+            }
+        });
+    }
+
+    // Show the full details of this pb_type when hovering over its header,
+    // unless the mode selector has a tooltip of its own.
+    let has_mode_selector = pb_type.modes.len() > 1 && (is_expanded || !has_children);
+    if ui.rect_contains_pointer(header_rect)
+        && !(has_mode_selector && ui.rect_contains_pointer(mode_selector_rect(rect, zoom)))
+    {
+        egui::Tooltip::always_open(
+            ui.ctx().clone(),
+            ui.layer_id(),
+            egui::Id::new(("pb_header_tooltip", instance_path)),
+            egui::PopupAnchor::Pointer,
+        )
+        .gap(12.0)
+        .show(|ui| {
+            ui.strong(&pb_type.name);
+            ui.label(format!("Class: {}", pb_type_class_name(&pb_type.class)));
+            ui.label(format!("num_pb: {}", pb_type.num_pb));
+            if let Some(blif_model) = &pb_type.blif_model {
+                ui.label(format!("blif_model: {blif_model}"));
+            }
+            if let Some(metadata) = &pb_type.metadata {
+                common_ui::render_metadata_tooltip(ui, metadata);
+            }
+            if depth_limited {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "Max render depth ({}) reached; raise it in the settings to expand this block.",
+                        state.max_render_depth
+                    ),
+                );
+            }
+        });
     }
 
     // If collapsed, only draw header and return empty port map
     if !is_expanded && has_children {
-        // Draw just the header background
+        // Draw just the header background, colored by what the block holds.
+        let header_bg = block_style::header_color(HeaderClass::of(pb_type), palette, dark_mode);
         painter.rect(
             header_rect,
             egui::CornerRadius::ZERO,
-            color_scheme::theme_header_bg(dark_mode),
+            header_bg,
             egui::Stroke::NONE,
             egui::epaint::StrokeKind::Inside,
         );
@@ -967,72 +2519,84 @@ fn draw_pb_type(
             header_rect.min.x + 5.0 * zoom
         };
         let font = egui::FontId::proportional(14.0 * zoom);
-        painter.text(
+        let name_rect = painter.text(
             egui::pos2(name_x, header_rect.center().y),
             egui::Align2::LEFT_CENTER,
             &pb_type.name,
             font,
-            color_scheme::theme_text_color(dark_mode),
+            block_style::contrasting_text_color(header_bg),
         );
 
+        if state.show_port_widths {
+            // Keep clear of the "max depth" marker at the right of the header.
+            let reserved_right = if depth_limited {
+                MAX_DEPTH_MARKER_WIDTH * zoom
+            } else {
+                0.0
+            };
+            draw_port_width_summary(
+                painter,
+                pb_type,
+                header_rect,
+                name_rect,
+                reserved_right,
+                zoom,
+                block_style::contrasting_text_color(header_bg),
+            );
+        }
+
         // Draw expand/collapse indicator on top
         if has_children {
             draw_expand_indicator(painter, header_rect, zoom, dark_mode);
         }
+        if depth_limited {
+            painter.text(
+                egui::pos2(header_rect.max.x - 5.0 * zoom, header_rect.center().y),
+                egui::Align2::RIGHT_CENTER,
+                "max depth",
+                egui::FontId::proportional(10.0 * zoom),
+                block_style::contrasting_text_color(header_bg).gamma_multiply(0.7),
+            );
+        }
 
+        draw_focus_outline(painter, state, instance_path, header_rect);
         return HashMap::new();
     }
 
     // Determine specific visual style based on class
     let my_ports = match pb_type.class {
         PBTypeClass::Lut => {
-            intra_block_drawing::draw_lut(painter, rect, pb_type, state, ui, dark_mode)
-        }
-        PBTypeClass::FlipFlop => {
-            intra_block_drawing::draw_flip_flop(painter, rect, pb_type, state, ui, dark_mode)
+            intra_block_drawing::draw_lut(painter, rect, pb_type, state, ui, palette, dark_mode)
         }
+        PBTypeClass::FlipFlop => intra_block_drawing::draw_flip_flop(
+            painter, rect, pb_type, state, ui, palette, dark_mode,
+        ),
         PBTypeClass::Memory => {
-            intra_block_drawing::draw_memory(painter, rect, pb_type, state, ui, dark_mode)
+            intra_block_drawing::draw_memory(painter, rect, pb_type, state, ui, palette, dark_mode)
         }
         PBTypeClass::None
         | PBTypeClass::InterconnectDirect
         | PBTypeClass::InterconnectMux
         | PBTypeClass::InterconnectComplete => {
             if pb_type.blif_model.is_some() {
-                intra_block_drawing::draw_blif_block(painter, rect, pb_type, state, ui, dark_mode)
+                intra_block_drawing::draw_blif_block(
+                    painter, rect, pb_type, state, ui, palette, dark_mode,
+                )
             } else {
                 intra_block_drawing::draw_generic_block(
-                    painter, rect, pb_type, state, ui, dark_mode,
+                    painter, rect, pb_type, state, ui, palette, dark_mode,
                 )
             }
         }
     };
 
     if pb_type.modes.len() > 1 {
-        let mut mode_idx = *state.selected_modes.get(instance_path).unwrap_or(&0);
-        mode_idx = validate_mode_index(pb_type, mode_idx);
-        // Update state with validated mode index if it was corrected
-        if mode_idx != *state.selected_modes.get(instance_path).unwrap_or(&0) {
-            state
-                .selected_modes
-                .insert(instance_path.to_string(), mode_idx);
-        }
-        let mode_name = &pb_type.modes[mode_idx].name;
-
-        // Truncate mode name if it's too long
-        let selector_width = (120.0_f32 * zoom).min(rect.width() * 0.4);
-        let display_name = if mode_name.len() > 15 {
-            format!("{}...", &mode_name[..12])
-        } else {
-            mode_name.clone()
-        };
+        let mode_idx = state.selected_mode(pb_type, instance_path);
 
-        let selector_height = 18.0 * zoom;
-        let margin = 5.0 * zoom;
-        let selector_rect = egui::Rect::from_min_size(
-            rect.min + egui::vec2(rect.width() - selector_width - margin, 2.0 * zoom),
-            egui::vec2(selector_width, selector_height),
-        );
+        // Truncate mode name if it's too long; the full name is in the tooltip.
+        let selector_rect = mode_selector_rect(rect, zoom);
+        let selector_width = selector_rect.width();
+        let display_name = mode_label(pb_type, mode_idx, 15);
 
         let mut selected_mode = mode_idx;
 
@@ -1044,15 +2608,15 @@ fn draw_pb_type(
                 .selected_text(&display_name)
                 .show_ui(ui, |ui| {
                     for (i, mode) in pb_type.modes.iter().enumerate() {
-                        let item_text = if mode.name.len() > 40 {
-                            format!("{}...", &mode.name[..37])
-                        } else {
-                            mode.name.clone()
-                        };
-                        ui.selectable_value(&mut selected_mode, i, &item_text);
+                        let item_text = mode_label(pb_type, i, 40);
+                        let item = ui.selectable_value(&mut selected_mode, i, &item_text);
+                        if item_text != mode.name || mode.metadata.is_some() {
+                            item.on_hover_ui(|ui| mode_hover_ui(ui, pb_type, i));
+                        }
                     }
                 })
-                .response;
+                .response
+                .on_hover_ui(|ui| mode_hover_ui(ui, pb_type, mode_idx));
             ui.set_style(old_style);
             response
         });
@@ -1092,15 +2656,16 @@ fn draw_pb_type(
             0.0
         };
 
-        let start_x = rect.min.x + (PADDING * zoom) + mux_gutter + complete_spacing;
-        let start_y = rect.min.y + (HEADER_HEIGHT * zoom) + (PADDING * zoom);
+        let start_x = rect.min.x + (state.padding * zoom) + mux_gutter + complete_spacing;
+        let start_y = rect.min.y + (HEADER_HEIGHT * zoom) + (state.padding * zoom);
 
         let mut cursor_x = start_x;
         let mut cursor_y = start_y;
 
         for child_pb in children {
+            let num_visible = visible_instance_count(state, instance_path, child_pb);
             let mut max_col_width: f32 = 0.0;
-            for i in 0..child_pb.num_pb {
+            for i in 0..num_visible {
                 let instance_name = generate_child_instance_name(child_pb, i);
                 let child_path = format!("{}.{}", instance_path, instance_name);
 
@@ -1117,6 +2682,7 @@ fn draw_pb_type(
                     ui,
                     expand_all,
                     draw_interconnects,
+                    palette,
                     dark_mode,
                 );
 
@@ -1124,85 +2690,128 @@ fn draw_pb_type(
                     children_ports.insert(format!("{}.{}", instance_name, port_name), p);
                 }
 
-                cursor_y += child_single_size.y + (PADDING * zoom);
+                cursor_y += child_single_size.y + (state.padding * zoom);
+            }
+
+            // Summarize the hidden instances of a collapsed array.
+            if num_visible < child_pb.num_pb {
+                let badge_rect = egui::Rect::from_min_size(
+                    egui::pos2(
+                        cursor_x,
+                        cursor_y - (state.padding - ARRAY_BADGE_GAP) * zoom,
+                    ),
+                    egui::vec2(max_col_width, ARRAY_BADGE_HEIGHT * zoom),
+                );
+                draw_collapsed_array_badge(
+                    painter,
+                    badge_rect,
+                    child_pb,
+                    state,
+                    instance_path,
+                    ui,
+                    palette,
+                    dark_mode,
+                );
+                cursor_y = badge_rect.max.y + (state.padding * zoom);
             }
 
             match direction {
                 LayoutDirection::Vertical => {}
                 LayoutDirection::Horizontal => {
-                    cursor_x += max_col_width + (PADDING * zoom);
+                    cursor_x += max_col_width + (state.padding * zoom);
                     cursor_y = start_y;
                 }
             }
         }
     }
 
+    if state.show_connection_heat && has_children && is_expanded {
+        let endpoint_counts = count_child_endpoints(
+            pb_type,
+            get_interconnects_for_mode(pb_type, mode_index),
+            &my_ports,
+            &children_ports,
+        );
+        draw_connection_heat(painter, &endpoint_counts, state, instance_path, dark_mode);
+    }
+
     if draw_interconnects && has_children && is_expanded {
         let interconnects = get_interconnects_for_mode(pb_type, mode_index);
 
+        // Wires for references that match no port silently vanish, so collect
+        // them to be reported.
+        let num_unresolved_before = state.unresolved_refs.len();
+        for inter in interconnects {
+            for port_ref in expand_port_list(&inter.input)
+                .into_iter()
+                .chain(expand_port_list(&inter.output))
+            {
+                if !port_ref_exists(&port_ref, pb_type, children) {
+                    state.unresolved_refs.push(UnresolvedInterconnectRef {
+                        instance_path: instance_path.to_string(),
+                        interconnect_name: inter.name.clone(),
+                        port_ref,
+                    });
+                }
+            }
+        }
+        if state.unresolved_refs.len() > num_unresolved_before {
+            draw_unresolved_refs_badge(
+                painter,
+                rect,
+                &state.unresolved_refs[num_unresolved_before..],
+                instance_path,
+                zoom,
+                ui,
+            );
+        }
+
         for inter in interconnects {
+            state.interconnect_hovered = false;
             match inter.interconnect_type {
                 fpga_arch_parser::InterconnectType::Direct => {
                     let raw_sources = expand_port_list(&inter.input);
                     let raw_sinks = expand_port_list(&inter.output);
-                    let mut sources =
+                    let sources =
                         resolve_bus_list(&raw_sources, &pb_type.name, &my_ports, &children_ports);
-                    let mut sinks =
+                    let sinks =
                         resolve_bus_list(&raw_sinks, &pb_type.name, &my_ports, &children_ports);
-
-                    // Align buses by numeric index when both sides are indexed, so
-                    // fle[0] maps to O[0], fle[1] to O[1], etc. We try to parse an
-                    // outer index (before the dot) first, then an inner bit index.
-                    // If either side lacks indices or lengths differ, we keep order.
-                    let parse_indices = |s: &str| {
-                        // outer like "fle[3].out[0]"
-                        let outer = s.find('[').and_then(|start| {
-                            s[start + 1..]
-                                .find(']')
-                                .and_then(|e| s[start + 1..start + 1 + e].parse::<i32>().ok())
-                        });
-                        // inner like ".out[0]"
-                        let inner = s.rfind('[').and_then(|start| {
-                            s[start + 1..]
-                                .find(']')
-                                .and_then(|e| s[start + 1..start + 1 + e].parse::<i32>().ok())
-                        });
-                        (outer, inner)
+                    let (sources, sinks) = align_direct_wires(sources, sinks);
+
+                    let wires: Vec<(&str, &str)> = sources
+                        .iter()
+                        .zip(&sinks)
+                        .map(|(src, dst)| (src.as_str(), dst.as_str()))
+                        .collect();
+                    let groups = if state.bundle_buses && zoom < BUNDLE_EXPAND_ZOOM {
+                        group_bus_wires(&wires)
+                    } else {
+                        wires.iter().map(|wire| vec![*wire]).collect()
                     };
-
-                    let src_all_idx = sources.iter().all(|s| parse_indices(s).0.is_some());
-                    let dst_all_idx = sinks.iter().all(|s| parse_indices(s).0.is_some());
-
-                    if src_all_idx && dst_all_idx && sources.len() == sinks.len() {
-                        let mut src_with = sources
-                            .into_iter()
-                            .filter_map(|s| {
-                                let (o, i) = parse_indices(&s);
-                                Some(((o?, i.unwrap_or(0)), s))
-                            })
-                            .collect::<Vec<_>>();
-                        let mut dst_with = sinks
-                            .into_iter()
-                            .filter_map(|d| {
-                                let (o, i) = parse_indices(&d);
-                                Some(((o?, i.unwrap_or(0)), d))
-                            })
-                            .collect::<Vec<_>>();
-                        src_with.sort_by_key(|(k, _)| *k);
-                        dst_with.sort_by_key(|(k, _)| *k);
-                        sources = src_with.into_iter().map(|(_, s)| s).collect();
-                        sinks = dst_with.into_iter().map(|(_, d)| d).collect();
-                    }
-
-                    for (i, src) in sources.iter().enumerate() {
-                        if i < sinks.len() {
-                            let dst = &sinks[i];
+                    for group in groups {
+                        if group.len() > 1
+                            && draw_bus_bundle(
+                                painter,
+                                &group,
+                                pb_type,
+                                &clock_ports,
+                                &my_ports,
+                                &children_ports,
+                                state,
+                                ui,
+                                rect,
+                                dark_mode,
+                            )
+                        {
+                            continue;
+                        }
+                        for (src, dst) in group {
                             draw_direct_connection(
                                 painter,
                                 src,
                                 dst,
                                 pb_type,
-                                children,
+                                &clock_ports,
                                 &my_ports,
                                 &children_ports,
                                 state,
@@ -1227,7 +2836,7 @@ fn draw_pb_type(
                         &sources,
                         &sinks,
                         pb_type,
-                        children,
+                        &clock_ports,
                         &my_ports,
                         &children_ports,
                         state,
@@ -1249,7 +2858,7 @@ fn draw_pb_type(
                         &sources,
                         &sinks,
                         pb_type,
-                        children,
+                        &clock_ports,
                         &my_ports,
                         &children_ports,
                         state,
@@ -1259,10 +2868,340 @@ fn draw_pb_type(
                     );
                 }
             }
+
+            if state.interconnect_hovered
+                && let Some(metadata) = &inter.metadata
+            {
+                egui::Tooltip::always_open(
+                    ui.ctx().clone(),
+                    ui.layer_id(),
+                    egui::Id::new(("interconnect_tooltip", instance_path, &inter.name)),
+                    egui::PopupAnchor::Pointer,
+                )
+                .gap(12.0)
+                .show(|ui| {
+                    ui.strong(&inter.name);
+                    common_ui::render_metadata_tooltip(ui, metadata);
+                });
+            }
+        }
+
+        for inter in interconnects {
+            draw_pack_pattern_wires(
+                painter,
+                inter,
+                pb_type,
+                &clock_ports,
+                &my_ports,
+                &children_ports,
+                state,
+                rect,
+                palette,
+                dark_mode,
+            );
+        }
+
+        // Highlight the wires of the interconnect picked in the table.
+        let selected_name = match &state.selected_interconnect {
+            Some((path, name)) if path == instance_path => Some(name),
+            _ => None,
+        };
+        if let Some(inter) =
+            selected_name.and_then(|name| interconnects.iter().find(|i| i.name == *name))
+        {
+            for port_ref in expand_port_list(&inter.input)
+                .into_iter()
+                .chain(expand_port_list(&inter.output))
+            {
+                if let Some(pos) =
+                    resolve_port_pos(&port_ref, &pb_type.name, &my_ports, &children_ports)
+                {
+                    state.highlighted_positions_next_frame.push(pos);
+                }
+            }
+        }
+
+        // Highlight the pins along the path found by the reachability check.
+        if let Some(result) = &state.reachability.result
+            && result.is_for(instance_path, mode_index)
+            && let Some(pins) = &result.path
+        {
+            let positions: Vec<egui::Pos2> = pins
+                .iter()
+                .filter_map(|pin| resolve_port_pos(pin, &pb_type.name, &my_ports, &children_ports))
+                .collect();
+            state.highlighted_positions_next_frame.extend(positions);
+        }
+    }
+
+    draw_focus_outline(painter, state, instance_path, rect);
+    my_ports
+}
+
+/// Where the mode selector sits in the header of a block drawn at `rect`.
+fn mode_selector_rect(rect: egui::Rect, zoom: f32) -> egui::Rect {
+    let selector_width = (120.0_f32 * zoom).min(rect.width() * 0.4);
+    let margin = 5.0 * zoom;
+    egui::Rect::from_min_size(
+        rect.min + egui::vec2(rect.width() - selector_width - margin, 2.0 * zoom),
+        egui::vec2(selector_width, 18.0 * zoom),
+    )
+}
+
+/// Name of a mode of `pb_type` for the mode selector, truncated to
+/// `max_chars`, marked if it is the physical mode or the packer never uses it.
+fn mode_label(pb_type: &PBType, mode_index: usize, max_chars: usize) -> String {
+    let mode = &pb_type.modes[mode_index];
+    let mut label = truncate_label(&mode.name, max_chars);
+    if mode.disable_packing {
+        label = format!("🚫 {label}");
+    }
+    if pb_type.physical_mode_index() == Some(mode_index) {
+        label = format!("★ {label}");
+    }
+    label
+}
+
+/// Tooltip of a mode in the mode selector, noting whether it is the physical
+/// mode and whether packing is disabled for it, followed by its `<metadata>`.
+fn mode_hover_ui(ui: &mut egui::Ui, pb_type: &PBType, mode_index: usize) {
+    ui.label(mode_hover_text(pb_type, mode_index));
+    if let Some(metadata) = &pb_type.modes[mode_index].metadata {
+        common_ui::render_metadata_tooltip(ui, metadata);
+    }
+}
+
+/// The name and notes line of `mode_hover_ui`.
+fn mode_hover_text(pb_type: &PBType, mode_index: usize) -> String {
+    let mode = &pb_type.modes[mode_index];
+    let mut notes = Vec::new();
+    if pb_type.physical_mode_index() == Some(mode_index) {
+        notes.push("physical mode");
+    }
+    if mode.disable_packing {
+        notes.push("packing disabled");
+    }
+    if notes.is_empty() {
+        format!("Mode: {}", mode.name)
+    } else {
+        format!("Mode: {} ({})", mode.name, notes.join(", "))
+    }
+}
+
+/// Outlines `rect` if the block at `instance_path` has the keyboard focus.
+fn draw_focus_outline(
+    painter: &egui::Painter,
+    state: &IntraTileState,
+    instance_path: &str,
+    rect: egui::Rect,
+) {
+    if state.focused_block_path.as_deref() != Some(instance_path) {
+        return;
+    }
+    let zoom = state.zoom_clamped();
+    painter.rect_stroke(
+        rect.expand(3.0 * zoom),
+        egui::CornerRadius::same(2),
+        egui::Stroke::new(2.0 * zoom, color_scheme::FOCUS_OUTLINE_COLOR),
+        egui::epaint::StrokeKind::Outside,
+    );
+}
+
+/// Counts the interconnect pins of `interconnects` that land on each child
+/// instance (e.g. "ble4[0]"), keyed by instance name. Pins of the parent itself are not counted.
+fn count_child_endpoints(
+    pb_type: &PBType,
+    interconnects: &[fpga_arch_parser::Interconnect],
+    my_ports: &HashMap<String, egui::Pos2>,
+    children_ports: &HashMap<String, egui::Pos2>,
+) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for inter in interconnects {
+        let endpoints = expand_port_list(&inter.input)
+            .into_iter()
+            .chain(expand_port_list(&inter.output))
+            .collect::<Vec<_>>();
+        for pin in resolve_bus_list(&endpoints, &pb_type.name, my_ports, children_ports) {
+            let Some((block, _)) = pin.split_once('.') else {
+                continue;
+            };
+            if block == pb_type.name {
+                continue;
+            }
+            // Match the instance naming used by resolve_port_pos: "ble4.in"
+            // may mean "ble4[0]", and "lut[0].in" may mean a single "lut".
+            let instance = if children_ports.contains_key(&pin) {
+                block.to_string()
+            } else if let Some(base) = block.strip_suffix("[0]") {
+                base.to_string()
+            } else {
+                format!("{block}[0]")
+            };
+            *counts.entry(instance).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Tints the drawn children of `instance_path` from cool to warm by their
+/// share of the most-connected sibling's interconnect pins.
+fn draw_connection_heat(
+    painter: &egui::Painter,
+    endpoint_counts: &HashMap<String, usize>,
+    state: &IntraTileState,
+    instance_path: &str,
+    dark_mode: bool,
+) {
+    let Some(max_count) = endpoint_counts.values().copied().max() else {
+        return;
+    };
+    for (instance, count) in endpoint_counts {
+        let Some(child_rect) = state.instance_rect(&format!("{instance_path}.{instance}")) else {
+            continue;
+        };
+        let t = *count as f32 / max_count as f32;
+        painter.rect_filled(
+            child_rect,
+            egui::CornerRadius::ZERO,
+            color_scheme::heatmap_color(t, dark_mode).gamma_multiply(0.45),
+        );
+    }
+}
+
+/// Draws a red warning badge on the top-right corner of a block whose
+/// interconnects reference ports that do not exist.
+fn draw_unresolved_refs_badge(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    unresolved_refs: &[UnresolvedInterconnectRef],
+    instance_path: &str,
+    zoom: f32,
+    ui: &mut egui::Ui,
+) {
+    let radius = 8.0 * zoom;
+    // Straddle the corner so the badge does not cover the mode selector.
+    let center = rect.right_top();
+    painter.circle_filled(center, radius, egui::Color32::from_rgb(200, 40, 40));
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        "!",
+        egui::FontId::proportional(12.0 * zoom),
+        egui::Color32::WHITE,
+    );
+
+    let badge_rect = egui::Rect::from_center_size(center, egui::vec2(radius, radius) * 2.0);
+    let response = ui.interact(
+        badge_rect,
+        ui.id().with(("unresolved_refs", instance_path)),
+        egui::Sense::hover(),
+    );
+    response.on_hover_ui(|ui| {
+        ui.strong("Unresolved interconnect references");
+        for unresolved in unresolved_refs {
+            ui.label(format!(
+                "{}: {}",
+                unresolved.interconnect_name, unresolved.port_ref
+            ));
+        }
+    });
+}
+
+/// Number of (input, output, clock) pins of a pb_type.
+fn count_pins(pb_type: &PBType) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    for port in &pb_type.ports {
+        match port {
+            Port::Input(p) => counts.0 += p.num_pins,
+            Port::Output(p) => counts.1 += p.num_pins,
+            Port::Clock(p) => counts.2 += p.num_pins,
         }
     }
+    counts
+}
+
+/// Right-aligns a pin count summary such as "I:40 O:10 C:1" in a collapsed
+/// block's header, left of the `reserved_right` width kept for other
+/// markers. Nothing is drawn if it would overlap the block name.
+fn draw_port_width_summary(
+    painter: &egui::Painter,
+    pb_type: &PBType,
+    header_rect: egui::Rect,
+    name_rect: egui::Rect,
+    reserved_right: f32,
+    zoom: f32,
+    text_color: egui::Color32,
+) {
+    let (inputs, outputs, clocks) = count_pins(pb_type);
+    let summary = [("I", inputs), ("O", outputs), ("C", clocks)]
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(label, count)| format!("{label}:{count}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if summary.is_empty() {
+        return;
+    }
+
+    let galley = painter.layout_no_wrap(summary, egui::FontId::monospace(11.0 * zoom), text_color);
+    let margin = 8.0 * zoom;
+    let min_x = header_rect.max.x - reserved_right - margin - galley.size().x;
+    if min_x < name_rect.max.x + margin {
+        return;
+    }
+    let text_pos = egui::pos2(min_x, header_rect.center().y - galley.size().y / 2.0);
+    painter.galley(text_pos, galley, text_color);
+}
+
+/// Draws the "×N" badge shown under the first instance of a collapsed array.
+/// Clicking the badge shows every instance of the array.
+fn draw_collapsed_array_badge(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    child_pb: &PBType,
+    state: &mut IntraTileState,
+    instance_path: &str,
+    ui: &mut egui::Ui,
+    palette: Palette,
+    dark_mode: bool,
+) {
+    let zoom = state.zoom_clamped();
+    let response = ui
+        .scope_builder(egui::UiBuilder::new().max_rect(rect), |ui| {
+            ui.allocate_response(rect.size(), egui::Sense::click())
+        })
+        .inner;
 
-    my_ports
+    let stroke_color = if response.hovered() {
+        color_scheme::HIGHLIGHT_COLOR
+    } else {
+        color_scheme::theme_border_color(dark_mode)
+    };
+    let fill = block_style::header_color(HeaderClass::of(child_pb), palette, dark_mode);
+    painter.rect(
+        rect,
+        egui::CornerRadius::same((4.0 * zoom) as u8),
+        fill,
+        egui::Stroke::new(1.0 * zoom, stroke_color),
+        egui::epaint::StrokeKind::Inside,
+    );
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        format!("⋯  ×{} {}", child_pb.num_pb, child_pb.name),
+        egui::FontId::proportional(12.0 * zoom),
+        block_style::contrasting_text_color(fill),
+    );
+
+    let response = response.on_hover_text(format!(
+        "Click to show all {} instances of {}",
+        child_pb.num_pb, child_pb.name
+    ));
+    if response.clicked() {
+        state
+            .expanded_arrays
+            .insert(array_key(instance_path, child_pb));
+    }
 }
 
 //-----------------------------------------------------------
@@ -1278,7 +3217,49 @@ fn draw_wire_segment(
     ui: &mut egui::Ui,
     is_clock: bool,
 ) {
+    state.draw_stats.wires += 1;
     let zoom = state.zoom_clamped();
+    let points = wire_route_points(start, end, parent_rect, zoom, is_clock);
+
+    if let Some(pointer_pos) = ui.ctx().pointer_latest_pos() {
+        let mut hovered = false;
+        for i in 0..points.len() - 1 {
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let pad = 5.0 * zoom;
+            let min_x = p1.x.min(p2.x) - pad;
+            let max_x = p1.x.max(p2.x) + pad;
+            let min_y = p1.y.min(p2.y) - pad;
+            let max_y = p1.y.max(p2.y) + pad;
+
+            if pointer_pos.x >= min_x
+                && pointer_pos.x <= max_x
+                && pointer_pos.y >= min_y
+                && pointer_pos.y <= max_y
+            {
+                hovered = true;
+                break;
+            }
+        }
+
+        if hovered {
+            state.highlighted_positions_next_frame.push(start);
+            state.highlighted_positions_next_frame.push(end);
+            state.interconnect_hovered = true;
+        }
+    }
+
+    painter.add(egui::Shape::line(points, stroke));
+}
+
+/// Computes the orthogonal route a wire takes from `start` to `end`.
+fn wire_route_points(
+    start: egui::Pos2,
+    end: egui::Pos2,
+    parent_rect: egui::Rect,
+    zoom: f32,
+    is_clock: bool,
+) -> Vec<egui::Pos2> {
     let mut points = Vec::new();
     points.push(start);
 
@@ -1328,35 +3309,96 @@ fn draw_wire_segment(
     if !is_clock {
         points.push(end);
     }
+    points
+}
 
-    if let Some(pointer_pos) = ui.ctx().pointer_latest_pos() {
-        let mut hovered = false;
-        for i in 0..points.len() - 1 {
-            let p1 = points[i];
-            let p2 = points[i + 1];
-            let pad = 5.0 * zoom;
-            let min_x = p1.x.min(p2.x) - pad;
-            let max_x = p1.x.max(p2.x) + pad;
-            let min_y = p1.y.min(p2.y) - pad;
-            let max_y = p1.y.max(p2.y) + pad;
+/// Draws the connections of an interconnect that belong to one of its pack
+/// patterns as dashed lines in the pattern's color, over the regular wiring.
+fn draw_pack_pattern_wires(
+    painter: &egui::Painter,
+    inter: &fpga_arch_parser::Interconnect,
+    current_pb: &PBType,
+    clock_ports: &ClockPorts,
+    my_ports: &HashMap<String, egui::Pos2>,
+    children_ports: &HashMap<String, egui::Pos2>,
+    state: &IntraTileState,
+    parent_rect: egui::Rect,
+    palette: Palette,
+    dark_mode: bool,
+) {
+    if inter.pack_patterns.is_empty() {
+        return;
+    }
 
-            if pointer_pos.x >= min_x
-                && pointer_pos.x <= max_x
-                && pointer_pos.y >= min_y
-                && pointer_pos.y <= max_y
-            {
-                hovered = true;
-                break;
-            }
-        }
+    let zoom = state.zoom_clamped();
+    let sources = resolve_bus_list(
+        &expand_port_list(&inter.input),
+        &current_pb.name,
+        my_ports,
+        children_ports,
+    );
+    let sinks = resolve_bus_list(
+        &expand_port_list(&inter.output),
+        &current_pb.name,
+        my_ports,
+        children_ports,
+    );
+    let matches_port = |port_ref: &str, pattern_port: &str| {
+        split_port_ref(port_ref, &current_pb.name) == split_port_ref(pattern_port, &current_pb.name)
+    };
 
-        if hovered {
-            state.highlighted_positions_next_frame.push(start);
-            state.highlighted_positions_next_frame.push(end);
+    for pattern in &inter.pack_patterns {
+        let Some(rank) = state
+            .pack_pattern_names
+            .iter()
+            .position(|name| *name == pattern.name)
+        else {
+            continue;
+        };
+        let stroke = egui::Stroke::new(
+            3.0 * zoom,
+            color_scheme::pack_pattern_color(rank, palette, dark_mode),
+        );
+
+        let pattern_sources: Vec<&String> = sources
+            .iter()
+            .filter(|src| matches_port(src, &pattern.in_port))
+            .collect();
+        let pattern_sinks: Vec<&String> = sinks
+            .iter()
+            .filter(|dst| matches_port(dst, &pattern.out_port))
+            .collect();
+
+        // Directs connect their pins pairwise; muxes and completes may
+        // connect any source to any sink.
+        let wires: Vec<(&String, &String)> = match inter.interconnect_type {
+            fpga_arch_parser::InterconnectType::Direct => pattern_sources
+                .iter()
+                .copied()
+                .zip(pattern_sinks.iter().copied())
+                .collect(),
+            fpga_arch_parser::InterconnectType::Mux
+            | fpga_arch_parser::InterconnectType::Complete => pattern_sources
+                .iter()
+                .flat_map(|src| pattern_sinks.iter().map(move |dst| (*src, *dst)))
+                .collect(),
+        };
+
+        for (src, dst) in wires {
+            let src_pos = resolve_port_pos(src, &current_pb.name, my_ports, children_ports);
+            let dst_pos = resolve_port_pos(dst, &current_pb.name, my_ports, children_ports);
+            if let (Some(start), Some(end)) = (src_pos, dst_pos) {
+                let is_clock = clock_ports.contains(src) || clock_ports.contains(dst);
+                let points = wire_route_points(start, end, parent_rect, zoom, is_clock);
+                painter.extend(egui::Shape::dashed_line(
+                    &points,
+                    stroke,
+                    8.0 * zoom,
+                    4.0 * zoom,
+                ));
+            }
         }
     }
-
-    painter.add(egui::Shape::line(points, stroke));
 }
 
 fn pb_name_from_port(port: &str) -> Option<&str> {
@@ -1407,6 +3449,7 @@ fn draw_clock_wire_segment(
     channel_y: f32,
 ) {
     let _ = parent_rect;
+    state.draw_stats.wires += 1;
     let points = vec![
         start,
         egui::pos2(start.x, channel_y),
@@ -1438,6 +3481,7 @@ fn draw_clock_wire_segment(
         if hovered {
             state.highlighted_positions_next_frame.push(start);
             state.highlighted_positions_next_frame.push(end);
+            state.interconnect_hovered = true;
         }
     }
 
@@ -1449,7 +3493,7 @@ fn draw_direct_connection(
     src: &str,
     dst: &str,
     current_pb: &PBType,
-    children: &[PBType],
+    clock_ports: &ClockPorts,
     my_ports: &HashMap<String, egui::Pos2>,
     children_ports: &HashMap<String, egui::Pos2>,
     state: &mut IntraTileState,
@@ -1470,18 +3514,14 @@ fn draw_direct_connection(
                 .iter()
                 .any(|p| p.distance(end) < 1.0);
 
-        let stroke_color = if is_highlighted {
-            color_scheme::HIGHLIGHT_COLOR
-        } else {
-            color_scheme::theme_interconnect_bg(dark_mode)
-        };
-
-        let stroke_width = if is_highlighted { 2.5 } else { 1.5 };
-        let stroke = egui::Stroke::new(stroke_width, stroke_color);
+        let stroke = state.wire_style.stroke(
+            is_highlighted,
+            color_scheme::theme_interconnect_bg(dark_mode),
+            state.zoom_clamped(),
+        );
 
         // Check if this is a clock connection using port class instead of string matching
-        let is_clock =
-            is_clock_port(src, current_pb, children) || is_clock_port(dst, current_pb, children);
+        let is_clock = clock_ports.contains(src) || clock_ports.contains(dst);
 
         // For direct clock links (e.g., ff clk -> ble clk), keep a simple route
         // using the generic wire segment to avoid long detours.
@@ -1498,12 +3538,119 @@ fn draw_direct_connection(
     }
 }
 
+/// Strips the pin index from a port reference: "fle[2].out[3]" -> "fle[2].out".
+pub(crate) fn bus_name(port_ref: &str) -> &str {
+    match port_ref.rsplit_once('.') {
+        Some((block, port)) => match port.find('[') {
+            Some(open) => &port_ref[..block.len() + 1 + open],
+            None => port_ref,
+        },
+        None => port_ref,
+    }
+}
+
+/// Groups direct wires by the pair of buses they run between, in the order
+/// each pair first appears.
+fn group_bus_wires<'a>(wires: &[(&'a str, &'a str)]) -> Vec<Vec<(&'a str, &'a str)>> {
+    let mut groups: Vec<Vec<(&str, &str)>> = Vec::new();
+    let mut group_of: HashMap<(&str, &str), usize> = HashMap::new();
+    for &(src, dst) in wires {
+        let idx = *group_of
+            .entry((bus_name(src), bus_name(dst)))
+            .or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+        groups[idx].push((src, dst));
+    }
+    groups
+}
+
+/// Draws the wires of a bus as one thick line between the centers of its
+/// source and sink pins, with a slash and the bus width across it. Returns
+/// false without drawing anything when the pointer is over the bundle or a
+/// pin cannot be placed, so the wires are drawn one by one instead.
+fn draw_bus_bundle(
+    painter: &egui::Painter,
+    wires: &[(&str, &str)],
+    current_pb: &PBType,
+    clock_ports: &ClockPorts,
+    my_ports: &HashMap<String, egui::Pos2>,
+    children_ports: &HashMap<String, egui::Pos2>,
+    state: &mut IntraTileState,
+    ui: &mut egui::Ui,
+    parent_rect: egui::Rect,
+    dark_mode: bool,
+) -> bool {
+    let mut ends = Vec::with_capacity(wires.len());
+    for (src, dst) in wires {
+        let src_pos = resolve_port_pos(src, &current_pb.name, my_ports, children_ports);
+        let dst_pos = resolve_port_pos(dst, &current_pb.name, my_ports, children_ports);
+        let (Some(start), Some(end)) = (src_pos, dst_pos) else {
+            return false;
+        };
+        ends.push((start, end));
+    }
+    let centroid = |points: &mut dyn Iterator<Item = egui::Pos2>| {
+        let sum = points.fold(egui::Vec2::ZERO, |sum, p| sum + p.to_vec2());
+        (sum / ends.len() as f32).to_pos2()
+    };
+    let start = centroid(&mut ends.iter().map(|(start, _)| *start));
+    let end = centroid(&mut ends.iter().map(|(_, end)| *end));
+
+    let zoom = state.zoom_clamped();
+    let (src, dst) = wires[0];
+    let is_clock = clock_ports.contains(src) || clock_ports.contains(dst);
+    let points = wire_route_points(start, end, parent_rect, zoom, is_clock);
+
+    let pad = 5.0 * zoom;
+    let hovered = ui.ctx().pointer_latest_pos().is_some_and(|pointer_pos| {
+        points.windows(2).any(|segment| {
+            egui::Rect::from_two_pos(segment[0], segment[1])
+                .expand(pad)
+                .contains(pointer_pos)
+        })
+    });
+    if hovered {
+        return false;
+    }
+
+    let is_highlighted = ends.iter().any(|(start, end)| {
+        state
+            .highlighted_positions_this_frame
+            .iter()
+            .any(|p| p.distance(*start) < 1.0 || p.distance(*end) < 1.0)
+    });
+    let color = color_scheme::theme_interconnect_bg(dark_mode);
+    let wire_stroke = state.wire_style.stroke(is_highlighted, color, zoom);
+
+    state.draw_stats.wires += 1;
+    let slash_at = points[0] + (points[1] - points[0]) * 0.5;
+    painter.add(egui::Shape::line(
+        points,
+        state.wire_style.bundle_stroke(is_highlighted, color, zoom),
+    ));
+
+    // Schematic bus notation: a short slash across the line, labelled with
+    // the number of wires.
+    let slash = egui::vec2(4.0, -6.0) * zoom;
+    painter.line_segment([slash_at - slash, slash_at + slash], wire_stroke);
+    painter.text(
+        slash_at + egui::vec2(5.0, -6.0) * zoom,
+        egui::Align2::LEFT_BOTTOM,
+        wires.len().to_string(),
+        egui::FontId::proportional(10.0 * zoom),
+        wire_stroke.color,
+    );
+    true
+}
+
 fn draw_complete_interconnect(
     painter: &egui::Painter,
     sources: &[String],
     sinks: &[String],
     current_pb: &PBType,
-    children: &[PBType],
+    clock_ports: &ClockPorts,
     my_ports: &HashMap<String, egui::Pos2>,
     children_ports: &HashMap<String, egui::Pos2>,
     state: &mut IntraTileState,
@@ -1605,10 +3752,8 @@ fn draw_complete_interconnect(
     // their bounding edges rather than a fixed offset.
     let is_clock_block = resolved_sources
         .iter()
-        .all(|(s, _)| is_clock_port(s, current_pb, children))
-        && resolved_sinks
-            .iter()
-            .all(|(s, _)| is_clock_port(s, current_pb, children));
+        .all(|(s, _)| clock_ports.contains(s))
+        && resolved_sinks.iter().all(|(s, _)| clock_ports.contains(s));
 
     let block_center_x = if is_clock_block {
         // Try to derive the sink PB bounds from recorded rects.
@@ -1658,6 +3803,7 @@ fn draw_complete_interconnect(
         egui::Rect::from_center_size(egui::pos2(block_center_x, avg_y), egui::vec2(width, height));
 
     let block_hovered = ui.rect_contains_pointer(rect);
+    state.interconnect_hovered |= block_hovered;
     let is_block_highlighted = block_hovered
         || state
             .highlighted_positions_this_frame
@@ -1665,7 +3811,7 @@ fn draw_complete_interconnect(
             .any(|p| rect.contains(*p));
 
     let stroke_color = if is_block_highlighted {
-        color_scheme::HIGHLIGHT_COLOR
+        state.wire_style.highlight_color
     } else {
         color_scheme::theme_border_color(dark_mode)
     };
@@ -1706,14 +3852,13 @@ fn draw_complete_interconnect(
                     .highlighted_positions_this_frame
                     .iter()
                     .any(|p| p.distance(*src_pos) < 1.0);
-            let wire_color = if wire_highlighted {
-                color_scheme::HIGHLIGHT_COLOR
-            } else {
-                color_scheme::theme_interconnect_bg(dark_mode)
-            };
-            let wire_stroke = egui::Stroke::new(1.5 * zoom, wire_color);
+            let wire_stroke = state.wire_style.stroke(
+                wire_highlighted,
+                color_scheme::theme_interconnect_bg(dark_mode),
+                zoom,
+            );
 
-            let is_clock = is_clock_port(src_name, current_pb, children);
+            let is_clock = clock_ports.contains(src_name);
             if is_clock {
                 // Approach from the left with an extra turn: left offset, then up to target y, then into block.
                 let offset_x = rect.min.x - 10.0 * zoom;
@@ -1780,14 +3925,13 @@ fn draw_complete_interconnect(
                 .highlighted_positions_this_frame
                 .iter()
                 .any(|p| p.distance(*dst_pos) < 1.0);
-        let wire_color = if wire_highlighted {
-            color_scheme::HIGHLIGHT_COLOR
-        } else {
-            color_scheme::theme_interconnect_bg(dark_mode)
-        };
-        let wire_stroke = egui::Stroke::new(1.5 * zoom, wire_color);
+        let wire_stroke = state.wire_style.stroke(
+            wire_highlighted,
+            color_scheme::theme_interconnect_bg(dark_mode),
+            zoom,
+        );
 
-        let is_clock = is_clock_port(dst_name, current_pb, children);
+        let is_clock = clock_ports.contains(dst_name);
         if is_clock {
             let channel_y = dst_pos.y + 5.0 * zoom;
             // Add a right-hand offset before heading toward the sink.
@@ -1825,7 +3969,7 @@ fn draw_interconnect_block(
     sources: &[String],
     sinks: &[String],
     current_pb: &PBType,
-    children: &[PBType],
+    clock_ports: &ClockPorts,
     my_ports: &HashMap<String, egui::Pos2>,
     children_ports: &HashMap<String, egui::Pos2>,
     state: &mut IntraTileState,
@@ -1905,6 +4049,7 @@ fn draw_interconnect_block(
     let mut block_hovered = false;
     if ui.rect_contains_pointer(rect) {
         block_hovered = true;
+        state.interconnect_hovered = true;
     }
 
     let is_block_highlighted = block_hovered
@@ -1914,7 +4059,7 @@ fn draw_interconnect_block(
             .any(|p| rect.contains(*p));
 
     let stroke_color = if is_block_highlighted {
-        color_scheme::HIGHLIGHT_COLOR
+        state.wire_style.highlight_color
     } else {
         color_scheme::theme_border_color(dark_mode)
     };
@@ -1974,18 +4119,17 @@ fn draw_interconnect_block(
                 .highlighted_positions_this_frame
                 .iter()
                 .any(|p| p.distance(*src_pos) < 1.0);
-        let wire_color = if wire_highlighted {
-            color_scheme::HIGHLIGHT_COLOR
-        } else {
-            color_scheme::theme_interconnect_bg(dark_mode)
-        };
-        let wire_stroke = egui::Stroke::new(1.5 * zoom, wire_color);
+        let wire_stroke = state.wire_style.stroke(
+            wire_highlighted,
+            color_scheme::theme_interconnect_bg(dark_mode),
+            zoom,
+        );
 
         let input_y = rect.min.y + input_step * (i as f32 + 1.0);
         let target = egui::pos2(left_edge_x, input_y);
 
         // Check if this is a clock connection by examining source port name
-        let is_clock = is_clock_port(src_name, current_pb, children);
+        let is_clock = clock_ports.contains(src_name);
         if is_clock {
             let default_mid = src_pos.y + (target.y - src_pos.y) * 0.5;
             let channel_y = match (pb_name_from_port(src_name), Some(current_pb.name.as_str())) {
@@ -2041,16 +4185,15 @@ fn draw_interconnect_block(
                 .highlighted_positions_this_frame
                 .iter()
                 .any(|p| p.distance(dst_pos) < 1.0);
-        let wire_color = if wire_highlighted {
-            color_scheme::HIGHLIGHT_COLOR
-        } else {
-            egui::Color32::from_rgba_unmultiplied(100, 100, 100, 100)
-        };
-        let wire_stroke = egui::Stroke::new(1.5 * zoom, wire_color);
+        let wire_stroke = state.wire_style.stroke(
+            wire_highlighted,
+            egui::Color32::from_rgba_unmultiplied(100, 100, 100, 100),
+            zoom,
+        );
 
         let start = egui::pos2(right_edge_x, block_center.y);
         // Check if this is a clock connection by examining sink port name
-        let is_clock = is_clock_port(dst_name, current_pb, children);
+        let is_clock = clock_ports.contains(dst_name);
         if is_clock {
             let default_mid = start.y + (dst_pos.y - start.y) * 0.5;
             let channel_y = match (pb_name_from_port(dst_name), Some(current_pb.name.as_str())) {
@@ -2085,3 +4228,580 @@ fn draw_interconnect_block(
         }
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{k4_n4_clb, k4_n4_with};
+
+    fn render_frame(root_pb: &PBType, state: &mut IntraTileState) {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                render_intra_tile_view(ui, root_pb, state, false, true, Palette::Standard, false);
+            });
+        });
+    }
+
+    /// Port pin keys like "in[2]", placed at the origin.
+    fn pin_positions(pb_type: &PBType, prefix: &str) -> HashMap<String, egui::Pos2> {
+        let mut ports = HashMap::new();
+        for port in &pb_type.ports {
+            let (name, num_pins) = match port {
+                Port::Input(p) => (&p.name, p.num_pins),
+                Port::Output(p) => (&p.name, p.num_pins),
+                Port::Clock(p) => (&p.name, p.num_pins),
+            };
+            for pin in 0..num_pins {
+                ports.insert(format!("{prefix}{name}[{pin}]"), egui::Pos2::ZERO);
+            }
+        }
+        ports
+    }
+
+    #[test]
+    fn checks_port_refs_against_the_pb_types() {
+        let clb = k4_n4_clb();
+        let children = get_children_for_mode(&clb, 0);
+
+        assert!(port_ref_exists("clb.I[9]", &clb, children));
+        assert!(port_ref_exists("fle[3].in[0]", &clb, children));
+        assert!(port_ref_exists("fle.clk", &clb, children));
+        // No child is called ble.
+        assert!(!port_ref_exists("ble[0].in[0]", &clb, children));
+        // The fle has no port called data.
+        assert!(!port_ref_exists("fle[0].data[0]", &clb, children));
+        // Out of range: there are four fles with four inputs each.
+        assert!(!port_ref_exists("fle[4].in[0]", &clb, children));
+        assert!(!port_ref_exists("fle[0].in[4]", &clb, children));
+        assert!(!port_ref_exists("clb.I[10]", &clb, children));
+    }
+
+    #[test]
+    fn finds_clock_ports_by_declared_type() {
+        assert_eq!(split_port_ref("fle[0].clk[0]", "clb"), ("fle", "clk"));
+        assert_eq!(split_port_ref("clb.clk", "clb"), ("clb", "clk"));
+        assert_eq!(split_port_ref("clk[0]", "clb"), ("clb", "clk"));
+
+        let clb = k4_n4_clb();
+        let clock_ports = ClockPorts::new(&clb, get_children_for_mode(&clb, 0));
+        assert!(clock_ports.contains("clk[0]"));
+        assert!(clock_ports.contains("clb.clk"));
+        assert!(clock_ports.contains("fle[3].clk[0]"));
+        // Data ports, and clocks of blocks that are not children, are not.
+        assert!(!clock_ports.contains("clb.I[0]"));
+        assert!(!clock_ports.contains("fle[0].in[0]"));
+        assert!(!clock_ports.contains("ble4[0].clk[0]"));
+
+        // The declared type decides, not the name: a clock named "gclk_in"
+        // is a clock, and an input named "clk" is not.
+        let clb = k4_n4_with(&[(
+            r#"        <input name="in" num_pins="4"/>
+        <output name="out" num_pins="1"/>
+        <clock name="clk" num_pins="1"/>"#,
+            r#"        <input name="in" num_pins="4"/>
+        <input name="clk" num_pins="1"/>
+        <output name="out" num_pins="1"/>
+        <clock name="gclk_in" num_pins="1"/>"#,
+        )])
+        .complex_block_list
+        .into_iter()
+        .find(|pb| pb.name == "clb")
+        .expect("k4_N4_90nm.xml has a clb");
+        let clock_ports = ClockPorts::new(&clb, get_children_for_mode(&clb, 0));
+        assert!(clock_ports.contains("fle[0].gclk_in[0]"));
+        assert!(!clock_ports.contains("fle[0].clk[0]"));
+    }
+
+    #[test]
+    fn counts_interconnect_pins_per_child() {
+        let clb = k4_n4_clb();
+        let ble4 = &get_children_for_mode(&get_children_for_mode(&clb, 0)[0], 0)[0];
+        assert_eq!(ble4.name, "ble4");
+
+        let my_ports = pin_positions(ble4, "");
+        let mut children_ports = HashMap::new();
+        for child in get_children_for_mode(ble4, 0) {
+            let instance = generate_child_instance_name(child, 0);
+            children_ports.extend(pin_positions(child, &format!("{instance}.")));
+        }
+
+        let counts = count_child_endpoints(
+            ble4,
+            get_interconnects_for_mode(ble4, 0),
+            &my_ports,
+            &children_ports,
+        );
+        // lut4: 4 inputs from ble4.in, plus its output feeding ff.D and the mux.
+        // ff: D, clk and Q.
+        assert_eq!(counts.get("lut4"), Some(&6));
+        assert_eq!(counts.get("ff"), Some(&3));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn exports_interconnect_table_as_csv() {
+        let clb = k4_n4_clb();
+        let state = IntraTileState::default();
+
+        let (path, csv) = interconnect_table_csv(&clb, &state);
+        assert_eq!(path, "clb");
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("Name,Type,Input,Output,Pack Patterns,Delays")
+        );
+        assert_eq!(
+            lines.count(),
+            get_interconnects_for_mode(&clb, 0).len(),
+            "one line per interconnect"
+        );
+
+        assert_eq!(csv_field("clb.I"), "clb.I");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn reset_clears_the_canvas_and_keeps_settings() {
+        let wire_style = WireStyle {
+            thickness: 2.0,
+            highlight_thickness: 5.0,
+            highlight_color: egui::Color32::BLUE,
+        };
+        let mut state = IntraTileState {
+            wire_style,
+            show_canvas_grid: true,
+            max_render_depth: 2,
+            ruler_mode: true,
+            bundle_buses: true,
+            show_pin_indices: true,
+            zoom: 2.0,
+            ..IntraTileState::default()
+        };
+        state.selected_modes.insert("clb.fle[0]".to_string(), 1);
+        state.expanded_blocks.insert("clb".to_string());
+        state.ruler_points = vec![egui::pos2(0.0, 0.0), egui::pos2(30.0, 40.0)];
+        state.reset();
+
+        // Settings outlive a reset of the canvas.
+        assert_eq!(state.wire_style, wire_style);
+        assert!(state.show_canvas_grid);
+        assert_eq!(state.max_render_depth, 2);
+        assert!(state.ruler_mode);
+        assert!(state.bundle_buses);
+        assert!(state.show_pin_indices);
+
+        // What the canvas shows does not.
+        assert_eq!(state.zoom, 1.0);
+        assert!(state.selected_modes.is_empty());
+        assert!(state.expanded_blocks.is_empty());
+        assert!(state.ruler_points.is_empty());
+        assert!(state.scroll_to_origin);
+    }
+
+    #[test]
+    fn restores_snapshot_exactly() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+        expand_blocks_to_depth(&mut state, &clb, 1);
+        state.selected_modes.insert("clb.fle[1]".to_string(), 1);
+        state.zoom = 1.5;
+        let snapshot = state.snapshot();
+
+        // Move away from the bookmarked view, then come back to it.
+        expand_all_blocks(&mut state, &clb, &clb.name);
+        state.selected_modes.clear();
+        state.zoom = 0.5;
+        state.selected_block_path = Some("clb.fle[0]".to_string());
+        state.restore(&snapshot);
+
+        assert_eq!(state.snapshot(), snapshot);
+        assert_eq!(state.selected_block_path, None);
+        assert_eq!(state.pending_scroll_offset, Some(snapshot.scroll_offset));
+        assert!(!state.scroll_to_origin);
+    }
+
+    #[test]
+    fn opens_blocks_in_their_physical_mode() {
+        // Give the fle a second mode and mark it as the physical one.
+        let mut clb = k4_n4_clb();
+        let fle = &mut clb.pb_types[0];
+        fle.modes.push(PBMode {
+            name: "physical".to_string(),
+            disable_packing: false,
+            pb_types: Vec::new(),
+            interconnects: Vec::new(),
+            metadata: None,
+        });
+        fle.physical_mode_name = Some("physical".to_string());
+
+        let mut state = IntraTileState::default();
+        state.selected_modes.insert("clb.fle[2]".to_string(), 0);
+
+        let fle = &clb.pb_types[0];
+        assert_eq!(state.selected_mode(&clb, "clb"), 0);
+        assert_eq!(state.selected_mode(fle, "clb.fle[0]"), 1);
+        assert_eq!(state.selected_mode(fle, "clb.fle[3]"), 1);
+        // A mode that was chosen is kept.
+        assert_eq!(state.selected_mode(fle, "clb.fle[2]"), 0);
+        // Blocks past the render depth get their physical mode too, once
+        // the depth is raised to show them.
+        state.max_render_depth = 1;
+        assert!(state.at_max_render_depth("clb.fle[0]"));
+        assert_eq!(state.selected_mode(fle, "clb.fle[0]"), 1);
+
+        assert_eq!(mode_label(fle, 1, 40), "★ physical");
+        assert_eq!(mode_label(fle, 0, 40), "n1_lut4");
+        assert_eq!(mode_hover_text(fle, 1), "Mode: physical (physical mode)");
+    }
+
+    #[test]
+    fn finds_and_cycles_through_matching_instances() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+        state.find_query = "LUT".to_string();
+        state.find_matches = find_in_hierarchy(&clb, &state, &state.find_query);
+        assert_eq!(
+            state.find_matches,
+            (0..4)
+                .map(|i| format!("clb.fle[{i}].ble4.lut4"))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(find_status(&state).as_deref(), Some("4 matches"));
+
+        // Stepping to a match opens the blocks above it and selects it.
+        step_find(&clb, &mut state, true);
+        assert_eq!(state.find_current, Some(0));
+        assert!(state.expanded_blocks.contains("clb.fle[0].ble4"));
+        assert_eq!(
+            state.selected_block_path.as_deref(),
+            Some("clb.fle[0].ble4.lut4")
+        );
+
+        // Stepping back from the first match wraps around to the last.
+        step_find(&clb, &mut state, false);
+        assert_eq!(find_status(&state).as_deref(), Some("match 4 of 4"));
+        step_find(&clb, &mut state, true);
+        assert_eq!(find_status(&state).as_deref(), Some("match 1 of 4"));
+
+        state.find_query = "dsp".to_string();
+        state.find_matches = find_in_hierarchy(&clb, &state, &state.find_query);
+        assert_eq!(find_status(&state).as_deref(), Some("no matches"));
+    }
+
+    #[test]
+    fn collapses_only_the_subtree() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+        expand_all_blocks(&mut state, &clb, &clb.name);
+        // Left over from a mode that is no longer selected.
+        state
+            .expanded_blocks
+            .insert("clb.fle[1].other_mode_block".to_string());
+        let expanded_before = state.expanded_blocks.len();
+
+        collapse_subtree(&mut state, "clb.fle[1]");
+
+        assert!(
+            !state
+                .expanded_blocks
+                .iter()
+                .any(|path| path == "clb.fle[1]" || path.starts_with("clb.fle[1].")),
+            "{:?}",
+            state.expanded_blocks
+        );
+        assert!(state.expanded_blocks.contains("clb"));
+        assert!(state.expanded_blocks.contains("clb.fle[0]"));
+        assert!(state.expanded_blocks.contains("clb.fle[2].ble4"));
+        assert!(state.expanded_blocks.len() < expanded_before);
+    }
+
+    #[test]
+    fn breadcrumb_collapses_back_to_its_level() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+        expand_all_blocks(&mut state, &clb, &clb.name);
+        focus_block(&mut state, "clb.fle[2].ble4");
+        assert_eq!(state.breadcrumb_path.as_deref(), Some("clb.fle[2].ble4"));
+
+        collapse_below(&clb, &mut state, "clb.fle[2]");
+
+        assert!(state.expanded_blocks.contains("clb.fle[2]"));
+        assert!(!state.expanded_blocks.contains("clb.fle[2].ble4"));
+        // Other branches are left alone.
+        assert!(state.expanded_blocks.contains("clb.fle[1].ble4"));
+        assert_eq!(state.breadcrumb_path.as_deref(), Some("clb.fle[2]"));
+    }
+
+    #[test]
+    fn arrow_keys_move_focus_through_hierarchy() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+
+        // With nothing focused, the root takes the focus and has no parent.
+        move_focus(&clb, &mut state, FocusMove::NextSibling);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb"));
+        move_focus(&clb, &mut state, FocusMove::Parent);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb"));
+
+        // Moving down opens the block.
+        move_focus(&clb, &mut state, FocusMove::FirstChild);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb.fle[0]"));
+        assert!(state.expanded_blocks.contains("clb"));
+
+        move_focus(&clb, &mut state, FocusMove::NextSibling);
+        move_focus(&clb, &mut state, FocusMove::NextSibling);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb.fle[2]"));
+        move_focus(&clb, &mut state, FocusMove::PreviousSibling);
+        move_focus(&clb, &mut state, FocusMove::PreviousSibling);
+        move_focus(&clb, &mut state, FocusMove::PreviousSibling);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb.fle[0]"));
+
+        move_focus(&clb, &mut state, FocusMove::FirstChild);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb.fle[0].ble4"));
+        assert_eq!(state.breadcrumb_path.as_deref(), Some("clb.fle[0].ble4"));
+        move_focus(&clb, &mut state, FocusMove::Parent);
+        assert_eq!(state.focused_block_path.as_deref(), Some("clb.fle[0]"));
+
+        toggle_focused_block(&clb, &mut state);
+        assert!(!state.expanded_blocks.contains("clb.fle[0]"));
+        toggle_focused_block(&clb, &mut state);
+        assert!(state.expanded_blocks.contains("clb.fle[0]"));
+    }
+
+    #[test]
+    fn strokes_wires_with_wire_style() {
+        let wire_style = WireStyle {
+            thickness: 2.0,
+            highlight_thickness: 5.0,
+            highlight_color: egui::Color32::BLUE,
+        };
+        let color = egui::Color32::GRAY;
+        assert_eq!(
+            wire_style.stroke(false, color, 2.0),
+            egui::Stroke::new(4.0, color)
+        );
+        assert_eq!(
+            wire_style.stroke(true, color, 2.0),
+            egui::Stroke::new(10.0, egui::Color32::BLUE)
+        );
+        assert_eq!(
+            wire_style.bundle_stroke(false, color, 2.0),
+            egui::Stroke::new(10.0, color)
+        );
+        assert_eq!(
+            wire_style.bundle_stroke(true, color, 2.0),
+            egui::Stroke::new(25.0, egui::Color32::BLUE)
+        );
+    }
+
+    #[test]
+    fn dots_background_grid_when_enabled() {
+        let clb = k4_n4_clb();
+        let grid_dots = |state: &mut IntraTileState| {
+            let ctx = egui::Context::default();
+            let output = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    render_intra_tile_view(ui, &clb, state, false, true, Palette::Standard, false);
+                });
+            });
+            let grid_color = color_scheme::theme_canvas_grid_color(false);
+            output
+                .shapes
+                .into_iter()
+                .filter_map(|clipped| match clipped.shape {
+                    egui::Shape::Circle(circle) if circle.fill == grid_color => Some(circle.center),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut state = IntraTileState::default();
+        assert!(grid_dots(&mut state).is_empty());
+
+        state.show_canvas_grid = true;
+        state.zoom = 2.0;
+        let dots = grid_dots(&mut state);
+        assert!(dots.len() > 1);
+        let spacing = CANVAS_GRID_SPACING * 2.0;
+        let on_grid = |distance: f32| {
+            let steps = distance / spacing;
+            (steps - steps.round()).abs() < 1e-3
+        };
+        for dot in &dots {
+            let offset = *dot - dots[0];
+            assert!(on_grid(offset.x) && on_grid(offset.y));
+        }
+    }
+
+    #[test]
+    fn blocks_past_the_max_render_depth_stay_collapsed() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState {
+            max_render_depth: 1,
+            ..IntraTileState::default()
+        };
+        assert!(!state.at_max_render_depth("clb"));
+        assert!(state.at_max_render_depth("clb.fle[0]"));
+
+        // Expanding everything stops at the limit.
+        expand_all_blocks(&mut state, &clb, "clb");
+        assert_eq!(state.expanded_blocks, HashSet::from(["clb".to_string()]));
+
+        // A block expanded past the limit is still laid out collapsed.
+        state.expanded_blocks.insert("clb.fle[0]".to_string());
+        let paths: Vec<String> = layout_metrics(&clb, &mut state)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert!(paths.contains(&"clb.fle[0]".to_string()));
+        assert!(paths.iter().all(|path| path.matches('.').count() <= 1));
+        render_frame(&clb, &mut state);
+        assert!(state.instance_rect("clb.fle[0]").is_some());
+        assert!(state.instance_rect("clb.fle[0].ble4").is_none());
+    }
+
+    #[test]
+    fn ruler_measures_in_pixels_and_at_full_zoom() {
+        assert_eq!(ruler_label(120.0, 2.0), "240 px (120 at 100%)");
+        assert_eq!(ruler_label(50.0, 1.0), "50 px (50 at 100%)");
+    }
+
+    #[test]
+    fn ruler_clicks_measure_at_the_current_zoom() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState {
+            ruler_mode: true,
+            zoom: 2.0,
+            ..IntraTileState::default()
+        };
+        let ctx = egui::Context::default();
+        let run = |state: &mut IntraTileState, events: Vec<egui::Event>| {
+            let input = egui::RawInput {
+                events,
+                ..egui::RawInput::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    render_intra_tile_view(ui, &clb, state, false, true, Palette::Standard, false);
+                });
+            });
+        };
+        let click = |pos: egui::Pos2, pressed: bool| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::default(),
+        };
+
+        run(&mut state, Vec::new());
+        let origin = state.instance_rect("clb").expect("root block is drawn").min;
+        // 60 x 80 screen pixels apart at 200% is 100 px, or 50 at 100%.
+        for pos in [
+            origin + egui::vec2(4.0, 4.0),
+            origin + egui::vec2(64.0, 84.0),
+        ] {
+            run(
+                &mut state,
+                vec![egui::Event::PointerMoved(pos), click(pos, true)],
+            );
+            run(&mut state, vec![click(pos, false)]);
+        }
+
+        let [start, end] = state.ruler_points[..] else {
+            panic!("expected two ruler points, got {:?}", state.ruler_points);
+        };
+        let unscaled_distance = (end - start).length();
+        assert!(
+            (unscaled_distance - 50.0).abs() < 1e-3,
+            "{unscaled_distance}"
+        );
+        assert_eq!(
+            ruler_label(unscaled_distance, state.zoom_clamped()),
+            "100 px (50 at 100%)"
+        );
+    }
+
+    #[test]
+    fn truncates_labels_by_character() {
+        assert_eq!(truncate_label("n1_lut4", 15), "n1_lut4");
+        assert_eq!(
+            truncate_label("arithmetic_fracturable", 15),
+            "arithmetic_f..."
+        );
+        // Slicing at byte 12 would land inside one of the "é"s.
+        assert_eq!(truncate_label("mode_éééééé_μx_long", 15), "mode_éééééé_...");
+    }
+
+    #[test]
+    fn records_rects_of_drawn_instances() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+
+        // Collapsed, only the root block is drawn.
+        render_frame(&clb, &mut state);
+        let clb_rect = state.instance_rect("clb").expect("root block is drawn");
+        assert!(state.instance_rect("clb.fle[0]").is_none());
+
+        // Expanding the root draws its children inside it.
+        state.expanded_blocks.insert("clb".to_string());
+        render_frame(&clb, &mut state);
+        let clb_rect_expanded = state.instance_rect("clb").expect("root block is drawn");
+        assert!(clb_rect_expanded.width() >= clb_rect.width());
+        for i in 0..4 {
+            let fle_rect = state
+                .instance_rect(&format!("clb.fle[{i}]"))
+                .expect("child instance is drawn");
+            assert!(clb_rect_expanded.contains_rect(fle_rect));
+        }
+        assert!(state.instance_rect("clb.fle[0].ble4").is_none());
+
+        // The same state lays out to the same positions every frame.
+        let fle_rect = state.instance_rect("clb.fle[1]");
+        render_frame(&clb, &mut state);
+        assert_eq!(state.instance_rect("clb.fle[1]"), fle_rect);
+    }
+
+    #[test]
+    fn groups_direct_wires_by_bus() {
+        let wires = [
+            ("clb.I[0]", "fle[0].in[0]"),
+            ("clb.I[1]", "fle[0].in[1]"),
+            ("fle[0].out[0]", "clb.O[0]"),
+            ("clb.I[2]", "fle[0].in[2]"),
+            ("clb.I[3]", "fle[1].in[0]"),
+            ("clb.clk", "fle[0].clk"),
+        ];
+        assert_eq!(bus_name("fle[2].out[3]"), "fle[2].out");
+        assert_eq!(bus_name("clb.clk"), "clb.clk");
+        assert_eq!(
+            group_bus_wires(&wires),
+            vec![
+                vec![wires[0], wires[1], wires[3]],
+                vec![wires[2]],
+                vec![wires[4]],
+                vec![wires[5]],
+            ]
+        );
+    }
+
+    #[test]
+    fn bundles_bus_wires_until_zoomed_in() {
+        let clb = k4_n4_clb();
+        let mut state = IntraTileState::default();
+        expand_all_blocks(&mut state, &clb, &clb.name);
+        render_frame(&clb, &mut state);
+        let unbundled_wires = state.draw_stats.wires;
+
+        // In each of the 4 fles, the 4-bit fle.in -> ble4.in and
+        // ble4.in -> lut4.in directs each become a single bundle.
+        state.bundle_buses = true;
+        render_frame(&clb, &mut state);
+        assert_eq!(state.draw_stats.wires, unbundled_wires - 4 * 2 * 3);
+
+        state.zoom = BUNDLE_EXPAND_ZOOM;
+        render_frame(&clb, &mut state);
+        assert_eq!(state.draw_stats.wires, unbundled_wires);
+    }
+}
@@ -2,30 +2,44 @@
 //!
 //! A Rust-based visualizer for VTR FPGA architecture description files.
 
+mod arch_diff;
+mod arch_index;
+mod arch_sections;
 mod block_style;
 mod color_scheme;
 mod common_ui;
 mod complex_block_view;
 mod crr_sb_view;
 mod crr_view;
+mod delay_matrix_view;
 mod error_report;
 mod grid;
 mod grid_renderer;
 mod grid_view;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless_render;
 mod intra_block_drawing;
 mod intra_hierarchy_tree;
+mod intra_reachability;
 mod intra_tile;
+mod perf_overlay;
 mod primitive_view;
 mod samples;
 mod settings;
 mod summary_view;
+#[cfg(test)]
+mod test_fixtures;
 mod tile_rendering;
 mod tile_view;
+#[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+mod url_loader;
 mod viewer;
+mod xml_source;
 
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(clap::Parser)]
 #[command(about = "Visualizer for VTR FPGA architecture description files")]
+#[command(group(clap::ArgGroup::new("headless").args(["parse_only", "render", "layout_metrics"])))]
 struct Args {
     /// Path to the architecture file to open
     file: Option<std::path::PathBuf>,
@@ -33,6 +47,80 @@ struct Args {
     /// Parse the architecture file and report errors without opening the GUI
     #[arg(long, requires = "file")]
     parse_only: bool,
+
+    /// With --parse-only or --render, accept incomplete architectures (e.g.
+    /// tiles without equivalent sites)
+    #[arg(long, requires = "headless")]
+    lenient: bool,
+
+    /// Render the grid and every tile of the architecture file to SVG images
+    /// without opening the GUI. PNG is not supported; rasterize the SVGs with
+    /// another tool if needed
+    #[arg(long, value_name = "ARCH", requires = "out", conflicts_with_all = ["file", "parse_only"])]
+    render: Option<std::path::PathBuf>,
+
+    /// Directory the images from --render are written to
+    #[arg(long, value_name = "DIR", requires = "render")]
+    out: Option<std::path::PathBuf>,
+
+    /// Round the coordinates in the images from --render to multiples of STEP
+    /// pixels (1 if no step is given), so lines stay crisp when rasterized.
+    /// Off by default, since the images are SVG
+    #[arg(long, value_name = "STEP", requires = "render", num_args = 0..=1, default_missing_value = "1", value_parser = parse_snap_step)]
+    snap: Option<f32>,
+
+    /// Colors of the images from --render: "standard" or "color-blind-safe"
+    #[arg(long, value_name = "PALETTE", requires = "render", default_value = "standard", value_parser = parse_palette)]
+    palette: color_scheme::Palette,
+
+    /// Print the measured size of every block in a tile of the architecture
+    /// file, for layout regression tests
+    #[arg(long, hide = true, value_name = "ARCH", requires = "tile", conflicts_with_all = ["file", "parse_only", "render"])]
+    layout_metrics: Option<std::path::PathBuf>,
+
+    /// Tile whose blocks --layout-metrics measures
+    #[arg(long, hide = true, value_name = "NAME", requires = "layout_metrics")]
+    tile: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_snap_step(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(step) if step.is_finite() && step > 0.0 => Ok(step),
+        _ => Err(format!("'{value}' is not a positive number of pixels")),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_palette(value: &str) -> Result<color_scheme::Palette, String> {
+    match value {
+        "standard" => Ok(color_scheme::Palette::Standard),
+        "color-blind-safe" => Ok(color_scheme::Palette::ColorBlindSafe),
+        _ => Err(format!(
+            "'{value}' is not a palette; expected 'standard' or 'color-blind-safe'"
+        )),
+    }
+}
+
+/// Parses the architecture file for one of the modes that run without the
+/// GUI, reporting the error and exiting if it does not parse.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_or_exit(file_path: &std::path::Path, lenient: bool) -> fpga_arch_parser::FPGAArch {
+    let options = fpga_arch_parser::ParseOptions {
+        lenient,
+        ..Default::default()
+    };
+    match fpga_arch_parser::parse_with_options(file_path, &options) {
+        Ok(arch) => arch,
+        Err(e) => {
+            eprintln!(
+                "Parse error in {}:\n{}",
+                file_path.display(),
+                error_report::format_parse_error(&e, Some(file_path))
+            );
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -48,17 +136,42 @@ fn main() -> Result<(), eframe::Error> {
     if args.parse_only {
         // Safe: clap enforces `file` is present when `--parse-only` is used via `requires = "file"`.
         let file_path = args.file.as_deref().unwrap();
-        match fpga_arch_parser::parse(file_path) {
-            Ok(_) => {
-                println!("Successfully parsed: {}", file_path.display());
+        parse_or_exit(file_path, args.lenient);
+        println!("Successfully parsed: {}", file_path.display());
+        return Ok(());
+    }
+
+    // --render: draw the grid and each tile offscreen and write them out as images.
+    if let Some(file_path) = args.render.as_deref() {
+        // Safe: clap enforces `out` is present when `--render` is used via `requires = "out"`.
+        let out_dir = args.out.as_deref().unwrap();
+        let arch = parse_or_exit(file_path, args.lenient);
+        match headless_render::render_arch_to_dir(&arch, out_dir, args.snap, args.palette) {
+            Ok(written) => {
+                for path in written {
+                    println!("Wrote: {}", path.display());
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Failed to write images to {}: {}", out_dir.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // --layout-metrics: print the size of each block of a tile, fully expanded.
+    if let Some(file_path) = args.layout_metrics.as_deref() {
+        // Safe: clap enforces `tile` is present when `--layout-metrics` is used via `requires = "tile"`.
+        let tile_name = args.tile.as_deref().unwrap();
+        let arch = parse_or_exit(file_path, args.lenient);
+        match headless_render::layout_metrics_report(&arch, tile_name) {
+            Ok(report) => {
+                print!("{report}");
                 return Ok(());
             }
             Err(e) => {
-                eprintln!(
-                    "Parse error in {}:\n{}",
-                    file_path.display(),
-                    error_report::format_parse_error(&e, Some(file_path))
-                );
+                eprintln!("{e}");
                 std::process::exit(1);
             }
         }
@@ -0,0 +1,49 @@
+//! Performance Overlay
+//!
+//! A debugging aid that shows the frame rate, the time spent on the last
+//! frame, and how much the active view drew in it.
+
+use eframe::egui;
+
+/// Shortcut that shows or hides the overlay.
+pub const PERF_OVERLAY_KEY: egui::Key = egui::Key::F12;
+
+/// What a view drew in its last frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrawStats {
+    pub blocks: usize,
+    pub wires: usize,
+}
+
+/// Shows the overlay in the bottom-right corner of the window. `draw_stats`
+/// is None when the active view does not count what it draws.
+pub fn render_perf_overlay(
+    ctx: &egui::Context,
+    fps: f32,
+    frame_time: Option<f32>,
+    draw_stats: Option<DrawStats>,
+) {
+    egui::Area::new(egui::Id::new("perf_overlay"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let font = egui::TextStyle::Monospace;
+                ui.label(egui::RichText::new(format!("FPS:    {fps:.1}")).text_style(font.clone()));
+                let frame_time = match frame_time {
+                    Some(seconds) => format!("{:.2} ms", seconds * 1000.0),
+                    None => "-".to_string(),
+                };
+                ui.label(
+                    egui::RichText::new(format!("Frame:  {frame_time}")).text_style(font.clone()),
+                );
+                let (blocks, wires) = match draw_stats {
+                    Some(stats) => (stats.blocks.to_string(), stats.wires.to_string()),
+                    None => ("-".to_string(), "-".to_string()),
+                };
+                ui.label(egui::RichText::new(format!("Blocks: {blocks}")).text_style(font.clone()));
+                ui.label(egui::RichText::new(format!("Wires:  {wires}")).text_style(font));
+            });
+        });
+}
@@ -1,10 +1,8 @@
 use std::collections::HashMap;
 
 use egui::{Color32, epaint::QuadraticBezierShape};
-use fpga_arch_parser::{
-    DelayInfo, DelayType, FPGAArch, Model, ModelPort, PBType, Port, TimingConstraintInfo,
-    TimingConstraintType,
-};
+use fpga_arch_parser::prelude::*;
+use fpga_arch_parser::{TimingConstraintInfo, TimingConstraintType};
 
 // --- Visual style constants ---
 
@@ -1500,13 +1498,13 @@ fn constraint_checkbox(ui: &mut egui::Ui, value: &mut bool, label: &str, color:
 
 /// Returns true if `pb_type`'s `blif_model` field references `model_name`.
 ///
-/// Built-in models (`.input`, `.output`, `.latch`, `.names`) are stored verbatim;
-/// custom models are prefixed with `.subckt `.
+/// Built-in models are named as written (`.input`, `.output`, `.latch`,
+/// `.names`); custom models by the name of their `.subckt`.
 fn pb_type_matches_model(pb_type: &PBType, model_name: &str) -> bool {
-    if model_name.starts_with('.') {
-        pb_type.blif_model.as_deref() == Some(model_name)
-    } else {
-        pb_type.blif_model.as_deref() == Some(&format!(".subckt {model_name}"))
+    match &pb_type.blif_model {
+        Some(BlifModel::Subckt(model)) => model == model_name,
+        Some(blif_model) => model_name.starts_with('.') && blif_model.raw() == model_name,
+        None => false,
     }
 }
 
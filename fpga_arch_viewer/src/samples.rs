@@ -8,6 +8,10 @@ pub struct SampleArchitecture {
 impl SampleArchitecture {
     pub fn all() -> &'static [Self] {
         static SAMPLES: &[SampleArchitecture] = &[
+            SampleArchitecture {
+                name: "Simple k4 Architecture",
+                data: include_bytes!("../../fpga_arch_parser/tests/k4_N4_90nm.xml"),
+            },
             SampleArchitecture {
                 name: "MCNC Architecture",
                 data: include_bytes!("../../fpga_arch_parser/tests/k6_frac_N10_40nm.xml"),
@@ -28,3 +32,22 @@ impl SampleArchitecture {
         SAMPLES
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_sample_parses() {
+        for sample in SampleArchitecture::all() {
+            let arch = fpga_arch_parser::parse_from_bytes(sample.data)
+                .unwrap_or_else(|e| panic!("{} failed to parse: {e:?}", sample.name));
+            assert!(!arch.tiles.is_empty(), "{} has no tiles", sample.name);
+            assert!(
+                !arch.layouts.layout_list.is_empty(),
+                "{} has no layouts",
+                sample.name
+            );
+        }
+    }
+}
@@ -1,10 +1,54 @@
 use crate::block_style::{DefaultBlockStyles, draw_block};
+use crate::color_scheme::Palette;
+use crate::complex_block_view::{ComplexBlockSettings, DefaultExpansion};
+use crate::grid_view::{
+    GridSettings, cell_borders_control, cell_size_control, empty_cells_control, y_origin_control,
+};
+use crate::intra_tile::{DEFAULT_MAX_RENDER_DEPTH, MAX_RENDER_DEPTH_LIMIT, WireStyle};
 use eframe::egui;
 
+/// Which theme the viewer is drawn in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ThemeChoice {
+    Light,
+    Dark,
+    /// Follow the light or dark preference of the operating system.
+    #[default]
+    Auto,
+}
+
+impl ThemeChoice {
+    pub const ALL: [Self; 3] = [Self::Light, Self::Dark, Self::Auto];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Light => "☀ Light",
+            Self::Dark => "🌙 Dark",
+            Self::Auto => "🖥 Auto",
+        }
+    }
+
+    /// Whether to draw in dark mode, given the theme the system reports, if
+    /// it reports one. Auto falls back to light.
+    pub fn is_dark(self, system_theme: Option<egui::Theme>) -> bool {
+        match self {
+            Self::Light => false,
+            Self::Dark => true,
+            Self::Auto => system_theme == Some(egui::Theme::Dark),
+        }
+    }
+}
+
 pub fn render_settings_page(
     ui: &mut egui::Ui,
     block_styles: &DefaultBlockStyles,
-    dark_mode: &mut bool,
+    theme: &mut ThemeChoice,
+    palette: &mut Palette,
+    dark_mode: bool,
+    lenient_parsing: &mut bool,
+    grid: &mut GridSettings,
+    complex_block: &mut ComplexBlockSettings,
+    show_perf_overlay: &mut bool,
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.heading("Settings");
@@ -17,9 +61,101 @@ pub fn render_settings_page(
             ui.horizontal(|ui| {
                 ui.label("Theme:");
                 ui.add_space(10.0);
-                ui.selectable_value(dark_mode, false, "☀ Light");
-                ui.selectable_value(dark_mode, true, "🌙 Dark");
+                for choice in ThemeChoice::ALL {
+                    ui.selectable_value(theme, choice, choice.label());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Palette:");
+                ui.add_space(10.0);
+                for choice in Palette::ALL {
+                    ui.selectable_value(palette, choice, choice.label());
+                }
+            })
+            .response
+            .on_hover_text(
+                "Colors of tile types, block classes, segments, and pack patterns. \
+                 The color-blind safe palette uses the Okabe-Ito colors.",
+            );
+        });
+
+        ui.add_space(30.0);
+
+        // Grid view options
+        ui.group(|ui| {
+            ui.heading("Grid View");
+            ui.add_space(10.0);
+            cell_size_control(ui, &mut grid.cell_size);
+            empty_cells_control(ui, &mut grid.mark_empty_cells);
+            cell_borders_control(ui, &mut grid.show_cell_borders);
+            y_origin_control(ui, &mut grid.y_origin);
+        });
+
+        ui.add_space(30.0);
+
+        // Complex block view options
+        ui.group(|ui| {
+            ui.heading("Complex Block View");
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label("Default expansion on open:");
+                egui::ComboBox::from_id_salt("default_expansion")
+                    .selected_text(complex_block.default_expansion.label())
+                    .show_ui(ui, |ui| {
+                        for expansion in DefaultExpansion::ALL {
+                            ui.selectable_value(
+                                &mut complex_block.default_expansion,
+                                expansion,
+                                expansion.label(),
+                            );
+                        }
+                    });
+            });
+            wire_style_controls(ui, &mut complex_block.wire_style);
+            ui.checkbox(&mut complex_block.show_canvas_grid, "Background grid")
+                .on_hover_text(
+                    "Draw dots every 50 px behind the blocks to help judge sizes and alignment",
+                );
+            ui.horizontal(|ui| {
+                ui.label("Max render depth:");
+                ui.add(egui::Slider::new(
+                    &mut complex_block.max_render_depth,
+                    1..=MAX_RENDER_DEPTH_LIMIT,
+                ))
+                .on_hover_text(
+                    "Blocks nested deeper than this are drawn collapsed, which keeps the viewer \
+                     responsive on very deep hierarchies",
+                );
+                if ui.small_button("Reset").clicked() {
+                    complex_block.max_render_depth = DEFAULT_MAX_RENDER_DEPTH;
+                }
             });
+            // Blocks can only be exported in the native app.
+            #[cfg(not(target_arch = "wasm32"))]
+            export_snap_control(ui, &mut complex_block.export_snap_step);
+        });
+
+        ui.add_space(30.0);
+
+        // Parsing options
+        ui.group(|ui| {
+            ui.heading("Parsing");
+            ui.add_space(10.0);
+            ui.checkbox(lenient_parsing, "Lenient parsing")
+                .on_hover_text(
+                    "Accept incomplete architectures (e.g. tiles without equivalent sites). \
+                     Applies the next time a file is opened.",
+                );
+        });
+
+        ui.add_space(30.0);
+
+        // Debugging options
+        ui.group(|ui| {
+            ui.heading("Debugging");
+            ui.add_space(10.0);
+            ui.checkbox(show_perf_overlay, "Show performance overlay (F12)")
+                .on_hover_text("Frame rate, frame time, and blocks and wires drawn per frame");
         });
 
         ui.add_space(30.0);
@@ -38,7 +174,7 @@ pub fn render_settings_page(
                 // IO Block
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    draw_block(ui, &block_styles.io, block_size, *dark_mode);
+                    draw_block(ui, &block_styles.io, block_size, dark_mode);
                     ui.add_space(8.0);
                     ui.label(egui::RichText::new(block_styles.io.full_name).size(12.0));
                 });
@@ -48,7 +184,7 @@ pub fn render_settings_page(
                 // LB Block
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    draw_block(ui, &block_styles.lb, block_size, *dark_mode);
+                    draw_block(ui, &block_styles.lb, block_size, dark_mode);
                     ui.add_space(8.0);
                     ui.label(egui::RichText::new(block_styles.lb.full_name).size(12.0));
                 });
@@ -60,7 +196,7 @@ pub fn render_settings_page(
                 // SB Block
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    draw_block(ui, &block_styles.sb, block_size, *dark_mode);
+                    draw_block(ui, &block_styles.sb, block_size, dark_mode);
                     ui.add_space(8.0);
                     ui.label(egui::RichText::new(block_styles.sb.full_name).size(12.0));
                 });
@@ -70,7 +206,7 @@ pub fn render_settings_page(
                 // CB Block
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    draw_block(ui, &block_styles.cb, block_size, *dark_mode);
+                    draw_block(ui, &block_styles.cb, block_size, dark_mode);
                     ui.add_space(8.0);
                     ui.label(egui::RichText::new(block_styles.cb.full_name).size(12.0));
                 });
@@ -100,3 +236,73 @@ pub fn render_settings_page(
         });
     });
 }
+
+/// Sliders for the wire widths and a picker for the highlight color.
+fn wire_style_controls(ui: &mut egui::Ui, wire_style: &mut WireStyle) {
+    ui.horizontal(|ui| {
+        ui.label("Wire thickness:");
+        ui.add(
+            egui::Slider::new(&mut wire_style.thickness, 0.5..=WireStyle::MAX_THICKNESS)
+                .suffix(" px"),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Highlighted wire thickness:");
+        ui.add(
+            egui::Slider::new(
+                &mut wire_style.highlight_thickness,
+                0.5..=WireStyle::MAX_THICKNESS,
+            )
+            .suffix(" px"),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Highlight color:");
+        ui.color_edit_button_srgba(&mut wire_style.highlight_color);
+        if ui.small_button("Reset").clicked() {
+            *wire_style = WireStyle::default();
+        }
+    });
+}
+
+/// Checkbox to round the coordinates of exported blocks to a grid, and the
+/// step of that grid.
+#[cfg(not(target_arch = "wasm32"))]
+fn export_snap_control(ui: &mut egui::Ui, snap_step: &mut Option<f32>) {
+    ui.horizontal(|ui| {
+        let mut snap = snap_step.is_some();
+        if ui
+            .checkbox(&mut snap, "Snap exported SVGs to a grid")
+            .on_hover_text(
+                "Round block and wire coordinates of exported blocks so lines stay crisp \
+                 when the image is rasterized. Does not change the view. Exports are SVG \
+                 only, so snapping is off unless turned on here.",
+            )
+            .changed()
+        {
+            *snap_step = snap.then_some(1.0);
+        }
+        if let Some(step) = snap_step {
+            ui.add(
+                egui::DragValue::new(step)
+                    .range(0.5..=50.0)
+                    .speed(0.5)
+                    .suffix(" px"),
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_theme_follows_the_system() {
+        assert!(ThemeChoice::Auto.is_dark(Some(egui::Theme::Dark)));
+        assert!(!ThemeChoice::Auto.is_dark(Some(egui::Theme::Light)));
+        assert!(!ThemeChoice::Auto.is_dark(None));
+        assert!(ThemeChoice::Dark.is_dark(Some(egui::Theme::Light)));
+        assert!(!ThemeChoice::Light.is_dark(Some(egui::Theme::Dark)));
+    }
+}
@@ -1,26 +1,53 @@
-use crate::{complex_block_view::ComplexBlockViewState, viewer::ViewMode};
-use fpga_arch_parser::FPGAArch;
+use crate::{
+    color_scheme::{self, Palette},
+    complex_block_view::ComplexBlockViewState,
+    grid::PlacementConflict,
+    viewer::ViewMode,
+};
+use fpga_arch_parser::prelude::*;
+use fpga_arch_parser::{CustomSwitchBlockLocation, CustomSwitchBlockType};
 
 #[derive(Default)]
-pub struct SummaryView {}
+pub struct SummaryView {
+    // Validation warnings for the loaded architecture.
+    validation_warnings: Vec<ValidationWarning>,
+    // Problems the parser worked around while loading the architecture.
+    parse_warnings: Vec<ParseWarning>,
+}
 
 impl SummaryView {
+    pub fn on_architecture_load(&mut self, arch: &FPGAArch, parse_warnings: Vec<ParseWarning>) {
+        self.validation_warnings = fpga_arch_parser::validate_architecture(arch);
+        self.parse_warnings = parse_warnings;
+    }
+
+    pub fn parse_warning_count(&self) -> usize {
+        self.parse_warnings.len()
+    }
+
+    /// `placement_conflicts` are those of the layout the grid view shows.
     pub fn render(
         &mut self,
         arch: &FPGAArch,
+        placement_conflicts: &[PlacementConflict],
         selected_tile_name: &mut Option<String>,
         complex_block_view_state: &mut ComplexBlockViewState,
         selected_model_name: &mut Option<String>,
         next_view_mode: &mut ViewMode,
+        palette: Palette,
+        dark_mode: bool,
         ctx: &egui::Context,
     ) {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_summary(
                 arch,
+                placement_conflicts,
                 selected_tile_name,
                 complex_block_view_state,
                 selected_model_name,
                 next_view_mode,
+                palette,
+                dark_mode,
                 ui,
             );
         });
@@ -29,10 +56,13 @@ impl SummaryView {
     fn render_summary(
         &mut self,
         arch: &FPGAArch,
+        placement_conflicts: &[PlacementConflict],
         selected_tile_name: &mut Option<String>,
         complex_block_view_state: &mut ComplexBlockViewState,
         selected_model_name: &mut Option<String>,
         next_view_mode: &mut ViewMode,
+        palette: Palette,
+        dark_mode: bool,
         ui: &mut egui::Ui,
     ) {
         egui::ScrollArea::vertical()
@@ -41,6 +71,55 @@ impl SummaryView {
                 ui.heading("FPGA Architecture Summary");
                 ui.add_space(10.0);
 
+                // Parse Warnings Section
+                if !self.parse_warnings.is_empty() {
+                    ui.group(|ui| {
+                        ui.heading(format!("Parse Warnings ({})", self.parse_warnings.len()));
+                        ui.separator();
+
+                        let warning_color = ui.visuals().warn_fg_color;
+                        for warning in &self.parse_warnings {
+                            ui.colored_label(warning_color, format!("⚠ {warning}"));
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                }
+
+                // Cells of the shown layout claimed by two tile types of
+                // equal priority.
+                if !placement_conflicts.is_empty() {
+                    ui.group(|ui| {
+                        ui.heading(format!("Layout Warnings ({})", placement_conflicts.len()));
+                        ui.separator();
+
+                        let warning_color = ui.visuals().warn_fg_color;
+                        for conflict in placement_conflicts {
+                            ui.colored_label(warning_color, format!("⚠ {conflict}"));
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                }
+
+                // Validation Section
+                if !self.validation_warnings.is_empty() {
+                    ui.group(|ui| {
+                        ui.heading(format!(
+                            "Validation Warnings ({})",
+                            self.validation_warnings.len()
+                        ));
+                        ui.separator();
+
+                        let warning_color = ui.visuals().warn_fg_color;
+                        for warning in &self.validation_warnings {
+                            ui.colored_label(warning_color, format!("⚠ {warning}"));
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                }
+
                 // Device Info Section
                 ui.group(|ui| {
                     ui.heading("Device Information");
@@ -57,6 +136,32 @@ impl SummaryView {
                     if let Some(fs) = arch.device.switch_block.sb_fs {
                         ui.label(format!("Switch Block Fs: {}", fs));
                     }
+                    if matches!(arch.device.switch_block.sb_type, SBType::Custom) {
+                        ui.collapsing(
+                            format!("Custom Switch Blocks ({})", arch.custom_switch_blocks.len()),
+                            |ui| {
+                                if arch.custom_switch_blocks.is_empty() {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        "No <switchblock> is defined in <switchblocklist>.",
+                                    );
+                                }
+                                for switch_block in &arch.custom_switch_blocks {
+                                    let sb_type = match switch_block.sb_type {
+                                        CustomSwitchBlockType::Unidir => "unidir",
+                                        CustomSwitchBlockType::Bidir => "bidir",
+                                    };
+                                    ui.label(format!(
+                                        "{} ({sb_type}, {})",
+                                        switch_block.name,
+                                        custom_switch_block_location_label(
+                                            &switch_block.switchblock_location
+                                        )
+                                    ));
+                                }
+                            },
+                        );
+                    }
                 });
 
                 ui.add_space(10.0);
@@ -162,21 +267,40 @@ impl SummaryView {
                     ui.heading(format!("Segments ({})", arch.segment_list.len()));
                     ui.separator();
 
+                    // Segments of the same length share a color.
+                    let mut distinct_lengths: Vec<SegmentLength> =
+                        arch.segment_list.iter().map(|s| s.length).collect();
+                    distinct_lengths.sort_unstable();
+                    distinct_lengths.dedup();
+
                     ui.collapsing("Segments", |ui| {
                         for (seg_idx, segment) in arch.segment_list.iter().enumerate() {
-                            ui.collapsing(
-                                format!("[{}] L{}: {}", seg_idx, segment.length, &segment.name),
-                                |ui| {
-                                    ui.label(format!("Axis: {:?}", segment.axis));
-                                    ui.label(format!("Type: {:?}", segment.segment_type));
-                                    ui.label(format!("Length: {}", segment.length));
-                                    ui.label(format!("Frequency: {:.2}", segment.freq));
-                                    ui.label(format!(
-                                        "Metal: R={}, C={:e}",
-                                        segment.r_metal, segment.c_metal
-                                    ));
-                                },
-                            );
+                            let length_rank = distinct_lengths
+                                .binary_search(&segment.length)
+                                .unwrap_or_default();
+                            let color =
+                                color_scheme::segment_length_color(length_rank, palette, dark_mode);
+                            ui.horizontal(|ui| {
+                                draw_segment_direction_indicator(ui, &segment.segment_type, color);
+                                ui.collapsing(
+                                    format!(
+                                        "[{}] {}: {}",
+                                        seg_idx,
+                                        segment_length_label(segment.length),
+                                        &segment.name
+                                    ),
+                                    |ui| {
+                                        ui.label(format!("Axis: {:?}", segment.axis));
+                                        ui.label(format!("Type: {:?}", segment.segment_type));
+                                        ui.label(format!("Length: {}", segment.length));
+                                        ui.label(format!("Frequency: {:.2}", segment.freq));
+                                        ui.label(format!(
+                                            "Metal: R={}, C={:e}",
+                                            segment.r_metal, segment.c_metal
+                                        ));
+                                    },
+                                );
+                            });
                         }
                     });
                 });
@@ -272,3 +396,61 @@ impl SummaryView {
             });
     }
 }
+
+/// Short label for a segment length, e.g. "L4" or "longline".
+fn segment_length_label(length: SegmentLength) -> String {
+    match length {
+        SegmentLength::Finite(length) => format!("L{length}"),
+        SegmentLength::LongLine => "longline".to_string(),
+    }
+}
+
+/// Describes where a custom switch block applies, e.g. "perimeter" or
+/// "x=2, y=3".
+fn custom_switch_block_location_label(location: &CustomSwitchBlockLocation) -> String {
+    match location {
+        CustomSwitchBlockLocation::Everywhere => "everywhere".to_string(),
+        CustomSwitchBlockLocation::Perimeter => "perimeter".to_string(),
+        CustomSwitchBlockLocation::Corner => "corner".to_string(),
+        CustomSwitchBlockLocation::Fringe => "fringe".to_string(),
+        CustomSwitchBlockLocation::Core => "core".to_string(),
+        CustomSwitchBlockLocation::XYSpecified { x, y } => format!("x={x}, y={y}"),
+    }
+}
+
+/// Draws a short wire with an arrowhead on each end that can drive it: one for
+/// unidirectional segments and both ends for bidirectional segments.
+fn draw_segment_direction_indicator(
+    ui: &mut egui::Ui,
+    segment_type: &SegmentType,
+    color: egui::Color32,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(48.0, 14.0), egui::Sense::hover());
+    let painter = ui.painter();
+    let left = egui::pos2(rect.min.x + 2.0, rect.center().y);
+    let right = egui::pos2(rect.max.x - 2.0, rect.center().y);
+    painter.line_segment([left, right], egui::Stroke::new(2.0, color));
+
+    let draw_arrowhead = |tip: egui::Pos2, dir: f32| {
+        let back = egui::vec2(-dir * 7.0, 0.0);
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                tip,
+                tip + back + egui::vec2(0.0, -5.0),
+                tip + back + egui::vec2(0.0, 5.0),
+            ],
+            color,
+            egui::Stroke::NONE,
+        ));
+    };
+    draw_arrowhead(right, 1.0);
+    let direction = match segment_type {
+        SegmentType::Unidir => "Unidirectional",
+        SegmentType::Bidir => {
+            draw_arrowhead(left, -1.0);
+            "Bidirectional"
+        }
+    };
+
+    response.on_hover_text(direction)
+}
@@ -0,0 +1,42 @@
+//! Test Fixtures
+//!
+//! The k4_N4_90nm architecture from the parser's tests, loaded one way for
+//! the unit tests of every view.
+
+use fpga_arch_parser::prelude::*;
+
+const K4_N4_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../fpga_arch_parser/tests/k4_N4_90nm.xml"
+);
+
+/// The text of k4_N4_90nm.xml with each `(from, to)` replacement applied in
+/// turn. Every `from` must appear, so a fixture that drifts from the file
+/// fails instead of quietly testing the unchanged architecture.
+pub fn k4_n4_xml(replacements: &[(&str, &str)]) -> String {
+    let mut xml = std::fs::read_to_string(K4_N4_PATH).expect("Failed to read k4_N4_90nm.xml");
+    for (from, to) in replacements {
+        assert!(xml.contains(from), "k4_N4_90nm.xml has no {from:?}");
+        xml = xml.replace(from, to);
+    }
+    xml
+}
+
+/// k4_N4_90nm.xml parsed with each `(from, to)` replacement applied.
+pub fn k4_n4_with(replacements: &[(&str, &str)]) -> FPGAArch {
+    fpga_arch_parser::parse_from_bytes(k4_n4_xml(replacements).as_bytes())
+        .expect("Failed to parse k4_N4_90nm.xml")
+}
+
+pub fn k4_n4() -> FPGAArch {
+    k4_n4_with(&[])
+}
+
+/// The clb complex block of k4_N4_90nm.xml.
+pub fn k4_n4_clb() -> PBType {
+    k4_n4()
+        .complex_block_list
+        .into_iter()
+        .find(|pb| pb.name == "clb")
+        .expect("k4_N4_90nm.xml has a clb")
+}
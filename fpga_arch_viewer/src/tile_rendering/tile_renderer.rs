@@ -2,10 +2,11 @@
 //!
 //! Used for rendering a tile, described in the architecture description file.
 //!
-//! NOTE: This only draws the logical block. It does not include the switch block
-//!       or the channel wires.
+//! NOTE: This only draws the logical block and any custom switch block locations
+//!       (<sb_loc>) of the tile. It does not include the channel wires.
 
-use fpga_arch_parser::{PinSide, Tile};
+use fpga_arch_parser::prelude::*;
+use fpga_arch_parser::{SwitchBlockLocationType, SwitchBlockLocationsPattern};
 
 use crate::block_style;
 use crate::color_scheme;
@@ -25,6 +26,24 @@ pub struct TileRenderer {
 
     /// The radius of drawn pins.
     pub pin_radius: f32,
+
+    /// The shapes that make up the custom switch block location markers.
+    pub sb_shapes: Vec<egui::Shape>,
+
+    /// The bounds of each custom switch block location marker. Indexed the same
+    /// as the custom <sb_loc> entries of the tile.
+    pub sb_rects: Vec<egui::Rect>,
+}
+
+/// Get the name of the given switch block location type, as written in the
+/// architecture description file.
+pub fn sb_type_name(sb_type: &SwitchBlockLocationType) -> &'static str {
+    match sb_type {
+        SwitchBlockLocationType::Full => "full",
+        SwitchBlockLocationType::Straight => "straight",
+        SwitchBlockLocationType::Turns => "turns",
+        SwitchBlockLocationType::None => "none",
+    }
 }
 
 pub fn build_render_tile(
@@ -155,10 +174,46 @@ pub fn build_render_tile(
         }
     }
 
+    // Draw the custom switch block locations. In VPR, the switch block of a grid
+    // location sits at the top-right corner of that location, so each <sb_loc> is
+    // placed at the top-right corner of the cell at its offset within the tile.
+    let mut sb_shapes: Vec<egui::Shape> = Vec::new();
+    let mut sb_rects: Vec<egui::Rect> = Vec::new();
+    if let Some(SwitchBlockLocationsPattern::Custom(custom_locations)) = tile
+        .switchblock_locations
+        .as_ref()
+        .map(|locations| &locations.pattern)
+    {
+        let cell_width = tile_bounding_box.width() / tile.width as f32;
+        let cell_height = tile_bounding_box.height() / tile.height as f32;
+        let sb_size = cell_min_length / 6.0;
+        for sb_loc in custom_locations {
+            let corner = egui::pos2(
+                tile_bounding_box.left() + (sb_loc.xoffset + 1) as f32 * cell_width,
+                tile_bounding_box.bottom() - (sb_loc.yoffset + 1) as f32 * cell_height,
+            );
+            let sb_rect = egui::Rect::from_center_size(corner, egui::Vec2::splat(sb_size));
+            let fill = match sb_loc.sb_type {
+                SwitchBlockLocationType::None => color_scheme::theme_block_bg(dark_mode),
+                _ => color_scheme::grid_sb_color(dark_mode),
+            };
+            sb_shapes.push(egui::Shape::rect_filled(sb_rect, 0.0, fill));
+            sb_shapes.push(egui::Shape::rect_stroke(
+                sb_rect,
+                egui::CornerRadius::ZERO,
+                egui::Stroke::new(1.5, color_scheme::theme_border_color(dark_mode)),
+                egui::epaint::StrokeKind::Inside,
+            ));
+            sb_rects.push(sb_rect);
+        }
+    }
+
     TileRenderer {
         lb_shapes,
         pin_shapes,
         pin_locations,
         pin_radius,
+        sb_shapes,
+        sb_rects,
     }
 }
@@ -1,11 +1,17 @@
 use egui::ScrollArea;
-use fpga_arch_parser::FPGAArch;
+use fpga_arch_parser::prelude::*;
+use fpga_arch_parser::{SubTileIOFC, SwitchBlockLocationsPattern};
 
 use std::collections::HashMap;
 
 use crate::{
-    color_scheme, common_ui, complex_block_view::ComplexBlockViewState, intra_hierarchy_tree,
-    tile_rendering::tile_renderer::build_render_tile, viewer::ViewMode,
+    arch_index::ArchIndex,
+    color_scheme, common_ui,
+    complex_block_view::ComplexBlockViewState,
+    grid_view::tile_area,
+    intra_hierarchy_tree,
+    tile_rendering::tile_renderer::{build_render_tile, sb_type_name},
+    viewer::ViewMode,
 };
 
 pub struct TileView {
@@ -26,6 +32,7 @@ impl TileView {
     pub fn render(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         complex_block_view_state: &mut ComplexBlockViewState,
         next_view_mode: &mut ViewMode,
         tile_colors: &HashMap<String, egui::Color32>,
@@ -45,6 +52,7 @@ impl TileView {
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_central_panel(
                 arch,
+                arch_index,
                 complex_block_view_state,
                 next_view_mode,
                 tile_colors,
@@ -97,6 +105,7 @@ impl TileView {
     fn render_central_panel(
         &mut self,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         complex_block_view_state: &mut ComplexBlockViewState,
         next_view_mode: &mut ViewMode,
         tile_colors: &HashMap<String, egui::Color32>,
@@ -105,7 +114,7 @@ impl TileView {
     ) {
         match &self.selected_tile_name {
             Some(tile_name) => {
-                if let Some(tile) = arch.tiles.iter().find(|t| t.name == *tile_name) {
+                if let Some(tile) = arch_index.tile(arch, tile_name) {
                     self.render_tile(
                         tile,
                         complex_block_view_state,
@@ -113,6 +122,7 @@ impl TileView {
                         tile_colors,
                         dark_mode,
                         arch,
+                        arch_index,
                         ui,
                     );
                 } else if common_ui::render_centered_message(
@@ -145,6 +155,7 @@ impl TileView {
         tile_colors: &HashMap<String, egui::Color32>,
         dark_mode: bool,
         arch: &FPGAArch,
+        arch_index: &ArchIndex,
         ui: &mut egui::Ui,
     ) {
         // Fixed tile visualization panel at the top
@@ -197,6 +208,46 @@ impl TileView {
                         build_render_tile(tile, &tile_bounding_box, &color, dark_mode);
                     painter.extend(tile_renderer.lb_shapes);
                     painter.extend(tile_renderer.pin_shapes);
+                    painter.extend(tile_renderer.sb_shapes);
+
+                    // Label each custom switch block location with its type and
+                    // switch override, and show the full details on hover.
+                    if let Some(SwitchBlockLocationsPattern::Custom(custom_locations)) = tile
+                        .switchblock_locations
+                        .as_ref()
+                        .map(|locations| &locations.pattern)
+                    {
+                        let font_size = (10.0 * self.tile_zoom).clamp(6.0, 16.0);
+                        for (sb_loc, sb_rect) in
+                            custom_locations.iter().zip(&tile_renderer.sb_rects)
+                        {
+                            let label = match &sb_loc.switch_override {
+                                Some(switch_name) => {
+                                    format!("{}\n{}", sb_type_name(&sb_loc.sb_type), switch_name)
+                                }
+                                None => sb_type_name(&sb_loc.sb_type).to_string(),
+                            };
+                            painter.text(
+                                sb_rect.left_bottom() + egui::vec2(-2.0, 2.0),
+                                egui::Align2::RIGHT_TOP,
+                                label,
+                                egui::FontId::proportional(font_size),
+                                color_scheme::theme_text_color(dark_mode),
+                            );
+
+                            let sb_hit_response = ui.put(*sb_rect, egui::Label::new(""));
+                            sb_hit_response.on_hover_ui(|ui| {
+                                ui.label(format!(
+                                    "Switch block ({}, {})",
+                                    sb_loc.xoffset, sb_loc.yoffset
+                                ));
+                                ui.label(format!("Type: {}", sb_type_name(&sb_loc.sb_type)));
+                                if let Some(switch_name) = &sb_loc.switch_override {
+                                    ui.label(format!("Switch override: {}", switch_name));
+                                }
+                            });
+                        }
+                    }
 
                     // When hovering over a pin, print the name of the pin.
                     for (pin_index, pin_locations) in tile_renderer.pin_locations.iter().enumerate()
@@ -230,12 +281,12 @@ impl TileView {
                     ui.separator();
 
                     ui.label(format!("Dimensions: {}x{}", tile.width, tile.height));
-                    ui.label(format!(
-                        "Area: {}",
-                        tile.area
-                            .map(|a| format!("{:.2}", a))
-                            .unwrap_or_else(|| "N/A".to_string())
-                    ));
+                    let (area, is_default) = tile_area(arch, tile);
+                    if is_default {
+                        ui.label(format!("Area: {:.2} (grid default)", area));
+                    } else {
+                        ui.label(format!("Area: {:.2}", area));
+                    }
                 });
 
                 ui.add_space(10.0);
@@ -281,6 +332,9 @@ impl TileView {
 
                         for port in &tile.ports {
                             let port_info = match port {
+                                fpga_arch_parser::Port::Input(p) if p.is_non_clock_global => {
+                                    format!("{} (Global input, {} pins)", p.name, p.num_pins)
+                                }
                                 fpga_arch_parser::Port::Input(p) => {
                                     format!("{} (Input, {} pins)", p.name, p.num_pins)
                                 }
@@ -307,6 +361,30 @@ impl TileView {
                         ui.collapsing(format!("[{}] {}", idx, &sub_tile.name), |ui| {
                             ui.label(format!("Capacity: {}", sub_tile.capacity));
                             ui.label(format!("Ports: {}", sub_tile.ports.len()));
+                            match &sub_tile.fc {
+                                Some(fc) => {
+                                    let source = if sub_tile.fc_from_default {
+                                        " (device default)"
+                                    } else {
+                                        ""
+                                    };
+                                    ui.label(format!(
+                                        "Fc: in {}, out {}{}",
+                                        fc_value_label(&fc.in_fc),
+                                        fc_value_label(&fc.out_fc),
+                                        source
+                                    ));
+                                    if !fc.fc_overrides.is_empty() {
+                                        ui.label(format!(
+                                            "Fc overrides: {}",
+                                            fc.fc_overrides.len()
+                                        ));
+                                    }
+                                }
+                                None => {
+                                    ui.label("Fc: none");
+                                }
+                            }
 
                             if !sub_tile.equivalent_sites.is_empty() {
                                 ui.collapsing(
@@ -337,11 +415,42 @@ impl TileView {
                 ui.add_space(10.0);
 
                 // Switch Block Locations Section
-                if tile.switchblock_locations.is_some() {
+                if let Some(locations) = &tile.switchblock_locations {
                     ui.group(|ui| {
                         ui.heading("Switch Block Locations");
                         ui.separator();
-                        ui.label("Switch block locations configured for this tile");
+
+                        let pattern_name = match &locations.pattern {
+                            SwitchBlockLocationsPattern::ExternalFullInternalStraight => {
+                                "external_full_internal_straight"
+                            }
+                            SwitchBlockLocationsPattern::All => "all",
+                            SwitchBlockLocationsPattern::External => "external",
+                            SwitchBlockLocationsPattern::Internal => "internal",
+                            SwitchBlockLocationsPattern::None => "none",
+                            SwitchBlockLocationsPattern::Custom(_) => "custom",
+                        };
+                        ui.label(format!("Pattern: {}", pattern_name));
+                        if let Some(internal_switch) = &locations.internal_switch {
+                            ui.label(format!("Internal switch: {}", internal_switch));
+                        }
+
+                        if let SwitchBlockLocationsPattern::Custom(custom_locations) =
+                            &locations.pattern
+                        {
+                            for sb_loc in custom_locations {
+                                let mut sb_info = format!(
+                                    "({}, {}) {}",
+                                    sb_loc.xoffset,
+                                    sb_loc.yoffset,
+                                    sb_type_name(&sb_loc.sb_type)
+                                );
+                                if let Some(switch_name) = &sb_loc.switch_override {
+                                    sb_info.push_str(&format!(" (switch: {})", switch_name));
+                                }
+                                ui.label(sb_info);
+                            }
+                        }
                     });
                 }
 
@@ -355,10 +464,20 @@ impl TileView {
                         egui::ScrollArea::both()
                             .max_height(ui.available_height())
                             .show(ui, |ui| {
-                                intra_hierarchy_tree::render_hierarchy_tree(ui, arch, tile);
+                                intra_hierarchy_tree::render_hierarchy_tree(
+                                    ui, arch, arch_index, tile,
+                                );
                             });
                     });
                 });
             });
     }
 }
+
+/// An Fc value as written in the file, e.g. "0.15 (frac)" or "4 (abs)".
+fn fc_value_label(fc: &SubTileIOFC) -> String {
+    match fc {
+        SubTileIOFC::Frac(frac) => format!("{frac} (frac)"),
+        SubTileIOFC::Abs(abs) => format!("{abs} (abs)"),
+    }
+}
@@ -0,0 +1,121 @@
+//! Open from URL
+//!
+//! Downloads an architecture file over HTTP(S) on a background thread and
+//! parses it from memory, reporting progress to the UI while it runs.
+
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use fpga_arch_parser::prelude::*;
+
+use crate::viewer::ParsedArch;
+
+/// Largest architecture file that will be downloaded.
+const MAX_DOWNLOAD_BYTES: u64 = 256 * 1024 * 1024;
+
+pub enum UrlLoadError {
+    /// The file could not be downloaded.
+    Download(String),
+    /// The file was downloaded but is not a valid architecture.
+    Parse(FPGAArchParseError),
+}
+
+/// A download running on a background thread.
+pub struct UrlDownload {
+    pub url: String,
+    received_bytes: Arc<AtomicU64>,
+    // Size announced by the server; 0 if it did not say.
+    total_bytes: Arc<AtomicU64>,
+    rx: Receiver<Result<ParsedArch, UrlLoadError>>,
+}
+
+impl UrlDownload {
+    /// Starts downloading and parsing `url`.
+    pub fn start(url: String, options: ParseOptions) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let received_bytes = Arc::new(AtomicU64::new(0));
+        let total_bytes = Arc::new(AtomicU64::new(0));
+
+        let worker_url = url.clone();
+        let worker_received = received_bytes.clone();
+        let worker_total = total_bytes.clone();
+        std::thread::spawn(move || {
+            let result = download(&worker_url, &worker_received, &worker_total).and_then(|data| {
+                ParsedArch::from_bytes(&data, &options).map_err(UrlLoadError::Parse)
+            });
+            let _ = tx.send(result);
+        });
+
+        Self {
+            url,
+            received_bytes,
+            total_bytes,
+            rx,
+        }
+    }
+
+    /// Bytes received so far, and the total size if the server reported it.
+    pub fn progress(&self) -> (u64, Option<u64>) {
+        let received = self.received_bytes.load(Ordering::Relaxed);
+        let total = self.total_bytes.load(Ordering::Relaxed);
+        (received, (total > 0).then_some(total))
+    }
+
+    /// The result, once the download and parse have finished.
+    pub fn try_finish(&self) -> Option<Result<ParsedArch, UrlLoadError>> {
+        match self.rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(UrlLoadError::Download(
+                "The download stopped unexpectedly".to_string(),
+            ))),
+        }
+    }
+
+    /// Name for the tab: the last segment of the URL path.
+    pub fn file_name(&self) -> String {
+        url_file_name(&self.url)
+    }
+}
+
+/// The last segment of the path of `url`, or the whole URL if it has none.
+pub fn url_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(url)
+        .to_string()
+}
+
+fn download(
+    url: &str,
+    received_bytes: &AtomicU64,
+    total_bytes: &AtomicU64,
+) -> Result<Vec<u8>, UrlLoadError> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| UrlLoadError::Download(e.to_string()))?;
+    let body = response.into_body();
+    if let Some(length) = body.content_length() {
+        total_bytes.store(length, Ordering::Relaxed);
+    }
+
+    let mut reader = body.into_with_config().limit(MAX_DOWNLOAD_BYTES).reader();
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        let read = reader
+            .read(&mut chunk)
+            .map_err(|e| UrlLoadError::Download(e.to_string()))?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&chunk[..read]);
+        received_bytes.store(data.len() as u64, Ordering::Relaxed);
+    }
+    Ok(data)
+}
@@ -1,8 +1,8 @@
 use eframe::egui;
-use fpga_arch_parser::FPGAArch;
+use fpga_arch_parser::prelude::*;
 use log::{info, warn};
 
-use crate::error_report::format_parse_error;
+use crate::error_report::{ErrorLocation, format_parse_error};
 
 #[cfg(target_arch = "wasm32")]
 use rfd::AsyncFileDialog;
@@ -11,16 +11,24 @@ use std::cell::RefCell;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::spawn_local;
 
+use crate::arch_diff::{self, ArchDiff, ChangeHighlight};
+use crate::arch_index::ArchIndex;
+use crate::arch_sections::{self, SectionInfo};
 use crate::block_style::DefaultBlockStyles;
+use crate::color_scheme::Palette;
 use crate::common_ui;
-use crate::complex_block_view::ComplexBlockView;
+use crate::complex_block_view::{ComplexBlockSettings, ComplexBlockView, DefaultExpansion};
 use crate::crr_sb_view::CRRSBView;
-use crate::grid_view::GridView;
+use crate::grid_view::{GridSettings, GridView};
+use crate::perf_overlay::{self, DrawStats, PERF_OVERLAY_KEY};
 use crate::primitive_view::PrimitiveView;
 use crate::samples::SampleArchitecture;
-use crate::settings;
+use crate::settings::{self, ThemeChoice};
 use crate::summary_view::SummaryView;
 use crate::tile_view::TileView;
+#[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+use crate::url_loader::{UrlDownload, UrlLoadError};
+use crate::xml_source;
 
 // Thread-local storage for pending wasm file loads
 // This avoids using unsafe raw pointers in the async file dialog closure
@@ -29,6 +37,34 @@ thread_local! {
     static PENDING_WASM_FILE_LOAD: RefCell<Option<(Vec<u8>, String)>> = RefCell::new(None);
 }
 
+/// An architecture parsed from a file, with the warnings from parsing it and
+/// the text it was parsed from.
+pub struct ParsedArch {
+    pub arch: FPGAArch,
+    pub warnings: Vec<ParseWarning>,
+    pub source: String,
+}
+
+impl ParsedArch {
+    pub fn from_bytes(data: &[u8], options: &ParseOptions) -> Result<Self, FPGAArchParseError> {
+        let (arch, warnings) = fpga_arch_parser::parse_from_bytes_with_warnings(data, options)?;
+        Ok(Self {
+            arch,
+            warnings,
+            source: String::from_utf8_lossy(data).into_owned(),
+        })
+    }
+
+    pub fn from_file(
+        arch_file: &std::path::Path,
+        options: &ParseOptions,
+    ) -> Result<Self, FPGAArchParseError> {
+        Self::from_bytes(&fpga_arch_parser::read_arch_source(arch_file)?, options)
+    }
+}
+
+type ParseResult = Result<ParsedArch, FPGAArchParseError>;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ViewMode {
     Summary,
@@ -39,6 +75,13 @@ pub enum ViewMode {
     CRRSwitchBlock,
 }
 
+/// Shortcut to jump to the inter-tile (grid) view.
+const INTER_TILE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Num1);
+/// Shortcut to jump to the intra-tile (complex block) view.
+const INTRA_TILE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Num2);
+
 // NOTE: These act more like tabs, so while you are looking at settings,
 //       the main page stays around in the background.
 // TODO: We should make these actual tabs.
@@ -51,27 +94,102 @@ pub enum Page {
 pub struct ViewerContext {
     pub show_about: bool,
     pub current_page: Page,
-    // Navigation state
-    pub navigation_history: Vec<ViewMode>,
-    pub skip_nav_history_update: bool,
     // Block styles
     pub block_styles: DefaultBlockStyles,
-    // Currently loaded architecture file path
-    pub loaded_file_path: Option<std::path::PathBuf>,
     // Cache the last window title we set
     pub window_title: String,
-    // Theme setting
+    // Theme setting, and whether it currently resolves to dark
+    pub theme: ThemeChoice,
     pub dark_mode: bool,
+    // Colors for tile types, block classes, segments, and pack patterns
+    pub palette: Palette,
+    // Parse incomplete architectures instead of rejecting them
+    pub lenient_parsing: bool,
+    // Cell size, empty cell marking, cell borders and y origin of the grid
+    // view
+    pub grid_settings: GridSettings,
+    // Default expansion, wire style, background grid and render depth of the
+    // complex block view
+    pub complex_block_settings: ComplexBlockSettings,
+    // Show the frame rate and draw counts over the page (toggled with F12)
+    pub show_perf_overlay: bool,
+    // Show the XML of the selected tile, block or interconnect in a side panel
+    pub show_xml_source: bool,
     // Error window state
     pub show_error: bool,
     pub error_title: String,
     pub error_message: String,
+    // Where in the file a parse error is, so it can be opened in an editor
+    pub error_location: Option<ErrorLocation>,
 }
 
-pub struct FpgaViewer {
-    // Parsed architecture
+/// Where a tab's architecture was loaded from. Tabs are keyed by it, and a
+/// tab is reloaded from it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArchSource {
+    /// A file on disk.
+    Path(std::path::PathBuf),
+    /// A file downloaded from this URL.
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    Url(String),
+    /// One of the built-in samples, by name.
+    Sample(&'static str),
+    /// A file read without a path, by name, such as one opened in the web
+    /// build. It cannot be read again.
+    Upload(String),
+}
+
+impl ArchSource {
+    /// Name of the file, shown on its tab.
+    pub fn file_name(&self) -> String {
+        match self {
+            ArchSource::Path(path) => path
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+            ArchSource::Url(url) => crate::url_loader::url_file_name(url),
+            ArchSource::Sample(name) => name.to_string(),
+            ArchSource::Upload(name) => name.clone(),
+        }
+    }
+
+    /// Where the file is in full: its absolute path, its URL, or its name.
+    pub fn location(&self) -> String {
+        match self {
+            ArchSource::Path(path) => std::path::absolute(path)
+                .unwrap_or_else(|_| path.clone())
+                .display()
+                .to_string(),
+            #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+            ArchSource::Url(url) => url.clone(),
+            ArchSource::Sample(name) => format!("Sample architecture {name}"),
+            ArchSource::Upload(name) => name.clone(),
+        }
+    }
+
+    /// The file's path, if it was loaded from disk.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            ArchSource::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// An architecture file opened in its own tab. Every tab keeps its own view
+/// state, so switching between files does not lose the user's place.
+pub struct LoadedArch {
+    // Parsed architecture. None if the file failed to parse; the tab is kept
+    // so the file can be fixed and reloaded.
     pub architecture: Option<FPGAArch>,
-    viewer_ctx: ViewerContext,
+    // Name lookups into the architecture, rebuilt whenever it is replaced
+    arch_index: ArchIndex,
+    // Where the file was loaded from
+    pub arch_source: ArchSource,
+    // Text the architecture was parsed from, with XIncludes resolved
+    source: Option<String>,
 
     summary_view: SummaryView,
     grid_view: GridView,
@@ -83,42 +201,467 @@ pub struct FpgaViewer {
     view_mode: ViewMode,
     next_view_mode: ViewMode,
 
+    // Navigation state
+    navigation_history: Vec<ViewMode>,
+    skip_nav_history_update: bool,
+
+    // What changed when the file was last reloaded, highlighted until it
+    // fades, and the time it was first shown.
+    arch_diff: Option<ArchDiff>,
+    arch_diff_shown_at: Option<f64>,
+
+    // Optional sections of the file, shown in a banner until it is closed.
+    optional_sections: Vec<SectionInfo>,
+    sections_banner_dismissed: bool,
+}
+
+impl LoadedArch {
+    fn new(arch_source: ArchSource) -> Self {
+        Self {
+            architecture: None,
+            arch_index: ArchIndex::default(),
+            arch_source,
+            source: None,
+            summary_view: SummaryView::default(),
+            grid_view: GridView::default(),
+            tile_view: TileView::default(),
+            complex_block_view: ComplexBlockView::default(),
+            primitive_view: PrimitiveView::default(),
+            crr_sb_view: CRRSBView::default(),
+            view_mode: ViewMode::Summary,
+            next_view_mode: ViewMode::Summary,
+            navigation_history: Vec::new(),
+            skip_nav_history_update: false,
+            arch_diff: None,
+            arch_diff_shown_at: None,
+            optional_sections: Vec::new(),
+            sections_banner_dismissed: false,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.arch_source.file_name()
+    }
+
+    fn set_architecture(&mut self, parsed: Option<ParsedArch>) {
+        let (architecture, parse_warnings, source) = match parsed {
+            Some(parsed) => (Some(parsed.arch), parsed.warnings, Some(parsed.source)),
+            None => (None, Vec::new(), None),
+        };
+        // Reloading a file that loaded before highlights what the edit changed.
+        self.arch_diff = match (&self.architecture, &architecture) {
+            (Some(old), Some(new)) => Some(ArchDiff::between(old, new)).filter(|d| !d.is_empty()),
+            _ => None,
+        };
+        self.arch_diff_shown_at = None;
+
+        if let Some(arch) = &architecture {
+            // Update views with new architecture.
+            self.grid_view.on_architecture_load(arch);
+            self.summary_view.on_architecture_load(arch, parse_warnings);
+            self.optional_sections = arch_sections::optional_sections(arch);
+        }
+        self.arch_index = architecture
+            .as_ref()
+            .map(ArchIndex::new)
+            .unwrap_or_default();
+        self.architecture = architecture;
+        self.source = source;
+    }
+
+    fn navigate_back(&mut self) {
+        // Navigate back in view mode history
+        if let Some(previous_mode) = self.navigation_history.pop() {
+            self.next_view_mode = previous_mode;
+            self.skip_nav_history_update = true;
+        }
+    }
+
+    /// What the current view drew in its last frame, if it keeps count.
+    fn draw_stats(&self) -> Option<DrawStats> {
+        match self.view_mode {
+            ViewMode::Grid => Some(self.grid_view.grid_renderer.draw_stats),
+            ViewMode::ComplexBlock => Some(
+                self.complex_block_view
+                    .complex_block_view_state
+                    .intra_tile_state
+                    .draw_stats,
+            ),
+            _ => None,
+        }
+    }
+
+    /// Switches to the intra-tile view. If no complex block is selected yet,
+    /// fall back to the first site of the selected tile (or the first tile).
+    fn open_intra_tile_view(&mut self) {
+        let state = &mut self.complex_block_view.complex_block_view_state;
+        if state.selected_complex_block_name.is_none()
+            && let Some(arch) = &self.architecture
+        {
+            let tile = self
+                .tile_view
+                .selected_tile_name
+                .as_ref()
+                .and_then(|name| self.arch_index.tile(arch, name))
+                .or_else(|| arch.tiles.first());
+            state.selected_complex_block_name = tile
+                .and_then(|t| t.sub_tiles.first())
+                .and_then(|sub_tile| sub_tile.equivalent_sites.first())
+                .map(|site| site.pb_type.clone());
+        }
+        self.next_view_mode = ViewMode::ComplexBlock;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn selected_complex_block_graph(&self) -> Option<(&str, &fpga_arch_parser::ComplexBlockGraph)> {
+        let arch = self.architecture.as_ref()?;
+        let name = self
+            .complex_block_view
+            .complex_block_view_state
+            .selected_complex_block_name
+            .as_deref()?;
+        let index = self.arch_index.complex_block_index(name)?;
+        Some((name, arch.complex_block_graphs.get(index)?))
+    }
+
+    /// The interconnect table of the selected complex block as CSV, along with
+    /// the instance path of the block it lists.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn selected_interconnect_csv(&self) -> Option<(String, String)> {
+        let arch = self.architecture.as_ref()?;
+        let state = &self.complex_block_view.complex_block_view_state;
+        let name = state.selected_complex_block_name.as_deref()?;
+        let root_pb = self.arch_index.complex_block(arch, name)?;
+        Some(crate::intra_tile::interconnect_table_csv(
+            root_pb,
+            &state.intra_tile_state,
+        ))
+    }
+
+    /// The tag and source line of the element selected in the current view.
+    fn selected_source_element(&self) -> Option<(&'static str, usize)> {
+        let arch = self.architecture.as_ref()?;
+        match self.view_mode {
+            ViewMode::Grid | ViewMode::Tile => {
+                let name = self.tile_view.selected_tile_name.as_ref()?;
+                let tile = self.arch_index.tile(arch, name)?;
+                Some(("tile", tile.source_line.0?))
+            }
+            ViewMode::ComplexBlock => {
+                let state = &self.complex_block_view.complex_block_view_state;
+                let name = state.selected_complex_block_name.as_deref()?;
+                let root_pb = self.arch_index.complex_block(arch, name)?;
+                crate::intra_tile::selected_source_element(root_pb, &state.intra_tile_state)
+            }
+            _ => None,
+        }
+    }
+
+    /// Resets every view's selection, expansion, and zoom while keeping the
+    /// parsed architecture loaded.
+    fn reset_views(&mut self) {
+        self.tile_view = TileView::default();
+        self.complex_block_view.reset();
+        self.grid_view.grid_state.reset_zoom();
+    }
+
+    /// Passes the fading highlight of the last reload's changes on to the
+    /// views, and drops it once it has faded out.
+    fn update_change_highlight(&mut self, ctx: &egui::Context) {
+        let strength = match &self.arch_diff {
+            Some(_) => {
+                let now = ctx.input(|i| i.time);
+                let shown_at = *self.arch_diff_shown_at.get_or_insert(now);
+                arch_diff::highlight_strength(now - shown_at, arch_diff::HIGHLIGHT_SECONDS)
+            }
+            None => 0.0,
+        };
+        if strength <= 0.0 {
+            self.arch_diff = None;
+        }
+
+        let (grid_changes, pb_type_changes) = match &self.arch_diff {
+            Some(diff) => {
+                ctx.request_repaint();
+                (
+                    Some(ChangeHighlight {
+                        names: diff.highlighted_tiles(),
+                        strength,
+                    }),
+                    Some(ChangeHighlight {
+                        names: diff.changed_pb_types.iter().cloned().collect(),
+                        strength,
+                    }),
+                )
+            }
+            None => (None, None),
+        };
+        self.grid_view.grid_state.recent_changes = grid_changes;
+        self.complex_block_view
+            .complex_block_view_state
+            .intra_tile_state
+            .recent_changes = pb_type_changes;
+    }
+
+    /// Lists what changed in the last reload in a corner of the window,
+    /// fading out along with the highlights.
+    fn render_change_summary(&self, ctx: &egui::Context) {
+        let (Some(diff), Some(shown_at)) = (&self.arch_diff, self.arch_diff_shown_at) else {
+            return;
+        };
+        let strength = arch_diff::highlight_strength(
+            ctx.input(|i| i.time) - shown_at,
+            arch_diff::HIGHLIGHT_SECONDS,
+        );
+        egui::Area::new(egui::Id::new("arch_diff_summary"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.set_opacity(strength);
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new("Changes since last load").strong());
+                    for (label, names) in [
+                        ("Added tiles", &diff.added_tiles),
+                        ("Removed tiles", &diff.removed_tiles),
+                        ("Changed tiles", &diff.changed_tiles),
+                        ("Changed pb_types", &diff.changed_pb_types),
+                    ] {
+                        if !names.is_empty() {
+                            ui.label(format!("{label}: {}", names.join(", ")));
+                        }
+                    }
+                });
+            });
+    }
+
+    fn render(&mut self, viewer_ctx: &mut ViewerContext, ctx: &egui::Context) {
+        self.update_change_highlight(ctx);
+        if self.architecture.is_some()
+            && !self.optional_sections.is_empty()
+            && !self.sections_banner_dismissed
+        {
+            egui::TopBottomPanel::top("arch_sections_banner").show(ctx, |ui| {
+                arch_sections::render_sections_banner(
+                    ui,
+                    &self.optional_sections,
+                    &mut self.sections_banner_dismissed,
+                );
+            });
+        }
+        if self.architecture.is_some() && viewer_ctx.show_xml_source {
+            xml_source::render_xml_source_panel(
+                ctx,
+                self.source.as_deref(),
+                self.selected_source_element(),
+            );
+        }
+        match &self.architecture {
+            Some(arch) => match self.view_mode {
+                ViewMode::Summary => self.summary_view.render(
+                    arch,
+                    self.grid_view
+                        .device_grid
+                        .as_ref()
+                        .map_or(&[], |grid| &grid.placement_conflicts),
+                    &mut self.tile_view.selected_tile_name,
+                    &mut self.complex_block_view.complex_block_view_state,
+                    &mut self.primitive_view.selected_model_name,
+                    &mut self.next_view_mode,
+                    viewer_ctx.palette,
+                    viewer_ctx.dark_mode,
+                    ctx,
+                ),
+                ViewMode::Grid => self.grid_view.render(
+                    arch,
+                    &self.arch_index,
+                    &mut self.tile_view.selected_tile_name,
+                    &mut self
+                        .complex_block_view
+                        .complex_block_view_state
+                        .selected_complex_block_name,
+                    &mut self.next_view_mode,
+                    &mut viewer_ctx.grid_settings,
+                    viewer_ctx.dark_mode,
+                    ctx,
+                ),
+                ViewMode::Tile => self.tile_view.render(
+                    arch,
+                    &self.arch_index,
+                    &mut self.complex_block_view.complex_block_view_state,
+                    &mut self.next_view_mode,
+                    &self.grid_view.tile_colors,
+                    viewer_ctx.dark_mode,
+                    ctx,
+                ),
+                ViewMode::ComplexBlock => self.complex_block_view.render(
+                    arch,
+                    &self.arch_index,
+                    &mut self.next_view_mode,
+                    viewer_ctx.complex_block_settings,
+                    viewer_ctx.palette,
+                    viewer_ctx.dark_mode,
+                    ctx,
+                ),
+                ViewMode::Primitive => self.primitive_view.render(arch, ctx),
+                ViewMode::CRRSwitchBlock => self.crr_sb_view.render(
+                    arch,
+                    &self.grid_view.tile_colors,
+                    viewer_ctx.dark_mode,
+                    ctx,
+                ),
+            },
+            None => {
+                // The file failed to parse, so there is nothing to show until it is reloaded.
+                let heading = format!("Failed to load {}", self.file_name());
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    common_ui::render_centered_message(
+                        ui,
+                        &heading,
+                        "Fix the errors in the file and reload it.",
+                        None,
+                    );
+                });
+            }
+        }
+
+        self.render_change_summary(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.export_requested_block(
+            viewer_ctx.complex_block_settings.export_snap_step,
+            viewer_ctx.palette,
+        );
+    }
+
+    /// Asks where to save the block picked with "Export this block" and writes
+    /// it, with what is drawn inside it, as SVG.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_requested_block(&mut self, snap_step: Option<f32>, palette: Palette) {
+        let state = &mut self.complex_block_view.complex_block_view_state;
+        let Some(instance_path) = state.intra_tile_state.export_block_request.take() else {
+            return;
+        };
+        let Some(root_pb) = self
+            .architecture
+            .as_ref()
+            .zip(state.selected_complex_block_name.as_deref())
+            .and_then(|(arch, name)| self.arch_index.complex_block(arch, name))
+        else {
+            return;
+        };
+        let Some(svg) = crate::headless_render::render_block_svg(
+            root_pb,
+            &state.intra_tile_state,
+            &instance_path,
+            state.draw_intra_interconnects,
+            palette,
+            snap_step,
+        ) else {
+            warn!("Block {instance_path} is not drawn, so it cannot be exported");
+            return;
+        };
+        let file_name = format!("{}.svg", instance_path.replace(['.', '[', ']'], "_"));
+        std::thread::spawn(move || {
+            let path = rfd::FileDialog::new()
+                .add_filter("SVG Images", &["svg"])
+                .set_title("Export Block")
+                .set_file_name(file_name)
+                .save_file();
+            if let Some(path) = path
+                && let Err(e) = std::fs::write(&path, svg)
+            {
+                warn!("Failed to write {}: {e}", path.display());
+            }
+        });
+    }
+
+    /// Next state logic for the view mode.
+    fn update_view_mode(&mut self, default_expansion: DefaultExpansion) {
+        if self.view_mode == self.next_view_mode {
+            return;
+        }
+
+        // Push current mode to history before transitioning
+        if !self.skip_nav_history_update {
+            self.navigation_history.push(self.view_mode);
+        }
+        self.skip_nav_history_update = false;
+
+        // Run code on the close of a view.
+        if self.view_mode == ViewMode::ComplexBlock {
+            self.complex_block_view.on_view_close()
+        }
+
+        // Run code on the open of a view.
+        if self.next_view_mode == ViewMode::ComplexBlock {
+            self.complex_block_view.on_view_open(
+                self.architecture.as_ref(),
+                &self.arch_index,
+                default_expansion,
+            )
+        }
+
+        self.view_mode = self.next_view_mode;
+    }
+}
+
+pub struct FpgaViewer {
+    // Open architecture files, one per tab
+    pub tabs: Vec<LoadedArch>,
+    active_tab: usize,
+    viewer_ctx: ViewerContext,
+
     fps: f32,
 
     // Receives the path chosen by the native file dialog, which runs in a
     // background thread so the UI thread is never blocked.
     #[cfg(not(target_arch = "wasm32"))]
     pending_file_dialog: Option<std::sync::mpsc::Receiver<Option<std::path::PathBuf>>>,
+
+    // The files currently being parsed in background threads, along with the
+    // channels their results will be sent on.
+    #[cfg(not(target_arch = "wasm32"))]
+    pending_parses: Vec<(std::path::PathBuf, std::sync::mpsc::Receiver<ParseResult>)>,
+
+    // Text of the "Open from URL" dialog; None while the dialog is closed.
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    url_dialog_text: Option<String>,
+    // The architecture currently being downloaded, if any.
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    pending_download: Option<UrlDownload>,
 }
 
 impl FpgaViewer {
     pub fn new(initial_file: Option<std::path::PathBuf>) -> Self {
         let mut viewer = Self {
-            architecture: None,
+            tabs: Vec::new(),
+            active_tab: 0,
             viewer_ctx: ViewerContext {
                 show_about: false,
                 current_page: Page::Main,
-                navigation_history: Vec::new(),
-                skip_nav_history_update: false,
                 block_styles: DefaultBlockStyles::new(),
-                loaded_file_path: None,
                 window_title: "FPGA Architecture Visualizer".to_string(),
+                theme: ThemeChoice::default(),
+                palette: Palette::default(),
                 dark_mode: false,
+                lenient_parsing: false,
+                grid_settings: GridSettings::default(),
+                complex_block_settings: ComplexBlockSettings::default(),
+                show_perf_overlay: false,
+                show_xml_source: false,
                 show_error: false,
                 error_title: String::new(),
                 error_message: String::new(),
+                error_location: None,
             },
-            summary_view: SummaryView::default(),
-            grid_view: GridView::default(),
-            tile_view: TileView::default(),
-            complex_block_view: ComplexBlockView::default(),
-            primitive_view: PrimitiveView::default(),
-            crr_sb_view: CRRSBView::default(),
-            view_mode: ViewMode::Summary,
-            next_view_mode: ViewMode::Summary,
             fps: 0.0,
             #[cfg(not(target_arch = "wasm32"))]
             pending_file_dialog: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_parses: Vec::new(),
+            #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+            url_dialog_text: None,
+            #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+            pending_download: None,
         };
         if let Some(path) = initial_file {
             viewer.load_architecture_file(path);
@@ -126,12 +669,48 @@ impl FpgaViewer {
         viewer
     }
 
+    fn active_tab(&self) -> Option<&LoadedArch> {
+        self.tabs.get(self.active_tab)
+    }
+
+    fn active_tab_mut(&mut self) -> Option<&mut LoadedArch> {
+        self.tabs.get_mut(self.active_tab)
+    }
+
     fn loaded_arch_filename(&self) -> Option<String> {
-        self.viewer_ctx
-            .loaded_file_path
-            .as_ref()
-            .and_then(|p| p.file_name())
-            .map(|s| s.to_string_lossy().to_string())
+        self.active_tab().map(LoadedArch::file_name)
+    }
+
+    fn set_next_view_mode(&mut self, view_mode: ViewMode) {
+        if let Some(tab) = self.active_tab_mut() {
+            tab.next_view_mode = view_mode;
+        }
+    }
+
+    /// Shows the result of loading `arch_source` in its tab, opening a new
+    /// tab unless it is already open, and makes that tab active.
+    fn open_in_tab(&mut self, arch_source: ArchSource, parsed: Option<ParsedArch>) {
+        let index = match self
+            .tabs
+            .iter()
+            .position(|tab| tab.arch_source == arch_source)
+        {
+            Some(index) => index,
+            None => {
+                self.tabs.push(LoadedArch::new(arch_source));
+                self.tabs.len() - 1
+            }
+        };
+        self.tabs[index].set_architecture(parsed);
+        self.active_tab = index;
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        self.tabs.remove(index);
+        // Keep the same tab active, or its right neighbour if it was closed.
+        if self.active_tab > index || self.active_tab >= self.tabs.len() {
+            self.active_tab = self.active_tab.saturating_sub(1);
+        }
     }
 
     fn desired_window_title(&self) -> String {
@@ -141,23 +720,48 @@ impl FpgaViewer {
         }
     }
 
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            lenient: self.viewer_ctx.lenient_parsing,
+            ..Default::default()
+        }
+    }
+
+    /// Parses the architecture file on a background thread so the UI stays
+    /// responsive for very large files. The result is picked up in `update`.
+    #[cfg(not(target_arch = "wasm32"))]
     fn load_architecture_file(&mut self, file_path: std::path::PathBuf) {
-        match fpga_arch_parser::parse(&file_path) {
-            Ok(arch) => {
-                // Update views with new architecture.
-                self.grid_view.on_architecture_load(&arch);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let worker_path = file_path.clone();
+        let options = self.parse_options();
+        std::thread::spawn(move || {
+            let _ = tx.send(ParsedArch::from_file(&worker_path, &options));
+        });
+        // Any parse of the same file still in flight is superseded by this one.
+        self.pending_parses.retain(|(path, _)| *path != file_path);
+        self.pending_parses.push((file_path, rx));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load_architecture_file(&mut self, file_path: std::path::PathBuf) {
+        let result = ParsedArch::from_file(&file_path, &self.parse_options());
+        self.on_architecture_file_parsed(result, file_path);
+    }
 
+    fn on_architecture_file_parsed(&mut self, result: ParseResult, file_path: std::path::PathBuf) {
+        let parsed = match result {
+            Ok(parsed) => {
                 // Update viewer context.
-                self.architecture = Some(arch);
                 self.viewer_ctx.show_error = false;
                 self.viewer_ctx.error_title.clear();
                 self.viewer_ctx.error_message.clear();
+                self.viewer_ctx.error_location = None;
 
                 // Print success.
                 info!("Successfully loaded architecture file: {:?}", file_path);
+                Some(parsed)
             }
             Err(e) => {
-                self.architecture = None;
                 self.viewer_ctx.show_error = true;
                 self.viewer_ctx.error_title = "Parse Error".to_owned();
                 self.viewer_ctx.error_message = format!(
@@ -165,47 +769,114 @@ impl FpgaViewer {
                     file_path,
                     format_parse_error(&e, Some(&file_path))
                 );
+                self.viewer_ctx.error_location = ErrorLocation::of(&e, &file_path);
+                None
             }
-        }
+        };
 
-        // Since this is a tool for debugging architectures, we should remember
-        // the path of the loaded file even if it fails so it can be fixed.
-        self.viewer_ctx.loaded_file_path = Some(file_path);
+        // Since this is a tool for debugging architectures, we should keep a
+        // tab for the file even if it fails so it can be fixed and reloaded.
+        self.open_in_tab(ArchSource::Path(file_path), parsed);
     }
 
-    fn load_architecture_from_bytes(&mut self, data: Vec<u8>, file_name: String) {
-        match fpga_arch_parser::parse_from_bytes(&data) {
-            Ok(arch) => {
-                // Update views with new architecture.
-                self.grid_view.on_architecture_load(&arch);
-
+    fn load_architecture_from_bytes(&mut self, data: Vec<u8>, arch_source: ArchSource) {
+        let file_name = arch_source.file_name();
+        let parsed = match ParsedArch::from_bytes(&data, &self.parse_options()) {
+            Ok(parsed) => {
                 // Update viewer context.
-                self.architecture = Some(arch);
                 self.viewer_ctx.show_error = false;
                 self.viewer_ctx.error_title.clear();
                 self.viewer_ctx.error_message.clear();
+                self.viewer_ctx.error_location = None;
 
                 // Print success.
                 info!("Successfully loaded architecture file: {}", file_name);
+                Some(parsed)
             }
             Err(e) => {
-                self.architecture = None;
                 self.viewer_ctx.show_error = true;
                 self.viewer_ctx.error_title = "Parse Error".to_owned();
+                self.viewer_ctx.error_location = None;
                 self.viewer_ctx.error_message = format!(
                     "Error loading architecture: {}\n\n{}",
                     file_name,
                     format_parse_error(&e, None)
                 );
+                None
             }
-        }
+        };
 
-        // Store the file name (we don't have a path in WASM)
-        self.viewer_ctx.loaded_file_path = Some(file_name.into());
+        self.open_in_tab(arch_source, parsed);
+    }
+
+    /// Picks up a finished download. Download failures only show an error;
+    /// files that fail to parse get a tab, like local files do.
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    fn poll_url_download(&mut self, ctx: &egui::Context) {
+        let Some(download) = &self.pending_download else {
+            return;
+        };
+        let Some(result) = download.try_finish() else {
+            ctx.request_repaint();
+            return;
+        };
+        let url = download.url.clone();
+        self.pending_download = None;
+
+        let parsed = match result {
+            Ok(parsed) => {
+                self.viewer_ctx.show_error = false;
+                self.viewer_ctx.error_title.clear();
+                self.viewer_ctx.error_message.clear();
+                self.viewer_ctx.error_location = None;
+                info!("Successfully loaded architecture from: {}", url);
+                Some(parsed)
+            }
+            Err(UrlLoadError::Download(message)) => {
+                self.viewer_ctx.show_error = true;
+                self.viewer_ctx.error_title = "Download Error".to_owned();
+                self.viewer_ctx.error_location = None;
+                self.viewer_ctx.error_message =
+                    format!("Error downloading architecture:\n{}\n\n{}", url, message);
+                return;
+            }
+            Err(UrlLoadError::Parse(e)) => {
+                self.viewer_ctx.show_error = true;
+                self.viewer_ctx.error_title = "Parse Error".to_owned();
+                self.viewer_ctx.error_location = None;
+                self.viewer_ctx.error_message = format!(
+                    "Error loading architecture:\n{}\n\n{}",
+                    url,
+                    format_parse_error(&e, None)
+                );
+                None
+            }
+        };
+        self.open_in_tab(ArchSource::Url(url), parsed);
     }
 
     fn load_sample_architecture(&mut self, sample: &SampleArchitecture) {
-        self.load_architecture_from_bytes(sample.data.to_vec(), sample.name.to_string());
+        self.load_architecture_from_bytes(sample.data.to_vec(), ArchSource::Sample(sample.name));
+    }
+
+    /// Loads the active tab's architecture again from where it came from.
+    fn reload_active_tab(&mut self) {
+        let Some(arch_source) = self.active_tab().map(|tab| tab.arch_source.clone()) else {
+            return;
+        };
+        match arch_source {
+            ArchSource::Path(path) => self.load_architecture_file(path),
+            #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+            ArchSource::Url(url) => {
+                self.pending_download = Some(UrlDownload::start(url, self.parse_options()));
+            }
+            ArchSource::Sample(name) => {
+                if let Some(sample) = SampleArchitecture::all().iter().find(|s| s.name == name) {
+                    self.load_sample_architecture(sample);
+                }
+            }
+            ArchSource::Upload(_) => {}
+        }
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -254,8 +925,7 @@ impl FpgaViewer {
                 dropped_files = i.raw.dropped_files.clone();
             }
         });
-        // The viewer can only display one architecture at a time, so load the
-        // first supported file and ignore the rest.
+        // Every supported file is opened in its own tab.
         for file in dropped_files {
             if let Some(file_path) = file.path {
                 let is_xml = file_path
@@ -264,7 +934,6 @@ impl FpgaViewer {
                     .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
                 if is_xml {
                     self.load_architecture_file(file_path);
-                    break;
                 } else {
                     warn!("Cannot open dropped filepath: {}", file_path.display());
                 }
@@ -274,8 +943,7 @@ impl FpgaViewer {
                     .rsplit_once('.')
                     .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case("xml"));
                 if is_xml {
-                    self.load_architecture_from_bytes(data.to_vec(), file.name);
-                    break;
+                    self.load_architecture_from_bytes(data.to_vec(), ArchSource::Upload(file.name));
                 } else {
                     warn!("Cannot open dropped file: {}", file.name);
                 }
@@ -289,10 +957,114 @@ impl FpgaViewer {
             return;
         }
 
-        // Navigate back in view mode history
-        if let Some(previous_mode) = self.viewer_ctx.navigation_history.pop() {
-            self.next_view_mode = previous_mode;
-            self.viewer_ctx.skip_nav_history_update = true;
+        if let Some(tab) = self.active_tab_mut() {
+            tab.navigate_back();
+        }
+    }
+
+    /// Asks where to save the selected complex block's graph and writes it as
+    /// Graphviz DOT. The dialog runs on a background thread like the open dialog.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_complex_block_dot(&self) {
+        let Some((name, graph)) = self
+            .active_tab()
+            .and_then(LoadedArch::selected_complex_block_graph)
+        else {
+            return;
+        };
+        let dot = graph.to_dot();
+        let file_name = format!("{name}.dot");
+        std::thread::spawn(move || {
+            let path = rfd::FileDialog::new()
+                .add_filter("Graphviz DOT Files", &["dot", "gv"])
+                .set_title("Export Complex Block Graph")
+                .set_file_name(file_name)
+                .save_file();
+            if let Some(path) = path
+                && let Err(e) = std::fs::write(&path, dot)
+            {
+                warn!("Failed to write {}: {e}", path.display());
+            }
+        });
+    }
+
+    /// Asks where to save the active architecture as a text outline and
+    /// writes it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_outline(&self) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let Some(arch) = &tab.architecture else {
+            return;
+        };
+        let outline = arch.to_outline();
+        let file_name = tab.file_name();
+        let stem = std::path::Path::new(&file_name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "architecture".to_string());
+        let file_name = format!("{stem}_outline.txt");
+        std::thread::spawn(move || {
+            let path = rfd::FileDialog::new()
+                .add_filter("Text Files", &["txt"])
+                .set_title("Export Outline")
+                .set_file_name(file_name)
+                .save_file();
+            if let Some(path) = path
+                && let Err(e) = std::fs::write(&path, outline)
+            {
+                warn!("Failed to write {}: {e}", path.display());
+            }
+        });
+    }
+
+    /// Asks where to save the interconnects listed in the interconnect table
+    /// and writes them as CSV.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_interconnects_csv(&self) {
+        let Some((path, csv)) = self
+            .active_tab()
+            .and_then(LoadedArch::selected_interconnect_csv)
+        else {
+            return;
+        };
+        let file_name = format!("{}_interconnects.csv", path.replace(['.', '[', ']'], "_"));
+        std::thread::spawn(move || {
+            let path = rfd::FileDialog::new()
+                .add_filter("CSV Files", &["csv"])
+                .set_title("Export Interconnects")
+                .set_file_name(file_name)
+                .save_file();
+            if let Some(path) = path
+                && let Err(e) = std::fs::write(&path, csv)
+            {
+                warn!("Failed to write {}: {e}", path.display());
+            }
+        });
+    }
+
+    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        // Do not steal key presses from text fields (e.g. grid dimensions).
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, PERF_OVERLAY_KEY)) {
+            self.viewer_ctx.show_perf_overlay = !self.viewer_ctx.show_perf_overlay;
+        }
+
+        if self.viewer_ctx.current_page != Page::Main {
+            return;
+        }
+
+        if ctx.input_mut(|i| i.consume_shortcut(&INTER_TILE_SHORTCUT)) {
+            self.set_next_view_mode(ViewMode::Grid);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&INTRA_TILE_SHORTCUT))
+            && let Some(tab) = self.active_tab_mut()
+        {
+            tab.open_intra_tile_view();
         }
     }
 
@@ -324,10 +1096,9 @@ impl FpgaViewer {
                     }
                     ui.add_space(10.0);
 
-                    #[cfg(not(target_arch = "wasm32"))]
-                    let reload_enabled = self.viewer_ctx.loaded_file_path.is_some();
-                    #[cfg(target_arch = "wasm32")]
-                    let reload_enabled = false;
+                    let reload_enabled = self
+                        .active_tab()
+                        .is_some_and(|tab| !matches!(tab.arch_source, ArchSource::Upload(_)));
                     let reload_button = ui.add_enabled_ui(reload_enabled, |ui| {
                         ui.add_sized(
                             [BUTTON_SIZE, BUTTON_SIZE],
@@ -336,25 +1107,13 @@ impl FpgaViewer {
                                 .corner_radius(BUTTON_SIZE / 2.0),
                         )
                     });
-                    if reload_button.inner.clicked()
-                        && let Some(path) = self.viewer_ctx.loaded_file_path.clone()
-                    {
-                        self.load_architecture_file(path);
-                    }
-                    if reload_button.inner.hovered() {
-                        #[cfg(not(target_arch = "wasm32"))]
-                        {
-                            reload_button
-                                .inner
-                                .on_hover_text("Reload architecture file");
-                        }
-                        #[cfg(target_arch = "wasm32")]
-                        {
-                            reload_button
-                                .inner
-                                .on_hover_text("Reload not available in web build");
-                        }
+                    if reload_button.inner.clicked() {
+                        self.reload_active_tab();
                     }
+                    reload_button
+                        .inner
+                        .on_hover_text("Reload architecture file")
+                        .on_disabled_hover_text("Files opened without a path cannot be reloaded");
                     ui.add_space(10.0);
 
                     let settings_button = ui.add_sized(
@@ -372,7 +1131,9 @@ impl FpgaViewer {
                     ui.add_space(10.0);
 
                     let back_enabled = self.viewer_ctx.current_page == Page::Settings
-                        || !self.viewer_ctx.navigation_history.is_empty();
+                        || self
+                            .active_tab()
+                            .is_some_and(|tab| !tab.navigation_history.is_empty());
                     let back_button = ui.add_enabled_ui(back_enabled, |ui| {
                         ui.add_sized(
                             [BUTTON_SIZE, BUTTON_SIZE],
@@ -387,7 +1148,10 @@ impl FpgaViewer {
                     if back_button.inner.hovered() {
                         if self.viewer_ctx.current_page == Page::Settings {
                             back_button.inner.on_hover_text("Back to main");
-                        } else if self.view_mode == ViewMode::ComplexBlock {
+                        } else if self
+                            .active_tab()
+                            .is_some_and(|tab| tab.view_mode == ViewMode::ComplexBlock)
+                        {
                             back_button.inner.on_hover_text("Back to grid view");
                         } else {
                             back_button.inner.on_hover_text("Go back");
@@ -405,6 +1169,17 @@ impl FpgaViewer {
                         self.open_file_dialog(ctx.clone());
                         ui.close();
                     }
+                    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+                    if ui
+                        .add_enabled(
+                            self.pending_download.is_none(),
+                            egui::Button::new("Open from URL..."),
+                        )
+                        .clicked()
+                    {
+                        self.url_dialog_text = Some(String::new());
+                        ui.close();
+                    }
                     ui.menu_button("Open Sample Architecture", |ui| {
                         for sample in SampleArchitecture::all() {
                             if ui.button(sample.name).clicked() {
@@ -413,6 +1188,71 @@ impl FpgaViewer {
                             }
                         }
                     });
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        // Only files on disk can be shown, if they still exist.
+                        let loaded_path = self
+                            .active_tab()
+                            .and_then(|tab| tab.arch_source.path())
+                            .filter(|p| p.is_file())
+                            .map(std::path::Path::to_path_buf);
+                        if ui
+                            .add_enabled(
+                                loaded_path.is_some(),
+                                egui::Button::new("Show in File Browser"),
+                            )
+                            .clicked()
+                        {
+                            if let Some(path) = loaded_path
+                                && let Err(e) = reveal_in_file_browser(&path)
+                            {
+                                warn!("Failed to open file browser for {}: {e}", path.display());
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(
+                                self.active_tab()
+                                    .and_then(LoadedArch::selected_complex_block_graph)
+                                    .is_some(),
+                                egui::Button::new("Export Complex Block as DOT..."),
+                            )
+                            .clicked()
+                        {
+                            self.export_complex_block_dot();
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(
+                                self.active_tab()
+                                    .and_then(LoadedArch::selected_interconnect_csv)
+                                    .is_some(),
+                                egui::Button::new("Export Interconnects as CSV..."),
+                            )
+                            .clicked()
+                        {
+                            self.export_interconnects_csv();
+                            ui.close();
+                        }
+                        if ui
+                            .add_enabled(
+                                self.active_tab()
+                                    .is_some_and(|tab| tab.architecture.is_some()),
+                                egui::Button::new("Export Outline..."),
+                            )
+                            .clicked()
+                        {
+                            self.export_outline();
+                            ui.close();
+                        }
+                    }
+                    if ui
+                        .add_enabled(!self.tabs.is_empty(), egui::Button::new("Close Tab"))
+                        .clicked()
+                    {
+                        self.close_tab(self.active_tab);
+                        ui.close();
+                    }
                     ui.separator();
                     if ui.button("Exit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -421,27 +1261,56 @@ impl FpgaViewer {
 
                 ui.menu_button("View", |ui| {
                     if ui.button("Summary View").clicked() {
-                        self.next_view_mode = ViewMode::Summary;
+                        self.set_next_view_mode(ViewMode::Summary);
                         ui.close();
                     }
-                    if ui.button("Grid View").clicked() {
-                        self.next_view_mode = ViewMode::Grid;
+                    if ui
+                        .add(
+                            egui::Button::new("Grid View")
+                                .shortcut_text(ctx.format_shortcut(&INTER_TILE_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.set_next_view_mode(ViewMode::Grid);
                         ui.close();
                     }
                     if ui.button("Tile View").clicked() {
-                        self.next_view_mode = ViewMode::Tile;
+                        self.set_next_view_mode(ViewMode::Tile);
                         ui.close();
                     }
-                    if ui.button("Complex Block View").clicked() {
-                        self.next_view_mode = ViewMode::ComplexBlock;
+                    if ui
+                        .add(
+                            egui::Button::new("Complex Block View")
+                                .shortcut_text(ctx.format_shortcut(&INTRA_TILE_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.set_next_view_mode(ViewMode::ComplexBlock);
                         ui.close();
                     }
                     if ui.button("Primitive View").clicked() {
-                        self.next_view_mode = ViewMode::Primitive;
+                        self.set_next_view_mode(ViewMode::Primitive);
                         ui.close();
                     }
                     if ui.button("CRR Switch Block View").clicked() {
-                        self.next_view_mode = ViewMode::CRRSwitchBlock;
+                        self.set_next_view_mode(ViewMode::CRRSwitchBlock);
+                        ui.close();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.viewer_ctx.show_xml_source, "XML Source")
+                        .on_hover_text("Show the XML of the selected tile, block, or interconnect");
+                    if ui
+                        .add_enabled(
+                            self.active_tab()
+                                .is_some_and(|tab| tab.architecture.is_some()),
+                            egui::Button::new("Reset View to Defaults"),
+                        )
+                        .on_hover_text("Clear selection, expansion, modes, and zoom")
+                        .clicked()
+                    {
+                        if let Some(tab) = self.active_tab_mut() {
+                            tab.reset_views();
+                        }
                         ui.close();
                     }
                 });
@@ -455,7 +1324,10 @@ impl FpgaViewer {
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if let Some(name) = self.loaded_arch_filename() {
-                        ui.label(egui::RichText::new(name).strong());
+                        let label = ui.label(egui::RichText::new(name).strong());
+                        if let Some(tab) = self.active_tab() {
+                            label.on_hover_text(tab.arch_source.location());
+                        }
                     } else {
                         ui.label(egui::RichText::new("No file loaded").weak());
                     }
@@ -476,57 +1348,76 @@ impl FpgaViewer {
                     settings::render_settings_page(
                         ui,
                         &self.viewer_ctx.block_styles,
-                        &mut self.viewer_ctx.dark_mode,
+                        &mut self.viewer_ctx.theme,
+                        &mut self.viewer_ctx.palette,
+                        self.viewer_ctx.dark_mode,
+                        &mut self.viewer_ctx.lenient_parsing,
+                        &mut self.viewer_ctx.grid_settings,
+                        &mut self.viewer_ctx.complex_block_settings,
+                        &mut self.viewer_ctx.show_perf_overlay,
                     );
                 });
             }
         }
     }
 
+    fn render_tab_bar(&mut self, ctx: &egui::Context) {
+        if self.tabs.is_empty() {
+            return;
+        }
+
+        let mut close_index = None;
+        egui::TopBottomPanel::top("tab_bar").show(ctx, |ui| {
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, tab) in self.tabs.iter().enumerate() {
+                        // Tabs whose file failed to parse are shown in the error color,
+                        // and tabs that loaded with parse warnings carry a count.
+                        let warning_count = tab.summary_view.parse_warning_count();
+                        let mut title = egui::RichText::new(tab.file_name());
+                        if tab.architecture.is_none() {
+                            title = title.color(ui.visuals().error_fg_color);
+                        } else if warning_count > 0 {
+                            title = egui::RichText::new(format!(
+                                "{} ⚠ {warning_count}",
+                                tab.file_name()
+                            ))
+                            .color(ui.visuals().warn_fg_color);
+                        }
+                        let mut hover_text = tab.arch_source.location();
+                        if tab.architecture.is_some() && warning_count > 0 {
+                            hover_text.push_str(&format!(
+                                "\n{warning_count} parse warning(s), listed in the Summary view"
+                            ));
+                        }
+                        if ui
+                            .selectable_label(index == self.active_tab, title)
+                            .on_hover_text(hover_text)
+                            .clicked()
+                        {
+                            self.active_tab = index;
+                        }
+                        if ui.small_button("✖").on_hover_text("Close tab").clicked() {
+                            close_index = Some(index);
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+        });
+
+        if let Some(index) = close_index {
+            self.close_tab(index);
+        }
+    }
+
     fn render_main_page(&mut self, ctx: &egui::Context) {
-        match &self.architecture {
-            Some(arch) => match self.view_mode {
-                ViewMode::Summary => self.summary_view.render(
-                    arch,
-                    &mut self.tile_view.selected_tile_name,
-                    &mut self.complex_block_view.complex_block_view_state,
-                    &mut self.primitive_view.selected_model_name,
-                    &mut self.next_view_mode,
-                    ctx,
-                ),
-                ViewMode::Grid => self.grid_view.render(
-                    arch,
-                    &mut self.tile_view.selected_tile_name,
-                    &mut self.next_view_mode,
-                    self.viewer_ctx.dark_mode,
-                    ctx,
-                ),
-                ViewMode::Tile => self.tile_view.render(
-                    arch,
-                    &mut self.complex_block_view.complex_block_view_state,
-                    &mut self.next_view_mode,
-                    &self.grid_view.tile_colors,
-                    self.viewer_ctx.dark_mode,
-                    ctx,
-                ),
-                ViewMode::ComplexBlock => self.complex_block_view.render(
-                    arch,
-                    &mut self.next_view_mode,
-                    self.viewer_ctx.dark_mode,
-                    ctx,
-                ),
-                ViewMode::Primitive => self.primitive_view.render(arch, ctx),
-                ViewMode::CRRSwitchBlock => self.crr_sb_view.render(
-                    arch,
-                    &self.grid_view.tile_colors,
-                    self.viewer_ctx.dark_mode,
-                    ctx,
-                ),
-            },
+        match self.tabs.get_mut(self.active_tab) {
+            Some(tab) => tab.render(&mut self.viewer_ctx, ctx),
             None => {
                 // If no architecture is loaded, no view can be seen, so show a welcome message.
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    common_ui::render_welcome_message(ui, &self.view_mode);
+                    common_ui::render_welcome_message(ui);
                 });
             }
         }
@@ -550,6 +1441,23 @@ impl FpgaViewer {
                     );
                     ui.add_space(20.0);
                     ui.vertical_centered(|ui| {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if let Some(location) = &self.viewer_ctx.error_location
+                            && ui
+                                .button(format!("Open in editor at line {}", location.line))
+                                .on_hover_text("Uses $VISUAL or $EDITOR if set")
+                                .clicked()
+                            && let Err(e) = crate::error_report::open_in_editor(location)
+                        {
+                            // Without an editor, the user can still go to the
+                            // error by hand.
+                            warn!("{}", e);
+                            self.viewer_ctx.error_message.push_str(&format!(
+                                "\n\nCould not open an editor ({}).\nThe error is at line {}, column {}.",
+                                e, location.line, location.column
+                            ));
+                            self.viewer_ctx.error_location = None;
+                        }
                         if ui.button("Close").clicked() {
                             self.viewer_ctx.show_error = false;
                         }
@@ -558,11 +1466,134 @@ impl FpgaViewer {
             });
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn render_parsing_overlay(&self, ctx: &egui::Context) {
+        if self.pending_parses.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("parsing_overlay"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    for (path, _) in &self.pending_parses {
+                        let file_name = path
+                            .file_name()
+                            .map(|s| s.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("Parsing {file_name}..."));
+                        });
+                    }
+                });
+            });
+    }
+
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    fn render_url_dialog(&mut self, ctx: &egui::Context) {
+        let Some(url) = &mut self.url_dialog_text else {
+            return;
+        };
+
+        let mut open = true;
+        let mut submit = false;
+        let mut cancel = false;
+        egui::Window::new("Open from URL")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Architecture file URL:");
+                let response = ui.add(
+                    egui::TextEdit::singleline(url)
+                        .hint_text("https://example.com/arch.xml")
+                        .desired_width(400.0),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    submit = true;
+                }
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    submit |= ui
+                        .add_enabled(!url.trim().is_empty(), egui::Button::new("Open"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        let url = url.trim().to_string();
+        if submit && !url.is_empty() {
+            self.pending_download = Some(UrlDownload::start(url, self.parse_options()));
+            self.url_dialog_text = None;
+        } else if cancel || !open {
+            self.url_dialog_text = None;
+        }
+    }
+
+    #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+    fn render_download_overlay(&self, ctx: &egui::Context) {
+        let Some(download) = &self.pending_download else {
+            return;
+        };
+
+        const MB: f32 = 1024.0 * 1024.0;
+        let (received, total) = download.progress();
+        egui::Area::new(egui::Id::new("download_overlay"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Downloading {}...", download.file_name()));
+                    });
+                    match total {
+                        Some(total) => {
+                            ui.add(
+                                egui::ProgressBar::new(received as f32 / total as f32)
+                                    .desired_width(250.0)
+                                    .text(format!(
+                                        "{:.1} / {:.1} MB",
+                                        received as f32 / MB,
+                                        total as f32 / MB
+                                    )),
+                            );
+                        }
+                        None => {
+                            ui.label(format!("{:.1} MB", received as f32 / MB));
+                        }
+                    }
+                });
+            });
+    }
+
     fn render_about_window(&mut self, ctx: &egui::Context) {
         if !self.viewer_ctx.show_about {
             return;
         }
 
+        // Summarize the loaded file, if any, so users can confirm what is open.
+        let loaded_file = match self.active_tab() {
+            Some(LoadedArch {
+                architecture: Some(arch),
+                arch_source,
+                ..
+            }) => {
+                let num_pb_types: usize = arch.complex_block_list.iter().map(count_pb_types).sum();
+                Some(vec![
+                    format!("File: {}", self.loaded_arch_filename().unwrap_or_default()),
+                    format!("Location: {}", arch_source.location()),
+                    format!("Tile Types: {}", arch.tiles.len()),
+                    format!("Layouts: {}", arch.layouts.layout_list.len()),
+                    format!("PB Types: {}", num_pb_types),
+                ])
+            }
+            _ => None,
+        };
+
         egui::Window::new("About")
             .collapsible(false)
             .resizable(false)
@@ -579,6 +1610,14 @@ impl FpgaViewer {
                     ui.add_space(10.0);
                     ui.label("Copyright (c) 2025 AlexandreSinger");
                     ui.label("Licensed under MIT License (SPDX: MIT)");
+                    if let Some(loaded_file) = &loaded_file {
+                        ui.add_space(10.0);
+                        ui.separator();
+                        ui.strong("Loaded Architecture");
+                        for line in loaded_file {
+                            ui.label(line);
+                        }
+                    }
                     ui.add_space(20.0);
                     if ui.button("Close").clicked() {
                         self.viewer_ctx.show_about = false;
@@ -588,15 +1627,54 @@ impl FpgaViewer {
     }
 }
 
+/// Counts a pb_type and every pb_type nested beneath it, across all modes.
+fn count_pb_types(pb_type: &fpga_arch_parser::PBType) -> usize {
+    1 + pb_type
+        .pb_types
+        .iter()
+        .chain(pb_type.modes.iter().flat_map(|mode| mode.pb_types.iter()))
+        .map(count_pb_types)
+        .sum::<usize>()
+}
+
+/// Opens the platform file browser on the folder containing `path`, selecting
+/// the file where the platform supports it.
+#[cfg(not(target_arch = "wasm32"))]
+fn reveal_in_file_browser(path: &std::path::Path) -> std::io::Result<()> {
+    use std::process::Command;
+
+    let path = std::path::absolute(path)?;
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(&path);
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut select_arg = std::ffi::OsString::from("/select,");
+        select_arg.push(&path);
+        let mut command = Command::new("explorer");
+        command.arg(select_arg);
+        command
+    } else {
+        let folder = path.parent().unwrap_or(&path);
+        let mut command = Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+    let mut child = command.spawn()?;
+    // Wait in the background so the finished process is not left a zombie.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
 impl eframe::App for FpgaViewer {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // Process any pending wasm file loads from the async file dialog
         #[cfg(target_arch = "wasm32")]
         {
             if let Some((data, file_name)) =
                 PENDING_WASM_FILE_LOAD.with(|pending| pending.borrow_mut().take())
             {
-                self.load_architecture_from_bytes(data, file_name);
+                self.load_architecture_from_bytes(data, ArchSource::Upload(file_name));
             }
         }
 
@@ -611,10 +1689,39 @@ impl eframe::App for FpgaViewer {
             }
         }
 
+        // Poll for the results of background parses.
+        #[cfg(not(target_arch = "wasm32"))]
+        for (path, rx) in std::mem::take(&mut self.pending_parses) {
+            match rx.try_recv() {
+                Ok(result) => self.on_architecture_file_parsed(result, path),
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    self.pending_parses.push((path, rx));
+                    ctx.request_repaint();
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    // The worker died without sending a result (e.g. it panicked).
+                    self.viewer_ctx.show_error = true;
+                    self.viewer_ctx.error_title = "Parse Error".to_owned();
+                    self.viewer_ctx.error_location = None;
+                    self.viewer_ctx.error_message =
+                        format!("Parsing stopped unexpectedly:\n{:?}", path);
+                }
+            }
+        }
+
+        // Pick up a finished download from "Open from URL".
+        #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+        self.poll_url_download(ctx);
+
         // Process files which were dropped into the app (if any).
         self.process_dropped_files(ctx);
 
-        // Apply theme
+        // Switch views from the keyboard.
+        self.handle_keyboard_shortcuts(ctx);
+
+        // Apply theme. The system theme is kept current by the windowing
+        // backend, so Auto follows the OS as soon as it changes.
+        self.viewer_ctx.dark_mode = self.viewer_ctx.theme.is_dark(ctx.system_theme());
         if self.viewer_ctx.dark_mode {
             ctx.set_visuals(egui::Visuals::dark());
         } else {
@@ -641,13 +1748,17 @@ impl eframe::App for FpgaViewer {
 
         // Render UI panels and windows
         self.render_menu_bar(ctx);
+        self.render_tab_bar(ctx);
         self.render_navigation_buttons(ctx);
 
         // Refresh colors after any menu action that may have loaded a new architecture.
         self.viewer_ctx
             .block_styles
             .update_colors(self.viewer_ctx.dark_mode);
-        self.grid_view.update_tile_colors(self.viewer_ctx.dark_mode);
+        let (palette, dark_mode) = (self.viewer_ctx.palette, self.viewer_ctx.dark_mode);
+        if let Some(tab) = self.active_tab_mut() {
+            tab.grid_view.update_tile_colors(palette, dark_mode);
+        }
 
         // Render the page.
         self.render_page(ctx);
@@ -658,25 +1769,63 @@ impl eframe::App for FpgaViewer {
         // About window
         self.render_about_window(ctx);
 
-        // Next state logic for the view mode.
-        if self.view_mode != self.next_view_mode {
-            // Push current mode to history before transitioning
-            if !self.viewer_ctx.skip_nav_history_update {
-                self.viewer_ctx.navigation_history.push(self.view_mode);
-            }
-            self.viewer_ctx.skip_nav_history_update = false;
+        // Parsing overlay
+        #[cfg(not(target_arch = "wasm32"))]
+        self.render_parsing_overlay(ctx);
 
-            // Run code on the close of a view.
-            if self.view_mode == ViewMode::ComplexBlock {
-                self.complex_block_view.on_view_close()
-            }
+        // Open from URL dialog and download progress
+        #[cfg(all(feature = "url-loading", not(target_arch = "wasm32")))]
+        {
+            self.render_url_dialog(ctx);
+            self.render_download_overlay(ctx);
+        }
 
-            // Run code on the open of a view.
-            if self.next_view_mode == ViewMode::ComplexBlock {
-                self.complex_block_view.on_view_open(&self.architecture)
-            }
+        // Performance overlay
+        if self.viewer_ctx.show_perf_overlay {
+            let draw_stats = match self.viewer_ctx.current_page {
+                Page::Main => self.active_tab().and_then(LoadedArch::draw_stats),
+                Page::Settings => None,
+            };
+            perf_overlay::render_perf_overlay(ctx, self.fps, frame.info().cpu_usage, draw_stats);
+        }
 
-            self.view_mode = self.next_view_mode;
+        // Next state logic for the view mode.
+        let default_expansion = self.viewer_ctx.complex_block_settings.default_expansion;
+        if let Some(tab) = self.active_tab_mut() {
+            tab.update_view_mode(default_expansion);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::k4_n4_xml;
+
+    #[test]
+    fn diffs_a_reload_only_against_the_same_source() {
+        let mut viewer = FpgaViewer::new(None);
+        let old = k4_n4_xml(&[]);
+        let new = k4_n4_xml(&[(r#"<tile name="clb">"#, r#"<tile name="logic">"#)]);
+        let upload = ArchSource::Upload("k4_N4_90nm.xml".to_string());
+
+        // A file with the same name from elsewhere opens in a tab of its own.
+        viewer.load_architecture_from_bytes(old.into_bytes(), upload.clone());
+        viewer.load_architecture_from_bytes(
+            new.clone().into_bytes(),
+            ArchSource::Sample("k4_N4_90nm.xml"),
+        );
+        assert_eq!(viewer.tabs.len(), 2);
+        assert!(viewer.tabs[1].arch_diff.is_none());
+
+        // Loading the same source again replaces its tab and shows the changes.
+        viewer.load_architecture_from_bytes(new.into_bytes(), upload);
+        assert_eq!(viewer.tabs.len(), 2);
+        assert_eq!(viewer.active_tab, 0);
+        let diff = viewer.tabs[0]
+            .arch_diff
+            .as_ref()
+            .expect("the tile was renamed");
+        assert_eq!(diff.added_tiles, vec!["logic"]);
+    }
+}
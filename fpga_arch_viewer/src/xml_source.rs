@@ -0,0 +1,156 @@
+//! XML Source Panel
+//!
+//! Shows the lines of the loaded file that define the selected tile, block,
+//! or interconnect, so what is drawn can be compared with the text behind it.
+
+use eframe::egui;
+
+/// Most lines shown for one element; the rest of a long element is cut off.
+const MAX_SNIPPET_LINES: usize = 200;
+
+/// Finds the lines, counting from 1, spanned by the first `<tag>` element
+/// that starts on `start_line`. Nested elements with the same tag are
+/// skipped over, so a pb_type ends at its own closing tag. Returns None if
+/// no such element starts on that line or it never closes.
+pub fn element_line_range(source: &str, tag: &str, start_line: usize) -> Option<(usize, usize)> {
+    let line_start = match start_line.checked_sub(1)? {
+        0 => 0,
+        skipped => source
+            .match_indices('\n')
+            .nth(skipped - 1)
+            .map(|(i, _)| i + 1)?,
+    };
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |i| line_start + i);
+    let open = format!("<{tag}");
+    let close = format!("</{tag}");
+    let element_start = line_start
+        + source[line_start..line_end]
+            .match_indices(&open)
+            .find(|(i, _)| ends_tag_name(&source[line_start + i + open.len()..]))?
+            .0;
+
+    let mut depth = 0usize;
+    let mut pos = element_start;
+    while let Some(offset) = source[pos..].find('<') {
+        let at = pos + offset;
+        let rest = &source[at..];
+        if let Some((prefix, suffix)) = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")]
+            .into_iter()
+            .find(|(prefix, _)| rest.starts_with(prefix))
+        {
+            pos = at + prefix.len() + rest[prefix.len()..].find(suffix)? + suffix.len();
+            continue;
+        }
+        let end = tag_end(source, at)?;
+        if rest.starts_with(&close) && ends_tag_name(&rest[close.len()..]) {
+            depth -= 1;
+        } else if rest.starts_with(&open)
+            && ends_tag_name(&rest[open.len()..])
+            && !source[..end].ends_with('/')
+        {
+            depth += 1;
+        }
+        if depth == 0 {
+            let lines = source[line_start..end].matches('\n').count();
+            return Some((start_line, start_line + lines));
+        }
+        pos = end + 1;
+    }
+    None
+}
+
+/// Whether `rest`, the text after a tag name, ends the name there.
+fn ends_tag_name(rest: &str) -> bool {
+    rest.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>')
+}
+
+/// Byte offset of the `>` closing the tag that opens at `start`, ignoring any
+/// `>` inside quoted attribute values.
+fn tag_end(source: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in source[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Shows the source of `element`, a tag name and the line it starts on, in a
+/// panel on the left of the window.
+pub fn render_xml_source_panel(
+    ctx: &egui::Context,
+    source: Option<&str>,
+    element: Option<(&str, usize)>,
+) {
+    egui::SidePanel::left("xml_source")
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.heading("XML Source");
+            ui.separator();
+            let Some(source) = source else {
+                ui.weak("The source of this file is not available.");
+                return;
+            };
+            let Some((tag, start_line)) = element else {
+                ui.weak("Select a tile, block, or interconnect to see its XML.");
+                return;
+            };
+            let (first, last) =
+                element_line_range(source, tag, start_line).unwrap_or((start_line, start_line));
+            let shown_last = last.min(first + MAX_SNIPPET_LINES - 1);
+            ui.label(format!("<{tag}> on lines {first}-{last}"));
+
+            let snippet = source
+                .lines()
+                .enumerate()
+                .skip(first - 1)
+                .take(shown_last + 1 - first)
+                .map(|(i, line)| format!("{:>6}  {line}", i + 1))
+                .collect::<Vec<_>>()
+                .join("\n");
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(snippet).monospace()).extend());
+                    if shown_last < last {
+                        ui.weak(format!("... {} more lines", last - shown_last));
+                    }
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"<complexblocklist>
+  <pb_type name="clb">
+    <!-- <pb_type name="commented"> -->
+    <pb_type name="ble" num_pb="4">
+      <pb_type name="lut" blif_model=".names"/>
+    </pb_type>
+    <interconnect>
+      <direct name="d" input="a>b" output="c"/>
+    </interconnect>
+  </pb_type>
+</complexblocklist>"#;
+
+    #[test]
+    fn finds_the_lines_of_an_element() {
+        // Nested pb_types and the commented-out one do not end the outer one.
+        assert_eq!(element_line_range(SOURCE, "pb_type", 2), Some((2, 10)));
+        assert_eq!(element_line_range(SOURCE, "pb_type", 4), Some((4, 6)));
+        assert_eq!(element_line_range(SOURCE, "pb_type", 5), Some((5, 5)));
+        // The `>` inside the quoted input does not end the tag.
+        assert_eq!(element_line_range(SOURCE, "direct", 8), Some((8, 8)));
+        assert_eq!(element_line_range(SOURCE, "mux", 8), None);
+        assert_eq!(element_line_range(SOURCE, "pb_type", 99), None);
+    }
+}
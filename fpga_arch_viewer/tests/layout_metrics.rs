@@ -0,0 +1,37 @@
+use std::process::Command;
+
+fn binary() -> &'static str {
+    env!("CARGO_BIN_EXE_fpga_arch_viewer")
+}
+
+fn valid_arch() -> &'static str {
+    concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../fpga_arch_parser/tests/k4_N4_90nm.xml"
+    )
+}
+
+fn layout_metrics(tile: &str) -> std::process::Output {
+    Command::new(binary())
+        .args(["--layout-metrics", valid_arch(), "--tile", tile])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn layout_metrics_match_snapshot() {
+    let output = layout_metrics("clb");
+    assert!(output.status.success());
+
+    // Regenerate with:
+    // fpga_arch_viewer --layout-metrics k4_N4_90nm.xml --tile clb > tests/fixtures/k4_N4_90nm_clb_layout.txt
+    let expected = include_str!("fixtures/k4_N4_90nm_clb_layout.txt");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+}
+
+#[test]
+fn layout_metrics_rejects_unknown_tile() {
+    let output = layout_metrics("no_such_tile");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no_such_tile"));
+}
@@ -81,6 +81,16 @@ fn parse_only_flag_without_path_exits_nonzero() {
     assert!(!status.success());
 }
 
+#[test]
+fn lenient_without_a_headless_mode_exits_nonzero() {
+    let status = Command::new(binary())
+        .args([valid_arch(), "--lenient"])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
 #[test]
 fn unknown_flag_exits_nonzero() {
     let status = Command::new(binary())
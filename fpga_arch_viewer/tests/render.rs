@@ -0,0 +1,131 @@
+use std::process::{Command, Stdio};
+
+fn binary() -> &'static str {
+    env!("CARGO_BIN_EXE_fpga_arch_viewer")
+}
+
+fn valid_arch() -> &'static str {
+    concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../fpga_arch_parser/tests/k4_N4_90nm.xml"
+    )
+}
+
+fn invalid_arch() -> &'static str {
+    concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/invalid_arch.xml"
+    )
+}
+
+fn out_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("fpga_arch_viewer_render_{name}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn render_writes_grid_and_tile_images() {
+    let out = out_dir("valid");
+    let status = Command::new(binary())
+        .args(["--render", valid_arch(), "--out"])
+        .arg(&out)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    for file in ["grid.svg", "tile_io.svg", "tile_clb.svg"] {
+        let svg = std::fs::read_to_string(out.join(file)).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+    let clb_svg = std::fs::read_to_string(out.join("tile_clb.svg")).unwrap();
+    assert!(clb_svg.contains(">clb</text>"));
+}
+
+#[test]
+fn render_invalid_exits_nonzero() {
+    let out = out_dir("invalid");
+    let output = Command::new(binary())
+        .args(["--render", invalid_arch(), "--out"])
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Parse error"));
+}
+
+#[test]
+fn render_without_out_exits_nonzero() {
+    let status = Command::new(binary())
+        .args(["--render", valid_arch()])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn render_snaps_coordinates_to_whole_pixels() {
+    let out = out_dir("snap");
+    let status = Command::new(binary())
+        .args(["--render", valid_arch(), "--snap", "--out"])
+        .arg(&out)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Stroke outlines sit half a stroke width inside the rect they outline.
+    let clb_svg = std::fs::read_to_string(out.join("tile_clb.svg")).unwrap();
+    for line in clb_svg
+        .lines()
+        .filter(|line| !line.contains("fill=\"none\" stroke"))
+    {
+        for part in line.split(" x=\"").skip(1) {
+            let x: f32 = part[..part.find('"').unwrap()].parse().unwrap();
+            assert_eq!(x.fract(), 0.0);
+        }
+    }
+}
+
+#[test]
+fn render_rejects_a_non_positive_snap_step() {
+    let status = Command::new(binary())
+        .args(["--render", valid_arch(), "--snap", "0", "--out"])
+        .arg(out_dir("bad_snap"))
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn render_uses_the_chosen_palette() {
+    let render = |name: &str, palette: &str| {
+        let out = out_dir(name);
+        let status = Command::new(binary())
+            .args(["--render", valid_arch(), "--palette", palette, "--out"])
+            .arg(&out)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read_to_string(out.join("tile_clb.svg")).unwrap()
+    };
+
+    // The Okabe-Ito blue only appears in the color-blind safe palette.
+    let okabe_ito_blue = "rgb(0,114,178)";
+    assert!(!render("palette_standard", "standard").contains(okabe_ito_blue));
+    assert!(render("palette_color_blind_safe", "color-blind-safe").contains(okabe_ito_blue));
+
+    let status = Command::new(binary())
+        .args(["--render", valid_arch(), "--palette", "sepia", "--out"])
+        .arg(out_dir("bad_palette"))
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}